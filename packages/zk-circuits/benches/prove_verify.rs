@@ -0,0 +1,90 @@
+//! Proof generation and verification benchmarks across circuit sizes
+//!
+//! Measures real timings to calibrate `performance::estimate_proof_time_ms`.
+//! Benchmarks are grouped per circuit, so one circuit can be selected with
+//! criterion's filter, e.g. `cargo bench --bench prove_verify -- trust_score`.
+//!
+//! Run without default features: the napi bindings only link when loaded
+//! into Node.js, so a standalone benchmark binary cannot include them.
+//!
+//! ```sh
+//! cargo bench --no-default-features --bench prove_verify -- trust_score
+//! ```
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use halo2_proofs::{
+    plonk::{create_proof, keygen_pk, keygen_vk, verify_proof, SingleVerifier},
+    poly::commitment::Params,
+    transcript::{Blake2bRead, Blake2bWrite, Challenge255},
+};
+use pasta_curves::{EqAffine, Fp};
+use rand::rngs::OsRng;
+use zk_circuits::circuits::optimizations::performance::CircuitSizeRecommendations;
+use zk_circuits::circuits::trust_score::TrustScoreCircuit;
+use zk_circuits::LendingCircuit;
+
+/// Circuit sizes to benchmark, from low-end mobile up to desktop
+const SIZES: [u32; 4] = [
+    CircuitSizeRecommendations::LOW_END_MOBILE,
+    CircuitSizeRecommendations::MID_RANGE_MOBILE,
+    CircuitSizeRecommendations::HIGH_END_MOBILE,
+    CircuitSizeRecommendations::DESKTOP,
+];
+
+/// Benchmark proving and verifying `circuit` at every size in `SIZES`
+///
+/// Keys are generated once per size and reused across iterations. Sizes below
+/// the circuit's `recommended_k` are skipped.
+fn bench_circuit<C: LendingCircuit<Fp>>(c: &mut Criterion, name: &str, circuit: C) {
+    let public_inputs = circuit.public_inputs();
+
+    let mut group = c.benchmark_group(name);
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(1));
+
+    for k in SIZES.into_iter().filter(|&k| k >= C::recommended_k()) {
+        let params = Params::<EqAffine>::new(k);
+        let vk = keygen_vk(&params, &circuit.without_witnesses()).expect("verifying key generation");
+        let pk = keygen_pk(&params, vk, &circuit.without_witnesses()).expect("proving key generation");
+
+        let prove = || {
+            let mut transcript = Blake2bWrite::<Vec<u8>, EqAffine, Challenge255<_>>::init(vec![]);
+            create_proof(
+                &params,
+                &pk,
+                std::slice::from_ref(&circuit),
+                &[&[&public_inputs]],
+                OsRng,
+                &mut transcript,
+            )
+            .expect("proof generation");
+            transcript.finalize()
+        };
+
+        group.bench_with_input(BenchmarkId::new("prove", k), &k, |b, _| b.iter(prove));
+
+        let proof = prove();
+        group.bench_with_input(BenchmarkId::new("verify", k), &k, |b, _| {
+            b.iter(|| {
+                let mut transcript = Blake2bRead::<&[u8], EqAffine, Challenge255<_>>::init(&proof);
+                verify_proof(
+                    &params,
+                    pk.get_vk(),
+                    SingleVerifier::new(&params),
+                    &[&[&public_inputs]],
+                    &mut transcript,
+                )
+                .expect("proof verifies")
+            })
+        });
+    }
+
+    group.finish();
+}
+
+fn trust_score(c: &mut Criterion) {
+    bench_circuit(c, "trust_score", TrustScoreCircuit::<Fp>::new(Some(85), 70));
+}
+
+criterion_group!(benches, trust_score);
+criterion_main!(benches);
@@ -0,0 +1,143 @@
+//! End-to-end trust score proofs from the command line
+//!
+//! `init` sets up params and keys and saves them to a key file, which the
+//! other subcommands load, so keygen only runs once. Proofs are written as
+//! proof envelopes, exactly as the bindings return them.
+//!
+//! Run without default features: the napi bindings only link when loaded
+//! into Node.js, so a standalone binary cannot include them.
+//!
+//! ```sh
+//! cargo run --no-default-features --features prover --example prove_cli -- init
+//! cargo run --no-default-features --features prover --example prove_cli -- prove-trust --score 85 --threshold 70 --out proof.bin
+//! cargo run --no-default-features --features prover --example prove_cli -- verify-trust --proof proof.bin --threshold 70 --expected true
+//! ```
+//!
+//! Every subcommand accepts `--keys <path>` to use a key file other than the
+//! default one in the temp directory.
+
+use std::process::ExitCode;
+
+#[cfg(not(feature = "node"))]
+mod cli {
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+    use super::ExitCode;
+
+    use zk_circuits::api::{init_system, ZkSystem};
+    use zk_circuits::keys::TRUST_SCORE_K;
+    use zk_circuits::ZkError;
+
+    const USAGE: &str = "\
+usage: prove_cli <command> [--keys <path>]
+
+commands:
+  init [--k <k>]
+  prove-trust --score <score> --threshold <threshold> --out <path>
+  verify-trust --proof <path> --threshold <threshold> --expected <true|false>";
+
+    /// `--name value` pairs following the subcommand
+    struct Flags(HashMap<String, String>);
+
+    impl Flags {
+        fn parse(args: &[String]) -> Result<Self, String> {
+            let mut flags = HashMap::new();
+            let mut args = args.iter();
+            while let Some(flag) = args.next() {
+                let name = flag.strip_prefix("--").ok_or_else(|| format!("Unexpected argument {}", flag))?;
+                let value = args.next().ok_or_else(|| format!("Missing value for {}", flag))?;
+                flags.insert(name.to_string(), value.clone());
+            }
+
+            Ok(Self(flags))
+        }
+
+        fn optional<T: std::str::FromStr>(&self, name: &str) -> Result<Option<T>, String> {
+            self.0
+                .get(name)
+                .map(|value| value.parse().map_err(|_| format!("Invalid value for --{}: {}", name, value)))
+                .transpose()
+        }
+
+        fn required<T: std::str::FromStr>(&self, name: &str) -> Result<T, String> {
+            self.optional(name)?.ok_or_else(|| format!("Missing --{}", name))
+        }
+
+        fn keys_path(&self) -> String {
+            self.0.get("keys").cloned().unwrap_or_else(|| {
+                let path: PathBuf = std::env::temp_dir().join("zk_circuits_prove_cli.keys");
+                path.to_string_lossy().into_owned()
+            })
+        }
+    }
+
+    fn load_system(flags: &Flags) -> Result<ZkSystem, String> {
+        let path = flags.keys_path();
+        ZkSystem::load(&path).map_err(|e| format!("{} (run `init` first to create {})", e, path))
+    }
+
+    fn run(command: &str, flags: &Flags) -> Result<(), String> {
+        let zk_error = |e: ZkError| e.to_string();
+
+        match command {
+            "init" => {
+                let k = flags.optional("k")?.unwrap_or(TRUST_SCORE_K);
+                let path = flags.keys_path();
+                let system = init_system(k).map_err(zk_error)?;
+                system.save(&path).map_err(zk_error)?;
+                println!("Saved k = {} keys to {}", k, path);
+                println!("Verifying key fingerprint: {}", system.verifying_key_fingerprint());
+            }
+            "prove-trust" => {
+                let score = flags.required("score")?;
+                let threshold = flags.required("threshold")?;
+                let out: String = flags.required("out")?;
+
+                let proof = load_system(flags)?.prove_trust_score(score, threshold).map_err(zk_error)?;
+                std::fs::write(&out, &proof).map_err(|e| format!("Failed to write {}: {}", out, e))?;
+                println!("Wrote {} byte proof to {}", proof.len(), out);
+            }
+            "verify-trust" => {
+                let path: String = flags.required("proof")?;
+                let threshold = flags.required("threshold")?;
+                let expected = flags.required("expected")?;
+
+                let proof = std::fs::read(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+                if !load_system(flags)?.verify_trust_score(&proof, threshold, expected).map_err(zk_error)? {
+                    return Err(format!("Proof does not verify for threshold {} with result {}", threshold, expected));
+                }
+                println!("Proof verified");
+            }
+            _ => return Err(format!("Unknown command {}\n\n{}", command, USAGE)),
+        }
+
+        Ok(())
+    }
+
+    pub fn main() -> ExitCode {
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        let Some((command, rest)) = args.split_first() else {
+            eprintln!("{}", USAGE);
+            return ExitCode::FAILURE;
+        };
+
+        match Flags::parse(rest).and_then(|flags| run(command, &flags)) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                eprintln!("{}", e);
+                ExitCode::FAILURE
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "node"))]
+fn main() -> ExitCode {
+    cli::main()
+}
+
+#[cfg(feature = "node")]
+fn main() -> ExitCode {
+    eprintln!("prove_cli cannot link the napi bindings; run it with --no-default-features --features prover");
+    ExitCode::FAILURE
+}
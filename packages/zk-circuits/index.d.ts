@@ -9,21 +9,184 @@
  * Initialize the ZK proof system with setup parameters.
  * This must be called before generating or verifying proofs.
  * 
+ * @param k - Circuit size (4-20); defaults to the desktop recommendation.
+ *            Proofs only verify against keys generated for the same k.
  * @returns Promise<boolean> - true if initialization was successful
- * @throws Error if initialization fails
+ * @throws Error if k is out of range or initialization fails
  */
-export function initializeZkSystem(): Promise<boolean>;
+export function initializeZkSystem(k?: number): Promise<boolean>;
 
 /**
  * Generate a zero-knowledge proof that a trust score meets the threshold
  * without revealing the actual trust score value.
  * 
+ * @param trustScore - The actual trust score, 0-100 (private input)
+ * @param threshold - The threshold to compare against, 0-100 (public input)
+ * @returns Promise<Uint8Array> - The generated proof, wrapped in a versioned envelope
+ * @throws Error if either value exceeds 100 or proof generation fails
+ */
+export function generateTrustScoreProof(trustScore: number, threshold: number): Promise<Uint8Array>;
+
+/**
+ * Generate a trust score proof as padded standard base64, for JSON APIs.
+ * 
+ * @param trustScore - The actual trust score, 0-100 (private input)
+ * @param threshold - The threshold to compare against, 0-100 (public input)
+ * @returns Promise<string> - The proof envelope in base64
+ * @throws Error if either value exceeds 100 or proof generation fails
+ */
+export function generateTrustScoreProofBase64(trustScore: number, threshold: number): Promise<string>;
+
+/**
+ * Generate a trust score proof as lowercase hex, for JSON APIs.
+ * 
+ * @param trustScore - The actual trust score, 0-100 (private input)
+ * @param threshold - The threshold to compare against, 0-100 (public input)
+ * @returns Promise<string> - The proof envelope in hex
+ * @throws Error if either value exceeds 100 or proof generation fails
+ */
+export function generateTrustScoreProofHex(trustScore: number, threshold: number): Promise<string>;
+
+/**
+ * Generate a trust score proof on a worker thread, leaving the event loop free
+ * while proving runs.
+ * 
  * @param trustScore - The actual trust score (private input)
  * @param threshold - The threshold to compare against (public input)
+ * @param cancelToken - Optional id from createCancellationToken; cancelling it
+ *                      rejects the call instead of returning the proof
  * @returns Promise<Uint8Array> - The generated proof data
- * @throws Error if proof generation fails
+ * @throws Error if either value exceeds 100, the system is not initialized,
+ *         proof generation fails, or the token is cancelled ([ZK12])
  */
-export function generateTrustScoreProof(trustScore: number, threshold: number): Promise<Uint8Array>;
+export function generateTrustScoreProofAsync(trustScore: number, threshold: number, cancelToken?: number): Promise<Uint8Array>;
+
+/**
+ * Generate one trust score proof per input on a worker thread, one after
+ * another, stopping early if the token is cancelled. A proof already in
+ * progress finishes first; halo2 cannot interrupt it.
+ * 
+ * @param inputs - Trust scores and thresholds to prove
+ * @param cancelToken - Optional id from createCancellationToken
+ * @returns Promise<Uint8Array[]> - One proof per input, in order
+ * @throws Error if any input is invalid, proving fails, or the token is
+ *         cancelled ([ZK12])
+ */
+export function generateTrustScoreProofsBatchAsync(inputs: TrustScoreParams[], cancelToken?: number): Promise<Uint8Array[]>;
+
+/**
+ * Create a token for cancelling async proofs, e.g. when a user navigates
+ * away mid-proof.
+ * 
+ * @returns number - Token id to pass to the async proving functions
+ */
+export function createCancellationToken(): number;
+
+/**
+ * Cancel every async proof started with a token. Cancellation takes effect
+ * between proofs.
+ * 
+ * @param token - Id from createCancellationToken
+ * @throws Error if the token is unknown or has been freed
+ */
+export function cancelProofs(token: number): void;
+
+/**
+ * Release a cancellation token's id.
+ * 
+ * @param token - Id from createCancellationToken
+ */
+export function freeCancellationToken(token: number): void;
+
+/**
+ * Generate a reproducible trust score proof (for testing purposes). The same
+ * seed and inputs always produce the same bytes; the proof is not
+ * zero-knowledge to anyone who knows the seed.
+ * 
+ * @param trustScore - The actual trust score (private input)
+ * @param threshold - The threshold to compare against (public input)
+ * @param seed - Non-negative integer seed for the proving randomness
+ * @returns Promise<Uint8Array> - The generated proof, wrapped in a versioned envelope
+ * @throws Error if either value exceeds 100, the system is not initialized,
+ *         the seed is out of range, or proof generation fails
+ */
+export function generateTrustScoreProofSeeded(trustScore: number, threshold: number, seed: number): Promise<Uint8Array>;
+
+/**
+ * Generate a trust score proof that expires with an epoch, such as a day
+ * number. It verifies only with verifyTrustScoreProofAtEpoch for that epoch.
+ * 
+ * @param trustScore - The actual trust score (private input)
+ * @param threshold - The threshold to compare against (public input)
+ * @param epoch - The epoch the proof is made for (public input)
+ * @returns Promise<Uint8Array> - The generated proof, wrapped in a versioned envelope
+ * @throws Error if either value exceeds 100, the system is not initialized,
+ *         or proof generation fails
+ */
+export function generateTrustScoreProofAtEpoch(trustScore: number, threshold: number, epoch: number): Promise<Uint8Array>;
+
+/**
+ * Opaque handle to trust score proving keys, created by createProverHandle.
+ */
+export interface ProverHandle {
+  readonly __proverHandle: unique symbol;
+}
+
+/**
+ * Create a handle with its own proving keys, for proving many inputs without
+ * going through the global system. Keys are generated once, here; proofs
+ * verify against any system initialized with the same k.
+ * 
+ * @param k - Circuit size (4-20); defaults to the desktop recommendation
+ * @returns Promise<ProverHandle> - The handle, released when garbage collected
+ * @throws Error if k is out of range or key generation fails
+ */
+export function createProverHandle(k?: number): Promise<ProverHandle>;
+
+/**
+ * Generate a trust score proof with a handle's keys.
+ * 
+ * @param handle - A handle from createProverHandle
+ * @param trustScore - The actual trust score, 0-100 (private input)
+ * @param threshold - The threshold to compare against, 0-100 (public input)
+ * @returns Promise<Uint8Array> - The generated proof, wrapped in a versioned envelope
+ * @throws Error if the handle has been freed, either value exceeds 100, or proof generation fails
+ */
+export function proveWithHandle(handle: ProverHandle, trustScore: number, threshold: number): Promise<Uint8Array>;
+
+/**
+ * Release a handle's keys without waiting for garbage collection. Proving
+ * with the handle afterwards fails.
+ * 
+ * @param handle - A handle from createProverHandle
+ */
+export function freeProverHandle(handle: ProverHandle): Promise<void>;
+
+/**
+ * Generate trust score proofs for several inputs in one call, sharing the
+ * proving key. Each entry holds either a proof or the error for that input;
+ * one invalid input does not abort the batch.
+ * 
+ * @param inputs - Trust score and threshold pairs
+ * @param onProgress - Optional callback, called with (done, total) after each
+ *                     result in input order; proving does not wait for it
+ * @returns Promise<BatchProofResult[]> - One result per input, in order
+ * @throws Error if the system is not initialized
+ */
+export function generateTrustScoreProofsBatch(
+  inputs: TrustScoreParams[],
+  onProgress?: (done: number, total: number) => void
+): Promise<BatchProofResult[]>;
+
+/**
+ * Generate one proof covering several trust score inputs. Much cheaper than
+ * a proof per input, but the inputs can only be verified together.
+ * 
+ * @param inputs - Trust score and threshold pairs
+ * @returns Promise<Uint8Array> - The combined proof, wrapped in a versioned envelope
+ * @throws Error if inputs is empty, any input is invalid, or the system is not initialized
+ */
+export function generateTrustScoresCombinedProof(inputs: TrustScoreParams[]): Promise<Uint8Array>;
 
 /**
  * Verify a trust score proof without learning the actual trust score.
@@ -31,10 +194,263 @@ export function generateTrustScoreProof(trustScore: number, threshold: number):
  * @param proofData - The proof data to verify
  * @param threshold - The threshold that was used in proof generation
  * @param expectedResult - Whether the trust score should be above threshold
+ * @param vkFingerprint - Optional expected verifying key fingerprint; checked
+ *                        before verification runs
  * @returns Promise<boolean> - true if the proof is valid
- * @throws Error if verification fails
+ * @throws Error if threshold exceeds 100, the verifying key does not match
+ *         vkFingerprint, the proof is empty or truncated, or its envelope is malformed or has an
+ *         unknown version or circuit tag
  */
-export function verifyTrustScoreProof(proofData: Uint8Array, threshold: number, expectedResult: boolean): Promise<boolean>;
+export function verifyTrustScoreProof(
+  proofData: Uint8Array,
+  threshold: number,
+  expectedResult: boolean,
+  vkFingerprint?: string
+): Promise<boolean>;
+
+/**
+ * Verify a base64 trust score proof, as produced by generateTrustScoreProofBase64.
+ * Otherwise the same as verifyTrustScoreProof.
+ * 
+ * @param proof - The proof envelope in padded standard base64
+ * @param threshold - The threshold that was used in proof generation
+ * @param expectedResult - Whether the trust score should be above threshold
+ * @param vkFingerprint - Optional expected verifying key fingerprint
+ * @returns Promise<boolean> - true if the proof is valid
+ * @throws Error (ZkErrorCode.Serialization) if proof is not valid base64, and
+ *         otherwise as verifyTrustScoreProof
+ */
+export function verifyTrustScoreProofBase64(
+  proof: string,
+  threshold: number,
+  expectedResult: boolean,
+  vkFingerprint?: string
+): Promise<boolean>;
+
+/**
+ * Verify a hex trust score proof, as produced by generateTrustScoreProofHex.
+ * Otherwise the same as verifyTrustScoreProof.
+ * 
+ * @param proof - The proof envelope in hex, either case
+ * @param threshold - The threshold that was used in proof generation
+ * @param expectedResult - Whether the trust score should be above threshold
+ * @param vkFingerprint - Optional expected verifying key fingerprint
+ * @returns Promise<boolean> - true if the proof is valid
+ * @throws Error (ZkErrorCode.Serialization) if proof is not valid hex, and
+ *         otherwise as verifyTrustScoreProof
+ */
+export function verifyTrustScoreProofHex(
+  proof: string,
+  threshold: number,
+  expectedResult: boolean,
+  vkFingerprint?: string
+): Promise<boolean>;
+
+/**
+ * Verify a trust score proof and read the result it attests, instead of
+ * trusting a caller-supplied expected result.
+ * 
+ * @param proofData - The proof data to verify
+ * @param threshold - The threshold that was used in proof generation
+ * @returns Promise<boolean> - true if the proof attests the score is at least threshold
+ * @throws Error if threshold exceeds 100, the proof is not valid for threshold
+ *         with either result, its envelope is malformed, or the system is not
+ *         initialized
+ */
+export function verifyTrustScoreProofOutcome(proofData: Uint8Array, threshold: number): Promise<boolean>;
+
+/**
+ * Verify a trust score proof made at most maxAge epochs before currentEpoch.
+ * Proofs made for any other epoch are rejected.
+ * 
+ * @param proofData - The proof data to verify
+ * @param threshold - The threshold that was used in proof generation
+ * @param expectedResult - Whether the trust score should be above threshold
+ * @param currentEpoch - The verifier's current epoch
+ * @param maxAge - How many epochs old a proof may be (default 0)
+ * @returns Promise<boolean> - true if the proof is valid and current
+ * @throws Error if threshold exceeds 100, the proof is empty or truncated, or
+ *         its envelope is malformed
+ */
+export function verifyTrustScoreProofAtEpoch(
+  proofData: Uint8Array,
+  threshold: number,
+  expectedResult: boolean,
+  currentEpoch: number,
+  maxAge?: number
+): Promise<boolean>;
+
+/**
+ * Verify several trust score proofs in one batch, amortizing the verification
+ * work across them.
+ * 
+ * @param proofs - The proofs to verify
+ * @param thresholds - The threshold each proof was generated for
+ * @param expectedResults - The claimed result of each proof
+ * @returns Promise<boolean[]> - Whether each proof is valid, in order
+ * @throws Error if the arrays differ in length, a threshold exceeds 100, or
+ *         the system is not initialized
+ */
+export function verifyTrustScoreProofsBatch(
+  proofs: Uint8Array[],
+  thresholds: number[],
+  expectedResults: boolean[]
+): Promise<boolean[]>;
+
+/**
+ * Verify several trust score proofs, each against its own threshold, in the
+ * given batch mode.
+ * 
+ * @param proofs - The proofs to verify
+ * @param thresholds - The threshold each proof was generated for
+ * @param expectedResults - The claimed result of each proof
+ * @param mode - 'all_or_nothing' for a single verdict on the whole batch, or
+ *        'report' for one verdict per proof
+ * @returns Promise<boolean | boolean[]> - Whether every proof is valid, or
+ *          whether each proof is valid, in order
+ * @throws Error if the arrays differ in length, a threshold exceeds 100, the
+ *         mode is unknown, or the system is not initialized
+ */
+export function verifyTrustScoreProofsBatchMode(
+  proofs: Uint8Array[],
+  thresholds: number[],
+  expectedResults: boolean[],
+  mode: 'all_or_nothing' | 'report'
+): Promise<boolean | boolean[]>;
+
+/**
+ * Verify a combined proof from generateTrustScoresCombinedProof.
+ * 
+ * @param proofData - The combined proof to verify
+ * @param thresholds - The threshold of each input, in input order
+ * @param expectedResults - The claimed result of each input, in input order
+ * @returns Promise<boolean> - true only if every claim holds
+ * @throws Error if the arrays differ in length or are empty, a threshold
+ *         exceeds 100, the envelope is malformed, or the system is not initialized
+ */
+export function verifyTrustScoresCombined(
+  proofData: Uint8Array,
+  thresholds: number[],
+  expectedResults: boolean[]
+): Promise<boolean>;
+
+/**
+ * Get the fingerprint of the loaded verifying key, for pinning in verifier config.
+ * 
+ * @returns Promise<string> - Lowercase hex fingerprint of the verifying key
+ * @throws Error if the ZK system is not initialized
+ */
+export function getVerifyingKeyFingerprint(): Promise<string>;
+
+/**
+ * Verify a trust score proof after checking the loaded verifying key
+ * against a pinned fingerprint.
+ * 
+ * @param proofData - The proof data to verify
+ * @param threshold - The threshold that was used in proof generation
+ * @param expectedResult - Whether the trust score should be above threshold
+ * @param vkFingerprint - The pinned verifying key fingerprint
+ * @returns Promise<boolean> - true if the proof is valid
+ * @throws Error if the loaded verifying key does not match the pinned fingerprint
+ */
+export function verifyTrustScoreProofPinned(proofData: Uint8Array, threshold: number, expectedResult: boolean, vkFingerprint: string): Promise<boolean>;
+
+/**
+ * Describe a proof envelope without verifying it, e.g. to label a proof in a
+ * UI before running verification. Does not mean the proof is valid.
+ * 
+ * @param proofData - The proof envelope to inspect
+ * @returns Promise<ProofInfo> - The circuit, envelope version and proof length
+ * @throws Error if the envelope cannot be read
+ */
+export function inspectProof(proofData: Uint8Array): Promise<ProofInfo>;
+
+/**
+ * Verify a proof against explicit public inputs, for integrators that build
+ * the instance themselves.
+ * 
+ * @param proofData - The proof envelope to verify
+ * @param publicInputs - One entry per instance column, each a sequence of
+ *                       32-byte little-endian field elements; trust score
+ *                       proofs have a single column, [result, threshold, epoch]
+ * @returns Promise<boolean> - true if the proof is valid for these inputs
+ * @throws Error if the ZK system is not initialized, the proof envelope is
+ *         malformed, a column is not a whole number of canonical field elements,
+ *         or the result is not 0 or 1
+ */
+export function verifyProofRaw(proofData: Uint8Array, publicInputs: Uint8Array[]): Promise<boolean>;
+
+/**
+ * Get the measured columns, gates and minimum k of a circuit.
+ * 
+ * @param circuit - One of trust_score, income_range, loan_history, identity or composite
+ * @returns Promise<string> - JSON with adviceColumns, fixedColumns, instanceColumns, gates and minK
+ * @throws Error if the circuit name is unknown
+ */
+export function getCircuitStats(circuit: string): Promise<string>;
+
+/**
+ * Describe each public input of a circuit, to build the instance for
+ * verifyProofRaw without guessing its order.
+ * 
+ * @param circuit - One of trust_score, trust_score_band, committed_trust_score,
+ *                  income_range, income_growth, loan_history, identity,
+ *                  distinct_identities, eligibility or composite
+ * @returns Promise<string> - JSON array of { index, name, kind, optional } in
+ *          instance order, with kind one of bool, integer or field; optional
+ *          slots are only present for some constructions of the circuit
+ * @throws Error if the circuit name is unknown
+ */
+export function getInstanceLayout(circuit: string): Promise<string>;
+
+/**
+ * Get one coherent proving configuration for a device class.
+ * 
+ * @param device - One of high_end_mobile, mid_range_mobile, low_end_mobile or
+ *                 desktop, or its id (0 to 3 in that order)
+ * @param numProofs - Size of the workload to advise on batching for
+ * @returns Promise<string> - JSON with k, batchSize, estimatedProofTimeMs, estimatedMemoryMb and useBatching
+ * @throws Error if the device type is unknown
+ */
+export function getDeviceProfile(device: string | number, numProofs: number): Promise<string>;
+
+/**
+ * Estimate the serialized size of a proof, e.g. to budget mobile bandwidth.
+ * Assumes a circuit shaped like the trust score circuit.
+ * 
+ * @param k - Circuit size
+ * @param numInstance - Number of instance columns
+ * @returns Promise<number> - Estimated proof size in bytes
+ */
+export function getEstimatedProofSize(k: number, numInstance: number): Promise<number>;
+
+/**
+ * Measure the size of a real trust score proof from the initialized system,
+ * for calibrating getEstimatedProofSize.
+ * 
+ * @returns Promise<number> - Proof size in bytes, excluding the envelope header
+ * @throws Error if the ZK system is not initialized or proof generation fails
+ */
+export function measureTrustScoreProofSize(): Promise<number>;
+
+/**
+ * Save the initialized setup parameters and keys to a file.
+ * 
+ * @param path - Destination file path
+ * @returns Promise<boolean> - true if the keys were saved
+ * @throws Error if the ZK system is not initialized or the file cannot be written
+ */
+export function saveZkKeys(path: string): Promise<boolean>;
+
+/**
+ * Initialize the ZK proof system from a key file written by saveZkKeys,
+ * instead of calling initializeZkSystem.
+ * 
+ * @param path - Key file path
+ * @returns Promise<boolean> - true if the keys were loaded
+ * @throws Error if the system is already initialized or the file is not a compatible key file
+ */
+export function loadZkKeys(path: string): Promise<boolean>;
 
 /**
  * Test the trust score circuit using a mock prover (for testing purposes).
@@ -43,10 +459,85 @@ export function verifyTrustScoreProof(proofData: Uint8Array, threshold: number,
  * @param trustScore - The trust score to test
  * @param threshold - The threshold to compare against
  * @returns Promise<boolean> - true if the circuit constraints are satisfied
- * @throws Error if the test fails
+ * @throws Error if either value exceeds 100 or the test fails
  */
 export function testTrustScoreCircuit(trustScore: number, threshold: number): Promise<boolean>;
 
+/**
+ * Outcome of a dry run
+ */
+export interface DryRunResult {
+  /** The circuit's constraints hold for the inputs, so a proof can be made */
+  satisfied: boolean;
+  /** The statement's result; false if not satisfied */
+  result: boolean;
+}
+
+/**
+ * Run the trust score circuit through the mock prover and report every
+ * constraint it fails, for debugging witnesses. Does not require
+ * initializeZkSystem.
+ * 
+ * @param trustScore - The trust score to test
+ * @param threshold - The threshold to compare against
+ * @returns Promise<string> - JSON with satisfied and failures, each failure
+ *          holding kind, gate, region, row and message
+ * @throws Error if the circuit cannot be synthesized
+ */
+export function dryRunTrustScoreWithDiagnostics(trustScore: number, threshold: number): Promise<string>;
+
+/**
+ * Check whether an income lies in [minRange, maxRange] without generating a
+ * proof. Cheap, and does not require initializeZkSystem.
+ * 
+ * @param income - The private income (a non-negative integer)
+ * @param minRange - The lowest income in the range
+ * @param maxRange - The highest income in the range
+ * @returns Promise<DryRunResult> - Whether a proof could be made and its result
+ * @throws Error if a value is negative or exceeds 80 bits, or minRange is
+ *         above maxRange
+ */
+export function dryRunIncomeRange(income: number, minRange: number, maxRange: number): Promise<DryRunResult>;
+
+/**
+ * Check whether identity data opens a commitment without generating a proof.
+ * Does not require initializeZkSystem.
+ * 
+ * @param identityData - The raw identity data
+ * @param nonce - The commitment nonce, a 32-byte little-endian field element
+ * @param commitment - The commitment, a 32-byte little-endian field element
+ * @param domainSeparator - The nullifier domain
+ * @param contextId - The context the commitment was made for
+ * @returns Promise<DryRunResult> - Whether a proof could be made and its result
+ * @throws Error if the nonce or commitment is not a field element
+ */
+export function dryRunIdentity(
+  identityData: Uint8Array,
+  nonce: Uint8Array,
+  commitment: Uint8Array,
+  domainSeparator: number,
+  contextId: number
+): Promise<DryRunResult>;
+
+/**
+ * Check whether a loan history meets a minimum success rate without
+ * generating a proof. Does not require initializeZkSystem.
+ * 
+ * @param numLoans - The number of loans taken
+ * @param successfulRepayments - The number repaid
+ * @param minSuccessRate - The minimum success rate in basis points (8000 = 80%)
+ * @param minLoans - The minimum number of loans for the rate to count
+ * @returns Promise<DryRunResult> - Whether a proof could be made and its result
+ * @throws Error if a loan count exceeds 16 bits, successfulRepayments exceeds
+ *         numLoans, or minSuccessRate exceeds 10000
+ */
+export function dryRunLoanHistory(
+  numLoans: number,
+  successfulRepayments: number,
+  minSuccessRate: number,
+  minLoans: number
+): Promise<DryRunResult>;
+
 /**
  * Result structure for proof operations (used internally)
  */
@@ -54,16 +545,55 @@ export interface ProofResult {
   success: boolean;
   proofData?: Uint8Array;
   errorMessage?: string;
+  /** 0 on success, otherwise one of ZkErrorCode */
+  errorCode: number;
 }
 
 /**
- * Parameters for trust score proof generation (used internally)
+ * Stable error codes. Errors thrown by the bindings carry the code as a
+ * `[ZK<code>]` prefix on their message.
+ */
+export enum ZkErrorCode {
+  NotInitialized = 1,
+  KeygenFailed = 2,
+  ProofFailed = 3,
+  VerifyFailed = 4,
+  InvalidInput = 5,
+  Serialization = 6,
+  UnknownKeyVersion = 7,
+  KeyVersionMismatch = 8,
+  UnsupportedProofVersion = 9,
+  UnknownCircuitTag = 10,
+  KeyMismatch = 11,
+  Cancelled = 12,
+}
+
+/**
+ * Parameters for trust score proof generation
  */
 export interface TrustScoreParams {
   trustScore: number;
   threshold: number;
 }
 
+/**
+ * Outcome of one input in a proof batch
+ */
+export interface BatchProofResult {
+  proof?: Uint8Array;
+  error?: string;
+}
+
+/**
+ * Description of a proof envelope, read without verifying the proof
+ */
+export interface ProofInfo {
+  /** Circuit name, e.g. trust_score */
+  circuit: string;
+  version: number;
+  proofLen: number;
+}
+
 /**
  * Error types that can be thrown by the ZK circuit functions
  */
@@ -0,0 +1,502 @@
+//! Typed Rust API for trust score proofs
+//!
+//! Mirrors the napi bindings without the Node runtime or global state: a
+//! `ZkSystem` owns its params and keys, and proofs leave it wrapped in a
+//! proof envelope exactly as they do from the bindings.
+//!
+// Doctest binaries cannot resolve the napi symbols, so only run it without
+// `node`, and verifier-only builds cannot prove
+#![cfg_attr(feature = "node", doc = "```no_run")]
+#![cfg_attr(all(not(feature = "node"), feature = "prover"), doc = "```")]
+#![cfg_attr(not(feature = "prover"), doc = "```ignore")]
+//! use zk_circuits::api::init_system;
+//! use zk_circuits::keys::TRUST_SCORE_K;
+//!
+//! let system = init_system(TRUST_SCORE_K)?;
+//!
+//! // Prove that a score of 85 clears a threshold of 70
+//! let proof = system.prove_trust_score(85, 70)?;
+//!
+//! assert!(system.verify_trust_score(&proof, 70, true)?);
+//! assert!(!system.verify_trust_score(&proof, 80, true)?);
+//! # Ok::<(), zk_circuits::ZkError>(())
+//! ```
+
+use crate::circuits::lending::LendingCircuit;
+use crate::circuits::trust_score::TrustScoreCircuit;
+#[cfg(feature = "prover")]
+use crate::envelope::encode_proof;
+use crate::envelope::{decode_proof, CircuitTag, HEADER_LEN};
+use crate::error::ZkError;
+use crate::keys::{TrustScoreKeys, TRUST_SCORE_K};
+#[cfg(feature = "prover")]
+use crate::prover::{prove_trust_score, prove_trust_score_at_epoch, prove_trust_score_with_rng, prove_trust_scores_combined};
+use crate::prover::{
+    decode_instance_column, verify_trust_score, verify_trust_score_in_epochs, verify_trust_score_outcome, verify_trust_scores_batch,
+    verify_trust_scores_batch_mode, verify_trust_scores_combined, verify_with_instances, BatchMode, BatchVerdict, TrustScoreClaim, TrustScoreOutcome,
+    TrustScoreVerifier, VerifyStrategy,
+};
+use crate::registry::{vk_fingerprint, vk_fingerprint_bytes};
+use crate::validation::validate_instance;
+use pasta_curves::Fp;
+#[cfg(feature = "prover")]
+use rand::{CryptoRng, RngCore};
+use std::ops::RangeInclusive;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Largest circuit size `init_system` accepts
+pub const MAX_K: u32 = 20;
+
+/// Check a requested circuit size against `TRUST_SCORE_K..=MAX_K`
+pub(crate) fn check_circuit_size(k: u32) -> Result<u32, ZkError> {
+    if !(TRUST_SCORE_K..=MAX_K).contains(&k) {
+        return Err(ZkError::InvalidInput(format!(
+            "Circuit size k = {} is outside {}..={}",
+            k, TRUST_SCORE_K, MAX_K
+        )));
+    }
+
+    Ok(k)
+}
+
+/// Set up params and trust score keys for circuit size `k`
+///
+/// Proofs only verify against a system set up for the same `k`.
+pub fn init_system(k: u32) -> Result<ZkSystem, ZkError> {
+    Ok(ZkSystem::from_keys(TrustScoreKeys::generate(check_circuit_size(k)?)?))
+}
+
+/// Unwrap a trust score proof envelope, rejecting proofs of other circuits
+///
+/// Buffers too short to hold an envelope header are invalid input rather than
+/// a malformed envelope, so an empty proof gets a clear error.
+pub(crate) fn trust_score_proof(envelope: &[u8]) -> Result<&[u8], ZkError> {
+    if envelope.is_empty() {
+        return Err(ZkError::InvalidInput("Proof is empty".into()));
+    }
+    if envelope.len() < HEADER_LEN {
+        return Err(ZkError::InvalidInput(format!(
+            "Proof is {} bytes, too short for a proof envelope",
+            envelope.len()
+        )));
+    }
+
+    match decode_proof(envelope)? {
+        (CircuitTag::TrustScore, proof) => Ok(proof),
+        (circuit, _) => Err(ZkError::InvalidInput(format!("Expected a trust score proof, got {:?}", circuit))),
+    }
+}
+
+/// Unwrap each claim's proof envelope, leaving an empty proof (which the
+/// batch verifiers report as invalid) for one that cannot be read
+fn unwrap_claims<'a>(claims: &[TrustScoreClaim<'a>]) -> Vec<TrustScoreClaim<'a>> {
+    claims
+        .iter()
+        .map(|claim| TrustScoreClaim {
+            proof: trust_score_proof(claim.proof).unwrap_or_default(),
+            threshold: claim.threshold,
+            expected_result: claim.expected_result,
+        })
+        .collect()
+}
+
+/// Flag for abandoning a run of proofs part way through
+///
+/// halo2's `create_proof` cannot be interrupted, so cancellation takes
+/// effect between proofs: the proof in flight when `cancel` is called still
+/// finishes, and no further ones start. Clones share the same flag.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stop every run watching this token at its next proof boundary
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// `Err(ZkError::Cancelled)` once cancelled
+    pub fn check(&self) -> Result<(), ZkError> {
+        if self.is_cancelled() {
+            return Err(ZkError::Cancelled);
+        }
+
+        Ok(())
+    }
+}
+
+/// Setup params and keys for proving and verifying trust scores
+pub struct ZkSystem {
+    keys: TrustScoreKeys,
+}
+
+impl ZkSystem {
+    /// Wrap existing keys
+    pub fn from_keys(keys: TrustScoreKeys) -> Self {
+        Self { keys }
+    }
+
+    /// Load keys written by `save`
+    pub fn load(path: &str) -> Result<Self, ZkError> {
+        Ok(Self::from_keys(TrustScoreKeys::load(path)?))
+    }
+
+    /// Save the params and keys to `path`
+    pub fn save(&self, path: &str) -> Result<(), ZkError> {
+        self.keys.save(path)
+    }
+
+    /// The params and keys this system proves and verifies with
+    pub fn keys(&self) -> &TrustScoreKeys {
+        &self.keys
+    }
+
+    /// Prove whether `trust_score` is at least `threshold`, wrapped in a
+    /// proof envelope
+    #[cfg(feature = "prover")]
+    pub fn prove_trust_score(&self, trust_score: u64, threshold: u64) -> Result<Vec<u8>, ZkError> {
+        let proof = prove_trust_score(&self.keys.params, &self.keys.proving_key, trust_score, threshold)?;
+        Ok(encode_proof(CircuitTag::TrustScore, &proof))
+    }
+
+    /// `prove_trust_score` for a proof that only verifies at `epoch`
+    #[cfg(feature = "prover")]
+    pub fn prove_trust_score_at_epoch(&self, trust_score: u64, threshold: u64, epoch: u64) -> Result<Vec<u8>, ZkError> {
+        let proof = prove_trust_score_at_epoch(&self.keys.params, &self.keys.proving_key, trust_score, threshold, epoch)?;
+        Ok(encode_proof(CircuitTag::TrustScore, &proof))
+    }
+
+    /// Prove `(trust_score, threshold)` inputs one at a time, stopping once
+    /// `cancel` is cancelled
+    ///
+    /// Yields one proof envelope per input, in order, as each is made. The
+    /// token is checked before each proof; once it is cancelled the iterator
+    /// yields a single `ZkError::Cancelled` and ends.
+    #[cfg(feature = "prover")]
+    pub fn prove_trust_scores_cancellable<'a>(
+        &'a self,
+        inputs: &'a [(u64, u64)],
+        cancel: &'a CancellationToken,
+    ) -> impl Iterator<Item = Result<Vec<u8>, ZkError>> + 'a {
+        let mut inputs = inputs.iter();
+        let mut stopped = false;
+
+        std::iter::from_fn(move || {
+            if stopped {
+                return None;
+            }
+            let &(trust_score, threshold) = inputs.next()?;
+            if let Err(e) = cancel.check() {
+                stopped = true;
+                return Some(Err(e));
+            }
+
+            Some(self.prove_trust_score(trust_score, threshold))
+        })
+    }
+
+    /// Prove several `(trust_score, threshold)` inputs in one proof envelope
+    ///
+    /// The envelope only verifies with `verify_trust_scores_combined`, against
+    /// claims in input order.
+    #[cfg(feature = "prover")]
+    pub fn prove_trust_scores_combined(&self, inputs: &[(u64, u64)]) -> Result<Vec<u8>, ZkError> {
+        let proof = prove_trust_scores_combined(&self.keys.params, &self.keys.proving_key, inputs)?;
+        Ok(encode_proof(CircuitTag::TrustScore, &proof))
+    }
+
+    /// `prove_trust_score` with the proving randomness drawn from `rng`, for
+    /// reproducible proofs in tests
+    #[cfg(feature = "prover")]
+    pub fn prove_trust_score_with_rng(
+        &self,
+        trust_score: u64,
+        threshold: u64,
+        rng: impl RngCore + CryptoRng,
+    ) -> Result<Vec<u8>, ZkError> {
+        let proof = prove_trust_score_with_rng(&self.keys.params, &self.keys.proving_key, trust_score, threshold, rng)?;
+        Ok(encode_proof(CircuitTag::TrustScore, &proof))
+    }
+
+    /// Length in bytes of a trust score proof from this system, without the
+    /// envelope, for calibrating `estimate_proof_size_bytes`
+    ///
+    /// Proves a placeholder score; the length does not depend on the witness.
+    #[cfg(feature = "prover")]
+    pub fn measure_proof_size_bytes(&self) -> Result<usize, ZkError> {
+        Ok(prove_trust_score(&self.keys.params, &self.keys.proving_key, 0, 0)?.len())
+    }
+
+    /// Verify a trust score proof envelope against `threshold` and the
+    /// claimed result
+    ///
+    /// Returns `Ok(false)` for a proof that does not verify. An envelope that
+    /// cannot be read, or holds another circuit's proof, is an error.
+    pub fn verify_trust_score(&self, envelope: &[u8], threshold: u64, expected_result: bool) -> Result<bool, ZkError> {
+        let proof = trust_score_proof(envelope)?;
+        verify_trust_score(&self.keys.params, &self.keys.verifying_key, proof, threshold, expected_result)
+    }
+
+    /// Verify a trust score proof envelope against `threshold` and report
+    /// the result it attests, without an expected result from the caller
+    ///
+    /// A proof that verifies with neither result fails with `VerifyFailed`.
+    pub fn verify_trust_score_outcome(&self, envelope: &[u8], threshold: u64) -> Result<TrustScoreOutcome, ZkError> {
+        let proof = trust_score_proof(envelope)?;
+        verify_trust_score_outcome(&self.keys.params, &self.keys.verifying_key, proof, threshold)
+    }
+
+    /// `verify_trust_score` for a proof made at any epoch in `epochs`
+    ///
+    /// Proofs made for an epoch outside the window return `Ok(false)`.
+    pub fn verify_trust_score_in_epochs(
+        &self,
+        envelope: &[u8],
+        threshold: u64,
+        expected_result: bool,
+        epochs: RangeInclusive<u64>,
+    ) -> Result<bool, ZkError> {
+        let proof = trust_score_proof(envelope)?;
+        verify_trust_score_in_epochs(&self.keys.params, &self.keys.verifying_key, proof, threshold, expected_result, epochs)
+    }
+
+    /// Verify a `prove_trust_scores_combined` envelope against one
+    /// `(threshold, expected_result)` claim per input
+    ///
+    /// Returns `Ok(false)` unless every claim holds.
+    pub fn verify_trust_scores_combined(&self, envelope: &[u8], claims: &[(u64, bool)]) -> Result<bool, ZkError> {
+        let proof = trust_score_proof(envelope)?;
+        verify_trust_scores_combined(&self.keys.params, &self.keys.verifying_key, proof, claims)
+    }
+
+    /// Verify a trust score proof envelope against serialized instance columns
+    ///
+    /// Each column is a run of 32-byte little-endian field elements (see
+    /// `encode_instance_column`); for trust score proofs that is one column,
+    /// `[result, threshold, epoch]`. Returns `Ok(false)` for a proof that does not
+    /// verify against them, and an error for a column that cannot be read or
+    /// holds a value outside its slot's domain, such as a result other than 0 or 1.
+    pub fn verify_proof_raw(&self, envelope: &[u8], public_inputs: &[Vec<u8>]) -> Result<bool, ZkError> {
+        let proof = trust_score_proof(envelope)?;
+        let instances = public_inputs
+            .iter()
+            .map(|column| decode_instance_column(column))
+            .collect::<Result<Vec<_>, _>>()?;
+        if let Some(column) = instances.first() {
+            validate_instance(&TrustScoreCircuit::<Fp>::instance_layout(), column)?;
+        }
+
+        verify_with_instances(&self.keys.params, &self.keys.verifying_key, proof, &instances)
+    }
+
+    /// Verify several trust score proof envelopes in one batch
+    ///
+    /// Each claim's `proof` is an envelope; one that cannot be read is
+    /// reported as not verifying.
+    pub fn verify_trust_scores_batch(&self, claims: &[TrustScoreClaim<'_>]) -> Result<Vec<bool>, ZkError> {
+        verify_trust_scores_batch(&self.keys.params, &self.keys.verifying_key, &unwrap_claims(claims))
+    }
+
+    /// Verify several trust score proof envelopes in one batch, reporting
+    /// as `mode` asks
+    ///
+    /// As with `verify_trust_scores_batch`, an envelope that cannot be read
+    /// counts as not verifying, so with `BatchMode::AllOrNothing` it fails
+    /// the batch.
+    pub fn verify_trust_scores_batch_mode(&self, claims: &[TrustScoreClaim<'_>], mode: BatchMode) -> Result<BatchVerdict, ZkError> {
+        verify_trust_scores_batch_mode(&self.keys.params, &self.keys.verifying_key, &unwrap_claims(claims), mode)
+    }
+
+    /// Start verifying a stream of trust score proof envelopes
+    ///
+    /// `VerifyStrategy::Accumulate` defers the expensive check to
+    /// `EnvelopeVerifier::finalize`, which then accepts or rejects them all.
+    pub fn trust_score_verifier(&self, strategy: VerifyStrategy) -> EnvelopeVerifier<'_> {
+        EnvelopeVerifier(TrustScoreVerifier::new(&self.keys.params, &self.keys.verifying_key, strategy))
+    }
+
+    /// Hex Blake2b fingerprint of the verifying key
+    pub fn verifying_key_fingerprint(&self) -> String {
+        vk_fingerprint(&self.keys.verifying_key)
+    }
+
+    /// Raw Blake2b fingerprint of the verifying key
+    pub fn verifying_key_fingerprint_bytes(&self) -> [u8; 32] {
+        vk_fingerprint_bytes(&self.keys.verifying_key)
+    }
+
+    /// Compare the verifying key against a pinned hex fingerprint
+    pub fn check_fingerprint(&self, pinned: &str) -> Result<(), ZkError> {
+        let actual = self.verifying_key_fingerprint();
+        if !actual.eq_ignore_ascii_case(pinned.trim()) {
+            return Err(ZkError::KeyVersionMismatch {
+                expected: pinned.to_string(),
+                actual,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// `TrustScoreVerifier` over proof envelopes, from `ZkSystem::trust_score_verifier`
+pub struct EnvelopeVerifier<'a>(TrustScoreVerifier<'a>);
+
+impl EnvelopeVerifier<'_> {
+    /// Feed a trust score proof envelope in (see `TrustScoreVerifier::verify`)
+    pub fn verify(&mut self, envelope: &[u8], threshold: u64, expected_result: bool) -> Result<bool, ZkError> {
+        self.0.verify(trust_score_proof(envelope)?, threshold, expected_result)
+    }
+
+    /// Whether every envelope fed in verified
+    pub fn finalize(self) -> bool {
+        self.0.finalize()
+    }
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use super::*;
+    use crate::circuits::optimizations::performance::estimate_proof_size_bytes;
+
+    #[test]
+    fn test_prove_and_verify() {
+        let system = init_system(TRUST_SCORE_K).unwrap();
+
+        let proof = system.prove_trust_score(65, 70).unwrap();
+        assert!(system.verify_trust_score(&proof, 70, false).unwrap());
+        assert!(!system.verify_trust_score(&proof, 70, true).unwrap());
+
+        assert!(matches!(system.prove_trust_score(500, 70), Err(ZkError::InvalidInput(_))));
+        assert!(matches!(system.verify_trust_score(&proof[1..], 70, false), Err(ZkError::Serialization(_))));
+    }
+
+    #[test]
+    fn test_cancel_stops_batch_between_proofs() {
+        let system = init_system(TRUST_SCORE_K).unwrap();
+        let inputs = [(85, 70), (65, 70), (90, 70), (40, 70)];
+        let cancel = CancellationToken::new();
+
+        let mut proofs = Vec::new();
+        for proof in system.prove_trust_scores_cancellable(&inputs, &cancel) {
+            proofs.push(proof);
+            if proofs.len() == 2 {
+                cancel.clone().cancel();
+            }
+        }
+
+        // Two proofs finished, then the run ended with a single cancellation
+        assert_eq!(proofs.len(), 3);
+        assert!(system.verify_trust_score(proofs[0].as_ref().unwrap(), 70, true).unwrap());
+        assert!(system.verify_trust_score(proofs[1].as_ref().unwrap(), 70, false).unwrap());
+        assert_eq!(proofs[2], Err(ZkError::Cancelled));
+
+        // A token cancelled up front proves nothing
+        let proofs: Vec<_> = system.prove_trust_scores_cancellable(&inputs, &cancel).collect();
+        assert_eq!(proofs, vec![Err(ZkError::Cancelled)]);
+    }
+
+    #[test]
+    fn test_short_proof_rejected() {
+        let system = init_system(TRUST_SCORE_K).unwrap();
+
+        for proof in [vec![], vec![0; 3]] {
+            assert!(matches!(system.verify_trust_score(&proof, 70, true), Err(ZkError::InvalidInput(_))));
+            assert!(matches!(system.verify_proof_raw(&proof, &[]), Err(ZkError::InvalidInput(_))));
+        }
+
+        // A header with no proof after it is caught before the transcript is read
+        let empty = encode_proof(CircuitTag::TrustScore, &[]);
+        assert!(matches!(system.verify_trust_score(&empty, 70, true), Err(ZkError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_verify_proof_raw() {
+        use crate::prover::encode_instance_column;
+        use pasta_curves::Fp;
+
+        let system = init_system(TRUST_SCORE_K).unwrap();
+        let proof = system.prove_trust_score(85, 70).unwrap();
+
+        let instance = |result: u64, threshold: u64| vec![encode_instance_column(&[Fp::from(result), Fp::from(threshold)])];
+        assert!(system.verify_proof_raw(&proof, &instance(1, 70)).unwrap());
+        assert!(!system.verify_proof_raw(&proof, &instance(0, 70)).unwrap());
+        assert!(!system.verify_proof_raw(&proof, &instance(1, 60)).unwrap());
+        assert!(matches!(system.verify_proof_raw(&proof, &[vec![0; 33]]), Err(ZkError::Serialization(_))));
+
+        // A result outside 0 and 1 is rejected before the transcript is read
+        let err = system.verify_proof_raw(&proof, &instance(2, 70)).unwrap_err();
+        assert_eq!(err, ZkError::InvalidInput("result must be 0 or 1".into()));
+    }
+
+    #[test]
+    fn test_systems_are_independent() {
+        let system = init_system(TRUST_SCORE_K).unwrap();
+        let other = init_system(TRUST_SCORE_K + 1).unwrap();
+
+        let proof = other.prove_trust_score(85, 70).unwrap();
+        assert!(other.verify_trust_score(&proof, 70, true).unwrap());
+        assert!(!system.verify_trust_score(&proof, 70, true).unwrap());
+        assert!(matches!(system.check_fingerprint(&other.verifying_key_fingerprint()), Err(ZkError::KeyVersionMismatch { .. })));
+    }
+
+    #[test]
+    fn test_batch_verification() {
+        let system = init_system(TRUST_SCORE_K).unwrap();
+        let above = system.prove_trust_score(85, 70).unwrap();
+        let below = system.prove_trust_score(65, 70).unwrap();
+
+        let claim = |proof, expected_result| TrustScoreClaim { proof, threshold: 70, expected_result };
+        let claims = [claim(&above, true), claim(&below, false), claim(&below, true), claim(&above[..4], true)];
+        assert_eq!(system.verify_trust_scores_batch(&claims).unwrap(), vec![true, true, false, false]);
+    }
+
+    #[test]
+    fn test_accumulated_envelopes() {
+        let system = init_system(TRUST_SCORE_K).unwrap();
+        let above = system.prove_trust_score(85, 70).unwrap();
+        let below = system.prove_trust_score(65, 70).unwrap();
+
+        let mut verifier = system.trust_score_verifier(VerifyStrategy::Accumulate);
+        for (envelope, expected_result) in [(&above, true), (&below, false), (&above, true)] {
+            assert!(verifier.verify(envelope, 70, expected_result).unwrap());
+        }
+        assert!(verifier.finalize());
+
+        let mut verifier = system.trust_score_verifier(VerifyStrategy::Accumulate);
+        for (envelope, expected_result) in [(&above, true), (&below, true), (&above, true)] {
+            verifier.verify(envelope, 70, expected_result).unwrap();
+        }
+        assert!(!verifier.finalize());
+
+        let mut verifier = system.trust_score_verifier(VerifyStrategy::Single);
+        assert!(matches!(verifier.verify(&above[..4], 70, true), Err(ZkError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_proof_size_estimate() {
+        for k in [TRUST_SCORE_K, TRUST_SCORE_K + 4] {
+            let measured = init_system(k).unwrap().measure_proof_size_bytes().unwrap();
+            let estimated = estimate_proof_size_bytes(k, 1);
+            assert!(estimated * 2 > measured && measured * 2 > estimated, "k = {}: estimated {} bytes, measured {}", k, estimated, measured);
+        }
+    }
+
+    #[test]
+    fn test_circuit_size_validation() {
+        assert_eq!(check_circuit_size(TRUST_SCORE_K), Ok(TRUST_SCORE_K));
+        assert_eq!(check_circuit_size(MAX_K), Ok(MAX_K));
+        assert!(matches!(init_system(TRUST_SCORE_K - 1), Err(ZkError::InvalidInput(_))));
+        assert!(matches!(init_system(MAX_K + 1), Err(ZkError::InvalidInput(_))));
+    }
+}
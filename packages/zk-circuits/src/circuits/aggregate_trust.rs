@@ -0,0 +1,300 @@
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Selector},
+    poly::Rotation,
+};
+use ff::PrimeField;
+use std::marker::PhantomData;
+
+use super::AssignedCell;
+use super::lending::known;
+use super::loan_history::field_to_u64_truncating;
+use super::trust_score::{TrustScoreChip, TrustScoreConfig};
+
+/// Configuration for the aggregate trust score circuit
+#[derive(Clone, Debug)]
+pub struct AggregateTrustConfig {
+    /// Advice column for each statement's comparison result
+    pub result: Column<Advice>,
+    /// Running count of passing statements, ending in the total passed
+    pub passed: Column<Advice>,
+    /// Selector for the per-statement counting gate
+    pub count_selector: Selector,
+    /// Selector for the first counting row (the count starts at zero)
+    pub start_selector: Selector,
+    /// Comparison shared with the trust score circuit, used once per statement
+    pub comparison: TrustScoreConfig,
+}
+
+/// Cells assigned by the aggregate check
+pub struct AssignedAggregateTrust<F: PrimeField> {
+    /// Threshold cells, in statement order
+    pub thresholds: Vec<AssignedCell<F>>,
+    /// Number of statements whose score meets its threshold
+    pub passed: AssignedCell<F>,
+}
+
+/// Chip counting how many trust score statements pass
+pub struct AggregateTrustChip<F: PrimeField> {
+    config: AggregateTrustConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: PrimeField> AggregateTrustChip<F> {
+    pub fn construct(config: AggregateTrustConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 2],
+        comparison: TrustScoreConfig,
+    ) -> AggregateTrustConfig {
+        let [result, passed] = advice;
+        let count_selector = meta.selector();
+        let start_selector = meta.selector();
+
+        meta.enable_equality(result);
+        meta.enable_equality(passed);
+
+        // Each row adds one comparison result (already boolean) to the count
+        meta.create_gate("aggregate_trust_count", |meta| {
+            let s = meta.query_selector(count_selector);
+            let result = meta.query_advice(result, Rotation::cur());
+            let passed_next = meta.query_advice(passed, Rotation::next());
+            let passed = meta.query_advice(passed, Rotation::cur());
+
+            vec![s * (passed_next - passed - result)]
+        });
+
+        meta.create_gate("aggregate_trust_count_start", |meta| {
+            let s = meta.query_selector(start_selector);
+            let passed = meta.query_advice(passed, Rotation::cur());
+
+            vec![s * passed]
+        });
+
+        AggregateTrustConfig {
+            result,
+            passed,
+            count_selector,
+            start_selector,
+            comparison,
+        }
+    }
+
+    /// Compare each `(trust_score, threshold)` statement and count the passes
+    pub fn assign_aggregate(
+        &self,
+        mut layouter: impl Layouter<F>,
+        statements: &[(Value<F>, Value<F>)],
+    ) -> Result<AssignedAggregateTrust<F>, Error> {
+        let comparison = TrustScoreChip::construct(self.config.comparison.clone());
+        let compared = statements
+            .iter()
+            .enumerate()
+            .map(|(i, &(trust_score, threshold))| {
+                comparison.assign_comparison(layouter.namespace(|| format!("statement {}", i)), trust_score, threshold)
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        let passed = layouter.assign_region(
+            || "aggregate trust count",
+            |mut region| {
+                self.config.start_selector.enable(&mut region, 0)?;
+
+                let mut passed = Value::known(F::ZERO);
+                for (i, statement) in compared.iter().enumerate() {
+                    self.config.count_selector.enable(&mut region, i)?;
+
+                    region.assign_advice(|| "passed", self.config.passed, i, || passed)?;
+                    let result = statement.result.copy_advice(|| "result", &mut region, self.config.result, i)?;
+                    passed = passed + result.value().copied();
+                }
+
+                region.assign_advice(|| "passed", self.config.passed, compared.len(), || passed)
+            },
+        )?;
+
+        Ok(AssignedAggregateTrust {
+            thresholds: compared.into_iter().map(|statement| statement.threshold).collect(),
+            passed,
+        })
+    }
+}
+
+/// Circuit proving how many of N trust scores meet their thresholds
+///
+/// Lets a pool operator attest in one proof that, say, 97 of 100 borrowers
+/// passed, without revealing any score or which borrowers failed. The
+/// statements are re-proved with the trust score comparison rather than by
+/// verifying each borrower's proof in-circuit, so the operator needs the
+/// scores as witnesses. Each threshold is public so a statement cannot be
+/// compared against a lower one. Instance layout:
+/// `[passed, threshold_0, .., threshold_{N-1}]`.
+#[derive(Clone, Debug)]
+pub struct AggregateTrustCircuit<F: PrimeField> {
+    /// `(trust_score, threshold)` for each statement; the scores are private
+    /// and the thresholds public
+    pub statements: Vec<(Value<F>, Value<F>)>,
+}
+
+impl<F: PrimeField> AggregateTrustCircuit<F> {
+    pub fn new(trust_scores: Option<Vec<u64>>, thresholds: &[u64]) -> Self {
+        let trust_scores = match trust_scores {
+            Some(scores) => {
+                assert_eq!(scores.len(), thresholds.len(), "Each trust score needs a threshold");
+                scores.iter().map(|&score| Value::known(F::from(score))).collect()
+            }
+            None => vec![Value::unknown(); thresholds.len()],
+        };
+
+        Self {
+            statements: trust_scores
+                .into_iter()
+                .zip(thresholds)
+                .map(|(score, &threshold)| (score, Value::known(F::from(threshold))))
+                .collect(),
+        }
+    }
+
+    /// Public inputs `[passed, threshold_0, ..]`, with the count computed from
+    /// the known trust scores (0 without a witness)
+    pub fn expected_public_inputs(&self) -> Vec<F> {
+        let thresholds: Vec<u64> = self
+            .statements
+            .iter()
+            .map(|&(_, threshold)| field_to_u64_truncating(&known(threshold).unwrap_or(F::ZERO)))
+            .collect();
+        let scores: Option<Vec<u64>> = self
+            .statements
+            .iter()
+            .map(|&(score, _)| known(score).map(|score| field_to_u64_truncating(&score)))
+            .collect();
+        let passed = scores.map_or(0, |scores| utils::count_passed(&scores, &thresholds));
+
+        utils::public_inputs(passed, &thresholds)
+    }
+}
+
+impl<F: PrimeField> Circuit<F> for AggregateTrustCircuit<F> {
+    type Config = AggregateTrustConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            statements: self.statements.iter().map(|&(_, threshold)| (Value::unknown(), threshold)).collect(),
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let trust_score = meta.advice_column();
+        let threshold = meta.advice_column();
+        let result = meta.advice_column();
+        let decomposition = meta.advice_column();
+        let bounds = [(); 2].map(|_| meta.advice_column());
+        let instance = meta.instance_column();
+        let comparison = TrustScoreChip::configure(meta, trust_score, threshold, result, decomposition, bounds, instance);
+
+        let advice = [(); 2].map(|_| meta.advice_column());
+        AggregateTrustChip::configure(meta, advice, comparison)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = AggregateTrustChip::construct(config.clone());
+
+        let assigned = chip.assign_aggregate(layouter.namespace(|| "aggregate trust"), &self.statements)?;
+
+        let instance = config.comparison.instance;
+        layouter.constrain_instance(assigned.passed.cell(), instance, 0)?;
+        for (i, threshold) in assigned.thresholds.iter().enumerate() {
+            layouter.constrain_instance(threshold.cell(), instance, 1 + i)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Utility functions for aggregate trust score proofs
+pub mod utils {
+    use ff::PrimeField;
+
+    /// Number of scores that meet the threshold at the same position
+    pub fn count_passed(trust_scores: &[u64], thresholds: &[u64]) -> u64 {
+        trust_scores
+            .iter()
+            .zip(thresholds)
+            .filter(|(score, threshold)| score >= threshold)
+            .count() as u64
+    }
+
+    /// Build the public inputs in the circuit's instance order:
+    /// `[passed, threshold_0, .., threshold_{N-1}]`
+    pub fn public_inputs<F: PrimeField>(passed: u64, thresholds: &[u64]) -> Vec<F> {
+        std::iter::once(F::from(passed))
+            .chain(thresholds.iter().map(|&threshold| F::from(threshold)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::utils::*;
+    use halo2_proofs::dev::MockProver;
+    use pasta_curves::Fp;
+
+    const K: u32 = 6;
+
+    fn run(trust_scores: Vec<u64>, thresholds: &[u64], passed: u64) -> MockProver<Fp> {
+        let circuit = AggregateTrustCircuit::<Fp>::new(Some(trust_scores), thresholds);
+        MockProver::run(K, &circuit, vec![public_inputs(passed, thresholds)]).unwrap()
+    }
+
+    #[test]
+    fn test_one_of_two_passes() {
+        let thresholds = [70, 70];
+        assert_eq!(count_passed(&[85, 65], &thresholds), 1);
+
+        run(vec![85, 65], &thresholds, 1).assert_satisfied();
+        for wrong in [0, 2] {
+            assert!(run(vec![85, 65], &thresholds, wrong).verify().is_err());
+        }
+    }
+
+    #[test]
+    fn test_scores_at_threshold_pass() {
+        let thresholds = [60, 70, 80];
+        run(vec![60, 70, 80], &thresholds, 3).assert_satisfied();
+        run(vec![59, 69, 79], &thresholds, 0).assert_satisfied();
+    }
+
+    #[test]
+    fn test_thresholds_bound_to_instance() {
+        let circuit = AggregateTrustCircuit::<Fp>::new(Some(vec![85, 65]), &[70, 70]);
+        assert_eq!(circuit.expected_public_inputs(), public_inputs(1, &[70, 70]));
+
+        // Claiming both passed against a lowered second threshold is rejected
+        let prover = MockProver::run(K, &circuit, vec![public_inputs(2, &[70, 60])]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_circuit_without_witnesses() {
+        use crate::circuits::lending::assert_keygen;
+
+        let circuit = AggregateTrustCircuit::<Fp>::new(None, &[70, 80]);
+        let circuit_without_witnesses = circuit.without_witnesses();
+
+        assert_eq!(circuit_without_witnesses.statements.len(), 2);
+        assert_eq!(circuit_without_witnesses.expected_public_inputs(), public_inputs(0, &[70, 80]));
+        assert_keygen(K, &circuit_without_witnesses);
+    }
+}
@@ -0,0 +1,302 @@
+use halo2_gadgets::poseidon::{
+    primitives::{P128Pow5T3, Spec},
+    Pow5Config,
+};
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error},
+};
+use ff::PrimeField;
+use std::marker::PhantomData;
+
+use super::AssignedCell;
+use super::lending::{known, InstanceKind, InstanceSpec, LendingCircuit};
+use super::loan_history::field_to_u64_truncating;
+use super::hash::{poseidon2, HashChip};
+use super::trust_score::{TrustScoreChip, TrustScoreConfig};
+
+/// Configuration for the committed trust score circuit
+#[derive(Clone, Debug)]
+pub struct CommittedTrustScoreConfig<F: PrimeField> {
+    /// Advice column for the commitment blinding factor (private input)
+    pub blinding: Column<Advice>,
+    /// Comparison shared with the trust score circuit
+    pub comparison: TrustScoreConfig,
+    /// Poseidon configuration used to open the score commitment
+    pub poseidon: Pow5Config<F, 3, 2>,
+}
+
+/// Cells assigned by the committed comparison
+pub struct AssignedCommittedTrustScore<F: PrimeField> {
+    pub threshold: AssignedCell<F>,
+    pub result: AssignedCell<F>,
+    /// `Poseidon(trust_score, blinding)` over the compared score
+    pub score_commitment: AssignedCell<F>,
+}
+
+/// Chip comparing a trust score and committing to the same score
+pub struct CommittedTrustScoreChip<F: PrimeField> {
+    config: CommittedTrustScoreConfig<F>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: PrimeField> CommittedTrustScoreChip<F>
+where
+    P128Pow5T3: Spec<F, 3, 2>,
+{
+    pub fn construct(config: CommittedTrustScoreConfig<F>) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        blinding: Column<Advice>,
+        comparison: TrustScoreConfig,
+        poseidon: Pow5Config<F, 3, 2>,
+    ) -> CommittedTrustScoreConfig<F> {
+        meta.enable_equality(blinding);
+
+        CommittedTrustScoreConfig {
+            blinding,
+            comparison,
+            poseidon,
+        }
+    }
+
+    /// Compare `trust_score` against `threshold` and commit to the score
+    ///
+    /// The commitment hashes the comparison's own score cell, so it opens to
+    /// exactly the score that was compared.
+    pub fn assign_committed_comparison(
+        &self,
+        mut layouter: impl Layouter<F>,
+        trust_score: Value<F>,
+        blinding: Value<F>,
+        threshold: Value<F>,
+    ) -> Result<AssignedCommittedTrustScore<F>, Error> {
+        let comparison = TrustScoreChip::construct(self.config.comparison.clone());
+        let compared = comparison.assign_comparison(layouter.namespace(|| "trust score comparison"), trust_score, threshold)?;
+
+        let blinding = layouter.assign_region(
+            || "blinding",
+            |mut region| region.assign_advice(|| "blinding", self.config.blinding, 0, || blinding),
+        )?;
+
+        let score_commitment = poseidon2(
+            &self.config.poseidon,
+            layouter.namespace(|| "score commitment"),
+            compared.trust_score,
+            blinding,
+        )?;
+
+        Ok(AssignedCommittedTrustScore {
+            threshold: compared.threshold,
+            result: compared.result,
+            score_commitment,
+        })
+    }
+}
+
+/// Circuit proving a committed trust score clears a threshold
+///
+/// The trust score circuit with the score also bound to a public commitment
+/// `Poseidon(trust_score, blinding)`, such as one recorded alongside the
+/// borrower's identity. Other proofs that open the same commitment are then
+/// known to be about the same score, without revealing it. Instance layout:
+/// `[result, threshold, score_commitment]`.
+#[derive(Clone, Debug)]
+pub struct CommittedTrustScoreCircuit<F: PrimeField> {
+    /// Private input: the actual trust score
+    pub trust_score: Value<F>,
+    /// Private input: the blinding factor the commitment was made with
+    pub blinding: Value<F>,
+    /// Public input: the threshold to compare against
+    pub threshold: Value<F>,
+}
+
+impl<F: PrimeField> CommittedTrustScoreCircuit<F> {
+    pub fn new(trust_score: Option<u64>, blinding: Option<F>, threshold: u64) -> Self {
+        Self {
+            trust_score: trust_score.map(|score| Value::known(F::from(score))).unwrap_or_else(Value::unknown),
+            blinding: blinding.map(Value::known).unwrap_or_else(Value::unknown),
+            threshold: Value::known(F::from(threshold)),
+        }
+    }
+
+    /// Public inputs `[result, threshold, score_commitment]`, with the result
+    /// and commitment computed from the known witness (0 without one)
+    pub fn expected_public_inputs(&self) -> Vec<F>
+    where
+        P128Pow5T3: Spec<F, 3, 2>,
+    {
+        let threshold = field_to_u64_truncating(&known(self.threshold).unwrap_or(F::ZERO));
+        let trust_score = known(self.trust_score).map(|score| field_to_u64_truncating(&score));
+        let score_commitment = trust_score
+            .zip(known(self.blinding))
+            .map_or(F::ZERO, |(score, blinding)| utils::commit_score(score, blinding));
+
+        utils::public_inputs(trust_score.is_some_and(|score| score >= threshold), threshold, score_commitment)
+    }
+}
+
+impl<F: PrimeField> Circuit<F> for CommittedTrustScoreCircuit<F>
+where
+    P128Pow5T3: Spec<F, 3, 2>,
+{
+    type Config = CommittedTrustScoreConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            trust_score: Value::unknown(),
+            blinding: Value::unknown(),
+            threshold: self.threshold,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let trust_score = meta.advice_column();
+        let threshold = meta.advice_column();
+        let result = meta.advice_column();
+        let decomposition = meta.advice_column();
+        let bounds = [(); 2].map(|_| meta.advice_column());
+        let instance = meta.instance_column();
+        let comparison = TrustScoreChip::configure(meta, trust_score, threshold, result, decomposition, bounds, instance);
+
+        let blinding = meta.advice_column();
+        let poseidon = HashChip::configure_poseidon(meta);
+        CommittedTrustScoreChip::configure(meta, blinding, comparison, poseidon)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = CommittedTrustScoreChip::construct(config.clone());
+
+        let assigned = chip.assign_committed_comparison(
+            layouter.namespace(|| "committed trust score"),
+            self.trust_score,
+            self.blinding,
+            self.threshold,
+        )?;
+
+        let instance = config.comparison.instance;
+        layouter.constrain_instance(assigned.result.cell(), instance, 0)?;
+        layouter.constrain_instance(assigned.threshold.cell(), instance, 1)?;
+        layouter.constrain_instance(assigned.score_commitment.cell(), instance, 2)?;
+
+        Ok(())
+    }
+}
+
+impl<F: PrimeField> LendingCircuit<F> for CommittedTrustScoreCircuit<F>
+where
+    P128Pow5T3: Spec<F, 3, 2>,
+{
+    fn public_inputs(&self) -> Vec<F> {
+        self.expected_public_inputs()
+    }
+
+    fn recommended_k() -> u32 {
+        6
+    }
+
+    fn instance_layout() -> Vec<InstanceSpec> {
+        InstanceSpec::layout(&[
+            ("result", InstanceKind::Bool),
+            ("threshold", InstanceKind::Integer),
+            ("score_commitment", InstanceKind::Field),
+        ])
+    }
+}
+
+/// Utility functions for committed trust score proofs
+pub mod utils {
+    use ff::PrimeField;
+    use halo2_gadgets::poseidon::primitives::{P128Pow5T3, Spec};
+
+    use crate::circuits::hash::utils::hash2;
+
+    /// Commitment to a trust score, matching the in-circuit hash:
+    /// `Poseidon(trust_score, blinding)`
+    pub fn commit_score<F: PrimeField>(trust_score: u64, blinding: F) -> F
+    where
+        P128Pow5T3: Spec<F, 3, 2>,
+    {
+        hash2(F::from(trust_score), blinding)
+    }
+
+    /// Build the public inputs in the circuit's instance order:
+    /// `[result, threshold, score_commitment]`
+    pub fn public_inputs<F: PrimeField>(result: bool, threshold: u64, score_commitment: F) -> Vec<F> {
+        vec![if result { F::ONE } else { F::ZERO }, F::from(threshold), score_commitment]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::utils::*;
+    use halo2_proofs::dev::MockProver;
+    use pasta_curves::Fp;
+
+    const K: u32 = 6;
+
+    fn blinding() -> Fp {
+        Fp::from(424242u64)
+    }
+
+    #[test]
+    fn test_committed_score_above_threshold() {
+        let commitment = commit_score(85, blinding());
+        let circuit = CommittedTrustScoreCircuit::<Fp>::new(Some(85), Some(blinding()), 70);
+        assert_eq!(circuit.expected_public_inputs(), public_inputs(true, 70, commitment));
+
+        MockProver::run(K, &circuit, vec![public_inputs(true, 70, commitment)]).unwrap().assert_satisfied();
+        assert!(MockProver::run(K, &circuit, vec![public_inputs(false, 70, commitment)]).unwrap().verify().is_err());
+    }
+
+    #[test]
+    fn test_committed_score_below_threshold() {
+        let commitment = commit_score(65, blinding());
+        let circuit = CommittedTrustScoreCircuit::<Fp>::new(Some(65), Some(blinding()), 70);
+
+        MockProver::run(K, &circuit, vec![public_inputs(false, 70, commitment)]).unwrap().assert_satisfied();
+    }
+
+    #[test]
+    fn test_score_not_matching_commitment_rejected() {
+        // The borrower's recorded commitment is to 65, but they prove with 85
+        let commitment = commit_score(65, blinding());
+        let circuit = CommittedTrustScoreCircuit::<Fp>::new(Some(85), Some(blinding()), 70);
+        assert!(MockProver::run(K, &circuit, vec![public_inputs(true, 70, commitment)]).unwrap().verify().is_err());
+
+        // Nor does the right score open it under another blinding factor
+        let circuit = CommittedTrustScoreCircuit::<Fp>::new(Some(65), Some(blinding() + Fp::one()), 70);
+        assert!(MockProver::run(K, &circuit, vec![public_inputs(false, 70, commitment)]).unwrap().verify().is_err());
+    }
+
+    #[test]
+    fn test_every_instance_constrained() {
+        use crate::circuits::lending::assert_circuit;
+
+        let circuit = CommittedTrustScoreCircuit::<Fp>::new(Some(85), Some(blinding()), 70);
+        assert_circuit(K, &circuit, vec![public_inputs(true, 70, commit_score(85, blinding()))]);
+    }
+
+    #[test]
+    fn test_circuit_without_witnesses() {
+        use crate::circuits::lending::assert_keygen;
+
+        let circuit = CommittedTrustScoreCircuit::<Fp>::new(None, None, 70);
+        let circuit_without_witnesses = circuit.without_witnesses();
+
+        assert_eq!(circuit_without_witnesses.expected_public_inputs(), public_inputs(false, 70, Fp::zero()));
+        assert_keygen(K, &circuit_without_witnesses);
+    }
+}
@@ -0,0 +1,542 @@
+use halo2_gadgets::poseidon::primitives::{P128Pow5T3, Spec};
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance, Selector},
+    poly::Rotation,
+};
+use ff::PrimeField;
+
+use super::lending::{known, InstanceKind, InstanceSpec, LendingCircuit};
+use super::hash::HashChip;
+use super::income_range::{IncomeRangeChip, IncomeRangeCircuit, IncomeRangeConfig};
+use super::loan_history::{LoanHistoryChip, LoanHistoryCircuit, LoanHistoryConfig};
+use super::merkle::{utils::hash_pair, MerkleChip, MerkleConfig};
+use super::trust_score::{TrustScoreChip, TrustScoreCircuit, TrustScoreConfig};
+use crate::error::ZkError;
+
+/// Number of advice columns shared by the embedded chips
+const COMPOSITE_ADVICE_COLUMNS: usize = 15;
+
+/// Configuration for the composite lending circuit
+#[derive(Clone, Debug)]
+pub struct CompositeLendingConfig<F: PrimeField> {
+    pub trust_score: TrustScoreConfig,
+    pub income_range: IncomeRangeConfig,
+    pub loan_history: LoanHistoryConfig,
+    /// Poseidon hashing for the borrower commitment
+    pub merkle: MerkleConfig<F>,
+    /// Advice columns holding `[trust, income, loan, combined]` results
+    pub combine: [Column<Advice>; 4],
+    /// Instance column for public inputs/outputs
+    pub instance: Column<Instance>,
+    /// Selector for the result combination gate
+    pub combine_selector: Selector,
+}
+
+/// Circuit proving trust score, income range and loan history in one proof
+///
+/// All three checks are bound to the same borrower through the commitment
+/// `H(H(secret, score), H(H(income, loans), repayments))`, built from the very
+/// cells the sub-checks constrain, so the inputs cannot be mixed between
+/// borrowers. The individual results are private; only their conjunction is
+/// exposed. Instance layout:
+/// `[result, borrower_commitment, threshold, min_income, max_income, min_success_rate, min_loans]`.
+#[derive(Clone, Debug)]
+pub struct CompositeLendingCircuit<F: PrimeField> {
+    /// Private input: the borrower's identity secret
+    pub identity_secret: Value<F>,
+    pub trust_score: TrustScoreCircuit<F>,
+    pub income_range: IncomeRangeCircuit<F>,
+    pub loan_history: LoanHistoryCircuit<F>,
+}
+
+impl<F: PrimeField> CompositeLendingCircuit<F>
+where
+    P128Pow5T3: Spec<F, 3, 2>,
+{
+    /// Start a `LendingProofBuilder`
+    pub fn builder() -> LendingProofBuilder<F> {
+        LendingProofBuilder::default()
+    }
+
+    pub fn new(
+        identity_secret: Option<F>,
+        trust_score: TrustScoreCircuit<F>,
+        income_range: IncomeRangeCircuit<F>,
+        loan_history: LoanHistoryCircuit<F>,
+    ) -> Self {
+        Self {
+            identity_secret: identity_secret.map(Value::known).unwrap_or_else(Value::unknown),
+            trust_score,
+            income_range,
+            loan_history,
+        }
+    }
+
+    /// Public inputs in the instance order above, with the combined result
+    /// and borrower commitment computed from the known witness (0 without one)
+    pub fn expected_public_inputs(&self) -> Vec<F> {
+        let result = [
+            self.trust_score.expected_public_inputs()[0],
+            self.income_range.expected_public_inputs()[0],
+            self.loan_history.expected_public_inputs()[0],
+        ]
+        .iter()
+        .all(|&result| result == F::ONE);
+
+        let commitment = known(self.identity_secret)
+            .zip(known(self.trust_score.trust_score))
+            .zip(known(self.income_range.income))
+            .zip(known(self.loan_history.num_loans))
+            .zip(known(self.loan_history.successful_repayments))
+            .map(|((((secret, score), income), loans), repayments)| {
+                let identity = hash_pair(secret, score);
+                let history = hash_pair(hash_pair(income, loans), repayments);
+                hash_pair(identity, history)
+            })
+            .unwrap_or(F::ZERO);
+
+        let public = |value: Value<F>| known(value).unwrap_or(F::ZERO);
+        vec![
+            if result { F::ONE } else { F::ZERO },
+            commitment,
+            public(self.trust_score.threshold),
+            public(self.income_range.min_range),
+            public(self.income_range.max_range),
+            public(self.loan_history.min_success_rate),
+            public(self.loan_history.min_loans),
+        ]
+    }
+}
+
+/// Chainable construction of a `CompositeLendingCircuit`
+///
+/// Each sub-check is configured by name instead of by position. Checks left
+/// unconfigured get a pass-through witness that always succeeds (score 0 over
+/// threshold 0, income 0 in `[0, 0]`, one repaid loan over a zero minimum),
+/// so their public inputs are all zero. At least one check must be set.
+#[derive(Clone, Debug)]
+pub struct LendingProofBuilder<F: PrimeField> {
+    identity_secret: Option<F>,
+    trust_score: Option<TrustScoreCircuit<F>>,
+    income_range: Option<IncomeRangeCircuit<F>>,
+    loan_history: Option<LoanHistoryCircuit<F>>,
+}
+
+impl<F: PrimeField> Default for LendingProofBuilder<F> {
+    fn default() -> Self {
+        Self { identity_secret: None, trust_score: None, income_range: None, loan_history: None }
+    }
+}
+
+impl<F: PrimeField> LendingProofBuilder<F>
+where
+    P128Pow5T3: Spec<F, 3, 2>,
+{
+    /// The borrower's identity secret; left unset, the circuit has no
+    /// witness for it and is only usable for key generation
+    pub fn identity_secret(mut self, secret: F) -> Self {
+        self.identity_secret = Some(secret);
+        self
+    }
+
+    /// Require `score >= threshold`
+    pub fn trust_score(mut self, score: u64, threshold: u64) -> Self {
+        self.trust_score = Some(TrustScoreCircuit::new(Some(score), threshold));
+        self
+    }
+
+    /// Require `min <= income <= max`
+    pub fn income_range(mut self, income: u128, min: u128, max: u128) -> Self {
+        self.income_range = Some(IncomeRangeCircuit::new(Some(income), min, max));
+        self
+    }
+
+    /// Require a repayment rate of at least `min_rate` basis points, with no
+    /// minimum number of loans
+    pub fn loan_history(mut self, loans: u64, repaid: u64, min_rate: u64) -> Self {
+        self.loan_history = Some(LoanHistoryCircuit::new(Some(loans), Some(repaid), min_rate, 0));
+        self
+    }
+
+    /// Assemble the circuit, failing if no check was configured
+    pub fn build(self) -> Result<CompositeLendingCircuit<F>, ZkError> {
+        if self.trust_score.is_none() && self.income_range.is_none() && self.loan_history.is_none() {
+            return Err(ZkError::InvalidInput("A lending proof needs at least one configured check".into()));
+        }
+
+        Ok(CompositeLendingCircuit::new(
+            self.identity_secret,
+            self.trust_score.unwrap_or_else(|| TrustScoreCircuit::new(Some(0), 0)),
+            self.income_range.unwrap_or_else(|| IncomeRangeCircuit::new(Some(0), 0, 0)),
+            self.loan_history.unwrap_or_else(|| LoanHistoryCircuit::new(Some(1), Some(1), 0, 0)),
+        ))
+    }
+}
+
+impl<F: PrimeField> Circuit<F> for CompositeLendingCircuit<F>
+where
+    P128Pow5T3: Spec<F, 3, 2>,
+{
+    type Config = CompositeLendingConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            identity_secret: Value::unknown(),
+            trust_score: self.trust_score.without_witnesses(),
+            income_range: self.income_range.without_witnesses(),
+            loan_history: self.loan_history.without_witnesses(),
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        // The chips never share a region, so they can lay out over the same columns
+        let advice = [(); COMPOSITE_ADVICE_COLUMNS].map(|_| meta.advice_column());
+        let instance = meta.instance_column();
+        for column in advice {
+            meta.enable_equality(column);
+        }
+
+        let trust_score = TrustScoreChip::configure(meta, advice[0], advice[1], advice[2], advice[3], [advice[4], advice[5]], instance);
+        let income_range = IncomeRangeChip::configure(
+            meta,
+            advice[0],
+            advice[1],
+            advice[2],
+            advice[3],
+            [advice[4], advice[5], advice[6], advice[7], advice[8]],
+            instance,
+        );
+        let loan_history = LoanHistoryChip::configure(
+            meta,
+            advice[0],
+            advice[1],
+            advice[2],
+            advice[3],
+            advice[4],
+            advice[5],
+            [advice[6], advice[7], advice[8], advice[9], advice[10], advice[11], advice[12], advice[13], advice[14]],
+            instance,
+        );
+
+        let poseidon = HashChip::configure_poseidon(meta);
+        let merkle = MerkleChip::configure(meta, [advice[0], advice[1], advice[2], advice[3], advice[4]], poseidon);
+
+        let combine = [advice[0], advice[1], advice[2], advice[3]];
+        let combine_selector = meta.selector();
+
+        // The sub-results are already boolean, so their product is their conjunction
+        meta.create_gate("composite_result", |meta| {
+            let s = meta.query_selector(combine_selector);
+            let [trust, income, loan, combined] =
+                combine.map(|column| meta.query_advice(column, Rotation::cur()));
+
+            vec![s * (combined - trust * income * loan)]
+        });
+
+        CompositeLendingConfig {
+            trust_score,
+            income_range,
+            loan_history,
+            merkle,
+            combine,
+            instance,
+            combine_selector,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let trust = TrustScoreChip::construct(config.trust_score.clone()).assign_comparison(
+            layouter.namespace(|| "trust score comparison"),
+            self.trust_score.trust_score,
+            self.trust_score.threshold,
+        )?;
+        let income = IncomeRangeChip::construct(config.income_range.clone()).assign_range_check(
+            layouter.namespace(|| "income range check"),
+            self.income_range.income,
+            self.income_range.min_range,
+            self.income_range.max_range,
+        )?;
+        let loan = LoanHistoryChip::construct(config.loan_history.clone()).assign_loan_history_verification(
+            layouter.namespace(|| "loan history verification"),
+            self.loan_history.num_loans,
+            self.loan_history.successful_repayments,
+            self.loan_history.min_success_rate,
+            self.loan_history.min_loans,
+        )?;
+
+        // Borrower commitment over the cells the sub-checks used
+        let merkle_chip = MerkleChip::construct(config.merkle.clone());
+        let secret = layouter.assign_region(
+            || "identity secret",
+            |mut region| region.assign_advice(|| "identity secret", config.merkle.node, 0, || self.identity_secret),
+        )?;
+        let identity = merkle_chip.hash_pair(layouter.namespace(|| "identity"), secret, trust.trust_score.clone())?;
+        let history = merkle_chip.hash_pair(
+            layouter.namespace(|| "income and loans"),
+            income.income.clone(),
+            loan.num_loans.clone(),
+        )?;
+        let history = merkle_chip.hash_pair(
+            layouter.namespace(|| "repayments"),
+            history,
+            loan.successful_repayments.clone(),
+        )?;
+        let commitment = merkle_chip.hash_pair(layouter.namespace(|| "borrower commitment"), identity, history)?;
+
+        let combined = layouter.assign_region(
+            || "combine results",
+            |mut region| {
+                config.combine_selector.enable(&mut region, 0)?;
+
+                let [trust_col, income_col, loan_col, combined_col] = config.combine;
+                let t = trust.result.copy_advice(|| "trust result", &mut region, trust_col, 0)?;
+                let i = income.result.copy_advice(|| "income result", &mut region, income_col, 0)?;
+                let l = loan.result.copy_advice(|| "loan result", &mut region, loan_col, 0)?;
+
+                let combined = t.value().copied() * i.value().copied() * l.value().copied();
+                region.assign_advice(|| "combined result", combined_col, 0, || combined)
+            },
+        )?;
+
+        layouter.constrain_instance(combined.cell(), config.instance, 0)?;
+        layouter.constrain_instance(commitment.cell(), config.instance, 1)?;
+        layouter.constrain_instance(trust.threshold.cell(), config.instance, 2)?;
+        layouter.constrain_instance(income.min_range.cell(), config.instance, 3)?;
+        layouter.constrain_instance(income.max_range.cell(), config.instance, 4)?;
+        layouter.constrain_instance(loan.min_success_rate.cell(), config.instance, 5)?;
+        layouter.constrain_instance(loan.min_loans.cell(), config.instance, 6)?;
+
+        Ok(())
+    }
+}
+
+impl<F: PrimeField> LendingCircuit<F> for CompositeLendingCircuit<F>
+where
+    P128Pow5T3: Spec<F, 3, 2>,
+{
+    fn public_inputs(&self) -> Vec<F> {
+        self.expected_public_inputs()
+    }
+
+    fn recommended_k() -> u32 {
+        10
+    }
+
+    fn instance_layout() -> Vec<InstanceSpec> {
+        InstanceSpec::layout(&[
+            ("result", InstanceKind::Bool),
+            ("borrower_commitment", InstanceKind::Field),
+            ("threshold", InstanceKind::Integer),
+            ("min_income", InstanceKind::Integer),
+            ("max_income", InstanceKind::Integer),
+            ("min_success_rate", InstanceKind::Integer),
+            ("min_loans", InstanceKind::Integer),
+        ])
+    }
+}
+
+/// Utility functions for composite lending proofs
+pub mod utils {
+    use super::*;
+    use crate::circuits::merkle::utils::hash_pair;
+
+    /// Borrower commitment, matching the in-circuit hashing
+    pub fn borrower_commitment<F: PrimeField>(
+        identity_secret: F,
+        trust_score: u64,
+        income: u128,
+        num_loans: u64,
+        successful_repayments: u64,
+    ) -> F
+    where
+        P128Pow5T3: Spec<F, 3, 2>,
+    {
+        let identity = hash_pair(identity_secret, F::from(trust_score));
+        let history = hash_pair(F::from_u128(income), F::from(num_loans));
+        let history = hash_pair(history, F::from(successful_repayments));
+        hash_pair(identity, history)
+    }
+
+    /// Build the public inputs in the circuit's instance order
+    pub fn public_inputs<F: PrimeField>(
+        result: bool,
+        borrower_commitment: F,
+        threshold: u64,
+        min_income: u128,
+        max_income: u128,
+        min_success_rate: u64,
+        min_loans: u64,
+    ) -> Vec<F> {
+        vec![
+            if result { F::ONE } else { F::ZERO },
+            borrower_commitment,
+            F::from(threshold),
+            F::from_u128(min_income),
+            F::from_u128(max_income),
+            F::from(min_success_rate),
+            F::from(min_loans),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::utils::*;
+    use halo2_proofs::dev::MockProver;
+    use pasta_curves::Fp;
+
+    const K: u32 = 10;
+
+    fn secret() -> Fp {
+        Fp::from(424242u64)
+    }
+
+    fn run(score: u64, income: u128, loans: u64, repayments: u64, public_inputs: Vec<Fp>) -> MockProver<Fp> {
+        let circuit = CompositeLendingCircuit::new(
+            Some(secret()),
+            TrustScoreCircuit::new(Some(score), 70),
+            IncomeRangeCircuit::new(Some(income), 30_000, 80_000),
+            LoanHistoryCircuit::new(Some(loans), Some(repayments), 8_000, 3),
+        );
+        MockProver::run(K, &circuit, vec![public_inputs]).unwrap()
+    }
+
+    fn inputs(result: bool, score: u64, income: u128, loans: u64, repayments: u64) -> Vec<Fp> {
+        let commitment = borrower_commitment(secret(), score, income, loans, repayments);
+        public_inputs(result, commitment, 70, 30_000, 80_000, 8_000, 3)
+    }
+
+    #[test]
+    fn test_expected_public_inputs() {
+        for (score, income, result) in [(85, 50_000, true), (85, 95_000, false), (60, 50_000, false)] {
+            let circuit = CompositeLendingCircuit::new(
+                Some(secret()),
+                TrustScoreCircuit::new(Some(score), 70),
+                IncomeRangeCircuit::new(Some(income), 30_000, 80_000),
+                LoanHistoryCircuit::new(Some(10), Some(9), 8_000, 3),
+            );
+            let expected = circuit.expected_public_inputs();
+            assert_eq!(expected, inputs(result, score, income, 10, 9));
+            MockProver::run(K, &circuit, vec![expected]).unwrap().assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn test_all_checks_pass() {
+        let prover = run(85, 50_000, 10, 9, inputs(true, 85, 50_000, 10, 9));
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn test_one_failing_check_zeroes_result() {
+        // Income above the range; trust and loan history still pass
+        let prover = run(85, 95_000, 10, 9, inputs(false, 85, 95_000, 10, 9));
+        prover.assert_satisfied();
+
+        let prover = run(85, 95_000, 10, 9, inputs(true, 85, 95_000, 10, 9));
+        assert!(prover.verify().is_err());
+
+        // Loan history below the minimum success rate
+        let prover = run(85, 50_000, 10, 7, inputs(false, 85, 50_000, 10, 7));
+        prover.assert_satisfied();
+
+        // A perfect but too short loan history
+        let prover = run(85, 50_000, 2, 2, inputs(false, 85, 50_000, 2, 2));
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn test_commitment_binds_inputs() {
+        // A commitment to a different score cannot be reused
+        let prover = run(85, 50_000, 10, 9, inputs(true, 90, 50_000, 10, 9));
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_builder_trust_only() {
+        let circuit = CompositeLendingCircuit::builder()
+            .identity_secret(secret())
+            .trust_score(85, 70)
+            .build()
+            .unwrap();
+
+        let expected = circuit.expected_public_inputs();
+        let commitment = borrower_commitment(secret(), 85, 0, 1, 1);
+        assert_eq!(expected, public_inputs(true, commitment, 70, 0, 0, 0, 0));
+        MockProver::run(K, &circuit, vec![expected]).unwrap().assert_satisfied();
+
+        let failing = CompositeLendingCircuit::builder()
+            .identity_secret(secret())
+            .trust_score(60, 70)
+            .build()
+            .unwrap();
+        assert_eq!(failing.expected_public_inputs()[0], Fp::zero());
+        MockProver::run(K, &failing, vec![failing.expected_public_inputs()]).unwrap().assert_satisfied();
+    }
+
+    #[test]
+    fn test_builder_all_checks() {
+        let circuit = CompositeLendingCircuit::builder()
+            .identity_secret(secret())
+            .trust_score(85, 70)
+            .income_range(50_000, 30_000, 80_000)
+            .loan_history(10, 9, 8_000)
+            .build()
+            .unwrap();
+
+        let commitment = borrower_commitment(secret(), 85, 50_000, 10, 9);
+        let expected = public_inputs(true, commitment, 70, 30_000, 80_000, 8_000, 0);
+        assert_eq!(circuit.expected_public_inputs(), expected);
+        MockProver::run(K, &circuit, vec![expected]).unwrap().assert_satisfied();
+
+        // Order of the calls does not matter
+        let reordered = CompositeLendingCircuit::builder()
+            .loan_history(10, 9, 8_000)
+            .income_range(50_000, 30_000, 80_000)
+            .trust_score(85, 70)
+            .identity_secret(secret())
+            .build()
+            .unwrap();
+        assert_eq!(reordered.expected_public_inputs(), circuit.expected_public_inputs());
+    }
+
+    #[test]
+    fn test_fits_recommended_k() {
+        use crate::circuits::lending::run_mock;
+
+        let circuit = CompositeLendingCircuit::<Fp>::builder()
+            .identity_secret(secret())
+            .trust_score(85, 70)
+            .income_range(50_000, 30_000, 80_000)
+            .loan_history(10, 9, 8_000)
+            .build()
+            .unwrap();
+        run_mock(&circuit).unwrap();
+    }
+
+    #[test]
+    fn test_builder_requires_a_check() {
+        let result = CompositeLendingCircuit::<Fp>::builder().identity_secret(secret()).build();
+        assert!(matches!(result, Err(ZkError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_circuit_without_witnesses() {
+        use crate::circuits::lending::assert_keygen;
+
+        let circuit = CompositeLendingCircuit::new(
+            None,
+            TrustScoreCircuit::new(None, 70),
+            IncomeRangeCircuit::new(None, 30_000, 80_000),
+            LoanHistoryCircuit::new(None, None, 8_000, 3),
+        );
+        assert_keygen(K, &circuit.without_witnesses());
+    }
+}
@@ -0,0 +1,435 @@
+use halo2_gadgets::poseidon::primitives::{P128Pow5T3, Spec};
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Instance, Selector},
+    poly::Rotation,
+};
+use ff::PrimeField;
+use std::marker::PhantomData;
+
+use super::AssignedCell;
+use super::lending::{known, InstanceKind, InstanceSpec, LendingCircuit};
+use super::hash::{HashChip, HashConfig};
+
+/// Configuration for the distinct identities circuit
+#[derive(Clone, Debug)]
+pub struct DistinctIdentitiesConfig<F: PrimeField> {
+    /// Advice column for the identity hashes (private inputs); the comparison
+    /// holds the first in its first row and the second in the next
+    pub identity_hash: Column<Advice>,
+    /// Advice column for the commitment nonces (private inputs)
+    pub nonce: Column<Advice>,
+    /// Advice column for the commitments (public inputs)
+    pub commitment: Column<Advice>,
+    /// Advice column for the context both commitments are bound to (public input)
+    pub context_id: Column<Advice>,
+    /// Advice column for the inverse of the identity hash difference (zero if equal)
+    pub difference_inverse: Column<Advice>,
+    /// Advice column for the distinctness result
+    pub distinct: Column<Advice>,
+    /// Instance column for public inputs/outputs
+    pub instance: Column<Instance>,
+    /// Selector for the distinctness gate
+    pub selector: Selector,
+    /// Hash configuration used for the commitment openings
+    pub hash: HashConfig<F>,
+}
+
+/// Cells assigned by the distinctness check
+pub struct AssignedDistinctIdentities<F: PrimeField> {
+    pub commitments: [AssignedCell<F>; 2],
+    pub context_id: AssignedCell<F>,
+    pub distinct: AssignedCell<F>,
+}
+
+/// Chip proving two committed identities differ
+pub struct DistinctIdentitiesChip<F: PrimeField> {
+    config: DistinctIdentitiesConfig<F>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: PrimeField> DistinctIdentitiesChip<F>
+where
+    P128Pow5T3: Spec<F, 3, 2>,
+{
+    pub fn construct(config: DistinctIdentitiesConfig<F>) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        identity_hash: Column<Advice>,
+        nonce: Column<Advice>,
+        commitment: Column<Advice>,
+        context_id: Column<Advice>,
+        difference_inverse: Column<Advice>,
+        distinct: Column<Advice>,
+        hash: HashConfig<F>,
+        instance: Column<Instance>,
+    ) -> DistinctIdentitiesConfig<F> {
+        let selector = meta.selector();
+
+        meta.enable_equality(identity_hash);
+        meta.enable_equality(nonce);
+        meta.enable_equality(commitment);
+        meta.enable_equality(context_id);
+        meta.enable_equality(distinct);
+        meta.enable_equality(instance);
+
+        // distinct = 1 exactly when the identity hashes differ:
+        // distinct = diff * inv and diff * (1 - distinct) = 0 (so distinct is boolean)
+        meta.create_gate("distinct_identities", |meta| {
+            let s = meta.query_selector(selector);
+            let first = meta.query_advice(identity_hash, Rotation::cur());
+            let second = meta.query_advice(identity_hash, Rotation::next());
+            let inverse = meta.query_advice(difference_inverse, Rotation::cur());
+            let distinct = meta.query_advice(distinct, Rotation::cur());
+
+            let diff = first - second;
+
+            vec![
+                s.clone() * (distinct.clone() - diff.clone() * inverse),
+                s * (diff * (Expression::Constant(F::ONE) - distinct)),
+            ]
+        });
+
+        DistinctIdentitiesConfig {
+            identity_hash,
+            nonce,
+            commitment,
+            context_id,
+            difference_inverse,
+            distinct,
+            instance,
+            selector,
+            hash,
+        }
+    }
+
+    /// Open both commitments and compare the identities behind them
+    ///
+    /// Each commitment must equal
+    /// `Poseidon(Poseidon(identity_hash, context_id), nonce)` for the same
+    /// context, so neither identity hash can be swapped for another. The
+    /// result is 1 when the identity hashes differ and 0 when they are the
+    /// same identity, even under different nonces.
+    pub fn assign_distinct_identities(
+        &self,
+        mut layouter: impl Layouter<F>,
+        identity_hashes: [Value<F>; 2],
+        nonces: [Value<F>; 2],
+        commitments: [Value<F>; 2],
+        context_id: Value<F>,
+    ) -> Result<AssignedDistinctIdentities<F>, Error> {
+        let (identity_cells, nonce_cells, commitment_cells, context_id_cell) = layouter.assign_region(
+            || "identity inputs",
+            |mut region| {
+                // Assign identity hashes and nonces (private inputs)
+                let identity_cells = [0, 1].map(|row| {
+                    region.assign_advice(|| "identity hash", self.config.identity_hash, row, || identity_hashes[row])
+                });
+                let nonce_cells = [0, 1].map(|row| region.assign_advice(|| "nonce", self.config.nonce, row, || nonces[row]));
+
+                // Assign commitments and their shared context (public inputs)
+                let commitment_cells = [0, 1].map(|row| {
+                    region.assign_advice(|| "commitment", self.config.commitment, row, || commitments[row])
+                });
+                let context_id_cell = region.assign_advice(|| "context id", self.config.context_id, 0, || context_id)?;
+
+                let [identity_a, identity_b] = identity_cells;
+                let [nonce_a, nonce_b] = nonce_cells;
+                let [commitment_a, commitment_b] = commitment_cells;
+                Ok(([identity_a?, identity_b?], [nonce_a?, nonce_b?], [commitment_a?, commitment_b?], context_id_cell))
+            },
+        )?;
+
+        let hasher = HashChip::construct(self.config.hash.clone());
+        for (i, ((identity_hash, nonce), commitment)) in identity_cells.iter().zip(&nonce_cells).zip(&commitment_cells).enumerate() {
+            let in_context = hasher.hash2(
+                layouter.namespace(|| format!("identity {} in context", i)),
+                identity_hash.clone(),
+                context_id_cell.clone(),
+            )?;
+            let opening = hasher.hash2(layouter.namespace(|| format!("commitment {} opening", i)), in_context, nonce.clone())?;
+
+            layouter.assign_region(
+                || format!("bind commitment {}", i),
+                |mut region| region.constrain_equal(opening.cell(), commitment.cell()),
+            )?;
+        }
+
+        let distinct = layouter.assign_region(
+            || "distinct identities",
+            |mut region| {
+                self.config.selector.enable(&mut region, 0)?;
+
+                let [first, second] = &identity_cells;
+                let first = first.copy_advice(|| "first identity hash", &mut region, self.config.identity_hash, 0)?;
+                let second = second.copy_advice(|| "second identity hash", &mut region, self.config.identity_hash, 1)?;
+
+                let diff = first.value().copied() - second.value().copied();
+                region.assign_advice(
+                    || "difference inverse",
+                    self.config.difference_inverse,
+                    0,
+                    || diff.map(|d| d.invert().unwrap_or(F::ZERO)),
+                )?;
+
+                region.assign_advice(
+                    || "distinct",
+                    self.config.distinct,
+                    0,
+                    || diff.map(|d| if d == F::ZERO { F::ZERO } else { F::ONE }),
+                )
+            },
+        )?;
+
+        Ok(AssignedDistinctIdentities {
+            commitments: commitment_cells,
+            context_id: context_id_cell,
+            distinct,
+        })
+    }
+}
+
+/// Circuit proving two committed identities belong to different people
+///
+/// Used for co-signer checks: a borrower cannot co-sign their own loan under
+/// a second commitment, since recommitting with a fresh nonce leaves the
+/// identity hash unchanged. Both openings stay private and must be valid;
+/// only whether the identities differ is revealed. Identical identities are
+/// still provable, with `distinct = 0`.
+///
+/// Instance layout: `[distinct, commitment_a, commitment_b, context_id]`.
+#[derive(Clone, Debug)]
+pub struct DistinctIdentitiesCircuit<F: PrimeField> {
+    /// Private inputs: the identity hash behind each commitment
+    pub identity_hashes: [Value<F>; 2],
+    /// Private inputs: the nonce each commitment was created with
+    pub nonces: [Value<F>; 2],
+    /// Public inputs: the two commitments to compare
+    pub commitments: [Value<F>; 2],
+    /// Public input: the context both commitments were made for
+    pub context_id: Value<F>,
+}
+
+impl<F: PrimeField> DistinctIdentitiesCircuit<F> {
+    pub fn new(identity_hashes: [Option<F>; 2], nonces: [Option<F>; 2], commitments: [F; 2], context_id: u64) -> Self {
+        Self {
+            identity_hashes: identity_hashes.map(|hash| hash.map(Value::known).unwrap_or_else(Value::unknown)),
+            nonces: nonces.map(|nonce| nonce.map(Value::known).unwrap_or_else(Value::unknown)),
+            commitments: commitments.map(Value::known),
+            context_id: Value::known(F::from(context_id)),
+        }
+    }
+
+    /// Public inputs `[distinct, commitment_a, commitment_b, context_id]`,
+    /// with the result computed from the known witness (0 without one)
+    pub fn expected_public_inputs(&self) -> Vec<F> {
+        let [first, second] = self.identity_hashes.map(known);
+        let distinct = first.zip(second).is_some_and(|(first, second)| first != second);
+        let commitments = self.commitments.map(|commitment| known(commitment).unwrap_or(F::ZERO));
+
+        utils::public_inputs(distinct, commitments, known(self.context_id).unwrap_or(F::ZERO))
+    }
+}
+
+impl<F: PrimeField> Circuit<F> for DistinctIdentitiesCircuit<F>
+where
+    P128Pow5T3: Spec<F, 3, 2>,
+{
+    type Config = DistinctIdentitiesConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            identity_hashes: [Value::unknown(); 2],
+            nonces: [Value::unknown(); 2],
+            commitments: self.commitments,
+            context_id: self.context_id,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let identity_hash = meta.advice_column();
+        let nonce = meta.advice_column();
+        let commitment = meta.advice_column();
+        let context_id = meta.advice_column();
+        let difference_inverse = meta.advice_column();
+        let distinct = meta.advice_column();
+        let instance = meta.instance_column();
+        // The openings only hash pairs, so the nonce column can double as the
+        // hash chip's input count column
+        let poseidon = HashChip::configure_poseidon(meta);
+        let hash = HashChip::configure(meta, nonce, poseidon);
+
+        DistinctIdentitiesChip::configure(
+            meta,
+            identity_hash,
+            nonce,
+            commitment,
+            context_id,
+            difference_inverse,
+            distinct,
+            hash,
+            instance,
+        )
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = DistinctIdentitiesChip::construct(config.clone());
+
+        let assigned = chip.assign_distinct_identities(
+            layouter.namespace(|| "distinct identities"),
+            self.identity_hashes,
+            self.nonces,
+            self.commitments,
+            self.context_id,
+        )?;
+
+        // Expose the result (instance 0), both commitments (instances 1 and 2)
+        // and their context (instance 3)
+        layouter.constrain_instance(assigned.distinct.cell(), config.instance, 0)?;
+        for (i, commitment) in assigned.commitments.iter().enumerate() {
+            layouter.constrain_instance(commitment.cell(), config.instance, 1 + i)?;
+        }
+        layouter.constrain_instance(assigned.context_id.cell(), config.instance, 3)?;
+
+        Ok(())
+    }
+}
+
+impl<F: PrimeField> LendingCircuit<F> for DistinctIdentitiesCircuit<F>
+where
+    P128Pow5T3: Spec<F, 3, 2>,
+{
+    fn public_inputs(&self) -> Vec<F> {
+        self.expected_public_inputs()
+    }
+
+    fn recommended_k() -> u32 {
+        8
+    }
+
+    fn instance_layout() -> Vec<InstanceSpec> {
+        InstanceSpec::layout(&[
+            ("distinct", InstanceKind::Bool),
+            ("commitment_a", InstanceKind::Field),
+            ("commitment_b", InstanceKind::Field),
+            ("context_id", InstanceKind::Integer),
+        ])
+    }
+}
+
+/// Utility functions for distinct identity proofs
+pub mod utils {
+    use ff::PrimeField;
+
+    /// Build the public inputs in the circuit's instance order:
+    /// `[distinct, commitment_a, commitment_b, context_id]`
+    pub fn public_inputs<F: PrimeField>(distinct: bool, commitments: [F; 2], context_id: F) -> Vec<F> {
+        vec![if distinct { F::ONE } else { F::ZERO }, commitments[0], commitments[1], context_id]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::utils::public_inputs;
+    use crate::circuits::identity::utils::{commit, hash_identity};
+    use halo2_proofs::dev::MockProver;
+    use pasta_curves::Fp;
+
+    const K: u32 = 8;
+    const LENDING: u64 = 1;
+
+    fn borrower() -> (Fp, Fp) {
+        (hash_identity(b"borrower@example.com"), Fp::from(12345u64))
+    }
+
+    fn co_signer() -> (Fp, Fp) {
+        (hash_identity(b"cosigner@example.com"), Fp::from(67890u64))
+    }
+
+    fn circuit(first: (Fp, Fp), second: (Fp, Fp)) -> DistinctIdentitiesCircuit<Fp> {
+        DistinctIdentitiesCircuit::new(
+            [Some(first.0), Some(second.0)],
+            [Some(first.1), Some(second.1)],
+            [commit(first.0, first.1, LENDING), commit(second.0, second.1, LENDING)],
+            LENDING,
+        )
+    }
+
+    fn run(circuit: &DistinctIdentitiesCircuit<Fp>, distinct: bool) -> MockProver<Fp> {
+        let commitments = circuit.commitments.map(|commitment| known(commitment).unwrap());
+        MockProver::run(K, circuit, vec![public_inputs(distinct, commitments, Fp::from(LENDING))]).unwrap()
+    }
+
+    #[test]
+    fn test_distinct_identities() {
+        let circuit = circuit(borrower(), co_signer());
+
+        run(&circuit, true).assert_satisfied();
+        assert!(run(&circuit, false).verify().is_err());
+    }
+
+    #[test]
+    fn test_identical_identities() {
+        // Recommitting under a fresh nonce gives a new commitment for the same identity
+        let (identity_hash, _) = borrower();
+        let circuit = circuit(borrower(), (identity_hash, Fp::from(99999u64)));
+        assert_ne!(known(circuit.commitments[0]), known(circuit.commitments[1]));
+
+        run(&circuit, false).assert_satisfied();
+        assert!(run(&circuit, true).verify().is_err());
+    }
+
+    #[test]
+    fn test_wrong_opening_rejected() {
+        let (identity_hash, nonce) = borrower();
+
+        // A co-signer hash that does not open the second commitment
+        let mut swapped = circuit(borrower(), co_signer());
+        swapped.identity_hashes[1] = Value::known(hash_identity(b"someone-else@example.com"));
+        assert!(run(&swapped, true).verify().is_err());
+
+        // A commitment made for another context
+        let mut other_context = circuit(borrower(), co_signer());
+        other_context.commitments[0] = Value::known(commit(identity_hash, nonce, LENDING + 1));
+        assert!(run(&other_context, true).verify().is_err());
+    }
+
+    #[test]
+    fn test_every_instance_constrained() {
+        use crate::circuits::lending::assert_circuit;
+
+        let (identity_hash, _) = borrower();
+        for (circuit, distinct) in [
+            (circuit(borrower(), co_signer()), true),
+            (circuit(borrower(), (identity_hash, Fp::from(99999u64))), false),
+        ] {
+            let instances = circuit.expected_public_inputs();
+            assert_eq!(instances[0], if distinct { Fp::one() } else { Fp::zero() });
+            assert_circuit(K, &circuit, vec![instances]);
+        }
+    }
+
+    #[test]
+    fn test_circuit_without_witnesses() {
+        use crate::circuits::lending::assert_keygen;
+
+        let circuit = circuit(borrower(), co_signer());
+        let circuit_without_witnesses = circuit.without_witnesses();
+
+        assert_eq!(circuit_without_witnesses.expected_public_inputs()[0], Fp::zero());
+        assert_keygen(K, &circuit_without_witnesses);
+    }
+}
@@ -0,0 +1,284 @@
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Fixed, Instance, Selector},
+    poly::Rotation,
+};
+use ff::PrimeField;
+
+use super::lending::{known, InstanceKind, InstanceSpec, LendingCircuit};
+use super::income_range::{IncomeRangeChip, IncomeRangeCircuit, IncomeRangeConfig};
+use super::trust_score::{TrustScoreChip, TrustScoreCircuit, TrustScoreConfig};
+
+/// Number of advice columns shared by the embedded chips
+const ELIGIBILITY_ADVICE_COLUMNS: usize = 9;
+
+/// Configuration for the eligibility circuit
+#[derive(Clone, Debug)]
+pub struct EligibilityConfig {
+    pub trust_score: TrustScoreConfig,
+    pub income_range: IncomeRangeConfig,
+    /// Advice columns holding `[trust, income, eligible]` results
+    pub combine: [Column<Advice>; 3],
+    /// Fixed column holding the policy the threshold and range are pinned to
+    pub constants: Column<Fixed>,
+    /// Instance column for the eligibility result
+    pub instance: Column<Instance>,
+    /// Selector for the result combination gate
+    pub combine_selector: Selector,
+}
+
+/// Circuit proving a borrower clears a trust threshold with an income in range
+///
+/// The loan policy (threshold and income range) is pinned to constants in
+/// the circuit, so it is part of the verifying key rather than the public
+/// inputs: keys generated for one policy reject proofs made under another.
+/// Neither sub-result is revealed. Instance layout: `[eligible]`.
+#[derive(Clone, Debug)]
+pub struct EligibilityCircuit<F: PrimeField> {
+    pub trust_score: TrustScoreCircuit<F>,
+    pub income_range: IncomeRangeCircuit<F>,
+}
+
+impl<F: PrimeField> EligibilityCircuit<F> {
+    pub fn new(
+        trust_score: Option<u64>,
+        threshold: u64,
+        income: Option<u128>,
+        min_range: u128,
+        max_range: u128,
+    ) -> Self {
+        Self {
+            trust_score: TrustScoreCircuit::new(trust_score, threshold),
+            income_range: IncomeRangeCircuit::new(income, min_range, max_range),
+        }
+    }
+
+    /// Public inputs `[eligible]`, computed from the known witness (0 without one)
+    pub fn expected_public_inputs(&self) -> Vec<F> {
+        let trust_ok = self.trust_score.expected_public_inputs()[0] == F::ONE;
+        let income_in_range = self.income_range.expected_public_inputs()[0] == F::ONE;
+
+        utils::public_inputs(trust_ok && income_in_range)
+    }
+}
+
+impl<F: PrimeField> Circuit<F> for EligibilityCircuit<F> {
+    type Config = EligibilityConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            trust_score: self.trust_score.without_witnesses(),
+            income_range: self.income_range.without_witnesses(),
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        // The chips never share a region, so they can lay out over the same columns
+        let advice = [(); ELIGIBILITY_ADVICE_COLUMNS].map(|_| meta.advice_column());
+        let instance = meta.instance_column();
+        for column in advice {
+            meta.enable_equality(column);
+        }
+
+        let constants = meta.fixed_column();
+        meta.enable_constant(constants);
+
+        let trust_score = TrustScoreChip::configure(meta, advice[0], advice[1], advice[2], advice[3], [advice[4], advice[5]], instance);
+        let income_range = IncomeRangeChip::configure(
+            meta,
+            advice[0],
+            advice[1],
+            advice[2],
+            advice[3],
+            [advice[4], advice[5], advice[6], advice[7], advice[8]],
+            instance,
+        );
+
+        let combine = [advice[0], advice[1], advice[2]];
+        let combine_selector = meta.selector();
+
+        // Both sub-results are already boolean, so their product is their conjunction
+        meta.create_gate("eligibility_result", |meta| {
+            let s = meta.query_selector(combine_selector);
+            let [trust, income, eligible] = combine.map(|column| meta.query_advice(column, Rotation::cur()));
+
+            vec![s * (eligible - trust * income)]
+        });
+
+        EligibilityConfig {
+            trust_score,
+            income_range,
+            combine,
+            constants,
+            instance,
+            combine_selector,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let trust = TrustScoreChip::construct(config.trust_score.clone()).assign_comparison(
+            layouter.namespace(|| "trust score comparison"),
+            self.trust_score.trust_score,
+            self.trust_score.threshold,
+        )?;
+        let income = IncomeRangeChip::construct(config.income_range.clone()).assign_range_check(
+            layouter.namespace(|| "income range check"),
+            self.income_range.income,
+            self.income_range.min_range,
+            self.income_range.max_range,
+        )?;
+
+        // Pin the policy to constants; it is always known, even without witnesses
+        let policy = |value: Value<F>| known(value).ok_or(Error::Synthesis);
+        let (threshold, min_range, max_range) = (
+            policy(self.trust_score.threshold)?,
+            policy(self.income_range.min_range)?,
+            policy(self.income_range.max_range)?,
+        );
+        layouter.assign_region(
+            || "loan policy",
+            |mut region| {
+                region.constrain_constant(trust.threshold.cell(), threshold)?;
+                region.constrain_constant(income.min_range.cell(), min_range)?;
+                region.constrain_constant(income.max_range.cell(), max_range)
+            },
+        )?;
+
+        let eligible = layouter.assign_region(
+            || "combine results",
+            |mut region| {
+                config.combine_selector.enable(&mut region, 0)?;
+
+                let [trust_col, income_col, eligible_col] = config.combine;
+                let t = trust.result.copy_advice(|| "trust result", &mut region, trust_col, 0)?;
+                let i = income.result.copy_advice(|| "income result", &mut region, income_col, 0)?;
+
+                region.assign_advice(|| "eligible", eligible_col, 0, || t.value().copied() * i.value().copied())
+            },
+        )?;
+
+        layouter.constrain_instance(eligible.cell(), config.instance, 0)?;
+
+        Ok(())
+    }
+}
+
+impl<F: PrimeField> LendingCircuit<F> for EligibilityCircuit<F> {
+    fn public_inputs(&self) -> Vec<F> {
+        self.expected_public_inputs()
+    }
+
+    fn recommended_k() -> u32 {
+        8
+    }
+
+    fn instance_layout() -> Vec<InstanceSpec> {
+        InstanceSpec::layout(&[("eligible", InstanceKind::Bool)])
+    }
+}
+
+/// Utility functions for eligibility proofs
+pub mod utils {
+    use ff::PrimeField;
+
+    /// Build the public inputs in the circuit's instance order: `[eligible]`
+    pub fn public_inputs<F: PrimeField>(eligible: bool) -> Vec<F> {
+        vec![if eligible { F::ONE } else { F::ZERO }]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::utils::public_inputs;
+    use halo2_proofs::dev::MockProver;
+    use pasta_curves::Fp;
+
+    const K: u32 = 8;
+
+    fn run(trust_score: u64, income: u128, eligible: bool) -> MockProver<Fp> {
+        let circuit = EligibilityCircuit::<Fp>::new(Some(trust_score), 70, Some(income), 30_000, 80_000);
+        MockProver::run(K, &circuit, vec![public_inputs(eligible)]).unwrap()
+    }
+
+    #[test]
+    fn test_trust_pass_income_in_range() {
+        run(85, 50_000, true).assert_satisfied();
+        assert!(run(85, 50_000, false).verify().is_err());
+    }
+
+    #[test]
+    fn test_trust_pass_income_out_of_range() {
+        run(85, 90_000, false).assert_satisfied();
+        assert!(run(85, 90_000, true).verify().is_err());
+    }
+
+    #[test]
+    fn test_trust_fail_income_in_range() {
+        run(65, 50_000, false).assert_satisfied();
+        assert!(run(65, 50_000, true).verify().is_err());
+    }
+
+    #[test]
+    fn test_trust_fail_income_out_of_range() {
+        run(65, 20_000, false).assert_satisfied();
+        assert!(run(65, 20_000, true).verify().is_err());
+    }
+
+    #[test]
+    fn test_every_instance_constrained() {
+        use crate::circuits::lending::assert_circuit;
+
+        for (trust_score, income, eligible) in [(85, 50_000, true), (85, 90_000, false), (65, 50_000, false)] {
+            let circuit = EligibilityCircuit::<Fp>::new(Some(trust_score), 70, Some(income), 30_000, 80_000);
+            assert_eq!(circuit.expected_public_inputs(), public_inputs(eligible));
+            assert_circuit(K, &circuit, vec![public_inputs(eligible)]);
+        }
+    }
+
+    #[cfg(feature = "prover")]
+    #[test]
+    fn test_policy_bound_to_keys() {
+        use crate::envelope::CircuitTag;
+        use crate::prover::{prove_in_domain, verify_in_domain};
+        use halo2_proofs::{
+            plonk::{keygen_pk, keygen_vk},
+            poly::commitment::Params,
+        };
+        use pasta_curves::EqAffine;
+        use rand::rngs::OsRng;
+
+        let params = Params::<EqAffine>::new(K);
+        let policy = EligibilityCircuit::<Fp>::new(None, 70, None, 30_000, 80_000);
+        let vk = keygen_vk(&params, &policy).unwrap();
+        let pk = keygen_pk(&params, vk.clone(), &policy).unwrap();
+        // Eligibility proofs have no envelope tag of their own; any domain will do here
+        let prove = |circuit: EligibilityCircuit<Fp>| {
+            let instances = vec![public_inputs(true)];
+            let proof = prove_in_domain(CircuitTag::Composite, Vec::new(), &params, &pk, circuit, &instances, OsRng).unwrap();
+            verify_in_domain(CircuitTag::Composite, &params, &vk, &proof, &instances).unwrap()
+        };
+
+        assert!(prove(EligibilityCircuit::new(Some(85), 70, Some(50_000), 30_000, 80_000)));
+
+        // A borrower below the policy cannot pass by proving against a laxer one
+        assert!(!prove(EligibilityCircuit::new(Some(60), 50, Some(50_000), 30_000, 80_000)));
+        assert!(!prove(EligibilityCircuit::new(Some(85), 70, Some(90_000), 30_000, 100_000)));
+    }
+
+    #[test]
+    fn test_circuit_without_witnesses() {
+        use crate::circuits::lending::assert_keygen;
+
+        let circuit = EligibilityCircuit::<Fp>::new(None, 70, None, 30_000, 80_000);
+        let circuit_without_witnesses = circuit.without_witnesses();
+
+        assert_eq!(circuit_without_witnesses.expected_public_inputs(), public_inputs(false));
+        assert_keygen(K, &circuit_without_witnesses);
+    }
+}
@@ -0,0 +1,591 @@
+use halo2_gadgets::poseidon::primitives::{P128Pow5T3, Spec};
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Instance, Selector},
+    poly::Rotation,
+};
+use ff::PrimeField;
+use std::marker::PhantomData;
+
+use super::AssignedCell;
+use super::hash::HashChip;
+use super::lending::known;
+use super::loan_history::field_to_u64_truncating;
+use super::merkle::{MerkleChip, MerkleConfig};
+use super::optimizations::{RangeCheckChip, RangeCheckConfig};
+
+/// Bit width of the group balance, the loan amount and the required multiple
+pub const GROUP_FUND_VALUE_BITS: usize = 64;
+
+/// Bit width of the required coverage amount, `loan_amount * required_multiple`
+pub const GROUP_FUND_REQUIRED_BITS: usize = 2 * GROUP_FUND_VALUE_BITS;
+
+/// Configuration for the savings-group fund circuit
+#[derive(Clone, Debug)]
+pub struct GroupFundConfig<F: PrimeField> {
+    /// Advice column for the pooled group balance (private input)
+    pub group_balance: Column<Advice>,
+    /// Advice column for the balance commitment blinding factor (private input)
+    pub blinding: Column<Advice>,
+    /// Advice column for the requested loan amount (public input)
+    pub loan_amount: Column<Advice>,
+    /// Advice column for the required coverage multiple (public input)
+    pub required_multiple: Column<Advice>,
+    /// Advice column for the coverage result (1 if covered, 0 if not)
+    pub result: Column<Advice>,
+    /// Running sum decomposing `balance - loan * multiple + 2^128`
+    pub decomposition: Column<Advice>,
+    /// Instance column for public inputs/outputs
+    pub instance: Column<Instance>,
+    /// Selector for the coverage comparison gate
+    pub coverage_selector: Selector,
+    /// Selector for each step of the bit decomposition
+    pub bit_selector: Selector,
+    /// Range check bounding the private balance to 64 bits
+    pub range_check: RangeCheckConfig,
+    /// Merkle membership configuration (also provides Poseidon)
+    pub merkle: MerkleConfig<F>,
+}
+
+/// Chip comparing the pooled balance against the required loan coverage
+pub struct GroupFundChip<F: PrimeField> {
+    config: GroupFundConfig<F>,
+    _marker: PhantomData<F>,
+}
+
+/// Cells assigned by the coverage check
+pub struct AssignedCoverage<F: PrimeField> {
+    pub group_balance: AssignedCell<F>,
+    pub blinding: AssignedCell<F>,
+    pub loan_amount: AssignedCell<F>,
+    pub required_multiple: AssignedCell<F>,
+    pub result: AssignedCell<F>,
+}
+
+impl<F: PrimeField> GroupFundChip<F>
+where
+    P128Pow5T3: Spec<F, 3, 2>,
+{
+    pub fn construct(config: GroupFundConfig<F>) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 7],
+        merkle: MerkleConfig<F>,
+        instance: Column<Instance>,
+    ) -> GroupFundConfig<F> {
+        let [group_balance, blinding, loan_amount, required_multiple, result, decomposition, range_column] = advice;
+        let coverage_selector = meta.selector();
+        let bit_selector = meta.selector();
+        let range_check = RangeCheckChip::configure(meta, range_column, GROUP_FUND_VALUE_BITS);
+
+        // Enable equality constraints for public inputs/outputs
+        meta.enable_equality(group_balance);
+        meta.enable_equality(blinding);
+        meta.enable_equality(loan_amount);
+        meta.enable_equality(required_multiple);
+        meta.enable_equality(result);
+        meta.enable_equality(instance);
+
+        // Decompose diff = balance - loan * multiple + 2^N into N + 1 bits
+        // (most significant first), with N wide enough for the product of two
+        // 64-bit values. The top bit is set exactly when
+        // balance >= loan * multiple, so it is bound to `result`.
+        meta.create_gate("group_fund_coverage", |meta| {
+            let s = meta.query_selector(coverage_selector);
+            let balance = meta.query_advice(group_balance, Rotation::cur());
+            let loan = meta.query_advice(loan_amount, Rotation::cur());
+            let multiple = meta.query_advice(required_multiple, Rotation::cur());
+            let result = meta.query_advice(result, Rotation::cur());
+            let z_first = meta.query_advice(decomposition, Rotation::cur());
+            let msb = meta.query_advice(decomposition, Rotation::next());
+            let z_last = meta.query_advice(decomposition, Rotation((GROUP_FUND_REQUIRED_BITS + 1) as i32));
+
+            // 2^128 does not fit in a u128
+            let offset = Expression::Constant(F::from_u128(u128::MAX) + F::ONE);
+
+            vec![
+                // Ensure result is boolean (0 or 1)
+                s.clone() * (result.clone() * (result.clone() - Expression::Constant(F::ONE))),
+                s.clone() * z_first,
+                s.clone() * (msb - result),
+                s * (z_last - (balance - loan * multiple + offset)),
+            ]
+        });
+
+        meta.create_gate("group_fund_coverage_bit", |meta| {
+            let s = meta.query_selector(bit_selector);
+            let z_cur = meta.query_advice(decomposition, Rotation::cur());
+            let z_next = meta.query_advice(decomposition, Rotation::next());
+            let bit = z_next - z_cur * Expression::Constant(F::from(2));
+
+            vec![s * (bit.clone() * (bit - Expression::Constant(F::ONE)))]
+        });
+
+        GroupFundConfig {
+            group_balance,
+            blinding,
+            loan_amount,
+            required_multiple,
+            result,
+            decomposition,
+            instance,
+            coverage_selector,
+            bit_selector,
+            range_check,
+            merkle,
+        }
+    }
+
+    /// Assign the coverage comparison
+    ///
+    /// Range checks the private balance, so it cannot stand in for a negative
+    /// number in the comparison.
+    pub fn assign_coverage(
+        &self,
+        mut layouter: impl Layouter<F>,
+        group_balance: Value<F>,
+        blinding: Value<F>,
+        loan_amount: Value<F>,
+        required_multiple: Value<F>,
+    ) -> Result<AssignedCoverage<F>, Error> {
+        let assigned = layouter.assign_region(
+            || "group fund coverage",
+            |mut region| {
+                self.config.coverage_selector.enable(&mut region, 0)?;
+
+                let balance_cell = region.assign_advice(
+                    || "group balance",
+                    self.config.group_balance,
+                    0,
+                    || group_balance,
+                )?;
+                let blinding_cell = region.assign_advice(|| "blinding", self.config.blinding, 0, || blinding)?;
+                let loan_cell = region.assign_advice(|| "loan amount", self.config.loan_amount, 0, || loan_amount)?;
+                let multiple_cell = region.assign_advice(
+                    || "required multiple",
+                    self.config.required_multiple,
+                    0,
+                    || required_multiple,
+                )?;
+
+                // diff = balance - loan * multiple + 2^N, computed over integers
+                // as its top bit and its low N bits, since it needs N + 1 bits
+                let diff = group_balance
+                    .zip(loan_amount)
+                    .zip(required_multiple)
+                    .map(|((balance, loan), multiple)| {
+                        let balance = field_to_u64_truncating(&balance) as u128;
+                        let required = field_to_u64_truncating(&loan) as u128 * field_to_u64_truncating(&multiple) as u128;
+                        (balance >= required, balance.wrapping_sub(required))
+                    });
+
+                let num_bits = GROUP_FUND_REQUIRED_BITS + 1;
+                let mut z = Value::known(F::ZERO);
+                region.assign_advice(|| "z_0", self.config.decomposition, 0, || z)?;
+
+                let msb = diff.map(|(covered, _)| if covered { F::ONE } else { F::ZERO });
+                for i in 0..num_bits {
+                    self.config.bit_selector.enable(&mut region, i)?;
+                    let bit = if i == 0 {
+                        msb
+                    } else {
+                        diff.map(|(_, low)| F::from(((low >> (num_bits - 1 - i)) & 1) as u64))
+                    };
+                    z = z * Value::known(F::from(2)) + bit;
+                    region.assign_advice(|| format!("z_{}", i + 1), self.config.decomposition, i + 1, || z)?;
+                }
+
+                let result_cell = region.assign_advice(|| "coverage result", self.config.result, 0, || msb)?;
+
+                Ok(AssignedCoverage {
+                    group_balance: balance_cell,
+                    blinding: blinding_cell,
+                    loan_amount: loan_cell,
+                    required_multiple: multiple_cell,
+                    result: result_cell,
+                })
+            },
+        )?;
+
+        RangeCheckChip::construct(self.config.range_check.clone()).copy_range_check(
+            layouter.namespace(|| "group balance"),
+            &assigned.group_balance,
+            GROUP_FUND_VALUE_BITS,
+        )?;
+
+        Ok(assigned)
+    }
+}
+
+/// Circuit proving a borrower's savings group can cover a requested loan
+///
+/// The borrower proves that their identity commitment is a leaf of the group's
+/// Merkle membership tree, and that the group's pooled balance, hidden behind
+/// `Poseidon(balance, blinding)`, is at least `loan_amount * required_multiple`.
+/// Membership and the balance opening are hard constraints; coverage is exposed
+/// as a boolean. Instance layout:
+/// `[result, merkle_root, balance_commitment, loan_amount, required_multiple]`.
+#[derive(Clone, Debug)]
+pub struct GroupFundCircuit<F: PrimeField> {
+    /// Private input: the borrower's identity commitment (Merkle leaf)
+    pub member_leaf: Value<F>,
+    /// Private input: `(sibling, position)` pairs from leaf to root
+    pub merkle_path: Vec<(Value<F>, Value<F>)>,
+    /// Private input: the pooled group balance
+    pub group_balance: Value<F>,
+    /// Private input: blinding factor of the balance commitment
+    pub balance_blinding: Value<F>,
+    /// Public input: the requested loan amount
+    pub loan_amount: Value<F>,
+    /// Public input: how many times the pool must cover the loan
+    pub required_multiple: Value<F>,
+}
+
+impl<F: PrimeField> GroupFundCircuit<F>
+where
+    P128Pow5T3: Spec<F, 3, 2>,
+{
+    pub fn new(
+        member_leaf: Option<F>,
+        merkle_path: Option<Vec<(F, bool)>>,
+        depth: usize,
+        group_balance: Option<u64>,
+        balance_blinding: Option<F>,
+        loan_amount: u64,
+        required_multiple: u64,
+    ) -> Self {
+        let merkle_path = match merkle_path {
+            Some(path) => {
+                assert_eq!(path.len(), depth, "Merkle path length must match the tree depth");
+                super::merkle::utils::path_values(&path)
+            }
+            None => vec![(Value::unknown(), Value::unknown()); depth],
+        };
+
+        Self {
+            member_leaf: member_leaf.map(Value::known).unwrap_or_else(Value::unknown),
+            merkle_path,
+            group_balance: group_balance.map(|b| Value::known(F::from(b))).unwrap_or_else(Value::unknown),
+            balance_blinding: balance_blinding.map(Value::known).unwrap_or_else(Value::unknown),
+            loan_amount: Value::known(F::from(loan_amount)),
+            required_multiple: Value::known(F::from(required_multiple)),
+        }
+    }
+
+    /// Public inputs `[result, merkle_root, balance_commitment, loan_amount,
+    /// required_multiple]`, computed from the known witness (0 without one)
+    pub fn expected_public_inputs(&self) -> Vec<F> {
+        let loan_amount = known(self.loan_amount).map_or(0, |v| field_to_u64_truncating(&v));
+        let required_multiple = known(self.required_multiple).map_or(0, |v| field_to_u64_truncating(&v));
+        let balance = known(self.group_balance);
+
+        let covered = balance.is_some_and(|b| utils::is_covered(field_to_u64_truncating(&b), loan_amount, required_multiple));
+        let merkle_root = known(self.member_leaf)
+            .zip(super::merkle::utils::known_path(&self.merkle_path))
+            .map(|(leaf, path)| super::merkle::utils::compute_root(leaf, &path))
+            .unwrap_or(F::ZERO);
+        let commitment = balance
+            .zip(known(self.balance_blinding))
+            .map(|(balance, blinding)| super::merkle::utils::hash_pair(balance, blinding))
+            .unwrap_or(F::ZERO);
+
+        utils::public_inputs(covered, merkle_root, commitment, loan_amount, required_multiple)
+    }
+}
+
+impl<F: PrimeField> Circuit<F> for GroupFundCircuit<F>
+where
+    P128Pow5T3: Spec<F, 3, 2>,
+{
+    type Config = GroupFundConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            member_leaf: Value::unknown(),
+            merkle_path: vec![(Value::unknown(), Value::unknown()); self.merkle_path.len()],
+            group_balance: Value::unknown(),
+            balance_blinding: Value::unknown(),
+            loan_amount: self.loan_amount,
+            required_multiple: self.required_multiple,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let poseidon = HashChip::configure_poseidon(meta);
+        let merkle_advice = [(); 5].map(|_| meta.advice_column());
+        let merkle = MerkleChip::configure(meta, merkle_advice, poseidon);
+
+        let advice = [(); 7].map(|_| meta.advice_column());
+        let instance = meta.instance_column();
+
+        GroupFundChip::configure(meta, advice, merkle, instance)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = GroupFundChip::construct(config.clone());
+        let merkle_chip = MerkleChip::construct(config.merkle.clone());
+
+        let coverage = chip.assign_coverage(
+            layouter.namespace(|| "group fund coverage"),
+            self.group_balance,
+            self.balance_blinding,
+            self.loan_amount,
+            self.required_multiple,
+        )?;
+
+        // Membership: the leaf must hash up to the public root
+        let leaf = layouter.assign_region(
+            || "member leaf",
+            |mut region| region.assign_advice(|| "member leaf", config.merkle.node, 0, || self.member_leaf),
+        )?;
+        let root = merkle_chip.compute_root(layouter.namespace(|| "membership"), leaf, &self.merkle_path)?;
+
+        // Opening of the public balance commitment
+        let commitment = merkle_chip.hash_pair(
+            layouter.namespace(|| "balance commitment"),
+            coverage.group_balance.clone(),
+            coverage.blinding.clone(),
+        )?;
+
+        layouter.constrain_instance(coverage.result.cell(), config.instance, 0)?;
+        layouter.constrain_instance(root.cell(), config.instance, 1)?;
+        layouter.constrain_instance(commitment.cell(), config.instance, 2)?;
+        layouter.constrain_instance(coverage.loan_amount.cell(), config.instance, 3)?;
+        layouter.constrain_instance(coverage.required_multiple.cell(), config.instance, 4)?;
+
+        Ok(())
+    }
+}
+
+/// Utility functions for savings-group fund proofs
+pub mod utils {
+    use super::*;
+
+    /// Commitment to the pooled balance, matching the in-circuit opening
+    pub fn balance_commitment<F: PrimeField>(group_balance: u64, blinding: F) -> F
+    where
+        P128Pow5T3: Spec<F, 3, 2>,
+    {
+        super::super::merkle::utils::hash_pair(F::from(group_balance), blinding)
+    }
+
+    /// Whether the pool covers the loan by the required multiple
+    pub fn is_covered(group_balance: u64, loan_amount: u64, required_multiple: u64) -> bool {
+        group_balance as u128 >= loan_amount as u128 * required_multiple as u128
+    }
+
+    /// Build the public inputs in the circuit's instance order
+    pub fn public_inputs<F: PrimeField>(
+        covered: bool,
+        merkle_root: F,
+        balance_commitment: F,
+        loan_amount: u64,
+        required_multiple: u64,
+    ) -> Vec<F> {
+        vec![
+            if covered { F::ONE } else { F::ZERO },
+            merkle_root,
+            balance_commitment,
+            F::from(loan_amount),
+            F::from(required_multiple),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::utils::*;
+    use crate::circuits::merkle::utils::merkle_path;
+    use halo2_proofs::dev::MockProver;
+    use pasta_curves::Fp;
+
+    const K: u32 = 9;
+    const DEPTH: usize = 2;
+
+    fn members() -> Vec<Fp> {
+        (101..=104u64).map(Fp::from).collect()
+    }
+
+    fn run(
+        leaf: Fp,
+        path: Vec<(Fp, bool)>,
+        balance: u64,
+        loan: u64,
+        multiple: u64,
+        public_inputs: Vec<Fp>,
+    ) -> MockProver<Fp> {
+        let blinding = Fp::from(987654321u64);
+        let circuit = GroupFundCircuit::<Fp>::new(
+            Some(leaf),
+            Some(path),
+            DEPTH,
+            Some(balance),
+            Some(blinding),
+            loan,
+            multiple,
+        );
+        MockProver::run(K, &circuit, vec![public_inputs]).unwrap()
+    }
+
+    #[test]
+    fn test_expected_public_inputs() {
+        let (root, path) = merkle_path(&members(), 2);
+        let blinding = Fp::from(987654321u64);
+
+        for (balance, covered) in [(50_000, true), (25_000, false)] {
+            let circuit = GroupFundCircuit::<Fp>::new(
+                Some(members()[2]),
+                Some(path.clone()),
+                DEPTH,
+                Some(balance),
+                Some(blinding),
+                10_000,
+                3,
+            );
+            let expected = circuit.expected_public_inputs();
+            let commitment = balance_commitment(balance, blinding);
+            assert_eq!(expected, public_inputs(covered, root, commitment, 10_000, 3));
+            MockProver::run(K, &circuit, vec![expected]).unwrap().assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn test_member_with_sufficient_pool() {
+        let (root, path) = merkle_path(&members(), 1);
+        let commitment = balance_commitment(50_000, Fp::from(987654321u64));
+        assert!(is_covered(50_000, 10_000, 3));
+
+        let prover = run(members()[1], path, 50_000, 10_000, 3, public_inputs(true, root, commitment, 10_000, 3));
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn test_non_member_rejected() {
+        let (root, path) = merkle_path(&members(), 1);
+        let commitment = balance_commitment(50_000, Fp::from(987654321u64));
+
+        // An outsider reusing a member's path cannot reach the group root
+        let outsider = Fp::from(999u64);
+        let prover = run(outsider, path, 50_000, 10_000, 3, public_inputs(true, root, commitment, 10_000, 3));
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_insufficient_pool() {
+        let (root, path) = merkle_path(&members(), 3);
+        let commitment = balance_commitment(25_000, Fp::from(987654321u64));
+        assert!(!is_covered(25_000, 10_000, 3));
+
+        let prover = run(
+            members()[3],
+            path.clone(),
+            25_000,
+            10_000,
+            3,
+            public_inputs(true, root, commitment, 10_000, 3),
+        );
+        assert!(prover.verify().is_err());
+
+        // The honest outcome is a proof of non-coverage
+        let prover = run(members()[3], path, 25_000, 10_000, 3, public_inputs(false, root, commitment, 10_000, 3));
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn test_exact_coverage_and_wrong_commitment() {
+        let (root, path) = merkle_path(&members(), 0);
+        let commitment = balance_commitment(30_000, Fp::from(987654321u64));
+
+        let prover = run(
+            members()[0],
+            path.clone(),
+            30_000,
+            10_000,
+            3,
+            public_inputs(true, root, commitment, 10_000, 3),
+        );
+        prover.assert_satisfied();
+
+        // Claiming a different committed balance fails the opening
+        let other = balance_commitment(90_000, Fp::from(987654321u64));
+        let prover = run(members()[0], path, 30_000, 10_000, 3, public_inputs(true, root, other, 10_000, 3));
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_required_amount_beyond_64_bits() {
+        let (root, path) = merkle_path(&members(), 2);
+        let blinding = Fp::from(987654321u64);
+
+        // u64::MAX * 2 needs 65 bits; 1000 does not cover it
+        for (balance, loan, multiple, covered) in [
+            (1_000, u64::MAX, 2, false),
+            (u64::MAX, u64::MAX, u64::MAX, false),
+            (u64::MAX, 1 << 32, 1 << 32, false),
+            (u64::MAX, (1 << 32) - 1, (1 << 32) + 1, true),
+            (u64::MAX, u64::MAX, 1, true),
+        ] {
+            assert_eq!(is_covered(balance, loan, multiple), covered);
+            let circuit = GroupFundCircuit::<Fp>::new(
+                Some(members()[2]),
+                Some(path.clone()),
+                DEPTH,
+                Some(balance),
+                Some(blinding),
+                loan,
+                multiple,
+            );
+            let expected = circuit.expected_public_inputs();
+            assert_eq!(expected, public_inputs(covered, root, balance_commitment(balance, blinding), loan, multiple));
+            MockProver::run(K, &circuit, vec![expected.clone()]).unwrap().assert_satisfied();
+
+            // The opposite claim does not verify
+            let mut flipped = expected;
+            flipped[0] = Fp::one() - flipped[0];
+            assert!(MockProver::run(K, &circuit, vec![flipped]).unwrap().verify().is_err());
+        }
+    }
+
+    #[test]
+    fn test_negative_balance_rejected() {
+        use crate::circuits::lending::diagnose;
+
+        let (root, path) = merkle_path(&members(), 1);
+        let blinding = Fp::from(987654321u64);
+        let mut circuit = GroupFundCircuit::<Fp>::new(Some(members()[1]), Some(path), DEPTH, None, Some(blinding), 10_000, 3);
+        circuit.group_balance = Value::known(Fp::zero() - Fp::one());
+        let commitment = crate::circuits::merkle::utils::hash_pair(Fp::zero() - Fp::one(), blinding);
+
+        for covered in [true, false] {
+            let instance = public_inputs(covered, root, commitment, 10_000, 3);
+            assert!(MockProver::run(K, &circuit, vec![instance.clone()]).unwrap().verify().is_err());
+
+            // The balance's own range check flags it
+            let diagnostics = diagnose(K, &circuit, vec![instance]).unwrap();
+            assert!(diagnostics.failures.iter().any(|f| f.gate.as_deref() == Some("range_check_bit")), "{:?}", diagnostics.failures);
+        }
+    }
+
+    #[test]
+    fn test_circuit_without_witnesses() {
+        use crate::circuits::lending::assert_keygen;
+
+        let circuit = GroupFundCircuit::<Fp>::new(None, None, DEPTH, None, None, 10_000, 3);
+        let circuit_without_witnesses = circuit.without_witnesses();
+
+        assert_eq!(circuit_without_witnesses.merkle_path.len(), DEPTH);
+        assert_keygen(K, &circuit_without_witnesses);
+    }
+}
@@ -0,0 +1,225 @@
+use halo2_gadgets::poseidon::{
+    primitives::{ConstantLength, P128Pow5T3, Spec},
+    Hash, Pow5Chip, Pow5Config,
+};
+use halo2_proofs::{
+    circuit::Layouter,
+    plonk::{Advice, Column, ConstraintSystem, Error},
+};
+use ff::PrimeField;
+use std::marker::PhantomData;
+
+use super::AssignedCell;
+
+/// Configuration for the Poseidon hash chip
+#[derive(Clone, Debug)]
+pub struct HashConfig<F: PrimeField> {
+    /// Advice column for the input count `hash_many` starts from; may be
+    /// shared with other chips
+    pub length: Column<Advice>,
+    /// Poseidon configuration used for every hash
+    pub poseidon: Pow5Config<F, 3, 2>,
+}
+
+/// Chip hashing assigned cells with Poseidon, matching `utils`
+pub struct HashChip<F: PrimeField> {
+    config: HashConfig<F>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: PrimeField> HashChip<F>
+where
+    P128Pow5T3: Spec<F, 3, 2>,
+{
+    pub fn construct(config: HashConfig<F>) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Allocate the columns used by the Poseidon gadget
+    pub fn configure_poseidon(meta: &mut ConstraintSystem<F>) -> Pow5Config<F, 3, 2> {
+        let state = [(); 3].map(|_| meta.advice_column());
+        let partial_sbox = meta.advice_column();
+        let rc_a = [(); 3].map(|_| meta.fixed_column());
+        let rc_b = [(); 3].map(|_| meta.fixed_column());
+
+        // The sponge loads its initial capacity element as a constant, and
+        // `hash_many` its input count
+        meta.enable_constant(rc_b[0]);
+
+        Pow5Chip::configure::<P128Pow5T3>(meta, state, partial_sbox, rc_a, rc_b)
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        length: Column<Advice>,
+        poseidon: Pow5Config<F, 3, 2>,
+    ) -> HashConfig<F> {
+        meta.enable_equality(length);
+
+        HashConfig { length, poseidon }
+    }
+
+    /// `Poseidon(a, b)`, matching `utils::hash2`
+    pub fn hash2(
+        &self,
+        layouter: impl Layouter<F>,
+        a: AssignedCell<F>,
+        b: AssignedCell<F>,
+    ) -> Result<AssignedCell<F>, Error> {
+        poseidon2(&self.config.poseidon, layouter, a, b)
+    }
+
+    /// Hash any number of cells, matching `utils::poseidon_hash`
+    ///
+    /// The cells are chained through `hash2` starting from their count, so
+    /// inputs of different lengths cannot collide.
+    pub fn hash_many(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cells: &[AssignedCell<F>],
+    ) -> Result<AssignedCell<F>, Error> {
+        let length = layouter.assign_region(
+            || "input count",
+            |mut region| {
+                region.assign_advice_from_constant(|| "input count", self.config.length, 0, F::from(cells.len() as u64))
+            },
+        )?;
+
+        cells.iter().enumerate().try_fold(length, |acc, (i, cell)| {
+            self.hash2(layouter.namespace(|| format!("absorb {}", i)), acc, cell.clone())
+        })
+    }
+}
+
+/// `Poseidon(a, b)` with a bare Poseidon configuration, for chips that only
+/// hash pairs
+pub(crate) fn poseidon2<F: PrimeField>(
+    poseidon: &Pow5Config<F, 3, 2>,
+    mut layouter: impl Layouter<F>,
+    a: AssignedCell<F>,
+    b: AssignedCell<F>,
+) -> Result<AssignedCell<F>, Error>
+where
+    P128Pow5T3: Spec<F, 3, 2>,
+{
+    let hasher = Hash::<_, _, P128Pow5T3, ConstantLength<2>, 3, 2>::init(
+        Pow5Chip::construct(poseidon.clone()),
+        layouter.namespace(|| "init poseidon"),
+    )?;
+    hasher.hash(layouter.namespace(|| "poseidon"), [a, b])
+}
+
+/// Off-circuit Poseidon hashes matching `HashChip`
+pub mod utils {
+    use ff::PrimeField;
+    use halo2_gadgets::poseidon::primitives::{self as poseidon, ConstantLength, P128Pow5T3, Spec};
+
+    /// `Poseidon(a, b)`, matching `HashChip::hash2`
+    pub fn hash2<F: PrimeField>(a: F, b: F) -> F
+    where
+        P128Pow5T3: Spec<F, 3, 2>,
+    {
+        poseidon::Hash::<F, P128Pow5T3, ConstantLength<2>, 3, 2>::init().hash([a, b])
+    }
+
+    /// Hash of any number of field elements, matching `HashChip::hash_many`
+    pub fn poseidon_hash<F: PrimeField>(inputs: &[F]) -> F
+    where
+        P128Pow5T3: Spec<F, 3, 2>,
+    {
+        inputs.iter().fold(F::from(inputs.len() as u64), |acc, &input| hash2(acc, input))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::utils::*;
+    use halo2_proofs::{
+        circuit::{SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{Circuit, Instance},
+    };
+    use pasta_curves::Fp;
+
+    const K: u32 = 8;
+
+    /// Hashes its inputs both as a pair and all together
+    #[derive(Clone, Default)]
+    struct HashCircuit {
+        inputs: Vec<Fp>,
+    }
+
+    impl Circuit<Fp> for HashCircuit {
+        type Config = (HashConfig<Fp>, Column<Advice>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let input = meta.advice_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(input);
+            meta.enable_equality(instance);
+
+            let poseidon = HashChip::configure_poseidon(meta);
+            (HashChip::configure(meta, input, poseidon), input, instance)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let (config, input, instance) = config;
+            let chip = HashChip::construct(config);
+
+            let cells = layouter.assign_region(
+                || "inputs",
+                |mut region| {
+                    self.inputs
+                        .iter()
+                        .enumerate()
+                        .map(|(i, &value)| region.assign_advice(|| "input", input, i, || Value::known(value)))
+                        .collect::<Result<Vec<_>, _>>()
+                },
+            )?;
+
+            let pair = chip.hash2(layouter.namespace(|| "hash2"), cells[0].clone(), cells[1].clone())?;
+            let all = chip.hash_many(layouter.namespace(|| "hash_many"), &cells)?;
+            layouter.constrain_instance(pair.cell(), instance, 0)?;
+            layouter.constrain_instance(all.cell(), instance, 1)?;
+
+            Ok(())
+        }
+    }
+
+    fn run(inputs: &[u64], expected: Vec<Fp>) -> MockProver<Fp> {
+        let circuit = HashCircuit { inputs: inputs.iter().copied().map(Fp::from).collect() };
+        MockProver::run(K, &circuit, vec![expected]).unwrap()
+    }
+
+    #[test]
+    fn test_in_circuit_hashes_match() {
+        for inputs in [&[1u64, 2][..], &[1, 2, 3], &[85, 70, 0, 12345]] {
+            let fields: Vec<Fp> = inputs.iter().copied().map(Fp::from).collect();
+            run(inputs, vec![hash2(fields[0], fields[1]), poseidon_hash(&fields)]).assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn test_wrong_hash_rejected() {
+        let fields = [Fp::from(1), Fp::from(2), Fp::from(3)];
+        assert!(run(&[1, 2, 3], vec![hash2(fields[0], fields[1]), poseidon_hash(&fields[..2])]).verify().is_err());
+        assert!(run(&[1, 2, 3], vec![hash2(fields[1], fields[0]), poseidon_hash(&fields)]).verify().is_err());
+    }
+
+    #[test]
+    fn test_length_separates_inputs() {
+        let (a, b) = (Fp::from(1), Fp::from(2));
+        assert_ne!(poseidon_hash(&[a, b]), hash2(a, b));
+        assert_ne!(poseidon_hash(&[a, b, Fp::zero()]), poseidon_hash(&[a, b]));
+        assert_eq!(poseidon_hash::<Fp>(&[]), Fp::zero());
+    }
+}
@@ -0,0 +1,521 @@
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Instance, Selector},
+    poly::Rotation,
+};
+use ff::PrimeField;
+use std::marker::PhantomData;
+
+use super::AssignedCell;
+use super::lending::known;
+use super::loan_history::field_to_u64_truncating;
+
+/// Bit width used to prove the income lies inside the selected bucket
+pub const HISTOGRAM_VALUE_BITS: usize = 64;
+
+/// Configuration for the income histogram circuit
+#[derive(Clone, Debug)]
+pub struct IncomeHistogramConfig {
+    /// Advice column for the one-hot bucket bits (public outputs)
+    pub bucket_bit: Column<Advice>,
+    /// Advice column for the inclusive lower bound of each bucket (public input)
+    pub lower: Column<Advice>,
+    /// Advice column for the exclusive upper bound of each bucket (public input)
+    pub upper: Column<Advice>,
+    /// Running sum of `bit * lower`, ending in the selected bucket's lower bound
+    pub selected_lower: Column<Advice>,
+    /// Running sum of `bit * upper`, ending in the selected bucket's upper bound
+    pub selected_upper: Column<Advice>,
+    /// Running sum of the bucket bits, ending in the number of set bits
+    pub bit_count: Column<Advice>,
+    /// Advice column for the income (private input) and the two bound differences
+    pub income: Column<Advice>,
+    /// Advice column for the bit decomposition of the bound differences
+    pub decomposition: Column<Advice>,
+    /// Instance column for public inputs/outputs
+    pub instance: Column<Instance>,
+    /// Selector for the per-bucket accumulation gate
+    pub bucket_selector: Selector,
+    /// Selector for the first accumulation row (running sums start at zero)
+    pub start_selector: Selector,
+    /// Selector for the final row (exactly one bit set, income inside the bucket)
+    pub final_selector: Selector,
+    /// Selector for each step of the bit decomposition
+    pub bit_selector: Selector,
+    /// Selector for the first row of a decomposition (running sum starts at zero)
+    pub decomposition_start: Selector,
+}
+
+/// Chip proving that a private income falls into exactly one public bucket
+pub struct IncomeHistogramChip<F: PrimeField> {
+    config: IncomeHistogramConfig,
+    _marker: PhantomData<F>,
+}
+
+/// Cells produced by the bucket assignment
+pub struct AssignedBuckets<F: PrimeField> {
+    /// One-hot bucket bits, in bucket order
+    pub bits: Vec<AssignedCell<F>>,
+    /// `(lower, upper)` bound cells, in bucket order
+    pub bounds: Vec<(AssignedCell<F>, AssignedCell<F>)>,
+    /// `income - lower` for the selected bucket (must be range checked)
+    pub lower_diff: AssignedCell<F>,
+    /// `upper - 1 - income` for the selected bucket (must be range checked)
+    pub upper_diff: AssignedCell<F>,
+}
+
+impl<F: PrimeField> IncomeHistogramChip<F> {
+    pub fn construct(config: IncomeHistogramConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 8],
+        instance: Column<Instance>,
+    ) -> IncomeHistogramConfig {
+        let [bucket_bit, lower, upper, selected_lower, selected_upper, bit_count, income, decomposition] =
+            advice;
+
+        let bucket_selector = meta.selector();
+        let start_selector = meta.selector();
+        let final_selector = meta.selector();
+        let bit_selector = meta.selector();
+        let decomposition_start = meta.selector();
+
+        // Enable equality constraints for public inputs/outputs
+        meta.enable_equality(bucket_bit);
+        meta.enable_equality(lower);
+        meta.enable_equality(upper);
+        meta.enable_equality(income);
+        meta.enable_equality(decomposition);
+        meta.enable_equality(instance);
+
+        // Each bucket row folds `bit * bounds` into the running sums, so after the
+        // last bucket the sums hold the bounds of the single selected bucket
+        meta.create_gate("histogram_bucket_accumulation", |meta| {
+            let s = meta.query_selector(bucket_selector);
+            let bit = meta.query_advice(bucket_bit, Rotation::cur());
+            let lower = meta.query_advice(lower, Rotation::cur());
+            let upper = meta.query_advice(upper, Rotation::cur());
+            let sel_lower = meta.query_advice(selected_lower, Rotation::cur());
+            let sel_lower_next = meta.query_advice(selected_lower, Rotation::next());
+            let sel_upper = meta.query_advice(selected_upper, Rotation::cur());
+            let sel_upper_next = meta.query_advice(selected_upper, Rotation::next());
+            let count = meta.query_advice(bit_count, Rotation::cur());
+            let count_next = meta.query_advice(bit_count, Rotation::next());
+
+            vec![
+                // Ensure bit is boolean (0 or 1)
+                s.clone() * (bit.clone() * (bit.clone() - Expression::Constant(F::ONE))),
+                s.clone() * (sel_lower_next - sel_lower - bit.clone() * lower),
+                s.clone() * (sel_upper_next - sel_upper - bit.clone() * upper),
+                s * (count_next - count - bit),
+            ]
+        });
+
+        meta.create_gate("histogram_accumulation_start", |meta| {
+            let s = meta.query_selector(start_selector);
+            let sel_lower = meta.query_advice(selected_lower, Rotation::cur());
+            let sel_upper = meta.query_advice(selected_upper, Rotation::cur());
+            let count = meta.query_advice(bit_count, Rotation::cur());
+
+            vec![s.clone() * sel_lower, s.clone() * sel_upper, s * count]
+        });
+
+        // On the final row the income column holds the income, and the lower/upper
+        // columns hold `income - lower` and `upper - 1 - income`; both differences
+        // are range checked below, which proves `lower <= income < upper`
+        meta.create_gate("histogram_bucket_membership", |meta| {
+            let s = meta.query_selector(final_selector);
+            let income = meta.query_advice(income, Rotation::cur());
+            let lower_diff = meta.query_advice(lower, Rotation::cur());
+            let upper_diff = meta.query_advice(upper, Rotation::cur());
+            let sel_lower = meta.query_advice(selected_lower, Rotation::cur());
+            let sel_upper = meta.query_advice(selected_upper, Rotation::cur());
+            let count = meta.query_advice(bit_count, Rotation::cur());
+
+            vec![
+                // Exactly one bucket bit is set
+                s.clone() * (count - Expression::Constant(F::ONE)),
+                s.clone() * (lower_diff - (income.clone() - sel_lower)),
+                s * (upper_diff - (sel_upper - Expression::Constant(F::ONE) - income)),
+            ]
+        });
+
+        // Most-significant-bit-first running sum: z_{i+1} = 2 * z_i + b_i
+        meta.create_gate("histogram_range_bit", |meta| {
+            let s = meta.query_selector(bit_selector);
+            let z_cur = meta.query_advice(decomposition, Rotation::cur());
+            let z_next = meta.query_advice(decomposition, Rotation::next());
+            let bit = z_next - z_cur * Expression::Constant(F::from(2));
+
+            vec![s * (bit.clone() * (bit - Expression::Constant(F::ONE)))]
+        });
+
+        meta.create_gate("histogram_range_start", |meta| {
+            let s = meta.query_selector(decomposition_start);
+            let z = meta.query_advice(decomposition, Rotation::cur());
+
+            vec![s * z]
+        });
+
+        IncomeHistogramConfig {
+            bucket_bit,
+            lower,
+            upper,
+            selected_lower,
+            selected_upper,
+            bit_count,
+            income,
+            decomposition,
+            instance,
+            bucket_selector,
+            start_selector,
+            final_selector,
+            bit_selector,
+            decomposition_start,
+        }
+    }
+
+    /// Assign the bucket bits and bounds
+    pub fn assign_buckets(
+        &self,
+        mut layouter: impl Layouter<F>,
+        income: Value<F>,
+        buckets: &[(u64, u64)],
+    ) -> Result<AssignedBuckets<F>, Error> {
+        let selected = income.map(|inc| utils::bucket_index(field_to_u64_truncating(&inc), buckets));
+
+        layouter.assign_region(
+            || "histogram buckets",
+            |mut region| {
+                self.config.start_selector.enable(&mut region, 0)?;
+
+                let mut sel_lower = Value::known(F::ZERO);
+                let mut sel_upper = Value::known(F::ZERO);
+                let mut count = Value::known(F::ZERO);
+                let mut bits = Vec::with_capacity(buckets.len());
+                let mut bounds = Vec::with_capacity(buckets.len());
+
+                for (i, &(lower, upper)) in buckets.iter().enumerate() {
+                    self.config.bucket_selector.enable(&mut region, i)?;
+
+                    let bit = selected.map(|index| {
+                        if index == Some(i) {
+                            F::ONE
+                        } else {
+                            F::ZERO
+                        }
+                    });
+
+                    region.assign_advice(|| "selected lower", self.config.selected_lower, i, || sel_lower)?;
+                    region.assign_advice(|| "selected upper", self.config.selected_upper, i, || sel_upper)?;
+                    region.assign_advice(|| "bit count", self.config.bit_count, i, || count)?;
+
+                    let bit_cell = region.assign_advice(|| "bucket bit", self.config.bucket_bit, i, || bit)?;
+                    let lower_cell = region.assign_advice(
+                        || "bucket lower",
+                        self.config.lower,
+                        i,
+                        || Value::known(F::from(lower)),
+                    )?;
+                    let upper_cell = region.assign_advice(
+                        || "bucket upper",
+                        self.config.upper,
+                        i,
+                        || Value::known(F::from(upper)),
+                    )?;
+
+                    sel_lower = sel_lower + bit * Value::known(F::from(lower));
+                    sel_upper = sel_upper + bit * Value::known(F::from(upper));
+                    count = count + bit;
+
+                    bits.push(bit_cell);
+                    bounds.push((lower_cell, upper_cell));
+                }
+
+                // Final row: selected bounds and the differences to range check
+                let last = buckets.len();
+                self.config.final_selector.enable(&mut region, last)?;
+
+                region.assign_advice(|| "selected lower", self.config.selected_lower, last, || sel_lower)?;
+                region.assign_advice(|| "selected upper", self.config.selected_upper, last, || sel_upper)?;
+                region.assign_advice(|| "bit count", self.config.bit_count, last, || count)?;
+                region.assign_advice(|| "income", self.config.income, last, || income)?;
+
+                let lower_diff = region.assign_advice(
+                    || "income - lower",
+                    self.config.lower,
+                    last,
+                    || income - sel_lower,
+                )?;
+                let upper_diff = region.assign_advice(
+                    || "upper - 1 - income",
+                    self.config.upper,
+                    last,
+                    || sel_upper - Value::known(F::ONE) - income,
+                )?;
+
+                Ok(AssignedBuckets {
+                    bits,
+                    bounds,
+                    lower_diff,
+                    upper_diff,
+                })
+            },
+        )
+    }
+
+    /// Prove that an assigned value fits in `num_bits` bits
+    pub fn assign_range_check(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: &AssignedCell<F>,
+        num_bits: usize,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "histogram range check",
+            |mut region| {
+                self.config.decomposition_start.enable(&mut region, 0)?;
+
+                let bits = value.value().map(|v| {
+                    let repr = v.to_repr();
+                    let bytes = repr.as_ref();
+                    (0..num_bits)
+                        .rev()
+                        .map(|i| (bytes[i / 8] >> (i % 8)) & 1 == 1)
+                        .collect::<Vec<_>>()
+                });
+
+                let mut z = Value::known(F::ZERO);
+                region.assign_advice(|| "z_0", self.config.decomposition, 0, || z)?;
+
+                for i in 0..num_bits {
+                    self.config.bit_selector.enable(&mut region, i)?;
+                    let bit = bits.as_ref().map(|bits| if bits[i] { F::ONE } else { F::ZERO });
+                    z = z * Value::known(F::from(2)) + bit;
+
+                    let cell = region.assign_advice(
+                        || format!("z_{}", i + 1),
+                        self.config.decomposition,
+                        i + 1,
+                        || z,
+                    )?;
+
+                    if i + 1 == num_bits {
+                        region.constrain_equal(cell.cell(), value.cell())?;
+                    }
+                }
+
+                Ok(())
+            },
+        )
+    }
+}
+
+/// Circuit proving a private income lands in exactly one of K public bucket ranges
+///
+/// Buckets are half-open `[lower, upper)` ranges, so an income equal to a shared
+/// boundary lands in the upper bucket. The instance column is laid out as the
+/// one-hot bucket vector followed by each bucket's bounds:
+/// `[bit_0, .., bit_{K-1}, lower_0, upper_0, .., lower_{K-1}, upper_{K-1}]`.
+#[derive(Clone, Debug)]
+pub struct IncomeHistogramCircuit<F: PrimeField> {
+    /// Private input: the actual income
+    pub income: Value<F>,
+    /// Public input: the `[lower, upper)` range of each bucket
+    pub buckets: Vec<(u64, u64)>,
+}
+
+impl<F: PrimeField> IncomeHistogramCircuit<F> {
+    pub fn new(income: Option<u64>, buckets: Vec<(u64, u64)>) -> Self {
+        Self {
+            income: if let Some(inc) = income {
+                Value::known(F::from(inc))
+            } else {
+                Value::unknown()
+            },
+            buckets,
+        }
+    }
+
+    /// Public inputs: the one-hot bucket vector for the known income, then
+    /// the bucket bounds
+    pub fn expected_public_inputs(&self) -> Vec<F> {
+        let bucket = known(self.income).and_then(|income| utils::bucket_index(field_to_u64_truncating(&income), &self.buckets));
+
+        let mut inputs = utils::public_inputs(bucket.unwrap_or(0), &self.buckets);
+        if bucket.is_none() {
+            // Without a witness or a matching bucket the one-hot vector is all zero
+            inputs[..self.buckets.len()].fill(F::ZERO);
+        }
+        inputs
+    }
+}
+
+impl<F: PrimeField> Circuit<F> for IncomeHistogramCircuit<F> {
+    type Config = IncomeHistogramConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            income: Value::unknown(),
+            buckets: self.buckets.clone(),
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [(); 8].map(|_| meta.advice_column());
+        let instance = meta.instance_column();
+
+        IncomeHistogramChip::configure(meta, advice, instance)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = IncomeHistogramChip::construct(config.clone());
+
+        let assigned = chip.assign_buckets(
+            layouter.namespace(|| "histogram buckets"),
+            self.income,
+            &self.buckets,
+        )?;
+
+        chip.assign_range_check(
+            layouter.namespace(|| "income above lower bound"),
+            &assigned.lower_diff,
+            HISTOGRAM_VALUE_BITS,
+        )?;
+        chip.assign_range_check(
+            layouter.namespace(|| "income below upper bound"),
+            &assigned.upper_diff,
+            HISTOGRAM_VALUE_BITS,
+        )?;
+
+        // Expose the one-hot vector, then the bucket bounds
+        let num_buckets = self.buckets.len();
+        for (i, bit) in assigned.bits.iter().enumerate() {
+            layouter.constrain_instance(bit.cell(), config.instance, i)?;
+        }
+        for (i, (lower, upper)) in assigned.bounds.iter().enumerate() {
+            layouter.constrain_instance(lower.cell(), config.instance, num_buckets + 2 * i)?;
+            layouter.constrain_instance(upper.cell(), config.instance, num_buckets + 2 * i + 1)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Utility functions for income histograms
+pub mod utils {
+    use ff::PrimeField;
+
+    /// Index of the first `[lower, upper)` bucket containing `income`
+    pub fn bucket_index(income: u64, buckets: &[(u64, u64)]) -> Option<usize> {
+        buckets
+            .iter()
+            .position(|&(lower, upper)| lower <= income && income < upper)
+    }
+
+    /// Build the public inputs for a proof that the income is in `bucket`
+    pub fn public_inputs<F: PrimeField>(bucket: usize, buckets: &[(u64, u64)]) -> Vec<F> {
+        let one_hot = (0..buckets.len()).map(|i| if i == bucket { F::ONE } else { F::ZERO });
+        let bounds = buckets
+            .iter()
+            .flat_map(|&(lower, upper)| [F::from(lower), F::from(upper)]);
+
+        one_hot.chain(bounds).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::utils::*;
+    use halo2_proofs::dev::MockProver;
+    use pasta_curves::Fp;
+
+    const K: u32 = 8;
+
+    fn buckets() -> Vec<(u64, u64)> {
+        vec![(0, 30000), (30000, 60000), (60000, 1_000_000)]
+    }
+
+    #[test]
+    fn test_expected_public_inputs() {
+        for income in [12000u64, 30000, 999_999] {
+            let circuit = IncomeHistogramCircuit::<Fp>::new(Some(income), buckets());
+            let expected = circuit.expected_public_inputs();
+            assert_eq!(expected, public_inputs(bucket_index(income, &buckets()).unwrap(), &buckets()));
+            MockProver::run(K, &circuit, vec![expected]).unwrap().assert_satisfied();
+        }
+
+        // Without a witness no bucket is marked
+        let expected = IncomeHistogramCircuit::<Fp>::new(None, buckets()).expected_public_inputs();
+        assert!(expected[..3].iter().all(|&v| v == Fp::zero()));
+        assert_eq!(expected[3..], public_inputs::<Fp>(0, &buckets())[3..]);
+    }
+
+    #[test]
+    fn test_income_in_each_bucket() {
+        for (income, bucket) in [(12000u64, 0usize), (45000, 1), (95000, 2)] {
+            assert_eq!(bucket_index(income, &buckets()), Some(bucket));
+
+            let circuit = IncomeHistogramCircuit::<Fp>::new(Some(income), buckets());
+            let public_inputs = public_inputs::<Fp>(bucket, &buckets());
+
+            let prover = MockProver::run(K, &circuit, vec![public_inputs]).unwrap();
+            prover.assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn test_boundary_lands_in_upper_bucket() {
+        let circuit = IncomeHistogramCircuit::<Fp>::new(Some(30000), buckets());
+
+        let prover = MockProver::run(K, &circuit, vec![public_inputs::<Fp>(1, &buckets())]).unwrap();
+        prover.assert_satisfied();
+
+        let prover = MockProver::run(K, &circuit, vec![public_inputs::<Fp>(0, &buckets())]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_wrong_bucket_rejected() {
+        let circuit = IncomeHistogramCircuit::<Fp>::new(Some(45000), buckets());
+
+        for wrong in [0usize, 2] {
+            let prover = MockProver::run(K, &circuit, vec![public_inputs::<Fp>(wrong, &buckets())]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+    }
+
+    #[test]
+    fn test_income_outside_all_buckets() {
+        let income = 2_000_000u64;
+        assert_eq!(bucket_index(income, &buckets()), None);
+
+        // No bucket bit can be set honestly, so no one-hot vector is accepted
+        let circuit = IncomeHistogramCircuit::<Fp>::new(Some(income), buckets());
+        let prover = MockProver::run(K, &circuit, vec![public_inputs::<Fp>(2, &buckets())]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_circuit_without_witnesses() {
+        use crate::circuits::lending::assert_keygen;
+
+        let circuit = IncomeHistogramCircuit::<Fp>::new(None, buckets());
+        let circuit_without_witnesses = circuit.without_witnesses();
+
+        assert_eq!(circuit_without_witnesses.buckets, buckets());
+        assert_keygen(K, &circuit_without_witnesses);
+    }
+}
@@ -1,3 +1,4 @@
+use halo2_gadgets::poseidon::primitives::{P128Pow5T3, Spec};
 use halo2_proofs::{
     circuit::{Layouter, SimpleFloorPlanner, Value},
     plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Instance, Selector},
@@ -6,116 +7,211 @@ use halo2_proofs::{
 use ff::PrimeField;
 use std::marker::PhantomData;
 
+use super::AssignedCell;
+use super::lending::{known, InstanceKind, InstanceSpec, LendingCircuit};
+use super::hash::{HashChip, HashConfig};
+
 /// Configuration for the identity verification circuit
 #[derive(Clone, Debug)]
-pub struct IdentityConfig {
+pub struct IdentityConfig<F: PrimeField> {
     /// Advice column for the identity hash (private input)
     pub identity_hash: Column<Advice>,
     /// Advice column for the commitment (public input)
     pub commitment: Column<Advice>,
+    /// Advice column for the commitment nonce (private input)
+    pub nonce: Column<Advice>,
     /// Advice column for the verification result
     pub result: Column<Advice>,
+    /// Advice column for the nullifier domain separator (public input)
+    pub domain_separator: Column<Advice>,
+    /// Advice column for the context the commitment is bound to (public input)
+    pub context_id: Column<Advice>,
+    /// Advice column for the in-circuit opening
+    /// `Poseidon(Poseidon(identity_hash, context_id), nonce)`
+    pub opening: Column<Advice>,
+    /// Advice column for the inverse of `opening - commitment` (zero if equal)
+    pub difference_inverse: Column<Advice>,
     /// Instance column for public inputs/outputs
     pub instance: Column<Instance>,
     /// Selector for the identity verification gate
     pub selector: Selector,
+    /// Hash configuration used for the commitment opening and nullifier
+    pub hash: HashConfig<F>,
+}
+
+/// Cells assigned by the identity verification
+pub struct AssignedIdentity<F: PrimeField> {
+    pub identity_hash: AssignedCell<F>,
+    pub commitment: AssignedCell<F>,
+    pub domain_separator: AssignedCell<F>,
+    pub context_id: AssignedCell<F>,
+    /// The in-circuit opening, in the same row as the commitment
+    pub opening: AssignedCell<F>,
+    pub result: AssignedCell<F>,
+    pub nullifier: AssignedCell<F>,
 }
 
 /// Chip for identity verification operations
 pub struct IdentityChip<F: PrimeField> {
-    config: IdentityConfig,
+    config: IdentityConfig<F>,
     _marker: PhantomData<F>,
 }
 
-impl<F: PrimeField> IdentityChip<F> {
-    pub fn construct(config: IdentityConfig) -> Self {
+impl<F: PrimeField> IdentityChip<F>
+where
+    P128Pow5T3: Spec<F, 3, 2>,
+{
+    pub fn construct(config: IdentityConfig<F>) -> Self {
         Self {
             config,
             _marker: PhantomData,
         }
     }
 
+    /// `opening` holds the `[opening, difference_inverse]` columns of the
+    /// commitment check
+    #[allow(clippy::too_many_arguments)]
     pub fn configure(
         meta: &mut ConstraintSystem<F>,
         identity_hash: Column<Advice>,
         commitment: Column<Advice>,
+        nonce: Column<Advice>,
         result: Column<Advice>,
+        domain_separator: Column<Advice>,
+        context_id: Column<Advice>,
+        opening: [Column<Advice>; 2],
+        hash: HashConfig<F>,
         instance: Column<Instance>,
-    ) -> IdentityConfig {
+    ) -> IdentityConfig<F> {
+        let [opening, difference_inverse] = opening;
         let selector = meta.selector();
 
         // Enable equality constraints for public inputs/outputs
         meta.enable_equality(identity_hash);
         meta.enable_equality(commitment);
+        meta.enable_equality(nonce);
         meta.enable_equality(result);
+        meta.enable_equality(domain_separator);
+        meta.enable_equality(context_id);
+        meta.enable_equality(opening);
         meta.enable_equality(instance);
 
-        // Create the identity verification gate
-        // This gate checks if the identity hash matches the commitment
+        // result = 1 exactly when the opening equals the commitment:
+        // result = 1 - diff * inv and diff * result = 0 (so result is boolean)
         meta.create_gate("identity_verification", |meta| {
             let s = meta.query_selector(selector);
-            let _identity_hash = meta.query_advice(identity_hash, Rotation::cur());
-            let _commitment = meta.query_advice(commitment, Rotation::cur());
+            let opening = meta.query_advice(opening, Rotation::cur());
+            let commitment = meta.query_advice(commitment, Rotation::cur());
+            let inverse = meta.query_advice(difference_inverse, Rotation::cur());
             let result = meta.query_advice(result, Rotation::cur());
 
-            // For simplicity in this demo, we'll just ensure result is boolean
-            // A full implementation would include commitment scheme verification
+            let diff = opening - commitment;
+
             vec![
-                // Ensure result is boolean (0 or 1)
-                s * (result.clone() * (result - Expression::Constant(F::ONE))),
+                s.clone() * (result.clone() - (Expression::Constant(F::ONE) - diff.clone() * inverse)),
+                s * (diff * result),
             ]
         });
 
         IdentityConfig {
             identity_hash,
             commitment,
+            nonce,
             result,
+            domain_separator,
+            context_id,
+            opening,
+            difference_inverse,
             instance,
             selector,
+            hash,
         }
     }
 
-    /// Assign the identity verification
+    /// Open the commitment and derive the identity's nullifier
+    ///
+    /// The result is 1 when
+    /// `commitment == Poseidon(Poseidon(identity_hash, context_id), nonce)`, so
+    /// a commitment made for one context (e.g. lending) does not open in
+    /// another (e.g. governance). The nullifier is
+    /// `Poseidon(identity_hash, domain_separator)`: stable for an identity
+    /// within a domain, and unlinkable across domains.
     pub fn assign_identity_verification(
         &self,
         mut layouter: impl Layouter<F>,
         identity_hash: Value<F>,
+        nonce: Value<F>,
         commitment: Value<F>,
-    ) -> Result<AssignedCell<F>, Error> {
-        layouter.assign_region(
-            || "identity verification",
+        domain_separator: Value<F>,
+        context_id: Value<F>,
+    ) -> Result<AssignedIdentity<F>, Error> {
+        let (identity_hash_cell, nonce_cell, domain_separator_cell, context_id_cell) = layouter.assign_region(
+            || "identity inputs",
             |mut region| {
-                // Enable the selector
-                self.config.selector.enable(&mut region, 0)?;
-
                 // Assign identity hash (private input)
-                let _identity_hash_cell = region.assign_advice(
+                let identity_hash_cell = region.assign_advice(
                     || "identity hash",
                     self.config.identity_hash,
                     0,
                     || identity_hash,
                 )?;
 
+                // Assign commitment nonce (private input)
+                let nonce_cell = region.assign_advice(|| "nonce", self.config.nonce, 0, || nonce)?;
+
+                // Assign nullifier domain separator (public input)
+                let domain_separator_cell = region.assign_advice(
+                    || "domain separator",
+                    self.config.domain_separator,
+                    0,
+                    || domain_separator,
+                )?;
+
+                // Assign commitment context (public input)
+                let context_id_cell = region.assign_advice(|| "context id", self.config.context_id, 0, || context_id)?;
+
+                Ok((identity_hash_cell, nonce_cell, domain_separator_cell, context_id_cell))
+            },
+        )?;
+
+        let hasher = HashChip::construct(self.config.hash.clone());
+        let in_context = hasher.hash2(
+            layouter.namespace(|| "identity in context"),
+            identity_hash_cell.clone(),
+            context_id_cell.clone(),
+        )?;
+        let opening = hasher.hash2(layouter.namespace(|| "commitment opening"), in_context, nonce_cell)?;
+        let nullifier = hasher.hash2(
+            layouter.namespace(|| "nullifier"),
+            identity_hash_cell.clone(),
+            domain_separator_cell.clone(),
+        )?;
+
+        let (opening_cell, commitment_cell, result_cell) = layouter.assign_region(
+            || "identity verification",
+            |mut region| {
+                // Enable the selector
+                self.config.selector.enable(&mut region, 0)?;
+
+                let opening = opening.copy_advice(|| "opening", &mut region, self.config.opening, 0)?;
+
                 // Assign commitment (public input)
-                let _commitment_cell = region.assign_advice(
+                let commitment_cell = region.assign_advice(
                     || "commitment",
                     self.config.commitment,
                     0,
                     || commitment,
                 )?;
 
-                // Calculate and assign result
-                // In a real implementation, this would verify the commitment scheme
-                let result_value = identity_hash.zip(commitment).map(|(hash, comm)| {
-                    // Simple equality check for demonstration
-                    // In practice, this would be a more complex commitment verification
-                    if hash == comm {
-                        F::ONE
-                    } else {
-                        F::ZERO
-                    }
-                });
+                let diff = opening.value().copied() - commitment;
+                region.assign_advice(
+                    || "difference inverse",
+                    self.config.difference_inverse,
+                    0,
+                    || diff.map(|d| d.invert().unwrap_or(F::ZERO)),
+                )?;
 
+                let result_value = diff.map(|d| if d == F::ZERO { F::ONE } else { F::ZERO });
                 let result_cell = region.assign_advice(
                     || "verification result",
                     self.config.result,
@@ -123,60 +219,164 @@ impl<F: PrimeField> IdentityChip<F> {
                     || result_value,
                 )?;
 
-                Ok(result_cell)
+                Ok((opening, commitment_cell, result_cell))
             },
-        )
+        )?;
+
+        Ok(AssignedIdentity {
+            identity_hash: identity_hash_cell,
+            commitment: commitment_cell,
+            domain_separator: domain_separator_cell,
+            context_id: context_id_cell,
+            opening: opening_cell,
+            result: result_cell,
+            nullifier,
+        })
     }
 }
 
 /// The main identity verification circuit
+///
+/// Instance layout: `[result, nullifier, domain_separator, commitment,
+/// context_id]`. The domain separator is public so a verifier can reject
+/// nullifiers derived for another domain, and the context so it can reject
+/// commitments made for another platform context.
+///
+/// A circuit built with `new_bound` additionally requires the opening to equal
+/// the public commitment, so its proofs only exist for the identity behind a
+/// pre-agreed commitment and the result is always 1. Bound and unbound
+/// circuits have different verifying keys.
 #[derive(Clone, Debug)]
 pub struct IdentityCircuit<F: PrimeField> {
     /// Private input: the identity hash
     pub identity_hash: Value<F>,
+    /// Private input: the nonce the commitment was created with
+    pub nonce: Value<F>,
     /// Public input: the commitment to verify against
     pub commitment: Value<F>,
+    /// Public input: the domain the nullifier is scoped to
+    pub domain_separator: Value<F>,
+    /// Public input: the context the commitment was made for
+    pub context_id: Value<F>,
+    /// Whether the commitment must open, rather than the result reporting it
+    pub bound: bool,
 }
 
 impl<F: PrimeField> IdentityCircuit<F> {
-    pub fn new(identity_hash: Option<u64>, commitment: u64) -> Self {
+    pub fn new(identity_hash: Option<F>, nonce: Option<F>, commitment: F, domain_separator: u64, context_id: u64) -> Self {
         Self {
-            identity_hash: if let Some(hash) = identity_hash {
-                Value::known(F::from(hash))
-            } else {
-                Value::unknown()
-            },
-            commitment: Value::known(F::from(commitment)),
+            identity_hash: identity_hash.map(Value::known).unwrap_or_else(Value::unknown),
+            nonce: nonce.map(Value::known).unwrap_or_else(Value::unknown),
+            commitment: Value::known(commitment),
+            domain_separator: Value::known(F::from(domain_separator)),
+            context_id: Value::known(F::from(context_id)),
+            bound: false,
+        }
+    }
+
+    /// Create a circuit that only has proofs when the identity opens
+    /// `public_commitment`
+    ///
+    /// The raw identity hash stays private; the verifier binds the proof to
+    /// the identity through the commitment (instance 3).
+    pub fn new_bound(
+        identity_hash: Option<F>,
+        nonce: Option<F>,
+        public_commitment: F,
+        domain_separator: u64,
+        context_id: u64,
+    ) -> Self {
+        Self {
+            bound: true,
+            ..Self::new(identity_hash, nonce, public_commitment, domain_separator, context_id)
         }
     }
 
     /// Create a new circuit with field elements directly
-    pub fn new_with_fields(identity_hash: Value<F>, commitment: Value<F>) -> Self {
+    pub fn new_with_fields(
+        identity_hash: Value<F>,
+        nonce: Value<F>,
+        commitment: Value<F>,
+        domain_separator: Value<F>,
+        context_id: Value<F>,
+    ) -> Self {
         Self {
             identity_hash,
+            nonce,
             commitment,
+            domain_separator,
+            context_id,
+            bound: false,
         }
     }
+
+    /// Public inputs `[result, nullifier, domain_separator, commitment,
+    /// context_id]`, with the result and nullifier computed from the known
+    /// witness (0 without one)
+    pub fn expected_public_inputs(&self) -> Vec<F>
+    where
+        P128Pow5T3: Spec<F, 3, 2>,
+    {
+        let commitment = known(self.commitment).unwrap_or(F::ZERO);
+        let domain_separator = known(self.domain_separator).unwrap_or(F::ZERO);
+        let context_id = known(self.context_id).unwrap_or(F::ZERO);
+        let identity_hash = known(self.identity_hash);
+
+        let result = identity_hash
+            .zip(known(self.nonce))
+            .is_some_and(|(hash, nonce)| utils::commit_in_context(hash, nonce, context_id) == commitment);
+        let nullifier = identity_hash
+            .map(|hash| super::hash::utils::hash2(hash, domain_separator))
+            .unwrap_or(F::ZERO);
+
+        vec![if result { F::ONE } else { F::ZERO }, nullifier, domain_separator, commitment, context_id]
+    }
 }
 
-impl<F: PrimeField> Circuit<F> for IdentityCircuit<F> {
-    type Config = IdentityConfig;
+impl<F: PrimeField> Circuit<F> for IdentityCircuit<F>
+where
+    P128Pow5T3: Spec<F, 3, 2>,
+{
+    type Config = IdentityConfig<F>;
     type FloorPlanner = SimpleFloorPlanner;
 
     fn without_witnesses(&self) -> Self {
         Self {
             identity_hash: Value::unknown(),
+            nonce: Value::unknown(),
             commitment: self.commitment,
+            domain_separator: self.domain_separator,
+            context_id: self.context_id,
+            bound: self.bound,
         }
     }
 
     fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
         let identity_hash = meta.advice_column();
         let commitment = meta.advice_column();
+        let nonce = meta.advice_column();
         let result = meta.advice_column();
+        let domain_separator = meta.advice_column();
+        let context_id = meta.advice_column();
+        let opening = [(); 2].map(|_| meta.advice_column());
         let instance = meta.instance_column();
+        // The identity opening only hashes pairs, so the nonce column can
+        // double as the hash chip's input count column
+        let poseidon = HashChip::configure_poseidon(meta);
+        let hash = HashChip::configure(meta, nonce, poseidon);
 
-        IdentityChip::configure(meta, identity_hash, commitment, result, instance)
+        IdentityChip::configure(
+            meta,
+            identity_hash,
+            commitment,
+            nonce,
+            result,
+            domain_separator,
+            context_id,
+            opening,
+            hash,
+            instance,
+        )
     }
 
     fn synthesize(
@@ -187,49 +387,198 @@ impl<F: PrimeField> Circuit<F> for IdentityCircuit<F> {
         let chip = IdentityChip::construct(config.clone());
 
         // Assign the identity verification
-        let result_cell = chip.assign_identity_verification(
+        let assigned = chip.assign_identity_verification(
             layouter.namespace(|| "identity verification"),
             self.identity_hash,
+            self.nonce,
             self.commitment,
+            self.domain_separator,
+            self.context_id,
         )?;
 
+        if self.bound {
+            layouter.assign_region(
+                || "bind commitment",
+                |mut region| region.constrain_equal(assigned.opening.cell(), assigned.commitment.cell()),
+            )?;
+        }
+
         // Expose the result as public input (instance 0)
         layouter.constrain_instance(
-            result_cell.cell(),
+            assigned.result.cell(),
             config.instance,
             0,
         )?;
 
+        // Expose the nullifier (instance 1) and its domain (instance 2)
+        layouter.constrain_instance(
+            assigned.nullifier.cell(),
+            config.instance,
+            1,
+        )?;
+        layouter.constrain_instance(
+            assigned.domain_separator.cell(),
+            config.instance,
+            2,
+        )?;
+
+        // Bind the commitment being opened (instance 3) and its context (instance 4)
+        layouter.constrain_instance(
+            assigned.commitment.cell(),
+            config.instance,
+            3,
+        )?;
+        layouter.constrain_instance(
+            assigned.context_id.cell(),
+            config.instance,
+            4,
+        )?;
+
         Ok(())
     }
 }
 
-/// Helper type for assigned cells
-pub type AssignedCell<F> = halo2_proofs::circuit::AssignedCell<F, F>;
+impl<F: PrimeField> LendingCircuit<F> for IdentityCircuit<F>
+where
+    P128Pow5T3: Spec<F, 3, 2>,
+{
+    fn public_inputs(&self) -> Vec<F> {
+        self.expected_public_inputs()
+    }
+
+    fn recommended_k() -> u32 {
+        8
+    }
+
+    fn instance_layout() -> Vec<InstanceSpec> {
+        InstanceSpec::layout(&[
+            ("result", InstanceKind::Bool),
+            ("nullifier", InstanceKind::Field),
+            ("domain_separator", InstanceKind::Integer),
+            ("commitment", InstanceKind::Field),
+            ("context_id", InstanceKind::Integer),
+        ])
+    }
+}
 
 /// Utility functions for identity verification
 pub mod utils {
-    use super::*;
-    
-    /// Simple hash function for demonstration (not cryptographically secure)
-    pub fn simple_hash(data: &[u8]) -> u64 {
-        let mut hash = 0u64;
-        for &byte in data {
-            hash = hash.wrapping_mul(31).wrapping_add(byte as u64);
-        }
-        hash
+    use ff::{FromUniformBytes, PrimeField};
+    use halo2_gadgets::poseidon::primitives::{P128Pow5T3, Spec};
+
+    use crate::circuits::hash::utils::hash2;
+
+    /// Hash identity data to a field element with Poseidon
+    ///
+    /// The data is absorbed in 16-byte little-endian chunks, starting from its
+    /// length so that trailing zero bytes change the hash.
+    pub fn hash_identity<F: PrimeField>(identity_data: &[u8]) -> F
+    where
+        P128Pow5T3: Spec<F, 3, 2>,
+    {
+        identity_data
+            .chunks(16)
+            .fold(F::from(identity_data.len() as u64), |acc, chunk| {
+                let mut bytes = [0u8; 16];
+                bytes[..chunk.len()].copy_from_slice(chunk);
+                hash2(acc, F::from_u128(u128::from_le_bytes(bytes)))
+            })
     }
-    
-    /// Create a commitment to an identity (simplified)
-    pub fn create_commitment(identity_data: &[u8], nonce: u64) -> u64 {
-        let identity_hash = simple_hash(identity_data);
-        identity_hash.wrapping_add(nonce)
+
+    /// Commitment to an identity hash within a context, matching the in-circuit opening
+    pub fn commit<F: PrimeField>(identity_hash: F, nonce: F, context_id: u64) -> F
+    where
+        P128Pow5T3: Spec<F, 3, 2>,
+    {
+        commit_in_context(identity_hash, nonce, F::from(context_id))
     }
-    
-    /// Verify an identity commitment
-    pub fn verify_commitment(identity_data: &[u8], nonce: u64, commitment: u64) -> bool {
-        let expected_commitment = create_commitment(identity_data, nonce);
-        expected_commitment == commitment
+
+    /// `commit` with the context already a field element
+    pub(super) fn commit_in_context<F: PrimeField>(identity_hash: F, nonce: F, context_id: F) -> F
+    where
+        P128Pow5T3: Spec<F, 3, 2>,
+    {
+        hash2(hash2(identity_hash, context_id), nonce)
+    }
+
+    /// Create a commitment to an identity for one context:
+    /// `Poseidon(Poseidon(hash_identity(data), context_id), nonce)`
+    pub fn create_commitment<F: PrimeField>(identity_data: &[u8], nonce: F, context_id: u64) -> F
+    where
+        P128Pow5T3: Spec<F, 3, 2>,
+    {
+        commit(hash_identity(identity_data), nonce, context_id)
+    }
+
+    /// Derive a commitment nonce from a stored secret `seed`
+    ///
+    /// A personalized Blake2b hash of `context` and `seed`, reduced to a field
+    /// element, so a borrower who keeps only the seed can regenerate the nonce
+    /// to prove against an old commitment. Each commitment needs its own
+    /// `context`: reusing a nonce across commitments to the same identity
+    /// lets anyone who learns one opening link the others, and a lost nonce
+    /// makes its commitment unprovable. The seed must be secret and high
+    /// entropy, as anyone holding it can open every commitment derived from it.
+    pub fn derive_nonce<F: FromUniformBytes<64>>(seed: &[u8], context: &str) -> F {
+        let hash = blake2b_simd::Params::new()
+            .hash_length(64)
+            .personal(b"ZkCircuitNonceKD")
+            .to_state()
+            .update(&(context.len() as u64).to_le_bytes())
+            .update(context.as_bytes())
+            .update(seed)
+            .finalize();
+
+        let mut bytes = [0u8; 64];
+        bytes.copy_from_slice(hash.as_bytes());
+        F::from_uniform_bytes(&bytes)
+    }
+
+    /// `create_commitment` with the nonce derived from `seed`, returning the
+    /// commitment and the nonce to prove it with
+    ///
+    /// The nonce is derived for `context_id`, so commitments to the same data
+    /// in different contexts never share one; calling again with the same
+    /// seed regenerates both.
+    pub fn create_commitment_from_seed<F: FromUniformBytes<64>>(identity_data: &[u8], seed: &[u8], context_id: u64) -> (F, F)
+    where
+        P128Pow5T3: Spec<F, 3, 2>,
+    {
+        let nonce = derive_nonce(seed, &format!("identity commitment {}", context_id));
+        (create_commitment(identity_data, nonce, context_id), nonce)
+    }
+
+    /// Verify an identity commitment made for `context_id`
+    pub fn verify_commitment<F: PrimeField>(identity_data: &[u8], nonce: F, context_id: u64, commitment: F) -> bool
+    where
+        P128Pow5T3: Spec<F, 3, 2>,
+    {
+        create_commitment(identity_data, nonce, context_id) == commitment
+    }
+
+    /// Nullifier of an identity within a domain, matching the in-circuit hash
+    pub fn nullifier<F: PrimeField>(identity_hash: F, domain_separator: u64) -> F
+    where
+        P128Pow5T3: Spec<F, 3, 2>,
+    {
+        hash2(identity_hash, F::from(domain_separator))
+    }
+
+    /// Build the public inputs in the circuit's instance order
+    pub fn public_inputs<F: PrimeField>(
+        result: bool,
+        nullifier: F,
+        domain_separator: u64,
+        commitment: F,
+        context_id: u64,
+    ) -> Vec<F> {
+        vec![
+            if result { F::ONE } else { F::ZERO },
+            nullifier,
+            F::from(domain_separator),
+            commitment,
+            F::from(context_id),
+        ]
     }
 }
 
@@ -239,114 +588,259 @@ mod tests {
     use super::utils::*;
     use halo2_proofs::dev::MockProver;
     use pasta_curves::Fp;
-    use ff::Field;
+
+    const K: u32 = 8;
+    const DOMAIN: u64 = 2024;
+    const LENDING: u64 = 1;
+    const GOVERNANCE: u64 = 2;
+
+    fn nonce() -> Fp {
+        Fp::from(12345u64)
+    }
 
     #[test]
     fn test_identity_verification_success() {
-        let k = 4; // Circuit size parameter
-        
         // Create identity data and commitment
         let identity_data = b"user123@example.com";
-        let nonce = 12345u64;
-        let commitment = create_commitment(identity_data, nonce);
-        let identity_hash = simple_hash(identity_data).wrapping_add(nonce);
+        let commitment = create_commitment(identity_data, nonce(), LENDING);
+        let identity_hash = hash_identity(identity_data);
 
-        let circuit = IdentityCircuit::<Fp>::new(Some(identity_hash), commitment);
+        let circuit = IdentityCircuit::<Fp>::new(Some(identity_hash), Some(nonce()), commitment, DOMAIN, LENDING);
         
-        // The public input should be 1 (true) since the commitment matches
-        let public_inputs = vec![Fp::one()];
+        // The result should be 1 (true) since the commitment opens
+        let public_inputs = public_inputs(true, nullifier(identity_hash, DOMAIN), DOMAIN, commitment, LENDING);
 
-        let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
+        let prover = MockProver::run(K, &circuit, vec![public_inputs]).unwrap();
         prover.assert_satisfied();
     }
 
     #[test]
     fn test_identity_verification_failure() {
-        let k = 4;
-        
         // Create identity data and commitment
         let identity_data = b"user123@example.com";
-        let nonce = 12345u64;
-        let commitment = create_commitment(identity_data, nonce);
-        let wrong_identity_hash = simple_hash(b"wrong_user").wrapping_add(nonce);
+        let commitment = create_commitment(identity_data, nonce(), LENDING);
+        let wrong_identity_hash = hash_identity(b"wrong_user");
 
-        let circuit = IdentityCircuit::<Fp>::new(Some(wrong_identity_hash), commitment);
+        let circuit = IdentityCircuit::<Fp>::new(Some(wrong_identity_hash), Some(nonce()), commitment, DOMAIN, LENDING);
         
-        // The public input should be 0 (false) since the commitment doesn't match
-        let public_inputs = vec![Fp::zero()];
-
-        let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
+        // The result should be 0 (false) since the commitment doesn't open
+        let inputs = public_inputs(false, nullifier(wrong_identity_hash, DOMAIN), DOMAIN, commitment, LENDING);
+        let prover = MockProver::run(K, &circuit, vec![inputs]).unwrap();
         prover.assert_satisfied();
+
+        let inputs = public_inputs(true, nullifier(wrong_identity_hash, DOMAIN), DOMAIN, commitment, LENDING);
+        let prover = MockProver::run(K, &circuit, vec![inputs]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_derive_nonce() {
+        let seed = b"borrower seed kept on device";
+
+        // Deterministic per (seed, context), distinct across either
+        assert_eq!(derive_nonce::<Fp>(seed, "loan-1"), derive_nonce::<Fp>(seed, "loan-1"));
+        assert_ne!(derive_nonce::<Fp>(seed, "loan-1"), derive_nonce::<Fp>(seed, "loan-2"));
+        assert_ne!(derive_nonce::<Fp>(seed, "loan-1"), derive_nonce::<Fp>(b"another seed", "loan-1"));
+
+        // The context is length-prefixed, so shifting bytes into the seed changes the nonce
+        assert_ne!(derive_nonce::<Fp>(b"1seed", "loan-"), derive_nonce::<Fp>(b"seed", "loan-1"));
+    }
+
+    #[test]
+    fn test_commitment_from_seed() {
+        let identity_data = b"user123@example.com";
+        let seed = b"borrower seed kept on device";
+
+        let (commitment, nonce) = create_commitment_from_seed::<Fp>(identity_data, seed, LENDING);
+        assert_eq!(create_commitment_from_seed::<Fp>(identity_data, seed, LENDING), (commitment, nonce));
+        assert!(verify_commitment(identity_data, nonce, LENDING, commitment));
+
+        // Another context gets a fresh nonce
+        let (_, governance_nonce) = create_commitment_from_seed::<Fp>(identity_data, seed, GOVERNANCE);
+        assert_ne!(governance_nonce, nonce);
+
+        // The regenerated nonce proves the commitment
+        let identity_hash = hash_identity(identity_data);
+        let circuit = IdentityCircuit::<Fp>::new(Some(identity_hash), Some(nonce), commitment, DOMAIN, LENDING);
+        let inputs = public_inputs(true, nullifier(identity_hash, DOMAIN), DOMAIN, commitment, LENDING);
+        MockProver::run(K, &circuit, vec![inputs]).unwrap().assert_satisfied();
+    }
+
+    #[test]
+    fn test_wrong_nonce_rejected() {
+        let identity_hash = hash_identity::<Fp>(b"user123@example.com");
+        let commitment = commit(identity_hash, nonce(), LENDING);
+
+        let circuit = IdentityCircuit::<Fp>::new(Some(identity_hash), Some(nonce() + Fp::one()), commitment, DOMAIN, LENDING);
+        let inputs = public_inputs(true, nullifier(identity_hash, DOMAIN), DOMAIN, commitment, LENDING);
+
+        let prover = MockProver::run(K, &circuit, vec![inputs]).unwrap();
+        assert!(prover.verify().is_err());
     }
 
     #[test]
     fn test_identity_verification_with_field_elements() {
-        let k = 4;
-        
-        // Test with matching field elements
         let identity_hash = Fp::from(12345u64);
-        let commitment = Fp::from(12345u64);
+        let commitment = commit(identity_hash, nonce(), LENDING);
 
         let circuit = IdentityCircuit::<Fp>::new_with_fields(
             Value::known(identity_hash),
+            Value::known(nonce()),
             Value::known(commitment),
+            Value::known(Fp::from(DOMAIN)),
+            Value::known(Fp::from(LENDING)),
         );
         
-        let public_inputs = vec![Fp::one()];
+        let public_inputs = public_inputs(true, nullifier(identity_hash, DOMAIN), DOMAIN, commitment, LENDING);
 
-        let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
+        let prover = MockProver::run(K, &circuit, vec![public_inputs]).unwrap();
         prover.assert_satisfied();
     }
 
     #[test]
     fn test_identity_verification_different_values() {
-        let k = 4;
-        
-        // Test with different field elements
+        // The raw identity hash is not a valid opening of itself
         let identity_hash = Fp::from(12345u64);
-        let commitment = Fp::from(54321u64);
 
         let circuit = IdentityCircuit::<Fp>::new_with_fields(
             Value::known(identity_hash),
-            Value::known(commitment),
+            Value::known(nonce()),
+            Value::known(identity_hash),
+            Value::known(Fp::from(DOMAIN)),
+            Value::known(Fp::from(LENDING)),
         );
         
-        let public_inputs = vec![Fp::zero()];
+        let public_inputs = public_inputs(false, nullifier(identity_hash, DOMAIN), DOMAIN, identity_hash, LENDING);
 
-        let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
+        let prover = MockProver::run(K, &circuit, vec![public_inputs]).unwrap();
         prover.assert_satisfied();
     }
 
     #[test]
     fn test_circuit_without_witnesses() {
-        let k = 4;
-        let commitment = 12345u64;
+        use crate::circuits::lending::assert_keygen;
+
+        let commitment = Fp::from(12345u64);
+
+        let circuit = IdentityCircuit::<Fp>::new(None, None, commitment, DOMAIN, LENDING);
+        assert_keygen(K, &circuit.without_witnesses());
+
+        let bound = IdentityCircuit::<Fp>::new_bound(None, None, commitment, DOMAIN, LENDING);
+        assert_keygen(K, &bound.without_witnesses());
+    }
+
+    #[test]
+    fn test_nullifier_is_deterministic_per_identity() {
+        let alice_hash = Fp::from(1111u64);
+        let alice = nullifier(alice_hash, DOMAIN);
+        assert_eq!(alice, nullifier(alice_hash, DOMAIN));
+        assert_ne!(alice, nullifier(Fp::from(2222u64), DOMAIN));
+        assert_ne!(alice, nullifier(alice_hash, DOMAIN + 1));
+
+        // A registration proof only verifies against the identity's own nullifier
+        let commitment = commit(alice_hash, nonce(), LENDING);
+        let circuit = IdentityCircuit::<Fp>::new(Some(alice_hash), Some(nonce()), commitment, DOMAIN, LENDING);
+        let prover = MockProver::run(K, &circuit, vec![public_inputs(true, alice, DOMAIN, commitment, LENDING)]).unwrap();
+        prover.assert_satisfied();
 
-        let circuit = IdentityCircuit::<Fp>::new(None, commitment);
-        let circuit_without_witnesses = circuit.without_witnesses();
+        let bob = nullifier(Fp::from(2222u64), DOMAIN);
+        let prover = MockProver::run(K, &circuit, vec![public_inputs(true, bob, DOMAIN, commitment, LENDING)]).unwrap();
+        assert!(prover.verify().is_err());
 
-        // Should be able to create the circuit structure without witnesses
-        let _ = circuit_without_witnesses;
+        // Nor can it be replayed under another domain's nullifier
+        let other_domain = nullifier(alice_hash, DOMAIN + 1);
+        let prover = MockProver::run(
+            K,
+            &circuit,
+            vec![public_inputs(true, other_domain, DOMAIN + 1, commitment, LENDING)],
+        )
+        .unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_nullifier_independent_of_commitment() {
+        let identity_hash = Fp::from(1111u64);
+        let expected = nullifier(identity_hash, DOMAIN);
+
+        // Whatever commitment is checked, the identity yields the same nullifier
+        let valid = commit(identity_hash, nonce(), LENDING);
+        for (commitment, result) in [(valid, true), (Fp::from(9999u64), false)] {
+            let circuit = IdentityCircuit::<Fp>::new(Some(identity_hash), Some(nonce()), commitment, DOMAIN, LENDING);
+            let inputs = public_inputs(result, expected, DOMAIN, commitment, LENDING);
+            let prover = MockProver::run(K, &circuit, vec![inputs]).unwrap();
+            prover.assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn test_commitment_bound_to_context() {
+        let identity_hash = hash_identity::<Fp>(b"user123@example.com");
+        let commitment = commit(identity_hash, nonce(), LENDING);
+        assert_ne!(commitment, commit(identity_hash, nonce(), GOVERNANCE));
+
+        // A lending commitment does not open under the governance context
+        let circuit = IdentityCircuit::<Fp>::new(Some(identity_hash), Some(nonce()), commitment, DOMAIN, GOVERNANCE);
+        let inputs = public_inputs(true, nullifier(identity_hash, DOMAIN), DOMAIN, commitment, GOVERNANCE);
+        let prover = MockProver::run(K, &circuit, vec![inputs]).unwrap();
+        assert!(prover.verify().is_err());
+
+        let inputs = public_inputs(false, nullifier(identity_hash, DOMAIN), DOMAIN, commitment, GOVERNANCE);
+        let prover = MockProver::run(K, &circuit, vec![inputs]).unwrap();
+        prover.assert_satisfied();
+
+        // A lending proof is not accepted by a verifier expecting governance
+        let circuit = IdentityCircuit::<Fp>::new(Some(identity_hash), Some(nonce()), commitment, DOMAIN, LENDING);
+        let inputs = public_inputs(true, nullifier(identity_hash, DOMAIN), DOMAIN, commitment, GOVERNANCE);
+        let prover = MockProver::run(K, &circuit, vec![inputs]).unwrap();
+        assert!(prover.verify().is_err());
+
+        assert!(!verify_commitment(b"user123@example.com", nonce(), GOVERNANCE, commitment));
+    }
+
+    #[test]
+    fn test_bound_commitment() {
+        let identity_hash = hash_identity::<Fp>(b"user123@example.com");
+        let commitment_a = commit(identity_hash, nonce(), LENDING);
+        let commitment_b = commit(hash_identity::<Fp>(b"other@example.com"), nonce(), LENDING);
+
+        let circuit = IdentityCircuit::<Fp>::new_bound(Some(identity_hash), Some(nonce()), commitment_a, DOMAIN, LENDING);
+        assert!(circuit.without_witnesses().bound);
+        let inputs = public_inputs(true, nullifier(identity_hash, DOMAIN), DOMAIN, commitment_a, LENDING);
+        assert_eq!(circuit.expected_public_inputs(), inputs);
+        MockProver::run(K, &circuit, vec![inputs]).unwrap().assert_satisfied();
+
+        // A proof bound to commitment A fails against expected commitment B
+        let inputs = public_inputs(true, nullifier(identity_hash, DOMAIN), DOMAIN, commitment_b, LENDING);
+        assert!(MockProver::run(K, &circuit, vec![inputs]).unwrap().verify().is_err());
+
+        // Unlike the unbound circuit, a failed opening has no proof at all
+        let circuit = IdentityCircuit::<Fp>::new_bound(Some(identity_hash), Some(nonce()), commitment_b, DOMAIN, LENDING);
+        for result in [false, true] {
+            let inputs = public_inputs(result, nullifier(identity_hash, DOMAIN), DOMAIN, commitment_b, LENDING);
+            assert!(MockProver::run(K, &circuit, vec![inputs]).unwrap().verify().is_err());
+        }
     }
 
     #[test]
     fn test_utility_functions() {
         let identity_data = b"test@example.com";
-        let nonce = 98765u64;
+        let nonce = Fp::from(98765u64);
         
         // Test hash function
-        let hash1 = simple_hash(identity_data);
-        let hash2 = simple_hash(identity_data);
+        let hash1: Fp = hash_identity(identity_data);
+        let hash2: Fp = hash_identity(identity_data);
         assert_eq!(hash1, hash2); // Hash should be deterministic
         
         let different_data = b"different@example.com";
-        let hash3 = simple_hash(different_data);
+        let hash3: Fp = hash_identity(different_data);
         assert_ne!(hash1, hash3); // Different data should produce different hash
+        assert_ne!(hash_identity::<Fp>(b"ab"), hash_identity::<Fp>(b"ab\0"));
         
         // Test commitment functions
-        let commitment = create_commitment(identity_data, nonce);
-        assert!(verify_commitment(identity_data, nonce, commitment));
-        assert!(!verify_commitment(different_data, nonce, commitment));
-        assert!(!verify_commitment(identity_data, nonce + 1, commitment));
+        let commitment = create_commitment(identity_data, nonce, LENDING);
+        assert!(verify_commitment(identity_data, nonce, LENDING, commitment));
+        assert!(!verify_commitment(different_data, nonce, LENDING, commitment));
+        assert!(!verify_commitment(identity_data, nonce + Fp::one(), LENDING, commitment));
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,338 @@
+use halo2_gadgets::poseidon::primitives::{P128Pow5T3, Spec};
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Instance, Selector},
+    poly::Rotation,
+};
+use ff::PrimeField;
+use std::marker::PhantomData;
+
+use super::AssignedCell;
+use super::hash::HashChip;
+use super::lending::known;
+use super::merkle::{MerkleChip, MerkleConfig};
+
+/// Configuration for the allowlist membership circuit
+#[derive(Clone, Debug)]
+pub struct IdentityMembershipConfig<F: PrimeField> {
+    /// Advice column for the root recomputed from the leaf and its path
+    pub computed_root: Column<Advice>,
+    /// Advice column for the allowlist root (public input)
+    pub root: Column<Advice>,
+    /// Advice column for the inverse of `computed_root - root` (zero if equal)
+    pub difference_inverse: Column<Advice>,
+    /// Advice column for the membership result (1 if a member, 0 if not)
+    pub is_member: Column<Advice>,
+    /// Instance column for public inputs/outputs
+    pub instance: Column<Instance>,
+    /// Selector for the root comparison gate
+    pub selector: Selector,
+    /// Merkle path configuration (also provides Poseidon)
+    pub merkle: MerkleConfig<F>,
+}
+
+/// Cells assigned by the root comparison
+pub struct AssignedMembership<F: PrimeField> {
+    pub root: AssignedCell<F>,
+    pub is_member: AssignedCell<F>,
+}
+
+/// Chip comparing a recomputed Merkle root against the allowlist root
+pub struct IdentityMembershipChip<F: PrimeField> {
+    config: IdentityMembershipConfig<F>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: PrimeField> IdentityMembershipChip<F>
+where
+    P128Pow5T3: Spec<F, 3, 2>,
+{
+    pub fn construct(config: IdentityMembershipConfig<F>) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 4],
+        merkle: MerkleConfig<F>,
+        instance: Column<Instance>,
+    ) -> IdentityMembershipConfig<F> {
+        let [computed_root, root, difference_inverse, is_member] = advice;
+        let selector = meta.selector();
+
+        // Enable equality constraints for public inputs/outputs
+        meta.enable_equality(computed_root);
+        meta.enable_equality(root);
+        meta.enable_equality(is_member);
+        meta.enable_equality(instance);
+
+        // is_member = 1 exactly when the recomputed root equals the public one:
+        // is_member = 1 - diff * inv and diff * is_member = 0
+        meta.create_gate("identity_membership", |meta| {
+            let s = meta.query_selector(selector);
+            let computed_root = meta.query_advice(computed_root, Rotation::cur());
+            let root = meta.query_advice(root, Rotation::cur());
+            let inverse = meta.query_advice(difference_inverse, Rotation::cur());
+            let is_member = meta.query_advice(is_member, Rotation::cur());
+
+            let diff = computed_root - root;
+
+            vec![
+                s.clone() * (is_member.clone() - (Expression::Constant(F::ONE) - diff.clone() * inverse)),
+                s * (diff * is_member),
+            ]
+        });
+
+        IdentityMembershipConfig {
+            computed_root,
+            root,
+            difference_inverse,
+            is_member,
+            instance,
+            selector,
+            merkle,
+        }
+    }
+
+    /// Compare the recomputed root with the allowlist root
+    pub fn assign_membership(
+        &self,
+        mut layouter: impl Layouter<F>,
+        computed_root: AssignedCell<F>,
+        root: Value<F>,
+    ) -> Result<AssignedMembership<F>, Error> {
+        layouter.assign_region(
+            || "identity membership",
+            |mut region| {
+                self.config.selector.enable(&mut region, 0)?;
+
+                let computed = computed_root.copy_advice(|| "computed root", &mut region, self.config.computed_root, 0)?;
+                let root_cell = region.assign_advice(|| "root", self.config.root, 0, || root)?;
+
+                let diff = computed.value().copied() - root;
+                region.assign_advice(
+                    || "difference inverse",
+                    self.config.difference_inverse,
+                    0,
+                    || diff.map(|d| d.invert().unwrap_or(F::ZERO)),
+                )?;
+
+                let is_member = region.assign_advice(
+                    || "is member",
+                    self.config.is_member,
+                    0,
+                    || diff.map(|d| if d == F::ZERO { F::ONE } else { F::ZERO }),
+                )?;
+
+                Ok(AssignedMembership {
+                    root: root_cell,
+                    is_member,
+                })
+            },
+        )
+    }
+}
+
+/// Circuit proving an identity commitment is on a platform's allowlist
+///
+/// The allowlist is a Poseidon Merkle tree of identity commitments. The
+/// borrower's commitment and its authentication path stay private, so the
+/// proof does not reveal which entry matched. Membership is exposed as a
+/// boolean. Instance layout: `[is_member, root]`.
+#[derive(Clone, Debug)]
+pub struct IdentityMembershipCircuit<F: PrimeField> {
+    /// Private input: the borrower's identity commitment (Merkle leaf)
+    pub leaf: Value<F>,
+    /// Private input: `(sibling, position)` pairs from leaf to root
+    pub merkle_path: Vec<(Value<F>, Value<F>)>,
+    /// Public input: the allowlist root
+    pub root: Value<F>,
+}
+
+impl<F: PrimeField> IdentityMembershipCircuit<F>
+where
+    P128Pow5T3: Spec<F, 3, 2>,
+{
+    pub fn new(leaf: Option<F>, merkle_path: Option<Vec<(F, bool)>>, depth: usize, root: F) -> Self {
+        let merkle_path = match merkle_path {
+            Some(path) => {
+                assert_eq!(path.len(), depth, "Merkle path length must match the tree depth");
+                super::merkle::utils::path_values(&path)
+            }
+            None => vec![(Value::unknown(), Value::unknown()); depth],
+        };
+
+        Self {
+            leaf: leaf.map(Value::known).unwrap_or_else(Value::unknown),
+            merkle_path,
+            root: Value::known(root),
+        }
+    }
+
+    /// Public inputs `[is_member, root]`, with membership computed from the
+    /// known leaf and path (0 without a witness)
+    pub fn expected_public_inputs(&self) -> Vec<F> {
+        let root = known(self.root).unwrap_or(F::ZERO);
+        let is_member = known(self.leaf)
+            .zip(super::merkle::utils::known_path(&self.merkle_path))
+            .is_some_and(|(leaf, path)| utils::is_member(leaf, &path, root));
+
+        utils::public_inputs(is_member, root)
+    }
+}
+
+impl<F: PrimeField> Circuit<F> for IdentityMembershipCircuit<F>
+where
+    P128Pow5T3: Spec<F, 3, 2>,
+{
+    type Config = IdentityMembershipConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            leaf: Value::unknown(),
+            merkle_path: vec![(Value::unknown(), Value::unknown()); self.merkle_path.len()],
+            root: self.root,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let poseidon = HashChip::configure_poseidon(meta);
+        let merkle_advice = [(); 5].map(|_| meta.advice_column());
+        let merkle = MerkleChip::configure(meta, merkle_advice, poseidon);
+
+        let advice = [(); 4].map(|_| meta.advice_column());
+        let instance = meta.instance_column();
+
+        IdentityMembershipChip::configure(meta, advice, merkle, instance)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = IdentityMembershipChip::construct(config.clone());
+        let merkle_chip = MerkleChip::construct(config.merkle.clone());
+
+        let leaf = layouter.assign_region(
+            || "identity leaf",
+            |mut region| region.assign_advice(|| "identity leaf", config.merkle.node, 0, || self.leaf),
+        )?;
+        let computed_root = merkle_chip.compute_root(layouter.namespace(|| "allowlist path"), leaf, &self.merkle_path)?;
+
+        let membership = chip.assign_membership(layouter.namespace(|| "identity membership"), computed_root, self.root)?;
+
+        layouter.constrain_instance(membership.is_member.cell(), config.instance, 0)?;
+        layouter.constrain_instance(membership.root.cell(), config.instance, 1)?;
+
+        Ok(())
+    }
+}
+
+/// Utility functions for allowlist membership proofs
+pub mod utils {
+    use super::*;
+
+    /// Whether `leaf` and its `(sibling, is_right)` path hash up to `root`
+    pub fn is_member<F: PrimeField>(leaf: F, path: &[(F, bool)], root: F) -> bool
+    where
+        P128Pow5T3: Spec<F, 3, 2>,
+    {
+        super::super::merkle::utils::compute_root(leaf, path) == root
+    }
+
+    /// Build the public inputs in the circuit's instance order: `[is_member, root]`
+    pub fn public_inputs<F: PrimeField>(is_member: bool, root: F) -> Vec<F> {
+        vec![if is_member { F::ONE } else { F::ZERO }, root]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::utils::*;
+    use crate::circuits::merkle::utils::merkle_path;
+    use halo2_proofs::dev::MockProver;
+    use pasta_curves::Fp;
+
+    const K: u32 = 9;
+    const DEPTH: usize = 2;
+
+    fn allowlist() -> Vec<Fp> {
+        (501..=504u64).map(Fp::from).collect()
+    }
+
+    fn run(leaf: Fp, path: Vec<(Fp, bool)>, root: Fp, public_inputs: Vec<Fp>) -> MockProver<Fp> {
+        let circuit = IdentityMembershipCircuit::<Fp>::new(Some(leaf), Some(path), DEPTH, root);
+        MockProver::run(K, &circuit, vec![public_inputs]).unwrap()
+    }
+
+    #[test]
+    fn test_expected_public_inputs() {
+        let (root, mut path) = merkle_path(&allowlist(), 3);
+        let circuit = IdentityMembershipCircuit::<Fp>::new(Some(allowlist()[3]), Some(path.clone()), DEPTH, root);
+        assert_eq!(circuit.expected_public_inputs(), public_inputs(true, root));
+        MockProver::run(K, &circuit, vec![circuit.expected_public_inputs()]).unwrap().assert_satisfied();
+
+        path[0].0 += Fp::one();
+        let circuit = IdentityMembershipCircuit::<Fp>::new(Some(allowlist()[3]), Some(path), DEPTH, root);
+        assert_eq!(circuit.expected_public_inputs(), public_inputs(false, root));
+        MockProver::run(K, &circuit, vec![circuit.expected_public_inputs()]).unwrap().assert_satisfied();
+    }
+
+    #[test]
+    fn test_valid_path() {
+        for index in 0..allowlist().len() {
+            let (root, path) = merkle_path(&allowlist(), index);
+            assert!(is_member(allowlist()[index], &path, root));
+
+            let prover = run(allowlist()[index], path, root, public_inputs(true, root));
+            prover.assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn test_tampered_sibling() {
+        let (root, mut path) = merkle_path(&allowlist(), 2);
+        path[1].0 += Fp::one();
+        assert!(!is_member(allowlist()[2], &path, root));
+
+        let prover = run(allowlist()[2], path.clone(), root, public_inputs(true, root));
+        assert!(prover.verify().is_err());
+
+        // The honest outcome is a proof of non-membership
+        let prover = run(allowlist()[2], path, root, public_inputs(false, root));
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn test_wrong_root() {
+        let (root, path) = merkle_path(&allowlist(), 1);
+        let (other_root, _) = merkle_path(&[Fp::from(1u64), Fp::from(2u64)], 0);
+
+        let prover = run(allowlist()[1], path.clone(), other_root, public_inputs(true, other_root));
+        assert!(prover.verify().is_err());
+
+        let prover = run(allowlist()[1], path.clone(), other_root, public_inputs(false, other_root));
+        prover.assert_satisfied();
+
+        // The root in the instance is bound to the one the path is checked against
+        let prover = run(allowlist()[1], path, other_root, public_inputs(true, root));
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_circuit_without_witnesses() {
+        use crate::circuits::lending::assert_keygen;
+
+        let circuit = IdentityMembershipCircuit::<Fp>::new(None, None, DEPTH, Fp::zero());
+        let circuit_without_witnesses = circuit.without_witnesses();
+
+        assert_eq!(circuit_without_witnesses.merkle_path.len(), DEPTH);
+        assert_keygen(K, &circuit_without_witnesses);
+    }
+}
@@ -0,0 +1,406 @@
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Instance, Selector},
+    poly::Rotation,
+};
+use ff::PrimeField;
+use std::marker::PhantomData;
+
+use super::AssignedCell;
+use super::income_range::INCOME_BITS;
+use super::lending::known;
+use super::loan_history::{check_fits_u128, field_to_u128};
+use super::optimizations::{RangeCheckChip, RangeCheckConfig};
+
+/// Configuration for the income bucket circuit
+#[derive(Clone, Debug)]
+pub struct IncomeBucketConfig {
+    /// Advice column for the income (private input), copied onto every boundary row
+    pub income: Column<Advice>,
+    /// Advice column for the bucket boundaries (public inputs)
+    pub boundary: Column<Advice>,
+    /// Advice column for the `income >= boundary` bits
+    pub above: Column<Advice>,
+    /// Low N bits of `income - boundary + 2^N`
+    pub remainder: Column<Advice>,
+    /// Running count of boundaries at or below the income, ending in the bucket index
+    pub count: Column<Advice>,
+    /// Instance column for public inputs/outputs
+    pub instance: Column<Instance>,
+    /// Selector for the per-boundary comparison gate
+    pub boundary_selector: Selector,
+    /// Selector for the first row (the count starts at zero)
+    pub start_selector: Selector,
+    /// Range check proving the income and every remainder fit in N bits
+    pub range_check: RangeCheckConfig,
+}
+
+/// Cells assigned by the bucket computation
+pub struct AssignedBucket<F: PrimeField> {
+    pub income: AssignedCell<F>,
+    /// Boundary cells, in boundary order
+    pub boundaries: Vec<AssignedCell<F>>,
+    /// Index of the bucket the income falls in
+    pub index: AssignedCell<F>,
+}
+
+/// Chip proving which bucket a private income falls in
+pub struct IncomeBucketChip<F: PrimeField> {
+    config: IncomeBucketConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: PrimeField> IncomeBucketChip<F> {
+    pub fn construct(config: IncomeBucketConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 6],
+        instance: Column<Instance>,
+    ) -> IncomeBucketConfig {
+        let [income, boundary, above, remainder, count, range_column] = advice;
+
+        let boundary_selector = meta.selector();
+        let start_selector = meta.selector();
+        let range_check = RangeCheckChip::configure(meta, range_column, INCOME_BITS);
+
+        // Enable equality constraints for public inputs/outputs
+        meta.enable_equality(income);
+        meta.enable_equality(boundary);
+        meta.enable_equality(remainder);
+        meta.enable_equality(count);
+        meta.enable_equality(instance);
+
+        // Each boundary row compares the income against one boundary by splitting
+        // `income - boundary + 2^N` into a top bit and an N-bit remainder, and
+        // adds the bit to the running count. With sorted boundaries the final
+        // count is the index of the bucket containing the income.
+        meta.create_gate("income_bucket_boundary", |meta| {
+            let s = meta.query_selector(boundary_selector);
+            let income = meta.query_advice(income, Rotation::cur());
+            let boundary = meta.query_advice(boundary, Rotation::cur());
+            let above = meta.query_advice(above, Rotation::cur());
+            let remainder = meta.query_advice(remainder, Rotation::cur());
+            let count_cur = meta.query_advice(count, Rotation::cur());
+            let count_next = meta.query_advice(count, Rotation::next());
+
+            let offset = Expression::Constant(F::from_u128(1u128 << INCOME_BITS));
+
+            vec![
+                // Ensure above is boolean (0 or 1)
+                s.clone() * (above.clone() * (above.clone() - Expression::Constant(F::ONE))),
+                s.clone() * (income - boundary + offset.clone() - (above.clone() * offset + remainder)),
+                s * (count_next - count_cur - above),
+            ]
+        });
+
+        meta.create_gate("income_bucket_start", |meta| {
+            let s = meta.query_selector(start_selector);
+            let count = meta.query_advice(count, Rotation::cur());
+
+            vec![s * count]
+        });
+
+        IncomeBucketConfig {
+            income,
+            boundary,
+            above,
+            remainder,
+            count,
+            instance,
+            boundary_selector,
+            start_selector,
+            range_check,
+        }
+    }
+
+    /// Assign the boundary comparisons and the resulting bucket index
+    pub fn assign_bucket(
+        &self,
+        mut layouter: impl Layouter<F>,
+        income: Value<F>,
+        boundaries: &[u128],
+    ) -> Result<AssignedBucket<F>, Error> {
+        check_fits_u128(income)?;
+
+        let range_chip = RangeCheckChip::construct(self.config.range_check.clone());
+
+        // The comparisons are only sound for incomes that fit in N bits
+        let income_cell = range_chip.assign_range_check(layouter.namespace(|| "income"), income, INCOME_BITS)?;
+
+        let (assigned, remainders) = layouter.assign_region(
+            || "income bucket",
+            |mut region| {
+                self.config.start_selector.enable(&mut region, 0)?;
+
+                let mut count = Value::known(F::ZERO);
+                let mut boundary_cells = Vec::with_capacity(boundaries.len());
+                let mut remainders = Vec::with_capacity(boundaries.len());
+
+                for (i, &boundary) in boundaries.iter().enumerate() {
+                    self.config.boundary_selector.enable(&mut region, i)?;
+
+                    income_cell.copy_advice(|| "income", &mut region, self.config.income, i)?;
+                    region.assign_advice(|| "count", self.config.count, i, || count)?;
+                    let boundary_cell = region.assign_advice(
+                        || "boundary",
+                        self.config.boundary,
+                        i,
+                        || Value::known(F::from_u128(boundary)),
+                    )?;
+
+                    // `income - boundary + 2^N` has its top bit set iff income >= boundary
+                    let compare = income.map(|income| {
+                        let income = field_to_u128(&income).unwrap_or_default();
+                        let diff = income.wrapping_add(1u128 << INCOME_BITS).wrapping_sub(boundary);
+                        (diff >> INCOME_BITS == 1, diff & ((1u128 << INCOME_BITS) - 1))
+                    });
+                    let above = compare.map(|(above, _)| if above { F::ONE } else { F::ZERO });
+
+                    region.assign_advice(|| "above boundary", self.config.above, i, || above)?;
+                    let remainder = region.assign_advice(
+                        || "remainder",
+                        self.config.remainder,
+                        i,
+                        || compare.map(|(_, r)| F::from_u128(r)),
+                    )?;
+                    count = count + above;
+
+                    boundary_cells.push(boundary_cell);
+                    remainders.push(remainder);
+                }
+
+                let index = region.assign_advice(|| "bucket index", self.config.count, boundaries.len(), || count)?;
+
+                let assigned = AssignedBucket {
+                    income: income_cell.clone(),
+                    boundaries: boundary_cells,
+                    index,
+                };
+
+                Ok((assigned, remainders))
+            },
+        )?;
+
+        for (i, remainder) in remainders.iter().enumerate() {
+            range_chip.copy_range_check(layouter.namespace(|| format!("remainder {}", i)), remainder, INCOME_BITS)?;
+        }
+
+        Ok(assigned)
+    }
+}
+
+/// Circuit proving which income band a private income falls in
+///
+/// `n` sorted boundaries split incomes into `n + 1` buckets: bucket 0 is below
+/// the first boundary, bucket `i` is `[boundary_{i-1}, boundary_i)` and bucket
+/// `n` is at or above the last boundary. An income exactly on a boundary lands
+/// in the upper bucket, and incomes outside every finite band land in the
+/// open-ended first or last bucket, so each income has exactly one index.
+/// Only the index is revealed, not the individual comparisons. Instance layout:
+/// `[bucket_index, boundary_0, .., boundary_{n-1}]`.
+///
+/// The boundaries are public, so the verifier checks they are sorted (see
+/// `utils::check_boundaries`); unsorted boundaries make the index a count of
+/// boundaries below the income rather than a bucket.
+#[derive(Clone, Debug)]
+pub struct IncomeBucketCircuit<F: PrimeField> {
+    /// Private input: the actual income
+    pub income: Value<F>,
+    /// Public input: the sorted bucket boundaries
+    pub boundaries: Vec<u128>,
+}
+
+impl<F: PrimeField> IncomeBucketCircuit<F> {
+    pub fn new(income: Option<u128>, boundaries: Vec<u128>) -> Self {
+        Self {
+            income: if let Some(inc) = income {
+                Value::known(F::from_u128(inc))
+            } else {
+                Value::unknown()
+            },
+            boundaries,
+        }
+    }
+
+    /// Public inputs: the bucket index of the known income (0 without a
+    /// witness), then the boundaries
+    pub fn expected_public_inputs(&self) -> Vec<F> {
+        let index = known(self.income)
+            .and_then(|income| field_to_u128(&income))
+            .map(|income| utils::bucket_index(income, &self.boundaries))
+            .unwrap_or(0);
+
+        utils::public_inputs(index, &self.boundaries)
+    }
+}
+
+impl<F: PrimeField> Circuit<F> for IncomeBucketCircuit<F> {
+    type Config = IncomeBucketConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            income: Value::unknown(),
+            boundaries: self.boundaries.clone(),
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [(); 6].map(|_| meta.advice_column());
+        let instance = meta.instance_column();
+
+        IncomeBucketChip::configure(meta, advice, instance)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = IncomeBucketChip::construct(config.clone());
+
+        let assigned = chip.assign_bucket(
+            layouter.namespace(|| "income bucket"),
+            self.income,
+            &self.boundaries,
+        )?;
+
+        // Expose the bucket index, then the boundaries
+        layouter.constrain_instance(assigned.index.cell(), config.instance, 0)?;
+        for (i, boundary) in assigned.boundaries.iter().enumerate() {
+            layouter.constrain_instance(boundary.cell(), config.instance, i + 1)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Utility functions for income bucket proofs
+pub mod utils {
+    use super::INCOME_BITS;
+    use crate::error::ZkError;
+    use ff::PrimeField;
+
+    /// Index of the bucket containing `income`: the number of boundaries at or below it
+    pub fn bucket_index(income: u128, boundaries: &[u128]) -> usize {
+        boundaries.iter().filter(|&&boundary| boundary <= income).count()
+    }
+
+    /// Check the boundaries are strictly increasing and fit in the income width
+    pub fn check_boundaries(boundaries: &[u128]) -> Result<(), ZkError> {
+        if let Some(&boundary) = boundaries.iter().find(|&&b| b >= 1u128 << INCOME_BITS) {
+            return Err(ZkError::InvalidInput(format!(
+                "Boundary {} does not fit in {} bits",
+                boundary, INCOME_BITS
+            )));
+        }
+        if boundaries.windows(2).any(|pair| pair[0] >= pair[1]) {
+            return Err(ZkError::InvalidInput("Boundaries must be strictly increasing".into()));
+        }
+
+        Ok(())
+    }
+
+    /// Build the public inputs for a proof that the income is in `bucket`
+    pub fn public_inputs<F: PrimeField>(bucket: usize, boundaries: &[u128]) -> Vec<F> {
+        std::iter::once(F::from(bucket as u64))
+            .chain(boundaries.iter().map(|&boundary| F::from_u128(boundary)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::utils::*;
+    use halo2_proofs::dev::MockProver;
+    use pasta_curves::Fp;
+
+    const K: u32 = 9;
+
+    fn boundaries() -> Vec<u128> {
+        vec![30000, 60000]
+    }
+
+    fn run(income: u128, bucket: usize) -> MockProver<Fp> {
+        let circuit = IncomeBucketCircuit::<Fp>::new(Some(income), boundaries());
+        MockProver::run(K, &circuit, vec![public_inputs(bucket, &boundaries())]).unwrap()
+    }
+
+    #[test]
+    fn test_expected_public_inputs() {
+        for income in [0u128, 30000, 59999, 95000] {
+            let circuit = IncomeBucketCircuit::<Fp>::new(Some(income), boundaries());
+            let expected = circuit.expected_public_inputs();
+            assert_eq!(expected, public_inputs(bucket_index(income, &boundaries()), &boundaries()));
+            MockProver::run(K, &circuit, vec![expected]).unwrap().assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn test_income_in_each_bucket() {
+        for (income, bucket) in [(12000u128, 0usize), (45000, 1), (95000, 2)] {
+            assert_eq!(bucket_index(income, &boundaries()), bucket);
+            run(income, bucket).assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn test_boundary_lands_in_upper_bucket() {
+        assert_eq!(bucket_index(30000, &boundaries()), 1);
+        run(30000, 1).assert_satisfied();
+        assert!(run(30000, 0).verify().is_err());
+
+        // One below the boundary stays in the lower bucket
+        run(29999, 0).assert_satisfied();
+    }
+
+    #[test]
+    fn test_wrong_bucket_rejected() {
+        for wrong in [0usize, 2, 3] {
+            assert!(run(45000, wrong).verify().is_err());
+        }
+    }
+
+    #[test]
+    fn test_income_outside_all_bands() {
+        // Incomes beyond the outer boundaries land in the open-ended buckets
+        let boundaries = vec![30000, 60000];
+        run(0, 0).assert_satisfied();
+        run((1u128 << 64) + 5, boundaries.len()).assert_satisfied();
+
+        // An income wider than the comparison width has no bucket
+        let mut circuit = IncomeBucketCircuit::<Fp>::new(None, boundaries.clone());
+        circuit.income = Value::known(Fp::from_u128(1u128 << INCOME_BITS));
+        for bucket in 0..=boundaries.len() {
+            let prover = MockProver::run(K, &circuit, vec![public_inputs(bucket, &boundaries)]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+    }
+
+    #[test]
+    fn test_check_boundaries() {
+        assert!(check_boundaries(&boundaries()).is_ok());
+        assert!(check_boundaries(&[]).is_ok());
+        assert!(check_boundaries(&[60000, 30000]).is_err());
+        assert!(check_boundaries(&[30000, 30000]).is_err());
+        assert!(check_boundaries(&[1u128 << INCOME_BITS]).is_err());
+    }
+
+    #[test]
+    fn test_circuit_without_witnesses() {
+        use crate::circuits::lending::assert_keygen;
+
+        let circuit = IncomeBucketCircuit::<Fp>::new(None, boundaries());
+        let circuit_without_witnesses = circuit.without_witnesses();
+
+        assert_eq!(circuit_without_witnesses.boundaries, boundaries());
+        assert_keygen(K, &circuit_without_witnesses);
+    }
+}
@@ -0,0 +1,384 @@
+use halo2_gadgets::{
+    ecc::{
+        chip::{BaseFieldElem, EccChip, EccConfig, FixedPoint, FullScalar, ShortScalar, H},
+        FixedPoints, NonIdentityPoint, ScalarVar,
+    },
+    utilities::UtilitiesInstructions,
+    sinsemilla,
+    utilities::lookup_range_check::LookupRangeCheckConfig,
+};
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, TableColumn},
+};
+use ff::{Field, PrimeField};
+use pasta_curves::{arithmetic::CurveAffine, group::Group, pallas};
+use std::sync::OnceLock;
+
+use super::income_range::{IncomeRangeChip, IncomeRangeConfig};
+use super::lending::known;
+use super::loan_history::field_to_u128;
+
+/// Domain separator for hashing the commitment generators to the curve
+pub const INCOME_COMMITMENT_DOMAIN: &str = "message-credit:income-commitment";
+
+/// The ECC chip is generic over a set of fixed bases, but precomputing the
+/// window tables for a new base takes minutes. The commitment witnesses `G`
+/// and `H` as constant-constrained points and uses variable-base
+/// multiplication instead, so this set is empty.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NoFixedBases;
+
+macro_rules! unused_fixed_base {
+    ($name:ident, $kind:ty) => {
+        #[derive(Clone, Debug, PartialEq, Eq)]
+        pub struct $name;
+
+        impl FixedPoint<pallas::Affine> for $name {
+            type FixedScalarKind = $kind;
+
+            fn generator(&self) -> pallas::Affine {
+                unreachable!("income commitments use no fixed bases")
+            }
+
+            fn u(&self) -> Vec<[[u8; 32]; H]> {
+                unreachable!("income commitments use no fixed bases")
+            }
+
+            fn z(&self) -> Vec<u64> {
+                unreachable!("income commitments use no fixed bases")
+            }
+        }
+    };
+}
+
+unused_fixed_base!(NoFullScalarBase, FullScalar);
+unused_fixed_base!(NoShortScalarBase, ShortScalar);
+unused_fixed_base!(NoBaseFieldBase, BaseFieldElem);
+
+impl FixedPoints<pallas::Affine> for NoFixedBases {
+    type FullScalar = NoFullScalarBase;
+    type ShortScalar = NoShortScalarBase;
+    type Base = NoBaseFieldBase;
+}
+
+/// Configuration for the income commitment circuit
+#[derive(Clone, Debug)]
+pub struct IncomeCommitmentConfig {
+    /// Income range check over the committed income
+    pub income_range: IncomeRangeConfig,
+    /// Advice column for the blinding factor (private input)
+    pub blinding: Column<Advice>,
+    /// Scalar multiplication and point addition on Pallas
+    pub ecc: EccConfig<NoFixedBases>,
+    /// Table of `[0, 2^10)` backing the ECC chip's lookup range checks
+    pub lookup_table: TableColumn,
+}
+
+impl IncomeCommitmentConfig {
+    /// Fill the lookup table; the ECC gadget expects it preloaded
+    fn load_lookup_table(&self, layouter: &mut impl Layouter<pallas::Base>) -> Result<(), Error> {
+        layouter.assign_table(
+            || "range check table",
+            |mut table| {
+                for index in 0..(1 << sinsemilla::primitives::K) {
+                    table.assign_cell(
+                        || "range check value",
+                        self.lookup_table,
+                        index,
+                        || Value::known(pallas::Base::from(index as u64)),
+                    )?;
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+/// Witness a commitment generator, pinned to its constant coordinates
+fn assign_generator(
+    chip: &EccChip<NoFixedBases>,
+    mut layouter: impl Layouter<pallas::Base>,
+    generator: pallas::Affine,
+) -> Result<NonIdentityPoint<pallas::Affine, EccChip<NoFixedBases>>, Error> {
+    let point = NonIdentityPoint::new(chip.clone(), layouter.namespace(|| "witness"), Value::known(generator))?;
+    let coordinates = generator.coordinates().unwrap();
+
+    layouter.assign_region(
+        || "pin generator",
+        |mut region| {
+            region.constrain_constant(point.inner().x().cell(), *coordinates.x())?;
+            region.constrain_constant(point.inner().y().cell(), *coordinates.y())
+        },
+    )?;
+
+    Ok(point)
+}
+
+/// Circuit proving a Pedersen-committed income lies in `[min, max]`
+///
+/// The commitment `C = income*G + blinding*H` is public, the income and
+/// blinding stay private, and the circuit recomputes `C` from the same income
+/// cell the range check uses. Commitments add homomorphically:
+/// `commit(a, r) + commit(b, s) == commit(a + b, r + s)`, so a lender can sum
+/// several borrowers' commitments and ask for one proof that the total is in
+/// range without learning any individual income.
+///
+/// The commitment lives on Pallas, so the circuit is only implemented over
+/// its base field, and the blinding is a base field element used as an
+/// integer scalar. An aggregate therefore opens in-circuit only while the
+/// summed blindings stay below the field modulus; leave a few bits of
+/// headroom (e.g. 250-bit blindings) when commitments will be added.
+///
+/// Instance layout: `[in_range, C.x, C.y, min, max]`, with the identity
+/// encoded as `(0, 0)`.
+#[derive(Clone, Debug)]
+pub struct IncomeCommitmentCircuit<F: PrimeField> {
+    /// Private input: the committed income
+    pub income: Value<F>,
+    /// Private input: the commitment's blinding factor
+    pub blinding: Value<F>,
+    /// Public input: the minimum income
+    pub min_range: Value<F>,
+    /// Public input: the maximum income
+    pub max_range: Value<F>,
+}
+
+impl IncomeCommitmentCircuit<pallas::Base> {
+    pub fn new(income: Option<u128>, blinding: Option<pallas::Base>, min_range: u128, max_range: u128) -> Self {
+        Self {
+            income: income.map(|v| Value::known(pallas::Base::from_u128(v))).unwrap_or_else(Value::unknown),
+            blinding: blinding.map(Value::known).unwrap_or_else(Value::unknown),
+            min_range: Value::known(pallas::Base::from_u128(min_range)),
+            max_range: Value::known(pallas::Base::from_u128(max_range)),
+        }
+    }
+
+    /// Public inputs `[in_range, C.x, C.y, min, max]`, with the commitment
+    /// computed from the known income and blinding (the identity without a
+    /// witness)
+    pub fn expected_public_inputs(&self) -> Vec<pallas::Base> {
+        let to_u128 = |value: Value<pallas::Base>| known(value).and_then(|v| field_to_u128(&v));
+        let (income, min, max) = (to_u128(self.income), to_u128(self.min_range), to_u128(self.max_range));
+        let in_range = matches!((income, min, max), (Some(i), Some(lo), Some(hi)) if lo <= i && i <= hi);
+
+        let commitment = match (income, known(self.blinding)) {
+            (Some(income), Some(blinding)) => utils::commit(income, blinding),
+            _ => pallas::Point::identity(),
+        };
+
+        utils::public_inputs(
+            in_range,
+            &commitment,
+            known(self.min_range).unwrap_or(pallas::Base::ZERO),
+            known(self.max_range).unwrap_or(pallas::Base::ZERO),
+        )
+    }
+}
+
+impl Circuit<pallas::Base> for IncomeCommitmentCircuit<pallas::Base> {
+    type Config = IncomeCommitmentConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            income: Value::unknown(),
+            blinding: Value::unknown(),
+            min_range: self.min_range,
+            max_range: self.max_range,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+        let income = meta.advice_column();
+        let min_range = meta.advice_column();
+        let max_range = meta.advice_column();
+        let result = meta.advice_column();
+        let comparison = [(); 5].map(|_| meta.advice_column());
+        let instance = meta.instance_column();
+        let income_range =
+            IncomeRangeChip::configure(meta, income, min_range, max_range, result, comparison, instance);
+
+        let blinding = meta.advice_column();
+        meta.enable_equality(blinding);
+
+        let advices = [(); 10].map(|_| meta.advice_column());
+        let lagrange_coeffs = [(); 8].map(|_| meta.fixed_column());
+        let constants = meta.fixed_column();
+        meta.enable_constant(constants);
+
+        let lookup_table = meta.lookup_table_column();
+        let range_check = LookupRangeCheckConfig::configure(meta, advices[9], lookup_table);
+        let ecc = EccChip::<NoFixedBases>::configure(meta, advices, lagrange_coeffs, range_check);
+
+        IncomeCommitmentConfig { income_range, blinding, ecc, lookup_table }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<pallas::Base>,
+    ) -> Result<(), Error> {
+        let range_chip = IncomeRangeChip::construct(config.income_range.clone());
+        let ecc_chip = EccChip::construct(config.ecc.clone());
+        config.load_lookup_table(&mut layouter)?;
+
+        let assigned = range_chip.assign_range_check(
+            layouter.namespace(|| "income range check"),
+            self.income,
+            self.min_range,
+            self.max_range,
+        )?;
+
+        // C = income*G + blinding*H, with income taken from the range-checked cell
+        let (g, h) = utils::generators();
+        let g = assign_generator(&ecc_chip, layouter.namespace(|| "G"), g)?;
+        let h = assign_generator(&ecc_chip, layouter.namespace(|| "H"), h)?;
+
+        let income = ScalarVar::from_base(ecc_chip.clone(), layouter.namespace(|| "income scalar"), &assigned.income)?;
+        let (income_point, _) = g.mul(layouter.namespace(|| "income * G"), income)?;
+
+        let blinding = ecc_chip.load_private(layouter.namespace(|| "blinding"), config.blinding, self.blinding)?;
+        let blinding = ScalarVar::from_base(ecc_chip, layouter.namespace(|| "blinding scalar"), &blinding)?;
+        let (blinding_point, _) = h.mul(layouter.namespace(|| "blinding * H"), blinding)?;
+
+        let commitment = income_point.add(layouter.namespace(|| "commitment"), &blinding_point)?;
+
+        let instance = config.income_range.instance;
+        layouter.constrain_instance(assigned.result.cell(), instance, 0)?;
+        layouter.constrain_instance(commitment.inner().x().cell(), instance, 1)?;
+        layouter.constrain_instance(commitment.inner().y().cell(), instance, 2)?;
+        layouter.constrain_instance(assigned.min_range.cell(), instance, 3)?;
+        layouter.constrain_instance(assigned.max_range.cell(), instance, 4)?;
+
+        Ok(())
+    }
+}
+
+/// Utility functions for Pedersen income commitments
+pub mod utils {
+    use super::*;
+    use pasta_curves::arithmetic::CurveExt;
+    use pasta_curves::group::Curve;
+
+    /// The commitment generators `(G, H)`, hashed to the curve so nobody knows
+    /// the discrete log between them
+    pub fn generators() -> (pallas::Affine, pallas::Affine) {
+        static GENERATORS: OnceLock<(pallas::Affine, pallas::Affine)> = OnceLock::new();
+        *GENERATORS.get_or_init(|| {
+            let hasher = pallas::Point::hash_to_curve(INCOME_COMMITMENT_DOMAIN);
+            (hasher(b"G").to_affine(), hasher(b"H").to_affine())
+        })
+    }
+
+    /// Pedersen commitment `income*G + blinding*H`
+    pub fn commit(income: u128, blinding: pallas::Base) -> pallas::Point {
+        let (g, h) = generators();
+        // The base field is smaller than the scalar field, so this never fails
+        let blinding = pallas::Scalar::from_repr(blinding.to_repr()).unwrap();
+        g * pallas::Scalar::from_u128(income) + h * blinding
+    }
+
+    /// Affine coordinates of a commitment, `(0, 0)` for the identity
+    pub fn coordinates(commitment: &pallas::Point) -> (pallas::Base, pallas::Base) {
+        Option::from(commitment.to_affine().coordinates())
+            .map(|c: pasta_curves::arithmetic::Coordinates<pallas::Affine>| (*c.x(), *c.y()))
+            .unwrap_or((pallas::Base::ZERO, pallas::Base::ZERO))
+    }
+
+    /// Build the public inputs in the circuit's instance order:
+    /// `[in_range, C.x, C.y, min, max]`
+    pub fn public_inputs(in_range: bool, commitment: &pallas::Point, min: pallas::Base, max: pallas::Base) -> Vec<pallas::Base> {
+        let (x, y) = coordinates(commitment);
+        vec![if in_range { pallas::Base::ONE } else { pallas::Base::ZERO }, x, y, min, max]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::utils::*;
+    use halo2_proofs::dev::MockProver;
+    use pasta_curves::Fp;
+
+    const K: u32 = 11;
+
+    fn blinding(seed: u64) -> Fp {
+        Fp::from(seed) * Fp::from(0x9e37_79b9_7f4a_7c15u64).pow([3])
+    }
+
+    fn run(income: u128, blinding: Fp, min: u128, max: u128, public_inputs: Vec<Fp>) -> MockProver<Fp> {
+        let circuit = IncomeCommitmentCircuit::new(Some(income), Some(blinding), min, max);
+        MockProver::run(K, &circuit, vec![public_inputs]).unwrap()
+    }
+
+    fn inputs(in_range: bool, commitment: &pallas::Point, min: u128, max: u128) -> Vec<Fp> {
+        public_inputs(in_range, commitment, Fp::from_u128(min), Fp::from_u128(max))
+    }
+
+    #[test]
+    fn test_committed_income_in_range() {
+        let commitment = commit(50_000, blinding(1));
+        let prover = run(50_000, blinding(1), 30_000, 80_000, inputs(true, &commitment, 30_000, 80_000));
+        prover.assert_satisfied();
+
+        let circuit = IncomeCommitmentCircuit::new(Some(50_000), Some(blinding(1)), 30_000, 80_000);
+        assert_eq!(circuit.expected_public_inputs(), inputs(true, &commitment, 30_000, 80_000));
+    }
+
+    #[test]
+    fn test_committed_income_out_of_range() {
+        let commitment = commit(90_000, blinding(2));
+        let prover = run(90_000, blinding(2), 30_000, 80_000, inputs(true, &commitment, 30_000, 80_000));
+        assert!(prover.verify().is_err());
+
+        let prover = run(90_000, blinding(2), 30_000, 80_000, inputs(false, &commitment, 30_000, 80_000));
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn test_wrong_commitment_rejected() {
+        // Same income under a different blinding, and a different income
+        for commitment in [commit(50_000, blinding(4)), commit(50_001, blinding(3))] {
+            let prover = run(50_000, blinding(3), 30_000, 80_000, inputs(true, &commitment, 30_000, 80_000));
+            assert!(prover.verify().is_err());
+        }
+
+        // The bounds are bound to the instance as well
+        let commitment = commit(50_000, blinding(3));
+        let prover = run(50_000, blinding(3), 30_000, 80_000, inputs(true, &commitment, 60_000, 80_000));
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_commitments_add_homomorphically() {
+        let (a, b) = (42_000u128, 37_500u128);
+        let (r, s) = (blinding(5), blinding(6));
+        let sum = commit(a, r) + commit(b, s);
+        assert_eq!(sum, commit(a + b, r + s));
+
+        // The aggregate opens to the summed income and blinding
+        let prover = run(a + b, r + s, 60_000, 100_000, inputs(true, &sum, 60_000, 100_000));
+        prover.assert_satisfied();
+
+        // A zero income with zero blinding commits to the identity
+        let identity = commit(0, Fp::zero());
+        assert_eq!(coordinates(&identity), (Fp::zero(), Fp::zero()));
+        let prover = run(0, Fp::zero(), 0, 10, inputs(true, &identity, 0, 10));
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn test_circuit_without_witnesses() {
+        use crate::circuits::lending::assert_keygen;
+
+        let circuit = IncomeCommitmentCircuit::new(None, None, 30_000, 80_000);
+        let circuit_without_witnesses = circuit.without_witnesses();
+
+        assert_eq!(
+            circuit_without_witnesses.expected_public_inputs(),
+            inputs(false, &pallas::Point::identity(), 30_000, 80_000)
+        );
+        assert_keygen(K, &circuit_without_witnesses);
+    }
+}
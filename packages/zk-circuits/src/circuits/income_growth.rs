@@ -0,0 +1,451 @@
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Instance, Selector},
+    poly::Rotation,
+};
+use ff::PrimeField;
+use std::marker::PhantomData;
+
+use super::AssignedCell;
+use super::income_range::INCOME_BITS;
+use super::lending::{known, InstanceKind, InstanceSpec, LendingCircuit};
+use super::loan_history::{check_fits_u128, field_to_u128};
+use super::optimizations::{RangeCheckChip, RangeCheckConfig};
+
+/// Basis points in a whole (100%)
+pub const GROWTH_BPS_SCALE: u64 = 10000;
+
+/// Bit width of the minimum growth rate, in basis points
+pub const GROWTH_BPS_BITS: usize = 16;
+
+/// Bit width of the difference between the scaled incomes
+///
+/// Both products stay below `2^INCOME_BITS * (GROWTH_BPS_SCALE + 2^GROWTH_BPS_BITS)`,
+/// which is under `2^(INCOME_BITS + 17)`.
+pub const GROWTH_COMPARISON_BITS: usize = INCOME_BITS + 17;
+
+/// Configuration for the income growth circuit
+#[derive(Clone, Debug)]
+pub struct IncomeGrowthConfig {
+    /// Advice column for the earlier period's income (private input)
+    pub previous_income: Column<Advice>,
+    /// Advice column for the later period's income (private input)
+    pub current_income: Column<Advice>,
+    /// Advice column for the minimum growth in basis points (public input)
+    pub min_growth_bps: Column<Advice>,
+    /// Advice column for `current_income * GROWTH_BPS_SCALE`
+    pub scaled_current: Column<Advice>,
+    /// Advice column for `previous_income * (GROWTH_BPS_SCALE + min_growth_bps)`
+    pub required: Column<Advice>,
+    /// Advice column for the result (1 if the growth is met, 0 if not)
+    pub result: Column<Advice>,
+    /// Low N bits of `scaled_current - required + 2^N`
+    pub remainder: Column<Advice>,
+    /// Instance column for public inputs/outputs
+    pub instance: Column<Instance>,
+    /// Selector for the growth comparison gate
+    pub selector: Selector,
+    /// Range check bounding the incomes, the growth rate and the remainder
+    pub range_check: RangeCheckConfig,
+}
+
+/// Cells assigned by the income growth check
+pub struct AssignedIncomeGrowth<F: PrimeField> {
+    pub previous_income: AssignedCell<F>,
+    pub current_income: AssignedCell<F>,
+    pub min_growth_bps: AssignedCell<F>,
+    pub result: AssignedCell<F>,
+}
+
+/// Chip for income growth verification operations
+pub struct IncomeGrowthChip<F: PrimeField> {
+    config: IncomeGrowthConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: PrimeField> IncomeGrowthChip<F> {
+    pub fn construct(config: IncomeGrowthConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// `comparison` holds the `[scaled_current, required, remainder, range]`
+    /// columns
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        previous_income: Column<Advice>,
+        current_income: Column<Advice>,
+        min_growth_bps: Column<Advice>,
+        result: Column<Advice>,
+        comparison: [Column<Advice>; 4],
+        instance: Column<Instance>,
+    ) -> IncomeGrowthConfig {
+        let [scaled_current, required, remainder, range_column] = comparison;
+        let selector = meta.selector();
+        let range_check = RangeCheckChip::configure(meta, range_column, GROWTH_COMPARISON_BITS);
+
+        // Enable equality constraints for public inputs/outputs
+        meta.enable_equality(previous_income);
+        meta.enable_equality(current_income);
+        meta.enable_equality(min_growth_bps);
+        meta.enable_equality(result);
+        meta.enable_equality(remainder);
+        meta.enable_equality(instance);
+
+        // current * 10000 >= previous * (10000 + bps), compared by splitting
+        // `scaled_current - required + 2^N` into a top bit (the result) and an
+        // N-bit remainder. The inputs are range checked, so neither product
+        // can wrap around the field.
+        meta.create_gate("income_growth_check", |meta| {
+            let s = meta.query_selector(selector);
+            let previous = meta.query_advice(previous_income, Rotation::cur());
+            let current = meta.query_advice(current_income, Rotation::cur());
+            let bps = meta.query_advice(min_growth_bps, Rotation::cur());
+            let scaled_current = meta.query_advice(scaled_current, Rotation::cur());
+            let required = meta.query_advice(required, Rotation::cur());
+            let result = meta.query_advice(result, Rotation::cur());
+            let remainder = meta.query_advice(remainder, Rotation::cur());
+
+            let one = Expression::Constant(F::ONE);
+            let scale = Expression::Constant(F::from(GROWTH_BPS_SCALE));
+            let offset = Expression::Constant(F::from_u128(1u128 << GROWTH_COMPARISON_BITS));
+
+            vec![
+                s.clone() * (scaled_current.clone() - current * scale.clone()),
+                s.clone() * (required.clone() - previous * (scale + bps)),
+                s.clone() * (result.clone() * (result.clone() - one)),
+                s * (scaled_current - required + offset.clone() - (result * offset + remainder)),
+            ]
+        });
+
+        IncomeGrowthConfig {
+            previous_income,
+            current_income,
+            min_growth_bps,
+            scaled_current,
+            required,
+            result,
+            remainder,
+            instance,
+            selector,
+            range_check,
+        }
+    }
+
+    /// Assign the income growth check
+    pub fn assign_growth_check(
+        &self,
+        mut layouter: impl Layouter<F>,
+        previous_income: Value<F>,
+        current_income: Value<F>,
+        min_growth_bps: Value<F>,
+    ) -> Result<AssignedIncomeGrowth<F>, Error> {
+        check_fits_u128(previous_income)?;
+        check_fits_u128(current_income)?;
+        check_fits_u128(min_growth_bps)?;
+
+        let (assigned, remainder) = layouter.assign_region(
+            || "income growth check",
+            |mut region| {
+                // Enable the selector
+                self.config.selector.enable(&mut region, 0)?;
+
+                // Assign both incomes (private inputs)
+                let previous_cell = region.assign_advice(|| "previous income", self.config.previous_income, 0, || previous_income)?;
+                let current_cell = region.assign_advice(|| "current income", self.config.current_income, 0, || current_income)?;
+
+                // Assign the minimum growth (public input)
+                let bps_cell = region.assign_advice(|| "min growth bps", self.config.min_growth_bps, 0, || min_growth_bps)?;
+
+                let scale = Value::known(F::from(GROWTH_BPS_SCALE));
+                let scaled_current = current_income * scale;
+                let required = previous_income * (scale + min_growth_bps);
+                region.assign_advice(|| "scaled current income", self.config.scaled_current, 0, || scaled_current)?;
+                region.assign_advice(|| "required income", self.config.required, 0, || required)?;
+
+                // Compare numerically: `a - b + 2^N` has its top bit set iff a >= b
+                let comparison = scaled_current.zip(required).map(|(a, b)| {
+                    let (a, b) = (field_to_u128(&a).unwrap_or_default(), field_to_u128(&b).unwrap_or_default());
+                    let diff = a.wrapping_add(1u128 << GROWTH_COMPARISON_BITS).wrapping_sub(b);
+                    (diff >> GROWTH_COMPARISON_BITS == 1, diff & ((1u128 << GROWTH_COMPARISON_BITS) - 1))
+                });
+
+                let remainder = region.assign_advice(
+                    || "growth remainder",
+                    self.config.remainder,
+                    0,
+                    || comparison.map(|(_, r)| F::from_u128(r)),
+                )?;
+                let result_cell = region.assign_advice(
+                    || "growth result",
+                    self.config.result,
+                    0,
+                    || comparison.map(|(met, _)| if met { F::ONE } else { F::ZERO }),
+                )?;
+
+                let assigned = AssignedIncomeGrowth {
+                    previous_income: previous_cell,
+                    current_income: current_cell,
+                    min_growth_bps: bps_cell,
+                    result: result_cell,
+                };
+
+                Ok((assigned, remainder))
+            },
+        )?;
+
+        // Bound the inputs so the products cannot wrap, then the remainder
+        let range_chip = RangeCheckChip::construct(self.config.range_check.clone());
+        range_chip.copy_range_check(layouter.namespace(|| "previous income"), &assigned.previous_income, INCOME_BITS)?;
+        range_chip.copy_range_check(layouter.namespace(|| "current income"), &assigned.current_income, INCOME_BITS)?;
+        range_chip.copy_range_check(layouter.namespace(|| "min growth bps"), &assigned.min_growth_bps, GROWTH_BPS_BITS)?;
+        range_chip.copy_range_check(layouter.namespace(|| "growth remainder"), &remainder, GROWTH_COMPARISON_BITS)?;
+
+        Ok(assigned)
+    }
+}
+
+/// Circuit proving income grew by at least a public rate between two periods
+///
+/// Proves `current_income >= previous_income * (1 + min_growth_bps / 10000)`
+/// without revealing either income, as
+/// `current_income * 10000 >= previous_income * (10000 + min_growth_bps)`
+/// so no division is needed. Exactly meeting the rate counts as met. A zero
+/// previous income meets any rate; pair this with an income range proof to
+/// also require a minimum current income.
+///
+/// Instance layout: `[met, min_growth_bps]`.
+#[derive(Clone, Debug)]
+pub struct IncomeGrowthCircuit<F: PrimeField> {
+    /// Private input: the earlier period's income
+    pub previous_income: Value<F>,
+    /// Private input: the later period's income
+    pub current_income: Value<F>,
+    /// Public input: the minimum growth in basis points (100 = 1%)
+    pub min_growth_bps: Value<F>,
+}
+
+impl<F: PrimeField> IncomeGrowthCircuit<F> {
+    pub fn new(previous_income: Option<u128>, current_income: Option<u128>, min_growth_bps: u64) -> Self {
+        Self {
+            previous_income: previous_income.map(|v| Value::known(F::from_u128(v))).unwrap_or_else(Value::unknown),
+            current_income: current_income.map(|v| Value::known(F::from_u128(v))).unwrap_or_else(Value::unknown),
+            min_growth_bps: Value::known(F::from(min_growth_bps)),
+        }
+    }
+
+    /// Public inputs `[met, min_growth_bps]`, with the result computed from
+    /// the known incomes (0 without a witness)
+    pub fn expected_public_inputs(&self) -> Vec<F> {
+        let to_u128 = |value: Value<F>| known(value).and_then(|v| field_to_u128(&v));
+        let min_growth_bps = to_u128(self.min_growth_bps).and_then(|bps| u64::try_from(bps).ok()).unwrap_or(0);
+        let met = to_u128(self.previous_income)
+            .zip(to_u128(self.current_income))
+            .is_some_and(|(previous, current)| utils::meets_growth(previous, current, min_growth_bps));
+
+        utils::public_inputs(met, min_growth_bps)
+    }
+}
+
+impl<F: PrimeField> Circuit<F> for IncomeGrowthCircuit<F> {
+    type Config = IncomeGrowthConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            previous_income: Value::unknown(),
+            current_income: Value::unknown(),
+            min_growth_bps: self.min_growth_bps,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let previous_income = meta.advice_column();
+        let current_income = meta.advice_column();
+        let min_growth_bps = meta.advice_column();
+        let result = meta.advice_column();
+        let comparison = [(); 4].map(|_| meta.advice_column());
+        let instance = meta.instance_column();
+
+        IncomeGrowthChip::configure(meta, previous_income, current_income, min_growth_bps, result, comparison, instance)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = IncomeGrowthChip::construct(config.clone());
+
+        let assigned = chip.assign_growth_check(
+            layouter.namespace(|| "income growth check"),
+            self.previous_income,
+            self.current_income,
+            self.min_growth_bps,
+        )?;
+
+        // Expose the result (instance 0) and bind the rate (instance 1)
+        layouter.constrain_instance(assigned.result.cell(), config.instance, 0)?;
+        layouter.constrain_instance(assigned.min_growth_bps.cell(), config.instance, 1)?;
+
+        Ok(())
+    }
+}
+
+impl<F: PrimeField> LendingCircuit<F> for IncomeGrowthCircuit<F> {
+    fn public_inputs(&self) -> Vec<F> {
+        self.expected_public_inputs()
+    }
+
+    fn recommended_k() -> u32 {
+        9
+    }
+
+    fn instance_layout() -> Vec<InstanceSpec> {
+        InstanceSpec::layout(&[
+            ("met", InstanceKind::Bool),
+            ("min_growth_bps", InstanceKind::Integer),
+        ])
+    }
+}
+
+/// Utility functions for income growth proofs
+pub mod utils {
+    use super::GROWTH_BPS_SCALE;
+    use ff::PrimeField;
+
+    /// Whether `current_income >= previous_income * (1 + min_growth_bps / 10000)`,
+    /// matching the circuit's integer comparison
+    pub fn meets_growth(previous_income: u128, current_income: u128, min_growth_bps: u64) -> bool {
+        let scaled_current = current_income.checked_mul(GROWTH_BPS_SCALE as u128);
+        let required = previous_income.checked_mul(GROWTH_BPS_SCALE as u128 + min_growth_bps as u128);
+
+        match (scaled_current, required) {
+            (Some(scaled_current), Some(required)) => scaled_current >= required,
+            (_, None) => false,
+            (None, Some(_)) => true,
+        }
+    }
+
+    /// Build the public inputs in the circuit's instance order:
+    /// `[met, min_growth_bps]`
+    pub fn public_inputs<F: PrimeField>(met: bool, min_growth_bps: u64) -> Vec<F> {
+        vec![if met { F::ONE } else { F::ZERO }, F::from(min_growth_bps)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::utils::{meets_growth, public_inputs};
+    use halo2_proofs::dev::MockProver;
+    use pasta_curves::Fp;
+
+    const K: u32 = 9;
+
+    fn run(previous: u128, current: u128, min_growth_bps: u64, met: bool) -> MockProver<Fp> {
+        let circuit = IncomeGrowthCircuit::<Fp>::new(Some(previous), Some(current), min_growth_bps);
+        MockProver::run(K, &circuit, vec![public_inputs(met, min_growth_bps)]).unwrap()
+    }
+
+    #[test]
+    fn test_growth_met() {
+        // 50,000 -> 60,000 is 20% growth, above the 10% minimum
+        run(50_000, 60_000, 1000, true).assert_satisfied();
+        assert!(run(50_000, 60_000, 1000, false).verify().is_err());
+
+        // No growth required, and none made
+        run(50_000, 50_000, 0, true).assert_satisfied();
+    }
+
+    #[test]
+    fn test_growth_not_met() {
+        // 50,000 -> 54,000 is 8% growth, short of 10%
+        run(50_000, 54_000, 1000, false).assert_satisfied();
+        assert!(run(50_000, 54_000, 1000, true).verify().is_err());
+
+        // A falling income never meets a growth requirement
+        run(50_000, 40_000, 0, false).assert_satisfied();
+    }
+
+    #[test]
+    fn test_growth_exactly_at_threshold() {
+        run(50_000, 55_000, 1000, true).assert_satisfied();
+        run(50_000, 54_999, 1000, false).assert_satisfied();
+
+        // 1/3 growth cannot be met exactly in whole units
+        run(3, 4, 3333, true).assert_satisfied();
+        run(3, 4, 3334, false).assert_satisfied();
+    }
+
+    #[test]
+    fn test_zero_previous_income() {
+        run(0, 0, 0, true).assert_satisfied();
+        run(0, 1, u16::MAX as u64, true).assert_satisfied();
+        assert!(run(0, 1, 1000, false).verify().is_err());
+    }
+
+    #[test]
+    fn test_large_incomes() {
+        let max = (1u128 << INCOME_BITS) - 1;
+        run(max, max, 0, true).assert_satisfied();
+        run(max, max, 1, false).assert_satisfied();
+        run(max / 2, max, 9999, true).assert_satisfied();
+        run(1, max, u16::MAX as u64, true).assert_satisfied();
+    }
+
+    #[test]
+    fn test_out_of_range_inputs_rejected() {
+        // An income past INCOME_BITS could wrap its product around the field
+        let too_wide = 1u128 << INCOME_BITS;
+        assert!(run(too_wide, 1, 0, false).verify().is_err());
+        assert!(run(1, too_wide, 0, true).verify().is_err());
+
+        assert!(run(50_000, 1_000_000, 1 << GROWTH_BPS_BITS, true).verify().is_err());
+
+        let mut circuit = IncomeGrowthCircuit::<Fp>::new(None, Some(50_000), 1000);
+        circuit.previous_income = Value::known(-Fp::one());
+        assert!(MockProver::run(K, &circuit, vec![public_inputs(false, 1000)]).is_err());
+    }
+
+    #[test]
+    fn test_meets_growth() {
+        assert!(meets_growth(50_000, 55_000, 1000));
+        assert!(!meets_growth(50_000, 54_999, 1000));
+        assert!(meets_growth(0, 0, u64::MAX));
+        assert!(!meets_growth(u128::MAX, u128::MAX, 1));
+        assert!(meets_growth(1, u128::MAX, 1000));
+    }
+
+    #[test]
+    fn test_every_instance_constrained() {
+        use crate::circuits::lending::assert_circuit;
+
+        for (previous, current, min_growth_bps, met) in [(50_000, 60_000, 1000, true), (50_000, 54_000, 1000, false), (0, 0, 500, true)] {
+            let circuit = IncomeGrowthCircuit::<Fp>::new(Some(previous), Some(current), min_growth_bps);
+            assert_eq!(circuit.expected_public_inputs(), public_inputs(met, min_growth_bps));
+            assert_circuit(K, &circuit, vec![public_inputs(met, min_growth_bps)]);
+        }
+    }
+
+    #[test]
+    fn test_recommended_k() {
+        use crate::circuits::optimizations::performance::min_k_for;
+
+        let circuit = IncomeGrowthCircuit::<Fp>::new(Some(50_000), Some(60_000), 1000);
+        assert_eq!(min_k_for(&circuit), IncomeGrowthCircuit::<Fp>::recommended_k());
+    }
+
+    #[test]
+    fn test_circuit_without_witnesses() {
+        use crate::circuits::lending::assert_keygen;
+
+        let circuit = IncomeGrowthCircuit::<Fp>::new(Some(50_000), Some(60_000), 1000);
+        let circuit_without_witnesses = circuit.without_witnesses();
+
+        assert_eq!(circuit_without_witnesses.expected_public_inputs(), public_inputs(false, 1000));
+        assert_keygen(K, &circuit_without_witnesses);
+    }
+}
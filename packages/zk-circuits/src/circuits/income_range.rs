@@ -6,6 +6,14 @@ use halo2_proofs::{
 use ff::PrimeField;
 use std::marker::PhantomData;
 
+use super::AssignedCell;
+use super::lending::{known, InstanceKind, InstanceSpec, LendingCircuit};
+use super::loan_history::{check_fits_u128, field_to_u128};
+use super::optimizations::{RangeCheckChip, RangeCheckConfig};
+
+/// Bit width of incomes and range bounds
+pub const INCOME_BITS: usize = 80;
+
 /// Configuration for the income range circuit
 #[derive(Clone, Debug)]
 pub struct IncomeRangeConfig {
@@ -17,10 +25,28 @@ pub struct IncomeRangeConfig {
     pub max_range: Column<Advice>,
     /// Advice column for the result (1 if in range, 0 if not)
     pub result: Column<Advice>,
+    /// Advice column for the `income >= min_range` bit
+    pub above_min: Column<Advice>,
+    /// Advice column for the `income <= max_range` bit
+    pub below_max: Column<Advice>,
+    /// Low N bits of `income - min_range + 2^N`
+    pub min_remainder: Column<Advice>,
+    /// Low N bits of `max_range - income + 2^N`
+    pub max_remainder: Column<Advice>,
     /// Instance column for public inputs/outputs
     pub instance: Column<Instance>,
     /// Selector for the range check gate
     pub selector: Selector,
+    /// Range check proving both remainders fit in N bits
+    pub range_check: RangeCheckConfig,
+}
+
+/// Cells assigned by the income range check
+pub struct AssignedIncomeRange<F: PrimeField> {
+    pub income: AssignedCell<F>,
+    pub min_range: AssignedCell<F>,
+    pub max_range: AssignedCell<F>,
+    pub result: AssignedCell<F>,
 }
 
 /// Chip for income range verification operations
@@ -43,31 +69,52 @@ impl<F: PrimeField> IncomeRangeChip<F> {
         min_range: Column<Advice>,
         max_range: Column<Advice>,
         result: Column<Advice>,
+        comparison: [Column<Advice>; 5],
         instance: Column<Instance>,
     ) -> IncomeRangeConfig {
+        let [above_min, below_max, min_remainder, max_remainder, range_column] = comparison;
         let selector = meta.selector();
+        let range_check = RangeCheckChip::configure(meta, range_column, INCOME_BITS);
 
         // Enable equality constraints for public inputs/outputs
         meta.enable_equality(income);
         meta.enable_equality(min_range);
         meta.enable_equality(max_range);
         meta.enable_equality(result);
+        meta.enable_equality(min_remainder);
+        meta.enable_equality(max_remainder);
         meta.enable_equality(instance);
 
         // Create the range check gate
         // This gate checks if min_range <= income <= max_range
+        //
+        // Each bound is compared by splitting `a - b + 2^N` into a top bit and
+        // an N-bit remainder: the top bit is set exactly when a >= b. The
+        // remainders are range checked separately by the shared chip.
         meta.create_gate("income_range_check", |meta| {
             let s = meta.query_selector(selector);
-            let _income = meta.query_advice(income, Rotation::cur());
-            let _min_range = meta.query_advice(min_range, Rotation::cur());
-            let _max_range = meta.query_advice(max_range, Rotation::cur());
+            let income = meta.query_advice(income, Rotation::cur());
+            let min_range = meta.query_advice(min_range, Rotation::cur());
+            let max_range = meta.query_advice(max_range, Rotation::cur());
             let result = meta.query_advice(result, Rotation::cur());
+            let above_min = meta.query_advice(above_min, Rotation::cur());
+            let below_max = meta.query_advice(below_max, Rotation::cur());
+            let min_remainder = meta.query_advice(min_remainder, Rotation::cur());
+            let max_remainder = meta.query_advice(max_remainder, Rotation::cur());
+
+            let one = Expression::Constant(F::ONE);
+            let offset = Expression::Constant(F::from_u128(1u128 << INCOME_BITS));
 
-            // For simplicity in this demo, we'll just ensure result is boolean
-            // A full implementation would need range checks and comparison logic
             vec![
                 // Ensure result is boolean (0 or 1)
-                s * (result.clone() * (result - Expression::Constant(F::ONE))),
+                s.clone() * (result.clone() * (result.clone() - one.clone())),
+                s.clone() * (above_min.clone() * (above_min.clone() - one.clone())),
+                s.clone() * (below_max.clone() * (below_max.clone() - one)),
+                s.clone() * (income.clone() - min_range + offset.clone()
+                    - (above_min.clone() * offset.clone() + min_remainder)),
+                s.clone() * (max_range - income + offset.clone()
+                    - (below_max.clone() * offset + max_remainder)),
+                s * (result - above_min * below_max),
             ]
         });
 
@@ -76,8 +123,13 @@ impl<F: PrimeField> IncomeRangeChip<F> {
             min_range,
             max_range,
             result,
+            above_min,
+            below_max,
+            min_remainder,
+            max_remainder,
             instance,
             selector,
+            range_check,
         }
     }
 
@@ -88,15 +140,20 @@ impl<F: PrimeField> IncomeRangeChip<F> {
         income: Value<F>,
         min_range: Value<F>,
         max_range: Value<F>,
-    ) -> Result<AssignedCell<F>, Error> {
-        layouter.assign_region(
+    ) -> Result<AssignedIncomeRange<F>, Error> {
+        // The income is range checked in-circuit below, so an out-of-range
+        // witness is rejected by the constraints rather than here
+        check_fits_u128(min_range)?;
+        check_fits_u128(max_range)?;
+
+        let (assigned, min_remainder, max_remainder) = layouter.assign_region(
             || "income range check",
             |mut region| {
                 // Enable the selector
                 self.config.selector.enable(&mut region, 0)?;
 
                 // Assign income (private input)
-                let _income_cell = region.assign_advice(
+                let income_cell = region.assign_advice(
                     || "income",
                     self.config.income,
                     0,
@@ -104,7 +161,7 @@ impl<F: PrimeField> IncomeRangeChip<F> {
                 )?;
 
                 // Assign min range (public input)
-                let _min_range_cell = region.assign_advice(
+                let min_range_cell = region.assign_advice(
                     || "min range",
                     self.config.min_range,
                     0,
@@ -112,27 +169,44 @@ impl<F: PrimeField> IncomeRangeChip<F> {
                 )?;
 
                 // Assign max range (public input)
-                let _max_range_cell = region.assign_advice(
+                let max_range_cell = region.assign_advice(
                     || "max range",
                     self.config.max_range,
                     0,
                     || max_range,
                 )?;
 
-                // Calculate and assign result
-                let result_value = income.zip(min_range).zip(max_range).map(|((inc, min_r), max_r)| {
-                    // Convert field elements to u64 for comparison
-                    let inc_bytes = inc.to_repr();
-                    let min_bytes = min_r.to_repr();
-                    let max_bytes = max_r.to_repr();
-                    
-                    // Compare the byte representations
-                    if inc_bytes.as_ref() >= min_bytes.as_ref() && inc_bytes.as_ref() <= max_bytes.as_ref() {
-                        F::ONE
-                    } else {
-                        F::ZERO
-                    }
-                });
+                // Compare numerically: `a - b + 2^N` has its top bit set iff a >= b.
+                // The difference is taken in the field, so the remainders stay
+                // consistent with the gate even for an out-of-range income
+                let offset = F::from_u128(1u128 << INCOME_BITS);
+                let compare = |a: Value<F>, b: Value<F>| {
+                    a.zip(b).map(|(a, b)| {
+                        let diff = a - b + offset;
+                        let top = field_to_u128(&diff).is_some_and(|diff| diff >> INCOME_BITS == 1);
+                        (top, if top { diff - offset } else { diff })
+                    })
+                };
+                let above = compare(income, min_range);
+                let below = compare(max_range, income);
+
+                let bool_to_field = |b: bool| if b { F::ONE } else { F::ZERO };
+                region.assign_advice(|| "above min", self.config.above_min, 0, || above.map(|(b, _)| bool_to_field(b)))?;
+                region.assign_advice(|| "below max", self.config.below_max, 0, || below.map(|(b, _)| bool_to_field(b)))?;
+                let min_remainder = region.assign_advice(
+                    || "min remainder",
+                    self.config.min_remainder,
+                    0,
+                    || above.map(|(_, r)| r),
+                )?;
+                let max_remainder = region.assign_advice(
+                    || "max remainder",
+                    self.config.max_remainder,
+                    0,
+                    || below.map(|(_, r)| r),
+                )?;
+
+                let result_value = above.zip(below).map(|((above, _), (below, _))| bool_to_field(above && below));
 
                 let result_cell = region.assign_advice(
                     || "range check result",
@@ -141,13 +215,32 @@ impl<F: PrimeField> IncomeRangeChip<F> {
                     || result_value,
                 )?;
 
-                Ok(result_cell)
+                let assigned = AssignedIncomeRange {
+                    income: income_cell,
+                    min_range: min_range_cell,
+                    max_range: max_range_cell,
+                    result: result_cell,
+                };
+
+                Ok((assigned, min_remainder, max_remainder))
             },
-        )
+        )?;
+
+        // Bound the income to N bits, so a near-modulus value cannot act as a
+        // negative one in the subtractions, then prove both remainders fit
+        let range_chip = RangeCheckChip::construct(self.config.range_check.clone());
+        range_chip.copy_range_check(layouter.namespace(|| "income"), &assigned.income, INCOME_BITS)?;
+        range_chip.copy_range_check(layouter.namespace(|| "min remainder"), &min_remainder, INCOME_BITS)?;
+        range_chip.copy_range_check(layouter.namespace(|| "max remainder"), &max_remainder, INCOME_BITS)?;
+
+        Ok(assigned)
     }
 }
 
 /// The main income range circuit
+///
+/// Both ends of the range are public, so a pass cannot be claimed against a
+/// secretly wider range. Instance layout: `[in_range, min_range, max_range]`.
 #[derive(Clone, Debug)]
 pub struct IncomeRangeCircuit<F: PrimeField> {
     /// Private input: the actual income
@@ -159,17 +252,27 @@ pub struct IncomeRangeCircuit<F: PrimeField> {
 }
 
 impl<F: PrimeField> IncomeRangeCircuit<F> {
-    pub fn new(income: Option<u64>, min_range: u64, max_range: u64) -> Self {
+    pub fn new(income: Option<u128>, min_range: u128, max_range: u128) -> Self {
         Self {
             income: if let Some(inc) = income {
-                Value::known(F::from(inc))
+                Value::known(F::from_u128(inc))
             } else {
                 Value::unknown()
             },
-            min_range: Value::known(F::from(min_range)),
-            max_range: Value::known(F::from(max_range)),
+            min_range: Value::known(F::from_u128(min_range)),
+            max_range: Value::known(F::from_u128(max_range)),
         }
     }
+
+    /// Public inputs `[in_range, min_range, max_range]`, with the result
+    /// computed from the known income (0 without a witness)
+    pub fn expected_public_inputs(&self) -> Vec<F> {
+        let to_u128 = |value: Value<F>| known(value).and_then(|v| field_to_u128(&v));
+        let (min, max) = (to_u128(self.min_range).unwrap_or(0), to_u128(self.max_range).unwrap_or(0));
+        let in_range = to_u128(self.income).is_some_and(|income| min <= income && income <= max);
+
+        utils::public_inputs(in_range, min, max)
+    }
 }
 
 impl<F: PrimeField> Circuit<F> for IncomeRangeCircuit<F> {
@@ -189,9 +292,10 @@ impl<F: PrimeField> Circuit<F> for IncomeRangeCircuit<F> {
         let min_range = meta.advice_column();
         let max_range = meta.advice_column();
         let result = meta.advice_column();
+        let comparison = [(); 5].map(|_| meta.advice_column());
         let instance = meta.instance_column();
 
-        IncomeRangeChip::configure(meta, income, min_range, max_range, result, instance)
+        IncomeRangeChip::configure(meta, income, min_range, max_range, result, comparison, instance)
     }
 
     fn synthesize(
@@ -202,7 +306,7 @@ impl<F: PrimeField> Circuit<F> for IncomeRangeCircuit<F> {
         let chip = IncomeRangeChip::construct(config.clone());
 
         // Assign the range check
-        let result_cell = chip.assign_range_check(
+        let assigned = chip.assign_range_check(
             layouter.namespace(|| "income range check"),
             self.income,
             self.min_range,
@@ -211,36 +315,66 @@ impl<F: PrimeField> Circuit<F> for IncomeRangeCircuit<F> {
 
         // Expose the result as public input (instance 0)
         layouter.constrain_instance(
-            result_cell.cell(),
+            assigned.result.cell(),
             config.instance,
             0,
         )?;
 
+        // Bind the range (instances 1 and 2)
+        layouter.constrain_instance(assigned.min_range.cell(), config.instance, 1)?;
+        layouter.constrain_instance(assigned.max_range.cell(), config.instance, 2)?;
+
         Ok(())
     }
 }
 
-/// Helper type for assigned cells
-pub type AssignedCell<F> = halo2_proofs::circuit::AssignedCell<F, F>;
+impl<F: PrimeField> LendingCircuit<F> for IncomeRangeCircuit<F> {
+    fn public_inputs(&self) -> Vec<F> {
+        self.expected_public_inputs()
+    }
+
+    fn recommended_k() -> u32 {
+        8
+    }
+
+    fn instance_layout() -> Vec<InstanceSpec> {
+        InstanceSpec::layout(&[
+            ("in_range", InstanceKind::Bool),
+            ("min_range", InstanceKind::Integer),
+            ("max_range", InstanceKind::Integer),
+        ])
+    }
+}
+
+/// Utility functions for income range proofs
+pub mod utils {
+    use ff::PrimeField;
+
+    /// Build the public inputs in the circuit's instance order:
+    /// `[in_range, min_range, max_range]`
+    pub fn public_inputs<F: PrimeField>(in_range: bool, min_range: u128, max_range: u128) -> Vec<F> {
+        vec![if in_range { F::ONE } else { F::ZERO }, F::from_u128(min_range), F::from_u128(max_range)]
+    }
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use super::utils::public_inputs;
     use halo2_proofs::dev::MockProver;
     use pasta_curves::Fp;
-    use ff::Field;
 
     #[test]
     fn test_income_in_range() {
-        let k = 4; // Circuit size parameter
-        let income = 50000u64; // Income within range
-        let min_range = 30000u64;
-        let max_range = 80000u64;
+        let k = 8; // Circuit size parameter
+        let income = 50000u128; // Income within range
+        let min_range = 30000u128;
+        let max_range = 80000u128;
 
         let circuit = IncomeRangeCircuit::<Fp>::new(Some(income), min_range, max_range);
         
-        // The public input should be 1 (true) since 50000 is in [30000, 80000]
-        let public_inputs = vec![Fp::one()];
+        // The result should be 1 (true) since 50000 is in [30000, 80000]
+        let public_inputs = public_inputs(true, min_range, max_range);
 
         let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
         prover.assert_satisfied();
@@ -248,15 +382,15 @@ mod tests {
 
     #[test]
     fn test_income_below_range() {
-        let k = 4;
-        let income = 25000u64; // Income below range
-        let min_range = 30000u64;
-        let max_range = 80000u64;
+        let k = 8;
+        let income = 25000u128; // Income below range
+        let min_range = 30000u128;
+        let max_range = 80000u128;
 
         let circuit = IncomeRangeCircuit::<Fp>::new(Some(income), min_range, max_range);
         
-        // The public input should be 0 (false) since 25000 < 30000
-        let public_inputs = vec![Fp::zero()];
+        // The result should be 0 (false) since 25000 < 30000
+        let public_inputs = public_inputs(false, min_range, max_range);
 
         let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
         prover.assert_satisfied();
@@ -264,15 +398,15 @@ mod tests {
 
     #[test]
     fn test_income_above_range() {
-        let k = 4;
-        let income = 90000u64; // Income above range
-        let min_range = 30000u64;
-        let max_range = 80000u64;
+        let k = 8;
+        let income = 90000u128; // Income above range
+        let min_range = 30000u128;
+        let max_range = 80000u128;
 
         let circuit = IncomeRangeCircuit::<Fp>::new(Some(income), min_range, max_range);
         
-        // The public input should be 0 (false) since 90000 > 80000
-        let public_inputs = vec![Fp::zero()];
+        // The result should be 0 (false) since 90000 > 80000
+        let public_inputs = public_inputs(false, min_range, max_range);
 
         let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
         prover.assert_satisfied();
@@ -280,31 +414,192 @@ mod tests {
 
     #[test]
     fn test_income_at_range_boundaries() {
-        let k = 4;
+        let k = 8;
         
         // Test at minimum boundary
         let circuit1 = IncomeRangeCircuit::<Fp>::new(Some(30000), 30000, 80000);
-        let public_inputs1 = vec![Fp::one()];
+        let public_inputs1 = public_inputs(true, 30000, 80000);
         let prover1 = MockProver::run(k, &circuit1, vec![public_inputs1]).unwrap();
         prover1.assert_satisfied();
         
         // Test at maximum boundary
         let circuit2 = IncomeRangeCircuit::<Fp>::new(Some(80000), 30000, 80000);
-        let public_inputs2 = vec![Fp::one()];
+        let public_inputs2 = public_inputs(true, 30000, 80000);
         let prover2 = MockProver::run(k, &circuit2, vec![public_inputs2]).unwrap();
         prover2.assert_satisfied();
     }
 
+    #[test]
+    fn test_income_multi_byte_boundary() {
+        // Byte-wise comparison ranks 256 ([0x00, 0x01]) below 255 ([0xff])
+        let circuit = IncomeRangeCircuit::<Fp>::new(Some(256), 255, 80000);
+        let prover = MockProver::run(8, &circuit, vec![public_inputs(true, 255, 80000)]).unwrap();
+        prover.assert_satisfied();
+
+        let prover = MockProver::run(8, &circuit, vec![public_inputs(false, 255, 80000)]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_income_large_values() {
+        let circuit = IncomeRangeCircuit::<Fp>::new(Some(1_000_000_000), 65_536, 1 << 39);
+        let prover = MockProver::run(8, &circuit, vec![public_inputs(true, 65_536, 1 << 39)]).unwrap();
+        prover.assert_satisfied();
+
+        let circuit = IncomeRangeCircuit::<Fp>::new(Some(65_535), 65_536, 1 << 39);
+        let prover = MockProver::run(8, &circuit, vec![public_inputs(false, 65_536, 1 << 39)]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn test_income_wider_than_u64() {
+        // 2^64 + 5 must not collapse to 5 and land in [0, 10]
+        let income = (1u128 << 64) + 5;
+        let circuit = IncomeRangeCircuit::<Fp>::new(Some(income), 0, 10);
+        let prover = MockProver::run(8, &circuit, vec![public_inputs(false, 0, 10)]).unwrap();
+        prover.assert_satisfied();
+
+        let circuit = IncomeRangeCircuit::<Fp>::new(Some(income), 1u128 << 64, (1u128 << 64) + 10);
+        let prover = MockProver::run(8, &circuit, vec![public_inputs(true, 1u128 << 64, (1u128 << 64) + 10)]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn test_income_beyond_u128_rejected() {
+        use crate::circuits::lending::diagnose;
+
+        // -1 passes as below any range unless the income itself is bounded
+        let mut circuit = IncomeRangeCircuit::<Fp>::new(None, 30000, 80000);
+        circuit.income = Value::known(Fp::zero() - Fp::one());
+        for claimed in [false, true] {
+            let prover = MockProver::run(8, &circuit, vec![public_inputs(claimed, 30000, 80000)]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+
+        // Only the income's range check catches it
+        let diagnostics = diagnose(8, &circuit, vec![public_inputs(false, 30000, 80000)]).unwrap();
+        assert!(!diagnostics.satisfied);
+        assert!(diagnostics.failures.iter().all(|f| f.gate.as_deref() == Some("range_check_bit")), "{:?}", diagnostics.failures);
+    }
+
+    /// Circuit whose prover claims `result = 1` for an income below the range,
+    /// optionally also forging the `above_min` bit to match
+    #[derive(Clone)]
+    struct ForgedResultCircuit {
+        income: u128,
+        min_range: u128,
+        max_range: u128,
+        forge_above_min: bool,
+    }
+
+    impl Circuit<Fp> for ForgedResultCircuit {
+        type Config = IncomeRangeConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            IncomeRangeCircuit::<Fp>::configure(meta)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let offset = Fp::from_u128(1u128 << INCOME_BITS);
+            let (income, min_range, max_range) =
+                (Fp::from_u128(self.income), Fp::from_u128(self.min_range), Fp::from_u128(self.max_range));
+            let above_min = if self.forge_above_min { Fp::one() } else { Fp::zero() };
+
+            let (result, min_remainder, max_remainder) = layouter.assign_region(
+                || "forged range check",
+                |mut region| {
+                    config.selector.enable(&mut region, 0)?;
+                    region.assign_advice(|| "income", config.income, 0, || Value::known(income))?;
+                    region.assign_advice(|| "min range", config.min_range, 0, || Value::known(min_range))?;
+                    region.assign_advice(|| "max range", config.max_range, 0, || Value::known(max_range))?;
+                    region.assign_advice(|| "above min", config.above_min, 0, || Value::known(above_min))?;
+                    region.assign_advice(|| "below max", config.below_max, 0, || Value::known(Fp::one()))?;
+
+                    // Remainders that satisfy the decomposition constraints for the chosen bits
+                    let min_remainder = region.assign_advice(
+                        || "min remainder",
+                        config.min_remainder,
+                        0,
+                        || Value::known(income - min_range + offset - above_min * offset),
+                    )?;
+                    let max_remainder = region.assign_advice(
+                        || "max remainder",
+                        config.max_remainder,
+                        0,
+                        || Value::known(max_range - income),
+                    )?;
+
+                    let result = region.assign_advice(|| "result", config.result, 0, || Value::known(Fp::one()))?;
+                    Ok((result, min_remainder, max_remainder))
+                },
+            )?;
+
+            let range_chip = RangeCheckChip::construct(config.range_check.clone());
+            range_chip.copy_range_check(layouter.namespace(|| "min remainder"), &min_remainder, INCOME_BITS)?;
+            range_chip.copy_range_check(layouter.namespace(|| "max remainder"), &max_remainder, INCOME_BITS)?;
+            layouter.constrain_instance(result.cell(), config.instance, 0)
+        }
+    }
+
+    #[test]
+    fn test_forged_result_rejected() {
+        // result = 1 against an honest above_min = 0 breaks result = above_min * below_max
+        let circuit = ForgedResultCircuit { income: 25000, min_range: 30000, max_range: 80000, forge_above_min: false };
+        let prover = MockProver::run(8, &circuit, vec![vec![Fp::one()]]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // Forging above_min too leaves a negative remainder that fails its range check
+        let circuit = ForgedResultCircuit { forge_above_min: true, ..circuit };
+        let prover = MockProver::run(8, &circuit, vec![vec![Fp::one()]]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // The same assignment is accepted when the income is really in range
+        let circuit = ForgedResultCircuit { income: 30000, ..circuit };
+        let prover = MockProver::run(8, &circuit, vec![vec![Fp::one()]]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn test_range_bound_to_instance() {
+        let circuit = IncomeRangeCircuit::<Fp>::new(Some(50000), 30000, 80000);
+        assert_eq!(circuit.expected_public_inputs(), public_inputs(true, 30000, 80000));
+
+        // A pass against [30000, 80000] is not accepted for a verifier expecting [40000, 80000]
+        let prover = MockProver::run(8, &circuit, vec![public_inputs(true, 40000, 80000)]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // Nor can a prover hide a wider range behind the one the verifier expects
+        let wider = IncomeRangeCircuit::<Fp>::new(Some(25000), 20000, 80000);
+        let prover = MockProver::run(8, &wider, vec![public_inputs(true, 30000, 80000)]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_every_instance_constrained() {
+        use crate::circuits::lending::assert_circuit;
+
+        for (income, in_range) in [(50000, true), (25000, false), (90000, false)] {
+            let circuit = IncomeRangeCircuit::<Fp>::new(Some(income), 30000, 80000);
+            assert_circuit(8, &circuit, vec![public_inputs(in_range, 30000, 80000)]);
+        }
+    }
+
     #[test]
     fn test_circuit_without_witnesses() {
-        let k = 4;
-        let min_range = 30000u64;
-        let max_range = 80000u64;
+        use crate::circuits::lending::assert_keygen;
+
+        let min_range = 30000u128;
+        let max_range = 80000u128;
 
         let circuit = IncomeRangeCircuit::<Fp>::new(None, min_range, max_range);
         let circuit_without_witnesses = circuit.without_witnesses();
 
-        // Should be able to create the circuit structure without witnesses
-        let _ = circuit_without_witnesses;
+        assert_eq!(circuit_without_witnesses.expected_public_inputs(), public_inputs(false, min_range, max_range));
+        assert_keygen(IncomeRangeCircuit::<Fp>::recommended_k(), &circuit_without_witnesses);
     }
 }
\ No newline at end of file
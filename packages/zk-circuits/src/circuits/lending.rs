@@ -0,0 +1,455 @@
+//! Common interface over the lending circuits
+//!
+//! Lets batch provers and the bindings handle any circuit generically instead
+//! of hand-writing each one's size and instance layout.
+
+use ff::{FromUniformBytes, PrimeField};
+use halo2_proofs::{
+    circuit::Value,
+    dev::{FailureLocation, MockProver, VerifyFailure},
+    plonk::Circuit,
+};
+
+use crate::error::ZkError;
+
+/// A lending circuit that knows its own size and public inputs
+pub trait LendingCircuit<F: PrimeField>: Circuit<F> {
+    /// Public inputs in the circuit's instance order
+    ///
+    /// Outputs derived from the private witness (such as `result`) are zero
+    /// when the witness is unknown, so the layout is still well-formed.
+    fn public_inputs(&self) -> Vec<F>;
+
+    /// Smallest `k` the circuit fits in
+    fn recommended_k() -> u32;
+
+    /// What each public input slot holds, in instance order
+    ///
+    /// Matches the order of `public_inputs`, so clients can build the
+    /// instance vector without reading the circuit's documentation. Slots
+    /// marked `optional` come last and are only present for some
+    /// constructions of the circuit.
+    fn instance_layout() -> Vec<InstanceSpec>;
+}
+
+/// How a public input slot is encoded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InstanceKind {
+    /// 0 or 1
+    Bool,
+    /// A small non-negative integer, such as a threshold or an epoch
+    Integer,
+    /// An arbitrary field element, such as a commitment or a hash
+    Field,
+}
+
+impl InstanceKind {
+    /// Lowercase name used in JSON
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InstanceKind::Bool => "bool",
+            InstanceKind::Integer => "integer",
+            InstanceKind::Field => "field",
+        }
+    }
+}
+
+/// One public input slot of a circuit's instance column
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstanceSpec {
+    /// Row of the slot in the instance column
+    pub index: usize,
+    /// Name of the value, as used in the circuit's documentation
+    pub name: &'static str,
+    pub kind: InstanceKind,
+    /// Whether `public_inputs` may stop before this slot
+    pub optional: bool,
+}
+
+impl InstanceSpec {
+    /// Number each `(name, kind)` slot by its position
+    pub fn layout(slots: &[(&'static str, InstanceKind)]) -> Vec<InstanceSpec> {
+        slots
+            .iter()
+            .enumerate()
+            .map(|(index, &(name, kind))| InstanceSpec { index, name, kind, optional: false })
+            .collect()
+    }
+
+    /// `layout` followed by `optional` slots
+    pub fn layout_with_optional(
+        slots: &[(&'static str, InstanceKind)],
+        optional: &[(&'static str, InstanceKind)],
+    ) -> Vec<InstanceSpec> {
+        let mut layout = Self::layout(&[slots, optional].concat());
+        for spec in &mut layout[slots.len()..] {
+            spec.optional = true;
+        }
+        layout
+    }
+
+    /// A layout as a JSON array of `{ index, name, kind, optional }` objects
+    pub fn to_json(layout: &[InstanceSpec]) -> String {
+        let slots: Vec<_> = layout
+            .iter()
+            .map(|spec| {
+                serde_json::json!({
+                    "index": spec.index,
+                    "name": spec.name,
+                    "kind": spec.kind.as_str(),
+                    "optional": spec.optional,
+                })
+            })
+            .collect();
+
+        serde_json::Value::Array(slots).to_string()
+    }
+}
+
+/// Run a lending circuit through the `MockProver` against its own public inputs
+pub fn run_mock<F, C>(circuit: &C) -> Result<(), ZkError>
+where
+    F: PrimeField + FromUniformBytes<64> + Ord,
+    C: LendingCircuit<F>,
+{
+    let prover = MockProver::run(C::recommended_k(), circuit, vec![circuit.public_inputs()])
+        .map_err(|e| ZkError::ProofFailed(format!("Circuit synthesis failed: {:?}", e)))?;
+
+    prover
+        .verify()
+        .map_err(|failures| ZkError::VerifyFailed(format!("Circuit not satisfied: {:?}", failures)))
+}
+
+/// A constraint a circuit's witness violates, read from a `MockProver` failure
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConstraintFailure {
+    /// `constraint`, `constraint_poisoned`, `cell_not_assigned`,
+    /// `instance_cell_not_assigned`, `lookup` or `permutation`
+    pub kind: &'static str,
+    /// Name of the gate involved, for gate failures
+    pub gate: Option<String>,
+    /// Name of the region the failure lies in, if any
+    pub region: Option<String>,
+    /// Row within `region`, or the circuit row outside any region
+    pub row: Option<usize>,
+    /// halo2's full description of the failure
+    pub message: String,
+}
+
+impl ConstraintFailure {
+    fn from_failure(failure: &VerifyFailure) -> Self {
+        let (kind, gate, location) = match failure {
+            VerifyFailure::ConstraintNotSatisfied { constraint, location, .. } => {
+                ("constraint", Some(quoted_name(&constraint.to_string())), Some(location_of(location)))
+            }
+            VerifyFailure::ConstraintPoisoned { constraint } => ("constraint_poisoned", Some(quoted_name(&constraint.to_string())), None),
+            VerifyFailure::CellNotAssigned { gate, region, gate_offset, .. } => (
+                "cell_not_assigned",
+                Some(quoted_name(&gate.to_string())),
+                Some((Some(quoted_name(&region.to_string())), *gate_offset)),
+            ),
+            VerifyFailure::InstanceCellNotAssigned { gate, region, gate_offset, .. } => (
+                "instance_cell_not_assigned",
+                Some(quoted_name(&gate.to_string())),
+                Some((Some(quoted_name(&region.to_string())), *gate_offset)),
+            ),
+            VerifyFailure::Lookup { location, .. } => ("lookup", None, Some(location_of(location))),
+            VerifyFailure::Permutation { location, .. } => ("permutation", None, Some(location_of(location))),
+        };
+        let (region, row) = match location {
+            Some((region, row)) => (region, Some(row)),
+            None => (None, None),
+        };
+
+        Self {
+            kind,
+            gate,
+            region,
+            row,
+            message: failure.to_string(),
+        }
+    }
+}
+
+/// Region name and row of a failure location
+fn location_of(location: &FailureLocation) -> (Option<String>, usize) {
+    match location {
+        FailureLocation::InRegion { region, offset } => (Some(quoted_name(&region.to_string())), *offset),
+        FailureLocation::OutsideRegion { row } => (None, *row),
+    }
+}
+
+/// The last quoted name in halo2's display of a gate, constraint or region,
+/// such as `gate_name` in `Constraint 0 in gate 1 ('gate_name')`
+///
+/// The metadata types keep their names private, so they are read back from
+/// their `Display` form.
+fn quoted_name(display: &str) -> String {
+    display
+        .rfind("('")
+        .map(|start| display[start + 2..].trim_end_matches("')").to_string())
+        .unwrap_or_else(|| display.to_string())
+}
+
+/// Outcome of a dry run, with every constraint the witness violates
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostics {
+    /// The circuit's constraints hold for the witness
+    pub satisfied: bool,
+    /// Each violated constraint; empty when `satisfied`
+    pub failures: Vec<ConstraintFailure>,
+}
+
+impl Diagnostics {
+    /// Diagnostics as a JSON object with camelCase keys
+    pub fn to_json(&self) -> String {
+        let failures: Vec<_> = self
+            .failures
+            .iter()
+            .map(|failure| {
+                serde_json::json!({
+                    "kind": failure.kind,
+                    "gate": failure.gate,
+                    "region": failure.region,
+                    "row": failure.row,
+                    "message": failure.message,
+                })
+            })
+            .collect();
+
+        serde_json::json!({
+            "satisfied": self.satisfied,
+            "failures": failures,
+        })
+        .to_string()
+    }
+}
+
+/// Run a circuit through the `MockProver` at size `k`, reporting which
+/// constraints fail rather than only whether any do
+///
+/// Fails only if the circuit cannot be synthesized.
+pub fn diagnose<F, C>(k: u32, circuit: &C, instances: Vec<Vec<F>>) -> Result<Diagnostics, ZkError>
+where
+    F: PrimeField + FromUniformBytes<64> + Ord,
+    C: Circuit<F>,
+{
+    let prover = MockProver::run(k, circuit, instances)
+        .map_err(|e| ZkError::ProofFailed(format!("Circuit synthesis failed: {:?}", e)))?;
+
+    let failures = match prover.verify() {
+        Ok(()) => Vec::new(),
+        Err(failures) => failures.iter().map(ConstraintFailure::from_failure).collect(),
+    };
+
+    Ok(Diagnostics {
+        satisfied: failures.is_empty(),
+        failures,
+    })
+}
+
+/// `run_mock` with a `Diagnostics` report of every failing constraint
+pub fn dry_run_with_diagnostics<F, C>(circuit: &C) -> Result<Diagnostics, ZkError>
+where
+    F: PrimeField + FromUniformBytes<64> + Ord,
+    C: LendingCircuit<F>,
+{
+    diagnose(C::recommended_k(), circuit, vec![circuit.public_inputs()])
+}
+
+/// Check a circuit against every public input, not just the first
+///
+/// Runs the `MockProver` against `expected_instances` and asserts it is
+/// satisfied, then perturbs each instance value in turn and asserts the
+/// circuit rejects it, so a value the circuit never constrains is caught.
+#[cfg(test)]
+pub(crate) fn assert_circuit<C: LendingCircuit<pasta_curves::Fp>>(
+    k: u32,
+    circuit: &C,
+    expected_instances: Vec<Vec<pasta_curves::Fp>>,
+) {
+    MockProver::run(k, circuit, expected_instances.clone()).unwrap().assert_satisfied();
+
+    for (column, values) in expected_instances.iter().enumerate() {
+        for row in 0..values.len() {
+            let mut perturbed = expected_instances.clone();
+            perturbed[column][row] += pasta_curves::Fp::one();
+
+            let prover = MockProver::run(k, circuit, perturbed).unwrap();
+            assert!(
+                prover.verify().is_err(),
+                "instance column {} row {} is not constrained",
+                column,
+                row
+            );
+        }
+    }
+}
+
+/// Assert a circuit's layout stays within `budget` rows at its recommended `k`
+///
+/// Also checks the budget itself fits in the rows usable at that `k`, so a
+/// budget cannot be raised past the size target without changing the `k`.
+#[cfg(test)]
+pub(crate) fn assert_row_budget<C>(circuit: &C, budget: usize)
+where
+    C: LendingCircuit<pasta_curves::Fp> + std::fmt::Debug,
+{
+    use super::optimizations::performance::{rows_used, usable_rows};
+
+    let k = C::recommended_k();
+    let usable = usable_rows::<C>(k);
+    assert!(budget <= usable, "budget of {} rows exceeds the {} usable at k = {}", budget, usable, k);
+
+    let rows = rows_used(circuit, k);
+    assert!(rows <= budget, "circuit uses {} rows, over its budget of {}", rows, budget);
+}
+
+/// Assert proving and verifying keys can be generated for a circuit at size `k`
+///
+/// Keygen synthesizes with every witness value unknown, so passing a
+/// circuit's `without_witnesses()` form catches witness-side arithmetic that
+/// panics on or needs known values (such as `field_to_u64` on an unknown value).
+#[cfg(test)]
+pub(crate) fn assert_keygen<C: Circuit<pasta_curves::Fp>>(k: u32, circuit: &C) {
+    use halo2_proofs::{
+        plonk::{keygen_pk, keygen_vk},
+        poly::commitment::Params,
+    };
+
+    let params = Params::<pasta_curves::EqAffine>::new(k);
+    let vk = keygen_vk(&params, circuit).expect("verifying key generation failed");
+    keygen_pk(&params, vk, circuit).expect("proving key generation failed");
+}
+
+/// Extract a known value, or `None` for a circuit built without witnesses
+pub(crate) fn known<V>(value: Value<V>) -> Option<V> {
+    let mut known = None;
+    value.map(|v| known = Some(v));
+    known
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuits::composite::CompositeLendingCircuit;
+    use crate::circuits::identity::{utils::commit, IdentityCircuit};
+    use crate::circuits::income_range::IncomeRangeCircuit;
+    use crate::circuits::loan_history::LoanHistoryCircuit;
+    use crate::circuits::trust_score::TrustScoreCircuit;
+    use pasta_curves::Fp;
+
+    #[test]
+    fn test_run_mock_each_circuit() {
+        run_mock(&TrustScoreCircuit::<Fp>::new(Some(85), 70)).unwrap();
+        run_mock(&TrustScoreCircuit::<Fp>::new(Some(65), 70)).unwrap();
+        run_mock(&IncomeRangeCircuit::<Fp>::new(Some(50_000), 30_000, 80_000)).unwrap();
+        run_mock(&IncomeRangeCircuit::<Fp>::new(Some(90_000), 30_000, 80_000)).unwrap();
+        run_mock(&LoanHistoryCircuit::<Fp>::new(Some(10), Some(9), 8_000, 3)).unwrap();
+        run_mock(&LoanHistoryCircuit::<Fp>::new(Some(0), Some(0), 0, 0)).unwrap();
+
+        let identity_hash = Fp::from(1111u64);
+        let nonce = Fp::from(42u64);
+        let commitment = commit(identity_hash, nonce, 3);
+        run_mock(&IdentityCircuit::new(Some(identity_hash), Some(nonce), commitment, 7, 3)).unwrap();
+        run_mock(&IdentityCircuit::new(Some(identity_hash), Some(nonce + Fp::one()), commitment, 7, 3)).unwrap();
+    }
+
+    /// Row budgets for the mobile size targets; raising one past what its
+    /// recommended `k` can hold fails `assert_row_budget`
+    #[test]
+    fn test_row_budgets() {
+        // Trust score at k = 4: 16 rows, 10 of them usable
+        assert_row_budget(&TrustScoreCircuit::<Fp>::new(Some(85), 70), 10);
+        // Income range at k = 8, nearly filled by its three 80-bit range checks
+        assert_row_budget(&IncomeRangeCircuit::<Fp>::new(Some(50_000), 30_000, 80_000), 250);
+        // Loan history at k = 9, sized for the on-time check's second pass
+        assert_row_budget(&LoanHistoryCircuit::<Fp>::new(Some(10), Some(9), 8_000, 3), 128);
+        assert_row_budget(&LoanHistoryCircuit::<Fp>::new_with_on_time(Some(10), Some(9), Some(8), 8_000, 7_000, 3), 288);
+
+        // Identity at k = 8
+        let identity_hash = Fp::from(1111u64);
+        let nonce = Fp::from(42u64);
+        let commitment = commit(identity_hash, nonce, 3);
+        assert_row_budget(&IdentityCircuit::new(Some(identity_hash), Some(nonce), commitment, 7, 3), 160);
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        let diagnostics = dry_run_with_diagnostics(&TrustScoreCircuit::<Fp>::new(Some(85), 70)).unwrap();
+        assert!(diagnostics.satisfied);
+        assert!(diagnostics.failures.is_empty());
+
+        // A wrong public result breaks the copy into the instance column
+        let circuit = TrustScoreCircuit::<Fp>::new(Some(85), 70);
+        let diagnostics = diagnose(4, &circuit, vec![vec![Fp::zero(), Fp::from(70), Fp::zero()]]).unwrap();
+        assert!(!diagnostics.satisfied);
+        assert!(diagnostics.failures.iter().all(|failure| failure.kind == "permutation"));
+
+        let json: serde_json::Value = serde_json::from_str(&diagnostics.to_json()).unwrap();
+        assert_eq!(json["satisfied"], false);
+        assert_eq!(json["failures"][0]["kind"], "permutation");
+    }
+
+    #[test]
+    fn test_quoted_name() {
+        assert_eq!(quoted_name("Constraint 0 in gate 1 ('trust_score_comparison')"), "trust_score_comparison");
+        assert_eq!(quoted_name("Region 2 ('income range check')"), "income range check");
+        assert_eq!(quoted_name("unnamed"), "unnamed");
+    }
+
+    #[test]
+    fn test_instance_layout_matches_public_inputs() {
+        let identity_hash = Fp::from(1111u64);
+        let nonce = Fp::from(42u64);
+        let commitment = commit(identity_hash, nonce, 3);
+
+        assert_eq!(
+            TrustScoreCircuit::<Fp>::instance_layout().len(),
+            TrustScoreCircuit::<Fp>::new(Some(85), 70).public_inputs().len()
+        );
+        assert_eq!(
+            IncomeRangeCircuit::<Fp>::instance_layout().len(),
+            IncomeRangeCircuit::<Fp>::new(Some(50_000), 30_000, 80_000).public_inputs().len()
+        );
+        let layout = LoanHistoryCircuit::<Fp>::instance_layout();
+        let required = layout.iter().filter(|spec| !spec.optional).count();
+        assert_eq!(required, LoanHistoryCircuit::<Fp>::new(Some(10), Some(9), 8_000, 3).public_inputs().len());
+        assert_eq!(
+            required,
+            LoanHistoryCircuit::<Fp>::new_with_on_time(Some(10), Some(9), Some(8), 8_000, 7_000, 3).public_inputs().len()
+        );
+        assert_eq!(
+            layout.len(),
+            LoanHistoryCircuit::<Fp>::new_revealing_rate(Some(10), Some(9), 8_000, 3).public_inputs().len()
+        );
+        assert_eq!(
+            IdentityCircuit::<Fp>::instance_layout().len(),
+            IdentityCircuit::new(Some(identity_hash), Some(nonce), commitment, 7, 3).public_inputs().len()
+        );
+        assert_eq!(
+            CompositeLendingCircuit::<Fp>::instance_layout().len(),
+            CompositeLendingCircuit::<Fp>::builder().trust_score(85, 70).build().unwrap().public_inputs().len()
+        );
+    }
+
+    #[test]
+    fn test_instance_layout_json() {
+        let json: serde_json::Value = serde_json::from_str(&InstanceSpec::to_json(&TrustScoreCircuit::<Fp>::instance_layout())).unwrap();
+        assert_eq!(json[0], serde_json::json!({ "index": 0, "name": "result", "kind": "bool", "optional": false }));
+        assert_eq!(json[2]["name"], "epoch");
+    }
+
+    #[test]
+    fn test_public_inputs_match_utils() {
+        let circuit = TrustScoreCircuit::<Fp>::new(Some(85), 70);
+        assert_eq!(
+            circuit.public_inputs(),
+            crate::circuits::trust_score::utils::public_inputs::<Fp>(true, 70)
+        );
+
+        // Without a witness only the layout is meaningful
+        let circuit = TrustScoreCircuit::<Fp>::new(None, 70);
+        assert_eq!(circuit.public_inputs(), vec![Fp::zero(), Fp::from(70), Fp::zero()]);
+    }
+}
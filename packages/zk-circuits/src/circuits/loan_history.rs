@@ -6,6 +6,17 @@ use halo2_proofs::{
 use ff::PrimeField;
 use std::marker::PhantomData;
 
+use super::AssignedCell;
+use crate::error::{FieldConversionError, ZkError};
+use super::lending::{known, InstanceKind, InstanceSpec, LendingCircuit};
+use super::optimizations::{RangeCheckChip, RangeCheckConfig};
+
+/// Bit width of loan counts, success rates and the division remainders
+pub const LOAN_HISTORY_BITS: usize = 16;
+
+/// Fixed-point scale of the success rate (percentage * 100)
+pub const SUCCESS_RATE_SCALE: u64 = 10000;
+
 /// Configuration for the loan history verification circuit
 #[derive(Clone, Debug)]
 pub struct LoanHistoryConfig {
@@ -15,14 +26,47 @@ pub struct LoanHistoryConfig {
     pub successful_repayments: Column<Advice>,
     /// Advice column for the minimum success rate threshold (public input)
     pub min_success_rate: Column<Advice>,
+    /// Advice column for the minimum number of loans (public input)
+    pub min_loans: Column<Advice>,
     /// Advice column for the calculated success rate
     pub success_rate: Column<Advice>,
     /// Advice column for the result (1 if meets threshold, 0 if not)
     pub result: Column<Advice>,
+    /// Advice column for the inverse of `num_loans` (0 when there are no loans)
+    pub loans_inverse: Column<Advice>,
+    /// Advice column for the division remainder
+    pub remainder: Column<Advice>,
+    /// Advice column for `num_loans - 1 - remainder`, proving `remainder < num_loans`
+    pub remainder_gap: Column<Advice>,
+    /// Advice column for the `success_rate >= min_success_rate` bit
+    pub above_min: Column<Advice>,
+    /// Low N bits of `success_rate - min_success_rate + 2^N`
+    pub rate_remainder: Column<Advice>,
+    /// Advice column for the `num_loans >= min_loans` bit
+    pub enough_loans: Column<Advice>,
+    /// Low N bits of `num_loans - min_loans + 2^N`
+    pub loans_remainder: Column<Advice>,
+    /// Advice column for `num_loans - successful_repayments`, range checked
+    /// so a history cannot claim more repayments than loans
+    pub repayments_gap: Column<Advice>,
     /// Instance column for public inputs/outputs
     pub instance: Column<Instance>,
     /// Selector for the loan history verification gate
     pub selector: Selector,
+    /// Selector for the gate combining a success rate run with an on-time run
+    pub on_time_selector: Selector,
+    /// Range check for the private counts and the remainders
+    pub range_check: RangeCheckConfig,
+}
+
+/// Cells assigned by the loan history verification
+pub struct AssignedLoanHistory<F: PrimeField> {
+    pub num_loans: AssignedCell<F>,
+    pub successful_repayments: AssignedCell<F>,
+    pub min_success_rate: AssignedCell<F>,
+    pub min_loans: AssignedCell<F>,
+    pub success_rate: AssignedCell<F>,
+    pub result: AssignedCell<F>,
 }
 
 /// Chip for loan history verification operations
@@ -31,6 +75,78 @@ pub struct LoanHistoryChip<F: PrimeField> {
     _marker: PhantomData<F>,
 }
 
+/// Every value witnessed by the loan history gate
+#[derive(Clone, Copy)]
+struct LoanHistoryWitness<F: PrimeField> {
+    num_loans: Value<F>,
+    successful_repayments: Value<F>,
+    min_success_rate: Value<F>,
+    min_loans: Value<F>,
+    success_rate: Value<F>,
+    result: Value<F>,
+    loans_inverse: Value<F>,
+    remainder: Value<F>,
+    remainder_gap: Value<F>,
+    above_min: Value<F>,
+    rate_remainder: Value<F>,
+    enough_loans: Value<F>,
+    loans_remainder: Value<F>,
+    repayments_gap: Value<F>,
+}
+
+impl<F: PrimeField> LoanHistoryWitness<F> {
+    /// Compute the honest witness for the given history
+    fn compute(
+        num_loans: Value<F>,
+        successful_repayments: Value<F>,
+        min_success_rate: Value<F>,
+        min_loans: Value<F>,
+    ) -> Self {
+        // `a - b + 2^N` has its top bit set iff a >= b
+        let compare = |a: u128, b: u128| {
+            let diff = a.wrapping_add(1u128 << LOAN_HISTORY_BITS).wrapping_sub(b);
+            (diff >> LOAN_HISTORY_BITS == 1, diff & ((1u128 << LOAN_HISTORY_BITS) - 1))
+        };
+
+        let values = num_loans.zip(successful_repayments).zip(min_success_rate).zip(min_loans).map(|(((loans, repayments), min_rate), min_loans)| {
+            // The counts are range checked in-circuit, so a wider one only has
+            // to give some witness here; the minimums are checked beforehand
+            let to_u128 = |value: &F| try_field_to_u64(value).map_or(0, u128::from);
+            let loans = to_u128(&loans);
+            let scaled = to_u128(&repayments) * SUCCESS_RATE_SCALE as u128;
+
+            // Calculate percentage * 100 to work with integers;
+            // no loans means 0% success rate
+            let rate = scaled.checked_div(loans).unwrap_or(0);
+            let remainder = scaled.checked_rem(loans).unwrap_or(0);
+            let gap = loans.saturating_sub(1 + remainder);
+
+            let (above, rate_remainder) = compare(rate, to_u128(&min_rate));
+            let (enough, loans_remainder) = compare(loans, to_u128(&min_loans));
+
+            (rate, remainder, gap, above, rate_remainder, loans != 0 && above && enough, enough, loans_remainder)
+        });
+        let to_field = |b: bool| if b { F::ONE } else { F::ZERO };
+
+        Self {
+            num_loans,
+            successful_repayments,
+            min_success_rate,
+            min_loans,
+            success_rate: values.map(|v| F::from_u128(v.0)),
+            result: values.map(|v| to_field(v.5)),
+            loans_inverse: num_loans.map(|loans| loans.invert().unwrap_or(F::ZERO)),
+            remainder: values.map(|v| F::from_u128(v.1)),
+            remainder_gap: values.map(|v| F::from_u128(v.2)),
+            above_min: values.map(|v| to_field(v.3)),
+            rate_remainder: values.map(|v| F::from_u128(v.4)),
+            enough_loans: values.map(|v| to_field(v.6)),
+            loans_remainder: values.map(|v| F::from_u128(v.7)),
+            repayments_gap: num_loans - successful_repayments,
+        }
+    }
+}
+
 impl<F: PrimeField> LoanHistoryChip<F> {
     pub fn construct(config: LoanHistoryConfig) -> Self {
         Self {
@@ -39,39 +155,117 @@ impl<F: PrimeField> LoanHistoryChip<F> {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn configure(
         meta: &mut ConstraintSystem<F>,
         num_loans: Column<Advice>,
         successful_repayments: Column<Advice>,
         min_success_rate: Column<Advice>,
+        min_loans: Column<Advice>,
         success_rate: Column<Advice>,
         result: Column<Advice>,
+        division: [Column<Advice>; 9],
         instance: Column<Instance>,
     ) -> LoanHistoryConfig {
+        let [loans_inverse, remainder, remainder_gap, above_min, rate_remainder, enough_loans, loans_remainder, repayments_gap, range_column] =
+            division;
         let selector = meta.selector();
+        let range_check = RangeCheckChip::configure(meta, range_column, LOAN_HISTORY_BITS);
 
         // Enable equality constraints for public inputs/outputs
         meta.enable_equality(num_loans);
         meta.enable_equality(successful_repayments);
         meta.enable_equality(min_success_rate);
+        meta.enable_equality(min_loans);
         meta.enable_equality(success_rate);
         meta.enable_equality(result);
+        meta.enable_equality(remainder);
+        meta.enable_equality(remainder_gap);
+        meta.enable_equality(rate_remainder);
+        meta.enable_equality(loans_remainder);
+        meta.enable_equality(repayments_gap);
         meta.enable_equality(instance);
 
         // Create the loan history verification gate
+        //
+        // success_rate * num_loans + remainder == successful_repayments * 10000,
+        // with remainder and num_loans - 1 - remainder range checked, pins
+        // success_rate to the floor of the division. `no_loans` is an is-zero
+        // flag on num_loans; when set, the rate, remainder and result are 0.
+        // The result also requires num_loans >= min_loans, so a short history
+        // with a perfect rate does not pass. Range checking
+        // num_loans - successful_repayments rules out histories with more
+        // repayments than loans, which would otherwise rate above 100%.
         meta.create_gate("loan_history_verification", |meta| {
             let s = meta.query_selector(selector);
-            let _num_loans = meta.query_advice(num_loans, Rotation::cur());
-            let _successful_repayments = meta.query_advice(successful_repayments, Rotation::cur());
-            let _min_success_rate = meta.query_advice(min_success_rate, Rotation::cur());
-            let _success_rate = meta.query_advice(success_rate, Rotation::cur());
+            let num_loans = meta.query_advice(num_loans, Rotation::cur());
+            let successful_repayments = meta.query_advice(successful_repayments, Rotation::cur());
+            let min_success_rate = meta.query_advice(min_success_rate, Rotation::cur());
+            let success_rate = meta.query_advice(success_rate, Rotation::cur());
             let result = meta.query_advice(result, Rotation::cur());
+            let loans_inverse = meta.query_advice(loans_inverse, Rotation::cur());
+            let remainder = meta.query_advice(remainder, Rotation::cur());
+            let remainder_gap = meta.query_advice(remainder_gap, Rotation::cur());
+            let above_min = meta.query_advice(above_min, Rotation::cur());
+            let rate_remainder = meta.query_advice(rate_remainder, Rotation::cur());
+            let min_loans = meta.query_advice(min_loans, Rotation::cur());
+            let enough_loans = meta.query_advice(enough_loans, Rotation::cur());
+            let loans_remainder = meta.query_advice(loans_remainder, Rotation::cur());
+            let repayments_gap = meta.query_advice(repayments_gap, Rotation::cur());
+
+            let one = Expression::Constant(F::ONE);
+            let scale = Expression::Constant(F::from(SUCCESS_RATE_SCALE));
+            let offset = Expression::Constant(F::from(1u64 << LOAN_HISTORY_BITS));
+            let no_loans = one.clone() - num_loans.clone() * loans_inverse;
+            let has_loans = one.clone() - no_loans.clone();
 
-            // For simplicity in this demo, we'll just ensure result is boolean
-            // A full implementation would include proper division and comparison logic
             vec![
                 // Ensure result is boolean (0 or 1)
-                s * (result.clone() * (result - Expression::Constant(F::ONE))),
+                s.clone() * (result.clone() * (result.clone() - one.clone())),
+                s.clone() * (above_min.clone() * (above_min.clone() - one.clone())),
+                s.clone() * (enough_loans.clone() * (enough_loans.clone() - one.clone())),
+                // is-zero gadget on num_loans
+                s.clone() * (num_loans.clone() * no_loans.clone()),
+                // successful_repayments <= num_loans
+                s.clone() * (repayments_gap - (num_loans.clone() - successful_repayments.clone())),
+                // Division with remainder when there is at least one loan
+                s.clone() * (has_loans.clone()
+                    * (success_rate.clone() * num_loans.clone() + remainder.clone()
+                        - successful_repayments * scale)),
+                // remainder < num_loans, i.e. num_loans - 1 - remainder >= 0
+                s.clone() * (remainder_gap - (num_loans.clone() - one - remainder.clone() + no_loans.clone())),
+                // No loans: success rate and remainder are zero
+                s.clone() * (no_loans.clone() * success_rate.clone()),
+                s.clone() * (no_loans * remainder),
+                // success_rate >= min_success_rate
+                s.clone() * (success_rate - min_success_rate + offset.clone()
+                    - (above_min.clone() * offset.clone() + rate_remainder)),
+                // num_loans >= min_loans
+                s.clone() * (num_loans - min_loans + offset.clone()
+                    - (enough_loans.clone() * offset + loans_remainder)),
+                s * (result - above_min * has_loans * enough_loans),
+            ]
+        });
+
+        let on_time_selector = meta.selector();
+
+        // Combine two verification runs over the same loans: row 0 holds the
+        // successful repayments run, row 1 the on-time run, and row 2 the
+        // combined result. `repayments_gap` on row 0 holds
+        // successful_repayments - on_time_repayments, which is range checked
+        // so on-time repayments cannot exceed successful ones.
+        meta.create_gate("loan_history_on_time", |meta| {
+            let s = meta.query_selector(on_time_selector);
+            let repaid_count = meta.query_advice(successful_repayments, Rotation::cur());
+            let on_time_count = meta.query_advice(successful_repayments, Rotation::next());
+            let late_gap = meta.query_advice(repayments_gap, Rotation::cur());
+            let repaid = meta.query_advice(result, Rotation::cur());
+            let on_time = meta.query_advice(result, Rotation::next());
+            let combined = meta.query_advice(result, Rotation(2));
+
+            vec![
+                s.clone() * (late_gap - (repaid_count - on_time_count)),
+                s * (combined - repaid * on_time),
             ]
         });
 
@@ -79,99 +273,230 @@ impl<F: PrimeField> LoanHistoryChip<F> {
             num_loans,
             successful_repayments,
             min_success_rate,
+            min_loans,
             success_rate,
             result,
+            loans_inverse,
+            remainder,
+            remainder_gap,
+            above_min,
+            rate_remainder,
+            enough_loans,
+            loans_remainder,
+            repayments_gap,
             instance,
             selector,
+            on_time_selector,
+            range_check,
         }
     }
 
     /// Assign the loan history verification
     pub fn assign_loan_history_verification(
         &self,
-        mut layouter: impl Layouter<F>,
+        layouter: impl Layouter<F>,
         num_loans: Value<F>,
         successful_repayments: Value<F>,
         min_success_rate: Value<F>,
-    ) -> Result<AssignedCell<F>, Error> {
-        layouter.assign_region(
+        min_loans: Value<F>,
+    ) -> Result<AssignedLoanHistory<F>, Error> {
+        check_fits_u64(min_success_rate)?;
+        check_fits_u64(min_loans)?;
+
+        let witness = LoanHistoryWitness::compute(num_loans, successful_repayments, min_success_rate, min_loans);
+        self.assign_witness(layouter, witness)
+    }
+
+    fn assign_witness(
+        &self,
+        mut layouter: impl Layouter<F>,
+        witness: LoanHistoryWitness<F>,
+    ) -> Result<AssignedLoanHistory<F>, Error> {
+        let (assigned, range_checked) = layouter.assign_region(
             || "loan history verification",
             |mut region| {
                 // Enable the selector
                 self.config.selector.enable(&mut region, 0)?;
 
                 // Assign number of loans (private input)
-                let _num_loans_cell = region.assign_advice(
-                    || "number of loans",
-                    self.config.num_loans,
-                    0,
-                    || num_loans,
-                )?;
+                let num_loans = region.assign_advice(|| "number of loans", self.config.num_loans, 0, || witness.num_loans)?;
 
                 // Assign successful repayments (private input)
-                let _successful_repayments_cell = region.assign_advice(
+                let successful_repayments = region.assign_advice(
                     || "successful repayments",
                     self.config.successful_repayments,
                     0,
-                    || successful_repayments,
+                    || witness.successful_repayments,
                 )?;
 
                 // Assign minimum success rate threshold (public input)
-                let _min_success_rate_cell = region.assign_advice(
+                let min_success_rate = region.assign_advice(
                     || "minimum success rate",
                     self.config.min_success_rate,
                     0,
-                    || min_success_rate,
+                    || witness.min_success_rate,
                 )?;
 
-                // Calculate success rate (as percentage * 100 to avoid decimals)
-                let success_rate_value = num_loans.zip(successful_repayments).map(|(loans, repayments)| {
-                    // Convert to u64 for calculation
-                    let loans_u64 = field_to_u64(&loans);
-                    let repayments_u64 = field_to_u64(&repayments);
-                    
-                    if loans_u64 == 0 {
-                        F::ZERO // No loans means 0% success rate
-                    } else {
-                        // Calculate percentage * 100 to work with integers
-                        let rate = (repayments_u64 * 10000) / loans_u64;
-                        F::from(rate)
-                    }
-                });
-
-                let _success_rate_cell = region.assign_advice(
+                // Assign minimum number of loans (public input)
+                let min_loans = region.assign_advice(
+                    || "minimum loans",
+                    self.config.min_loans,
+                    0,
+                    || witness.min_loans,
+                )?;
+
+                // Success rate as percentage * 100, with its division witnesses
+                let success_rate = region.assign_advice(
                     || "calculated success rate",
                     self.config.success_rate,
                     0,
-                    || success_rate_value,
+                    || witness.success_rate,
+                )?;
+                region.assign_advice(|| "loans inverse", self.config.loans_inverse, 0, || witness.loans_inverse)?;
+                let remainder = region.assign_advice(|| "remainder", self.config.remainder, 0, || witness.remainder)?;
+                let remainder_gap = region.assign_advice(
+                    || "remainder gap",
+                    self.config.remainder_gap,
+                    0,
+                    || witness.remainder_gap,
+                )?;
+                region.assign_advice(|| "above min", self.config.above_min, 0, || witness.above_min)?;
+                let rate_remainder = region.assign_advice(
+                    || "rate remainder",
+                    self.config.rate_remainder,
+                    0,
+                    || witness.rate_remainder,
+                )?;
+                region.assign_advice(|| "enough loans", self.config.enough_loans, 0, || witness.enough_loans)?;
+                let loans_remainder = region.assign_advice(
+                    || "loans remainder",
+                    self.config.loans_remainder,
+                    0,
+                    || witness.loans_remainder,
                 )?;
 
-                // Calculate and assign result
-                let result_value = success_rate_value.zip(min_success_rate).map(|(rate, min_rate)| {
-                    let rate_u64 = field_to_u64(&rate);
-                    let min_rate_u64 = field_to_u64(&min_rate);
-                    
-                    if rate_u64 >= min_rate_u64 {
-                        F::ONE
-                    } else {
-                        F::ZERO
-                    }
-                });
+                let repayments_gap = region.assign_advice(
+                    || "repayments gap",
+                    self.config.repayments_gap,
+                    0,
+                    || witness.repayments_gap,
+                )?;
 
                 let result_cell = region.assign_advice(
                     || "verification result",
                     self.config.result,
                     0,
-                    || result_value,
+                    || witness.result,
                 )?;
 
-                Ok(result_cell)
+                // The private counts are range checked along with the
+                // remainders, so none can act as a negative number
+                let range_checked = [
+                    num_loans.clone(),
+                    successful_repayments.clone(),
+                    remainder,
+                    remainder_gap,
+                    rate_remainder,
+                    loans_remainder,
+                    repayments_gap,
+                ];
+
+                let assigned = AssignedLoanHistory {
+                    num_loans,
+                    successful_repayments,
+                    min_success_rate,
+                    min_loans,
+                    success_rate,
+                    result: result_cell,
+                };
+
+                Ok((assigned, range_checked))
             },
-        )
+        )?;
+
+        let range_chip = RangeCheckChip::construct(self.config.range_check.clone());
+        for (i, cell) in range_checked.iter().enumerate() {
+            range_chip.copy_range_check(layouter.namespace(|| format!("range check {}", i)), cell, LOAN_HISTORY_BITS)?;
+        }
+
+        Ok(assigned)
+    }
+
+    /// Combine a success rate run with an on-time run over the same loans
+    ///
+    /// `on_time` is a verification run with on-time repayments in place of
+    /// successful ones. Constrains both runs to the same loan count and
+    /// minimum, and the on-time repayments to at most the successful ones,
+    /// and returns the AND of their results.
+    pub fn assign_on_time_combination(
+        &self,
+        mut layouter: impl Layouter<F>,
+        repaid: &AssignedLoanHistory<F>,
+        on_time: &AssignedLoanHistory<F>,
+    ) -> Result<AssignedCell<F>, Error> {
+        let (combined, late_gap) = layouter.assign_region(
+            || "on-time combination",
+            |mut region| {
+                self.config.on_time_selector.enable(&mut region, 0)?;
+
+                region.constrain_equal(repaid.num_loans.cell(), on_time.num_loans.cell())?;
+                region.constrain_equal(repaid.min_loans.cell(), on_time.min_loans.cell())?;
+
+                let successful = repaid.successful_repayments.copy_advice(
+                    || "successful repayments",
+                    &mut region,
+                    self.config.successful_repayments,
+                    0,
+                )?;
+                let on_time_repayments = on_time.successful_repayments.copy_advice(
+                    || "on-time repayments",
+                    &mut region,
+                    self.config.successful_repayments,
+                    1,
+                )?;
+                let late_gap = region.assign_advice(
+                    || "late repayments",
+                    self.config.repayments_gap,
+                    0,
+                    || successful.value().copied() - on_time_repayments.value().copied(),
+                )?;
+
+                let repaid_result = repaid.result.copy_advice(|| "repaid result", &mut region, self.config.result, 0)?;
+                let on_time_result = on_time.result.copy_advice(|| "on-time result", &mut region, self.config.result, 1)?;
+                let combined = region.assign_advice(
+                    || "combined result",
+                    self.config.result,
+                    2,
+                    || repaid_result.value().copied() * on_time_result.value().copied(),
+                )?;
+
+                Ok((combined, late_gap))
+            },
+        )?;
+
+        RangeCheckChip::construct(self.config.range_check.clone()).copy_range_check(
+            layouter.namespace(|| "range check late repayments"),
+            &late_gap,
+            LOAN_HISTORY_BITS,
+        )?;
+
+        Ok(combined)
     }
 }
 
 /// The main loan history verification circuit
+///
+/// Instance layout: `[result, min_success_rate, min_on_time_rate, min_loans]`,
+/// with `success_rate` appended for a circuit built with `new_revealing_rate`.
+/// The policy is bound to the instance, so a proof only verifies against the
+/// minimums the verifier expects; `min_on_time_rate` is 0 for a circuit
+/// without an on-time requirement. Revealing the rate (in basis points, as
+/// computed in-circuit) lets each lender apply its own threshold to the same
+/// proof; such circuits have a different verifying key.
+///
+/// A circuit built with `new_with_on_time` also requires a minimum on-time
+/// repayment rate, and its `result` is the AND of both rate checks. The
+/// composite circuit checks the success rate only.
 #[derive(Clone, Debug)]
 pub struct LoanHistoryCircuit<F: PrimeField> {
     /// Private input: the number of loans taken
@@ -180,10 +505,25 @@ pub struct LoanHistoryCircuit<F: PrimeField> {
     pub successful_repayments: Value<F>,
     /// Public input: the minimum success rate threshold (as percentage * 100)
     pub min_success_rate: Value<F>,
+    /// Public input: the minimum number of loans for the rate to count
+    pub min_loans: Value<F>,
+    /// Whether the computed success rate is exposed as instance 1
+    pub reveal_success_rate: bool,
+    /// Private input: the number of repayments made on time
+    pub on_time_repayments: Value<F>,
+    /// Public input: the minimum on-time rate (as percentage * 100)
+    pub min_on_time_rate: Value<F>,
+    /// Whether the on-time rate is checked as well as the success rate
+    pub require_on_time: bool,
 }
 
 impl<F: PrimeField> LoanHistoryCircuit<F> {
-    pub fn new(num_loans: Option<u64>, successful_repayments: Option<u64>, min_success_rate: u64) -> Self {
+    pub fn new(
+        num_loans: Option<u64>,
+        successful_repayments: Option<u64>,
+        min_success_rate: u64,
+        min_loans: u64,
+    ) -> Self {
         Self {
             num_loans: if let Some(loans) = num_loans {
                 Value::known(F::from(loans))
@@ -196,7 +536,103 @@ impl<F: PrimeField> LoanHistoryCircuit<F> {
                 Value::unknown()
             },
             min_success_rate: Value::known(F::from(min_success_rate)),
+            min_loans: Value::known(F::from(min_loans)),
+            reveal_success_rate: false,
+            on_time_repayments: Value::unknown(),
+            min_on_time_rate: Value::known(F::ZERO),
+            require_on_time: false,
+        }
+    }
+
+    /// `new`, also requiring `on_time_repayments / num_loans` to be at least
+    /// `min_on_time_rate` (in basis points)
+    ///
+    /// Proves `on_time_repayments <= successful_repayments <= num_loans`, so a
+    /// borrower who repays every loan but many of them late does not pass.
+    pub fn new_with_on_time(
+        num_loans: Option<u64>,
+        successful_repayments: Option<u64>,
+        on_time_repayments: Option<u64>,
+        min_success_rate: u64,
+        min_on_time_rate: u64,
+        min_loans: u64,
+    ) -> Self {
+        Self {
+            on_time_repayments: if let Some(repayments) = on_time_repayments {
+                Value::known(F::from(repayments))
+            } else {
+                Value::unknown()
+            },
+            min_on_time_rate: Value::known(F::from(min_on_time_rate)),
+            require_on_time: true,
+            ..Self::new(num_loans, successful_repayments, min_success_rate, min_loans)
+        }
+    }
+
+    /// `new`, also exposing the computed success rate as a public input
+    pub fn new_revealing_rate(
+        num_loans: Option<u64>,
+        successful_repayments: Option<u64>,
+        min_success_rate: u64,
+        min_loans: u64,
+    ) -> Self {
+        Self {
+            reveal_success_rate: true,
+            ..Self::new(num_loans, successful_repayments, min_success_rate, min_loans)
+        }
+    }
+
+    /// Create a circuit with the minimum success rate given as a percentage
+    ///
+    /// `new` takes basis points (percentage * 100); this converts with
+    /// `utils::percentage_to_basis_points`, so `80.0` is the same as `8000`.
+    /// Fails for a percentage outside `0.0..=100.0`, including NaN.
+    pub fn from_percentages(
+        num_loans: Option<u64>,
+        successful_repayments: Option<u64>,
+        min_percent: f64,
+        min_loans: u64,
+    ) -> Result<Self, ZkError> {
+        Ok(Self::new(
+            num_loans,
+            successful_repayments,
+            utils::percentage_to_basis_points(min_percent)?,
+            min_loans,
+        ))
+    }
+
+    /// Public inputs `[result, min_success_rate, min_on_time_rate, min_loans]`
+    /// (with `success_rate` appended when revealed); the outputs are computed
+    /// from the known history (0 without a witness)
+    pub fn expected_public_inputs(&self) -> Vec<F> {
+        let witness = LoanHistoryWitness::compute(
+            self.num_loans,
+            self.successful_repayments,
+            self.min_success_rate,
+            self.min_loans,
+        );
+
+        let mut result = known(witness.result).unwrap_or(F::ZERO);
+        if self.require_on_time {
+            let on_time = LoanHistoryWitness::compute(
+                self.num_loans,
+                self.on_time_repayments,
+                self.min_on_time_rate,
+                self.min_loans,
+            );
+            result *= known(on_time.result).unwrap_or(F::ZERO);
+        }
+
+        let mut inputs = vec![
+            result,
+            known(self.min_success_rate).unwrap_or(F::ZERO),
+            known(self.min_on_time_rate).unwrap_or(F::ZERO),
+            known(self.min_loans).unwrap_or(F::ZERO),
+        ];
+        if self.reveal_success_rate {
+            inputs.push(known(witness.success_rate).unwrap_or(F::ZERO));
         }
+        inputs
     }
 }
 
@@ -209,6 +645,11 @@ impl<F: PrimeField> Circuit<F> for LoanHistoryCircuit<F> {
             num_loans: Value::unknown(),
             successful_repayments: Value::unknown(),
             min_success_rate: self.min_success_rate,
+            min_loans: self.min_loans,
+            reveal_success_rate: self.reveal_success_rate,
+            on_time_repayments: Value::unknown(),
+            min_on_time_rate: self.min_on_time_rate,
+            require_on_time: self.require_on_time,
         }
     }
 
@@ -216,8 +657,10 @@ impl<F: PrimeField> Circuit<F> for LoanHistoryCircuit<F> {
         let num_loans = meta.advice_column();
         let successful_repayments = meta.advice_column();
         let min_success_rate = meta.advice_column();
+        let min_loans = meta.advice_column();
         let success_rate = meta.advice_column();
         let result = meta.advice_column();
+        let division = [(); 9].map(|_| meta.advice_column());
         let instance = meta.instance_column();
 
         LoanHistoryChip::configure(
@@ -225,8 +668,10 @@ impl<F: PrimeField> Circuit<F> for LoanHistoryCircuit<F> {
             num_loans,
             successful_repayments,
             min_success_rate,
+            min_loans,
             success_rate,
             result,
+            division,
             instance,
         )
     }
@@ -239,29 +684,88 @@ impl<F: PrimeField> Circuit<F> for LoanHistoryCircuit<F> {
         let chip = LoanHistoryChip::construct(config.clone());
 
         // Assign the loan history verification
-        let result_cell = chip.assign_loan_history_verification(
+        let assigned = chip.assign_loan_history_verification(
             layouter.namespace(|| "loan history verification"),
             self.num_loans,
             self.successful_repayments,
             self.min_success_rate,
+            self.min_loans,
         )?;
 
+        // With on-time repayments, the result is the AND of both rate checks
+        let result = if self.require_on_time {
+            let on_time = chip.assign_loan_history_verification(
+                layouter.namespace(|| "on-time verification"),
+                self.num_loans,
+                self.on_time_repayments,
+                self.min_on_time_rate,
+                self.min_loans,
+            )?;
+            let combined = chip.assign_on_time_combination(layouter.namespace(|| "on-time combination"), &assigned, &on_time)?;
+
+            // Bind the on-time minimum (instance 2) next to the success rate one
+            layouter.constrain_instance(on_time.min_success_rate.cell(), config.instance, 2)?;
+            combined
+        } else {
+            // Without an on-time check the minimum is 0; it is still bound so
+            // the instance layout is the same either way
+            let min_on_time_rate = layouter.assign_region(
+                || "minimum on-time rate",
+                |mut region| region.assign_advice(|| "minimum on-time rate", config.min_success_rate, 0, || self.min_on_time_rate),
+            )?;
+            layouter.constrain_instance(min_on_time_rate.cell(), config.instance, 2)?;
+            assigned.result.clone()
+        };
+
         // Expose the result as public input (instance 0)
         layouter.constrain_instance(
-            result_cell.cell(),
+            result.cell(),
             config.instance,
             0,
         )?;
 
+        // Bind the policy (instances 1 and 3), so the prover cannot pick it
+        layouter.constrain_instance(assigned.min_success_rate.cell(), config.instance, 1)?;
+        layouter.constrain_instance(assigned.min_loans.cell(), config.instance, 3)?;
+
+        // Optionally reveal the computed success rate (instance 4)
+        if self.reveal_success_rate {
+            layouter.constrain_instance(assigned.success_rate.cell(), config.instance, 4)?;
+        }
+
         Ok(())
     }
 }
 
-/// Helper type for assigned cells
-pub type AssignedCell<F> = halo2_proofs::circuit::AssignedCell<F, F>;
+impl<F: PrimeField> LendingCircuit<F> for LoanHistoryCircuit<F> {
+    fn public_inputs(&self) -> Vec<F> {
+        self.expected_public_inputs()
+    }
+
+    /// Sized for `new_with_on_time`, which lays out the verification twice
+    fn recommended_k() -> u32 {
+        9
+    }
+
+    /// `success_rate` is only present for circuits built with `new_revealing_rate`
+    fn instance_layout() -> Vec<InstanceSpec> {
+        InstanceSpec::layout_with_optional(
+            &[
+                ("result", InstanceKind::Bool),
+                ("min_success_rate", InstanceKind::Integer),
+                ("min_on_time_rate", InstanceKind::Integer),
+                ("min_loans", InstanceKind::Integer),
+            ],
+            &[("success_rate", InstanceKind::Integer)],
+        )
+    }
+}
 
-/// Helper function to convert field element to u64
-fn field_to_u64<F: PrimeField>(field: &F) -> u64 {
+/// Helper function to convert field element to u64, dropping the high bytes
+///
+/// Only the low 8 bytes are read, so distinct field elements can map to the
+/// same `u64`; use `try_field_to_u64` where a value may be wider.
+pub(crate) fn field_to_u64_truncating<F: PrimeField>(field: &F) -> u64 {
     let bytes = field.to_repr();
     let mut result = 0u64;
     for (i, &byte) in bytes.as_ref().iter().take(8).enumerate() {
@@ -270,36 +774,149 @@ fn field_to_u64<F: PrimeField>(field: &F) -> u64 {
     result
 }
 
+/// Convert a field element to u64, failing if any byte past the eighth is set
+///
+/// Reads every byte whatever the value, so the time taken does not depend on
+/// where (or whether) a high byte is set.
+pub fn try_field_to_u64<F: PrimeField>(field: &F) -> Result<u64, FieldConversionError> {
+    let bytes = field.to_repr();
+    let mut value = 0u64;
+    let mut overflow = 0u8;
+    for (i, &byte) in bytes.as_ref().iter().enumerate() {
+        if i < 8 {
+            value |= (byte as u64) << (i * 8);
+        } else {
+            overflow |= byte;
+        }
+    }
+
+    if overflow != 0 {
+        return Err(FieldConversionError);
+    }
+    Ok(value)
+}
+
+/// Reject a known witness wider than 64 bits as a synthesis error
+pub(crate) fn check_fits_u64<F: PrimeField>(value: Value<F>) -> Result<(), Error> {
+    value.error_if_known_and(|v| try_field_to_u64(v).is_err())
+}
+
 /// Utility functions for loan history verification
 pub mod utils {
+    use crate::error::ZkError;
+    use ff::PrimeField;
+
     /// Calculate success rate as percentage * 100 (to avoid decimals)
-    pub fn calculate_success_rate(num_loans: u64, successful_repayments: u64) -> u64 {
-        if num_loans == 0 {
-            0
-        } else {
-            (successful_repayments * 10000) / num_loans
+    ///
+    /// Computed in `u128`, so it cannot overflow for any `u64` counts. Returns
+    /// `None` if `successful_repayments` exceeds `num_loans`, and a rate of 0
+    /// for an empty history.
+    pub fn calculate_success_rate(num_loans: u64, successful_repayments: u64) -> Option<u64> {
+        if successful_repayments > num_loans {
+            return None;
         }
+
+        let rate = (successful_repayments as u128 * 10000).checked_div(num_loans as u128).unwrap_or(0);
+        Some(rate as u64)
     }
     
     /// Check if loan history meets minimum success rate
+    ///
+    /// An invalid history (more repayments than loans) never meets it.
     pub fn meets_success_rate_threshold(
         num_loans: u64,
         successful_repayments: u64,
         min_success_rate: u64,
     ) -> bool {
-        let success_rate = calculate_success_rate(num_loans, successful_repayments);
-        success_rate >= min_success_rate
+        calculate_success_rate(num_loans, successful_repayments).is_some_and(|rate| rate >= min_success_rate)
+    }
+
+    /// Check if loan history meets both the success rate and minimum loan count
+    pub fn meets_loan_history_requirements(
+        num_loans: u64,
+        successful_repayments: u64,
+        min_success_rate: u64,
+        min_loans: u64,
+    ) -> bool {
+        num_loans > 0
+            && num_loans >= min_loans
+            && meets_success_rate_threshold(num_loans, successful_repayments, min_success_rate)
     }
     
-    /// Convert percentage to basis points (percentage * 100)
-    pub fn percentage_to_basis_points(percentage: f64) -> u64 {
-        (percentage * 100.0) as u64
+    /// Check a loan history against both a minimum success rate and a
+    /// minimum on-time rate, matching `LoanHistoryCircuit::new_with_on_time`
+    ///
+    /// A history with more on-time repayments than successful ones never
+    /// meets them.
+    pub fn meets_on_time_requirements(
+        num_loans: u64,
+        successful_repayments: u64,
+        on_time_repayments: u64,
+        min_success_rate: u64,
+        min_on_time_rate: u64,
+        min_loans: u64,
+    ) -> bool {
+        on_time_repayments <= successful_repayments
+            && meets_loan_history_requirements(num_loans, successful_repayments, min_success_rate, min_loans)
+            && meets_success_rate_threshold(num_loans, on_time_repayments, min_on_time_rate)
+    }
+
+    /// Convert percentage to basis points (percentage * 100), rounding to
+    /// the nearest basis point
+    ///
+    /// `99.999` is 10000 basis points rather than truncating to 9999. Fails
+    /// for a percentage outside `0.0..=100.0`, including NaN.
+    pub fn percentage_to_basis_points(percentage: f64) -> Result<u64, ZkError> {
+        if !(0.0..=100.0).contains(&percentage) {
+            return Err(ZkError::InvalidInput(format!("Percentage {}% is outside 0..=100", percentage)));
+        }
+
+        Ok((percentage * 100.0).round() as u64)
     }
     
     /// Convert basis points back to percentage
     pub fn basis_points_to_percentage(basis_points: u64) -> f64 {
         basis_points as f64 / 100.0
     }
+
+    /// Build the public inputs in the circuit's instance order for a circuit
+    /// without an on-time requirement: `[result, min_success_rate, 0, min_loans]`
+    pub fn public_inputs<F: PrimeField>(result: bool, min_success_rate: u64, min_loans: u64) -> Vec<F> {
+        public_inputs_with_on_time(result, min_success_rate, 0, min_loans)
+    }
+
+    /// Build the public inputs in the circuit's instance order for a circuit
+    /// built with `new_with_on_time`:
+    /// `[result, min_success_rate, min_on_time_rate, min_loans]`
+    pub fn public_inputs_with_on_time<F: PrimeField>(
+        result: bool,
+        min_success_rate: u64,
+        min_on_time_rate: u64,
+        min_loans: u64,
+    ) -> Vec<F> {
+        vec![
+            if result { F::ONE } else { F::ZERO },
+            F::from(min_success_rate),
+            F::from(min_on_time_rate),
+            F::from(min_loans),
+        ]
+    }
+}
+
+/// Convert a field element to u128, or `None` if it does not fit
+pub(crate) fn field_to_u128<F: PrimeField>(field: &F) -> Option<u128> {
+    let bytes = field.to_repr();
+    let (low, high) = bytes.as_ref().split_at(16);
+    if high.iter().any(|&byte| byte != 0) {
+        return None;
+    }
+
+    Some(low.iter().rev().fold(0u128, |acc, &byte| (acc << 8) | byte as u128))
+}
+
+/// Reject a known witness wider than 128 bits as a synthesis error
+pub(crate) fn check_fits_u128<F: PrimeField>(value: Value<F>) -> Result<(), Error> {
+    value.error_if_known_and(|v| field_to_u128(v).is_none())
 }
 
 #[cfg(test)]
@@ -308,23 +925,23 @@ mod tests {
     use super::utils::*;
     use halo2_proofs::dev::MockProver;
     use pasta_curves::Fp;
-    use ff::Field;
 
     #[test]
     fn test_loan_history_meets_threshold() {
-        let k = 4; // Circuit size parameter
+        let k = 7; // Circuit size parameter
         let num_loans = 10u64;
         let successful_repayments = 9u64; // 90% success rate
-        let min_success_rate = percentage_to_basis_points(80.0); // 80% minimum
+        let min_success_rate = percentage_to_basis_points(80.0).unwrap(); // 80% minimum
 
         let circuit = LoanHistoryCircuit::<Fp>::new(
             Some(num_loans),
             Some(successful_repayments),
             min_success_rate,
+            1,
         );
         
         // The public input should be 1 (true) since 90% >= 80%
-        let public_inputs = vec![Fp::one()];
+        let public_inputs = public_inputs(true, min_success_rate, 1);
 
         let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
         prover.assert_satisfied();
@@ -332,19 +949,20 @@ mod tests {
 
     #[test]
     fn test_loan_history_below_threshold() {
-        let k = 4;
+        let k = 7;
         let num_loans = 10u64;
         let successful_repayments = 6u64; // 60% success rate
-        let min_success_rate = percentage_to_basis_points(80.0); // 80% minimum
+        let min_success_rate = percentage_to_basis_points(80.0).unwrap(); // 80% minimum
 
         let circuit = LoanHistoryCircuit::<Fp>::new(
             Some(num_loans),
             Some(successful_repayments),
             min_success_rate,
+            1,
         );
         
         // The public input should be 0 (false) since 60% < 80%
-        let public_inputs = vec![Fp::zero()];
+        let public_inputs = public_inputs(false, min_success_rate, 1);
 
         let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
         prover.assert_satisfied();
@@ -352,19 +970,20 @@ mod tests {
 
     #[test]
     fn test_no_loan_history() {
-        let k = 4;
+        let k = 7;
         let num_loans = 0u64;
         let successful_repayments = 0u64;
-        let min_success_rate = percentage_to_basis_points(80.0);
+        let min_success_rate = percentage_to_basis_points(80.0).unwrap();
 
         let circuit = LoanHistoryCircuit::<Fp>::new(
             Some(num_loans),
             Some(successful_repayments),
             min_success_rate,
+            1,
         );
         
         // The public input should be 0 (false) since 0% < 80%
-        let public_inputs = vec![Fp::zero()];
+        let public_inputs = public_inputs(false, min_success_rate, 1);
 
         let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
         prover.assert_satisfied();
@@ -372,19 +991,20 @@ mod tests {
 
     #[test]
     fn test_perfect_loan_history() {
-        let k = 4;
+        let k = 7;
         let num_loans = 5u64;
         let successful_repayments = 5u64; // 100% success rate
-        let min_success_rate = percentage_to_basis_points(90.0); // 90% minimum
+        let min_success_rate = percentage_to_basis_points(90.0).unwrap(); // 90% minimum
 
         let circuit = LoanHistoryCircuit::<Fp>::new(
             Some(num_loans),
             Some(successful_repayments),
             min_success_rate,
+            1,
         );
         
         // The public input should be 1 (true) since 100% >= 90%
-        let public_inputs = vec![Fp::one()];
+        let public_inputs = public_inputs(true, min_success_rate, 1);
 
         let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
         prover.assert_satisfied();
@@ -392,43 +1012,159 @@ mod tests {
 
     #[test]
     fn test_circuit_without_witnesses() {
-        let k = 4;
-        let min_success_rate = percentage_to_basis_points(80.0);
+        use crate::circuits::lending::assert_keygen;
+
+        let min_success_rate = percentage_to_basis_points(80.0).unwrap();
+
+        let k = LoanHistoryCircuit::<Fp>::recommended_k();
+        let circuit = LoanHistoryCircuit::<Fp>::new(None, None, min_success_rate, 1);
+        assert_keygen(k, &circuit.without_witnesses());
+
+        let revealing = LoanHistoryCircuit::<Fp>::new_revealing_rate(None, None, min_success_rate, 1);
+        assert_keygen(k, &revealing.without_witnesses());
+
+        let on_time = LoanHistoryCircuit::<Fp>::new_with_on_time(None, None, None, min_success_rate, 7000, 1);
+        assert!(on_time.without_witnesses().require_on_time);
+        assert_keygen(k, &on_time.without_witnesses());
+    }
+
+    /// Circuit assigning a caller-supplied (possibly dishonest) witness
+    #[derive(Clone)]
+    struct DishonestLoanHistoryCircuit {
+        witness: LoanHistoryWitness<Fp>,
+    }
+
+    impl Circuit<Fp> for DishonestLoanHistoryCircuit {
+        type Config = LoanHistoryConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            LoanHistoryCircuit::<Fp>::configure(meta)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let chip = LoanHistoryChip::construct(config.clone());
+            let assigned = chip.assign_witness(layouter.namespace(|| "dishonest"), self.witness)?;
+            layouter.constrain_instance(assigned.result.cell(), config.instance, 0)
+        }
+    }
 
-        let circuit = LoanHistoryCircuit::<Fp>::new(None, None, min_success_rate);
-        let circuit_without_witnesses = circuit.without_witnesses();
+    fn honest_witness(num_loans: u64, successful_repayments: u64, min_success_rate: u64) -> LoanHistoryWitness<Fp> {
+        LoanHistoryWitness::compute(
+            Value::known(Fp::from(num_loans)),
+            Value::known(Fp::from(successful_repayments)),
+            Value::known(Fp::from(min_success_rate)),
+            Value::known(Fp::one()),
+        )
+    }
+
+    #[test]
+    fn test_dishonest_success_rate_rejected() {
+        // 6 of 10 repaid is 60%; claim 90% to clear an 80% threshold
+        let mut witness = honest_witness(10, 6, 8000);
+        witness.success_rate = Value::known(Fp::from(9000));
+        witness.above_min = Value::known(Fp::one());
+        witness.rate_remainder = Value::known(Fp::from(1000));
+        witness.result = Value::known(Fp::one());
+
+        let prover = MockProver::run(7, &DishonestLoanHistoryCircuit { witness }, vec![vec![Fp::one()]]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // Balancing the division with a negative remainder fails its range check
+        witness.remainder = Value::known(-Fp::from(30000));
+        let prover = MockProver::run(7, &DishonestLoanHistoryCircuit { witness }, vec![vec![Fp::one()]]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // The honest witness is accepted with the honest result
+        let prover = MockProver::run(
+            7,
+            &DishonestLoanHistoryCircuit { witness: honest_witness(10, 6, 8000) },
+            vec![vec![Fp::zero()]],
+        )
+        .unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn test_no_loans_cannot_claim_success() {
+        let mut witness = honest_witness(0, 0, 0);
+        witness.result = Value::known(Fp::one());
+
+        let prover = MockProver::run(7, &DishonestLoanHistoryCircuit { witness }, vec![vec![Fp::one()]]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // Even a zero threshold is not met without any loans
+        let circuit = LoanHistoryCircuit::<Fp>::new(Some(0), Some(0), 0, 1);
+        let prover = MockProver::run(7, &circuit, vec![public_inputs(false, 0, 1)]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn test_rounded_down_success_rate() {
+        // 2 of 3 is 6666.67 basis points, which rounds down below 6667
+        let circuit = LoanHistoryCircuit::<Fp>::new(Some(3), Some(2), 6667, 1);
+        let prover = MockProver::run(7, &circuit, vec![public_inputs(false, 6667, 1)]).unwrap();
+        prover.assert_satisfied();
 
-        // Should be able to create the circuit structure without witnesses
-        let _ = circuit_without_witnesses;
+        let circuit = LoanHistoryCircuit::<Fp>::new(Some(3), Some(2), 6666, 1);
+        let prover = MockProver::run(7, &circuit, vec![public_inputs(true, 6666, 1)]).unwrap();
+        prover.assert_satisfied();
     }
 
     #[test]
     fn test_utility_functions() {
         // Test success rate calculation
-        assert_eq!(calculate_success_rate(10, 9), 9000); // 90%
-        assert_eq!(calculate_success_rate(10, 8), 8000); // 80%
-        assert_eq!(calculate_success_rate(0, 0), 0); // No loans
+        assert_eq!(calculate_success_rate(10, 9), Some(9000)); // 90%
+        assert_eq!(calculate_success_rate(10, 8), Some(8000)); // 80%
+        assert_eq!(calculate_success_rate(0, 0), Some(0)); // No loans
         
         // Test threshold checking
         assert!(meets_success_rate_threshold(10, 9, 8000)); // 90% >= 80%
         assert!(!meets_success_rate_threshold(10, 7, 8000)); // 70% < 80%
         
         // Test percentage conversion
-        assert_eq!(percentage_to_basis_points(80.5), 8050);
+        assert_eq!(percentage_to_basis_points(80.5).unwrap(), 8050);
+        assert_eq!(percentage_to_basis_points(100.0).unwrap(), 10000);
+        assert_eq!(percentage_to_basis_points(99.999).unwrap(), 10000);
+        assert_eq!(percentage_to_basis_points(0.0).unwrap(), 0);
+        // 0.29 * 100.0 is 28.999999999999996 in floating point
+        assert_eq!(percentage_to_basis_points(0.29).unwrap(), 29);
+        for percentage in [f64::NAN, -1.0, 100.001, f64::INFINITY] {
+            assert!(matches!(percentage_to_basis_points(percentage), Err(ZkError::InvalidInput(_))));
+        }
         assert_eq!(basis_points_to_percentage(8050), 80.5);
     }
 
+    #[test]
+    fn test_success_rate_overflow_and_invalid_counts() {
+        // successful_repayments * 10000 no longer overflows u64
+        assert_eq!(calculate_success_rate(u64::MAX, u64::MAX), Some(10000));
+        assert_eq!(calculate_success_rate(4_000_000_000_000_000, 3_000_000_000_000_000), Some(7500));
+        assert!(meets_success_rate_threshold(u64::MAX, u64::MAX - 1, 9999));
+
+        // More repayments than loans is not a rate above 100%
+        assert_eq!(calculate_success_rate(3, 5), None);
+        assert_eq!(calculate_success_rate(0, 1), None);
+        assert!(!meets_success_rate_threshold(3, 5, 0));
+        assert!(!meets_loan_history_requirements(3, 5, 0, 0));
+    }
+
     #[test]
     fn test_edge_cases() {
-        let k = 4;
+        let k = 7;
         
         // Test with exactly meeting threshold
         let circuit = LoanHistoryCircuit::<Fp>::new(
             Some(10),
             Some(8), // Exactly 80%
-            percentage_to_basis_points(80.0),
+            percentage_to_basis_points(80.0).unwrap(),
+            1,
         );
-        let public_inputs = vec![Fp::one()];
+        let public_inputs = public_inputs(true, 8000, 1);
         let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
         prover.assert_satisfied();
         
@@ -436,10 +1172,256 @@ mod tests {
         let circuit2 = LoanHistoryCircuit::<Fp>::new(
             Some(1),
             Some(1), // 100% with just one loan
-            percentage_to_basis_points(50.0),
+            percentage_to_basis_points(50.0).unwrap(),
+            1,
         );
-        let public_inputs2 = vec![Fp::one()];
+        let public_inputs2 = utils::public_inputs(true, 5000, 1);
         let prover2 = MockProver::run(k, &circuit2, vec![public_inputs2]).unwrap();
         prover2.assert_satisfied();
     }
+
+    #[test]
+    fn test_min_loans_requirement() {
+        // A single perfect repayment does not clear a three-loan minimum
+        let circuit = LoanHistoryCircuit::<Fp>::new(Some(1), Some(1), 9000, 3);
+        let prover = MockProver::run(7, &circuit, vec![public_inputs(false, 9000, 3)]).unwrap();
+        prover.assert_satisfied();
+
+        let prover = MockProver::run(7, &circuit, vec![public_inputs(true, 9000, 3)]).unwrap();
+        assert!(prover.verify().is_err());
+
+        let circuit = LoanHistoryCircuit::<Fp>::new(Some(5), Some(5), 9000, 3);
+        let prover = MockProver::run(7, &circuit, vec![public_inputs(true, 9000, 3)]).unwrap();
+        prover.assert_satisfied();
+
+        // Exactly the minimum is enough
+        let circuit = LoanHistoryCircuit::<Fp>::new(Some(3), Some(3), 9000, 3);
+        let prover = MockProver::run(7, &circuit, vec![public_inputs(true, 9000, 3)]).unwrap();
+        prover.assert_satisfied();
+
+        assert!(!meets_loan_history_requirements(1, 1, 9000, 3));
+        assert!(meets_loan_history_requirements(5, 5, 9000, 3));
+    }
+
+    #[test]
+    fn test_policy_bound_to_instance() {
+        // A single repaid loan passes a zero-loan minimum of the prover's choosing...
+        let circuit = LoanHistoryCircuit::<Fp>::new(Some(1), Some(1), 8000, 0);
+        assert_eq!(circuit.expected_public_inputs(), public_inputs(true, 8000, 0));
+        MockProver::run(7, &circuit, vec![public_inputs(true, 8000, 0)]).unwrap().assert_satisfied();
+
+        // ...but not against a verifier expecting a three-loan minimum
+        let prover = MockProver::run(7, &circuit, vec![public_inputs(true, 8000, 3)]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // Nor does a zero success rate minimum pass for an 80% one
+        let circuit = LoanHistoryCircuit::<Fp>::new(Some(1), Some(0), 0, 0);
+        MockProver::run(7, &circuit, vec![public_inputs(true, 0, 0)]).unwrap().assert_satisfied();
+        for instance in [public_inputs(true, 8000, 0), public_inputs(true, 8000, 3)] {
+            assert!(MockProver::run(7, &circuit, vec![instance]).unwrap().verify().is_err());
+        }
+    }
+
+    #[test]
+    fn test_dishonest_loan_count_rejected() {
+        // Claim enough loans for a one-loan history against a three-loan minimum
+        let mut witness = LoanHistoryWitness::compute(
+            Value::known(Fp::from(1)),
+            Value::known(Fp::from(1)),
+            Value::known(Fp::from(9000)),
+            Value::known(Fp::from(3)),
+        );
+        witness.enough_loans = Value::known(Fp::one());
+        witness.loans_remainder = -Value::known(Fp::from(2));
+        witness.result = Value::known(Fp::one());
+
+        let prover = MockProver::run(7, &DishonestLoanHistoryCircuit { witness }, vec![vec![Fp::one()]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_more_repayments_than_loans_rejected() {
+        // 5 of 3 loans repaid would rate 166% and pass an 80% minimum
+        for result in [true, false] {
+            let circuit = LoanHistoryCircuit::<Fp>::new(Some(3), Some(5), 8000, 1);
+            let prover = MockProver::run(7, &circuit, vec![public_inputs(result, 8000, 1)]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+
+        // A forged in-range gap breaks the gap's definition instead
+        let mut witness = LoanHistoryWitness::compute(
+            Value::known(Fp::from(3)),
+            Value::known(Fp::from(5)),
+            Value::known(Fp::from(8000)),
+            Value::known(Fp::from(1)),
+        );
+        witness.repayments_gap = Value::known(Fp::zero());
+        let prover = MockProver::run(7, &DishonestLoanHistoryCircuit { witness }, vec![vec![Fp::one()]]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // Repaying every loan is still fine
+        let circuit = LoanHistoryCircuit::<Fp>::new(Some(3), Some(3), 8000, 1);
+        let prover = MockProver::run(7, &circuit, vec![public_inputs(true, 8000, 1)]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn test_from_percentages() {
+        let circuit = LoanHistoryCircuit::<Fp>::from_percentages(Some(10), Some(9), 80.0, 3).unwrap();
+        let expected = LoanHistoryCircuit::<Fp>::new(Some(10), Some(9), 8000, 3);
+        assert_eq!(known(circuit.min_success_rate), known(expected.min_success_rate));
+        assert_eq!(circuit.expected_public_inputs(), public_inputs(true, 8000, 3));
+
+        for min_percent in [0.0, 100.0] {
+            assert!(LoanHistoryCircuit::<Fp>::from_percentages(Some(10), Some(9), min_percent, 3).is_ok());
+        }
+        for min_percent in [-0.5, 100.5, f64::NAN, f64::INFINITY] {
+            assert!(matches!(
+                LoanHistoryCircuit::<Fp>::from_percentages(Some(10), Some(9), min_percent, 3),
+                Err(ZkError::InvalidInput(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_try_field_to_u64_flags_overflow() {
+        assert_eq!(try_field_to_u64(&Fp::from(u64::MAX)), Ok(u64::MAX));
+        assert_eq!(try_field_to_u64(&Fp::zero()), Ok(0));
+
+        // The truncating version silently maps 2^64 to 0
+        let wide = Fp::from(u64::MAX) + Fp::one();
+        assert_eq!(field_to_u64_truncating(&wide), 0);
+        assert_eq!(try_field_to_u64(&wide), Err(FieldConversionError));
+        assert_eq!(try_field_to_u64(&-Fp::one()), Err(FieldConversionError));
+    }
+
+    #[test]
+    fn test_revealed_success_rate() {
+        use crate::circuits::lending::assert_circuit;
+
+        for (num_loans, repayments) in [(10, 9), (10, 6), (3, 2), (5, 5), (0, 0)] {
+            let rate = calculate_success_rate(num_loans, repayments).unwrap();
+            let circuit = LoanHistoryCircuit::<Fp>::new_revealing_rate(Some(num_loans), Some(repayments), 8000, 1);
+            let result = meets_loan_history_requirements(num_loans, repayments, 8000, 1);
+
+            let expected = [public_inputs(result, 8000, 1), vec![Fp::from(rate)]].concat();
+            assert_eq!(circuit.expected_public_inputs(), expected);
+            assert_circuit(7, &circuit, vec![expected]);
+        }
+
+        // A lender with its own threshold reads the rate off the same proof;
+        // the prover cannot round 66.66% up to 66.67%
+        let circuit = LoanHistoryCircuit::<Fp>::new_revealing_rate(Some(3), Some(2), 6000, 1);
+        let prover = MockProver::run(7, &circuit, vec![[public_inputs(true, 6000, 1), vec![Fp::from(6667)]].concat()]).unwrap();
+        assert!(prover.verify().is_err());
+
+        assert!(circuit.without_witnesses().reveal_success_rate);
+        assert_eq!(LoanHistoryCircuit::<Fp>::new(Some(3), Some(2), 6000, 1).expected_public_inputs().len(), 4);
+    }
+
+    #[test]
+    fn test_on_time_repayments() {
+        use crate::circuits::lending::assert_circuit;
+
+        let k = LoanHistoryCircuit::<Fp>::recommended_k();
+        for (num_loans, repayments, on_time) in [(10, 10, 9), (10, 10, 3), (10, 9, 8), (10, 6, 6), (0, 0, 0)] {
+            let circuit = LoanHistoryCircuit::<Fp>::new_with_on_time(Some(num_loans), Some(repayments), Some(on_time), 8000, 7000, 1);
+            let result = meets_on_time_requirements(num_loans, repayments, on_time, 8000, 7000, 1);
+
+            let expected = public_inputs_with_on_time(result, 8000, 7000, 1);
+            assert_eq!(circuit.expected_public_inputs(), expected);
+            assert_circuit(k, &circuit, vec![expected]);
+        }
+    }
+
+    #[test]
+    fn test_all_repaid_but_late() {
+        let k = LoanHistoryCircuit::<Fp>::recommended_k();
+
+        // Every loan repaid, but only 3 of 10 on time
+        let circuit = LoanHistoryCircuit::<Fp>::new_with_on_time(Some(10), Some(10), Some(3), 8000, 7000, 1);
+        assert!(!meets_on_time_requirements(10, 10, 3, 8000, 7000, 1));
+        MockProver::run(k, &circuit, vec![public_inputs_with_on_time(false, 8000, 7000, 1)]).unwrap().assert_satisfied();
+        assert!(MockProver::run(k, &circuit, vec![public_inputs_with_on_time(true, 8000, 7000, 1)]).unwrap().verify().is_err());
+
+        // The same history passes on its success rate alone
+        let circuit = LoanHistoryCircuit::<Fp>::new(Some(10), Some(10), 8000, 1);
+        MockProver::run(k, &circuit, vec![public_inputs(true, 8000, 1)]).unwrap().assert_satisfied();
+    }
+
+    #[test]
+    fn test_forged_on_time_minimum_rejected() {
+        let k = LoanHistoryCircuit::<Fp>::recommended_k();
+
+        // Every loan repaid late passes an on-time minimum of 0...
+        let circuit = LoanHistoryCircuit::<Fp>::new_with_on_time(Some(10), Some(10), Some(0), 8000, 0, 1);
+        assert_eq!(circuit.expected_public_inputs(), public_inputs_with_on_time(true, 8000, 0, 1));
+        MockProver::run(k, &circuit, vec![circuit.expected_public_inputs()]).unwrap().assert_satisfied();
+
+        // ...but that proof fails against a verifier expecting 70%
+        let prover = MockProver::run(k, &circuit, vec![public_inputs_with_on_time(true, 8000, 7000, 1)]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_more_on_time_than_successful_rejected() {
+        let k = LoanHistoryCircuit::<Fp>::recommended_k();
+
+        // 9 on-time repayments out of 6 successful would pass a 70% on-time minimum
+        assert!(!meets_on_time_requirements(10, 6, 9, 5000, 7000, 1));
+        for result in [true, false] {
+            let circuit = LoanHistoryCircuit::<Fp>::new_with_on_time(Some(10), Some(6), Some(9), 5000, 7000, 1);
+            assert!(MockProver::run(k, &circuit, vec![public_inputs_with_on_time(result, 5000, 7000, 1)]).unwrap().verify().is_err());
+        }
+
+        // And never more on-time repayments than loans
+        let circuit = LoanHistoryCircuit::<Fp>::new_with_on_time(Some(3), Some(3), Some(5), 5000, 7000, 1);
+        assert!(MockProver::run(k, &circuit, vec![public_inputs_with_on_time(true, 5000, 7000, 1)]).unwrap().verify().is_err());
+    }
+
+    #[test]
+    fn test_oversized_loan_count_rejected() {
+        // 2^64 + 10 loans would read as 10 if truncated
+        let circuit = LoanHistoryCircuit::<Fp> {
+            num_loans: Value::known(Fp::from(u64::MAX) + Fp::from(11)),
+            ..LoanHistoryCircuit::new(None, Some(9), 8000, 1)
+        };
+        for result in [true, false] {
+            assert!(MockProver::run(7, &circuit, vec![public_inputs(result, 8000, 1)]).unwrap().verify().is_err());
+        }
+    }
+
+    #[test]
+    fn test_negative_counts_rejected() {
+        use crate::circuits::lending::diagnose;
+
+        let k = LoanHistoryCircuit::<Fp>::recommended_k();
+        let minus_one = Value::known(Fp::zero() - Fp::one());
+        let honest = LoanHistoryCircuit::<Fp>::new_with_on_time(Some(10), Some(9), Some(8), 8000, 7000, 1);
+        let circuits = [
+            LoanHistoryCircuit { num_loans: minus_one, ..honest.clone() },
+            LoanHistoryCircuit { successful_repayments: minus_one, ..honest.clone() },
+            LoanHistoryCircuit { on_time_repayments: minus_one, ..honest },
+        ];
+
+        for circuit in circuits {
+            for result in [true, false] {
+                let instance = public_inputs_with_on_time(result, 8000, 7000, 1);
+                assert!(MockProver::run(k, &circuit, vec![instance.clone()]).unwrap().verify().is_err());
+
+                // The counts' own range checks flag it
+                let diagnostics = diagnose(k, &circuit, vec![instance]).unwrap();
+                assert!(diagnostics.failures.iter().any(|f| f.gate.as_deref() == Some("range_check_bit")), "{:?}", diagnostics.failures);
+            }
+        }
+    }
+
+    #[test]
+    fn test_field_to_u128_flags_overflow() {
+        let wide = (1u128 << 64) + 5;
+        assert_eq!(field_to_u128(&Fp::from_u128(wide)), Some(wide));
+        assert_eq!(field_to_u128(&Fp::from_u128(u128::MAX)), Some(u128::MAX));
+        assert_eq!(field_to_u128(&(Fp::from_u128(u128::MAX) + Fp::one())), None);
+        assert_eq!(field_to_u128(&-Fp::one()), None);
+    }
 }
\ No newline at end of file
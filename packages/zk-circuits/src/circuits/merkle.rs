@@ -0,0 +1,234 @@
+use halo2_gadgets::poseidon::{
+    primitives::{P128Pow5T3, Spec},
+    Pow5Config,
+};
+use halo2_proofs::{
+    circuit::{Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+use ff::PrimeField;
+use std::marker::PhantomData;
+
+use super::AssignedCell;
+use super::hash::poseidon2;
+
+/// Configuration for the Merkle path chip
+#[derive(Clone, Debug)]
+pub struct MerkleConfig<F: PrimeField> {
+    /// Advice column for the current node on the path
+    pub node: Column<Advice>,
+    /// Advice column for the sibling at this level (private input)
+    pub sibling: Column<Advice>,
+    /// Advice column for the position bit (1 if the node is the right child)
+    pub position: Column<Advice>,
+    /// Advice column for the left hash input
+    pub left: Column<Advice>,
+    /// Advice column for the right hash input
+    pub right: Column<Advice>,
+    /// Selector for the conditional swap gate
+    pub selector: Selector,
+    /// Poseidon configuration used to hash each level
+    pub poseidon: Pow5Config<F, 3, 2>,
+}
+
+/// Chip recomputing a Merkle root from a leaf and its authentication path
+pub struct MerkleChip<F: PrimeField> {
+    config: MerkleConfig<F>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: PrimeField> MerkleChip<F>
+where
+    P128Pow5T3: Spec<F, 3, 2>,
+{
+    pub fn construct(config: MerkleConfig<F>) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 5],
+        poseidon: Pow5Config<F, 3, 2>,
+    ) -> MerkleConfig<F> {
+        let [node, sibling, position, left, right] = advice;
+        let selector = meta.selector();
+
+        meta.enable_equality(node);
+        meta.enable_equality(left);
+        meta.enable_equality(right);
+
+        // Order the pair by the position bit:
+        // position = 0 -> (node, sibling), position = 1 -> (sibling, node)
+        meta.create_gate("merkle_conditional_swap", |meta| {
+            let s = meta.query_selector(selector);
+            let node = meta.query_advice(node, Rotation::cur());
+            let sibling = meta.query_advice(sibling, Rotation::cur());
+            let position = meta.query_advice(position, Rotation::cur());
+            let left = meta.query_advice(left, Rotation::cur());
+            let right = meta.query_advice(right, Rotation::cur());
+
+            vec![
+                // Ensure position is boolean (0 or 1)
+                s.clone() * (position.clone() * (position.clone() - Expression::Constant(F::ONE))),
+                s.clone() * (left - (node.clone() + position.clone() * (sibling.clone() - node.clone()))),
+                s * (right - (sibling.clone() + position * (node - sibling))),
+            ]
+        });
+
+        MerkleConfig {
+            node,
+            sibling,
+            position,
+            left,
+            right,
+            selector,
+            poseidon,
+        }
+    }
+
+    /// Hash two assigned cells with Poseidon
+    pub fn hash_pair(
+        &self,
+        layouter: impl Layouter<F>,
+        left: AssignedCell<F>,
+        right: AssignedCell<F>,
+    ) -> Result<AssignedCell<F>, Error> {
+        poseidon2(&self.config.poseidon, layouter, left, right)
+    }
+
+    /// Recompute the root from an assigned leaf and its `(sibling, position)` path
+    pub fn compute_root(
+        &self,
+        mut layouter: impl Layouter<F>,
+        leaf: AssignedCell<F>,
+        path: &[(Value<F>, Value<F>)],
+    ) -> Result<AssignedCell<F>, Error> {
+        let mut node = leaf;
+
+        for (level, &(sibling, position)) in path.iter().enumerate() {
+            let (left, right) = layouter.assign_region(
+                || format!("merkle level {}", level),
+                |mut region| {
+                    self.config.selector.enable(&mut region, 0)?;
+
+                    node.copy_advice(|| "node", &mut region, self.config.node, 0)?;
+                    region.assign_advice(|| "sibling", self.config.sibling, 0, || sibling)?;
+                    region.assign_advice(|| "position", self.config.position, 0, || position)?;
+
+                    let swap = node.value().copied().zip(sibling).zip(position).map(|((node, sibling), position)| {
+                        if position == F::ONE {
+                            (sibling, node)
+                        } else {
+                            (node, sibling)
+                        }
+                    });
+
+                    let left = region.assign_advice(|| "left", self.config.left, 0, || swap.map(|(l, _)| l))?;
+                    let right = region.assign_advice(|| "right", self.config.right, 0, || swap.map(|(_, r)| r))?;
+
+                    Ok((left, right))
+                },
+            )?;
+
+            node = self.hash_pair(layouter.namespace(|| format!("merkle hash {}", level)), left, right)?;
+        }
+
+        Ok(node)
+    }
+}
+
+/// Off-circuit helpers matching the in-circuit Merkle hashing
+pub mod utils {
+    use super::*;
+    use crate::circuits::lending::known;
+
+    /// Poseidon hash of two field elements, matching `MerkleChip::hash_pair`
+    pub fn hash_pair<F: PrimeField>(left: F, right: F) -> F
+    where
+        P128Pow5T3: Spec<F, 3, 2>,
+    {
+        crate::circuits::hash::utils::hash2(left, right)
+    }
+
+    /// Recompute a root from a leaf and its `(sibling, is_right)` path
+    pub fn compute_root<F: PrimeField>(leaf: F, path: &[(F, bool)]) -> F
+    where
+        P128Pow5T3: Spec<F, 3, 2>,
+    {
+        path.iter().fold(leaf, |node, &(sibling, is_right)| {
+            if is_right {
+                hash_pair(sibling, node)
+            } else {
+                hash_pair(node, sibling)
+            }
+        })
+    }
+
+    /// Build the tree over `leaves` (padded with zeros to a power of two) and
+    /// return the root and the authentication path for `index`
+    pub fn merkle_path<F: PrimeField>(leaves: &[F], index: usize) -> (F, Vec<(F, bool)>)
+    where
+        P128Pow5T3: Spec<F, 3, 2>,
+    {
+        assert!(index < leaves.len(), "leaf index out of bounds");
+
+        let mut level = leaves.to_vec();
+        level.resize(leaves.len().next_power_of_two().max(2), F::ZERO);
+
+        let mut path = Vec::new();
+        let mut position = index;
+        while level.len() > 1 {
+            path.push((level[position ^ 1], position & 1 == 1));
+            level = level.chunks(2).map(|pair| hash_pair(pair[0], pair[1])).collect();
+            position /= 2;
+        }
+
+        (level[0], path)
+    }
+
+    /// Recover an off-circuit path from circuit witnesses, if all are known
+    pub fn known_path<F: PrimeField>(path: &[(Value<F>, Value<F>)]) -> Option<Vec<(F, bool)>> {
+        path.iter()
+            .map(|&(sibling, position)| known(sibling).zip(known(position)).map(|(s, p)| (s, p == F::ONE)))
+            .collect()
+    }
+
+    /// Convert an off-circuit path into circuit witnesses
+    pub fn path_values<F: PrimeField>(path: &[(F, bool)]) -> Vec<(Value<F>, Value<F>)> {
+        path.iter()
+            .map(|&(sibling, is_right)| {
+                (Value::known(sibling), Value::known(if is_right { F::ONE } else { F::ZERO }))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::utils::*;
+    use pasta_curves::Fp;
+
+    #[test]
+    fn test_merkle_path_recomputes_root() {
+        let leaves: Vec<Fp> = (1..=5u64).map(Fp::from).collect();
+
+        for (index, leaf) in leaves.iter().enumerate() {
+            let (root, path) = merkle_path(&leaves, index);
+            assert_eq!(path.len(), 3);
+            assert_eq!(compute_root(*leaf, &path), root);
+        }
+    }
+
+    #[test]
+    fn test_tampered_path_changes_root() {
+        let leaves: Vec<Fp> = (1..=4u64).map(Fp::from).collect();
+        let (root, mut path) = merkle_path(&leaves, 2);
+
+        path[0].0 += Fp::one();
+        assert_ne!(compute_root(leaves[2], &path), root);
+    }
+}
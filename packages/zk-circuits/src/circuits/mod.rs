@@ -2,14 +2,72 @@
 // This file will be implemented in subsequent tasks
 
 pub mod trust_score;
+pub mod trust_score_band;
+pub mod aggregate_trust;
+pub mod tiered_trust;
+pub mod committed_trust_score;
 pub mod income_range;
+pub mod income_commitment;
+pub mod income_growth;
 pub mod identity;
+pub mod identity_membership;
+pub mod distinct_identities;
 pub mod loan_history;
+pub mod weighted_loan_history;
 pub mod optimizations;
+pub mod histogram;
+pub mod income_bucket;
+pub mod hash;
+pub mod merkle;
+pub mod group_fund;
+pub mod composite;
+pub mod eligibility;
+pub mod lending;
 
 // Re-export circuit types
+// (each circuit keeps its own `utils` module; reach those through the module path)
+#[allow(ambiguous_glob_reexports)]
 pub use trust_score::*;
+#[allow(ambiguous_glob_reexports)]
+pub use trust_score_band::*;
+#[allow(ambiguous_glob_reexports)]
+pub use aggregate_trust::*;
+#[allow(ambiguous_glob_reexports)]
+pub use tiered_trust::*;
+#[allow(ambiguous_glob_reexports)]
+pub use committed_trust_score::*;
+#[allow(ambiguous_glob_reexports)]
 pub use income_range::*;
+#[allow(ambiguous_glob_reexports)]
+pub use income_commitment::*;
+#[allow(ambiguous_glob_reexports)]
+pub use income_growth::*;
+#[allow(ambiguous_glob_reexports)]
 pub use identity::*;
+#[allow(ambiguous_glob_reexports)]
+pub use identity_membership::*;
+#[allow(ambiguous_glob_reexports)]
+pub use distinct_identities::*;
+#[allow(ambiguous_glob_reexports)]
 pub use loan_history::*;
-pub use optimizations::*;
\ No newline at end of file
+#[allow(ambiguous_glob_reexports)]
+pub use weighted_loan_history::*;
+pub use optimizations::*;
+#[allow(ambiguous_glob_reexports)]
+pub use histogram::*;
+#[allow(ambiguous_glob_reexports)]
+pub use income_bucket::*;
+#[allow(ambiguous_glob_reexports)]
+pub use hash::*;
+#[allow(ambiguous_glob_reexports)]
+pub use merkle::*;
+#[allow(ambiguous_glob_reexports)]
+pub use group_fund::*;
+#[allow(ambiguous_glob_reexports)]
+pub use composite::*;
+#[allow(ambiguous_glob_reexports)]
+pub use eligibility::*;
+pub use lending::*;
+
+/// Helper type for assigned cells
+pub type AssignedCell<F> = halo2_proofs::circuit::AssignedCell<F, F>;
@@ -1,13 +1,18 @@
-/// Performance optimizations for mobile devices
-/// 
-/// This module contains optimizations to make ZK circuits more efficient
-/// on mobile devices with limited computational resources.
+//! Performance optimizations for mobile devices
+//! 
+//! This module contains optimizations to make ZK circuits more efficient
+//! on mobile devices with limited computational resources.
 
 use halo2_proofs::{
     circuit::{Layouter, Value},
-    plonk::{Advice, Column, ConstraintSystem, Error, Selector},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
 };
 use ff::PrimeField;
+use std::marker::PhantomData;
+
+use super::AssignedCell;
+use super::loan_history::field_to_u128;
 
 /// Configuration for optimized circuits
 #[derive(Clone, Debug)]
@@ -48,14 +53,150 @@ impl OptimizedConfig {
     }
 }
 
+/// Configuration for the shared range check chip
+#[derive(Clone, Debug)]
+pub struct RangeCheckConfig {
+    /// Advice column holding the running sum of the value's bits
+    pub value: Column<Advice>,
+    /// Selector for each step of the bit decomposition
+    pub bit_selector: Selector,
+    /// Selector pinning the running sum's starting cell to zero
+    pub start_selector: Selector,
+    /// Widest value the chip is configured to check
+    pub num_bits: usize,
+}
+
+/// Chip proving that a value fits in `num_bits` bits
+///
+/// Uses a single advice column: a most-significant-first running sum
+/// `z_0 = 0, z_{i+1} = 2 * z_i + b_i` with every `b_i` boolean, whose last
+/// cell is the checked value. Shared by the comparison circuits so each one
+/// does not reimplement its own decomposition.
+pub struct RangeCheckChip<F: PrimeField> {
+    config: RangeCheckConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: PrimeField> RangeCheckChip<F> {
+    pub fn construct(config: RangeCheckConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        value: Column<Advice>,
+        num_bits: usize,
+    ) -> RangeCheckConfig {
+        assert!(num_bits > 0 && num_bits < 128, "range check width must be in 1..128 bits");
+
+        let bit_selector = meta.selector();
+        let start_selector = meta.selector();
+
+        meta.enable_equality(value);
+
+        meta.create_gate("range_check_start", |meta| {
+            let s = meta.query_selector(start_selector);
+            let z = meta.query_advice(value, Rotation::cur());
+
+            vec![s * z]
+        });
+
+        meta.create_gate("range_check_bit", |meta| {
+            let s = meta.query_selector(bit_selector);
+            let z_cur = meta.query_advice(value, Rotation::cur());
+            let z_next = meta.query_advice(value, Rotation::next());
+            let bit = z_next - z_cur * Expression::Constant(F::from(2));
+
+            // Ensure each bit is boolean (0 or 1)
+            vec![s * (bit.clone() * (bit - Expression::Constant(F::ONE)))]
+        });
+
+        RangeCheckConfig {
+            value,
+            bit_selector,
+            start_selector,
+            num_bits,
+        }
+    }
+
+    /// Decompose `value` into `num_bits` bits and return the assigned value cell
+    ///
+    /// `num_bits` may be narrower than the configured width; wider checks are
+    /// rejected as a synthesis error.
+    pub fn assign_range_check(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: Value<F>,
+        num_bits: usize,
+    ) -> Result<AssignedCell<F>, Error> {
+        if num_bits == 0 || num_bits > self.config.num_bits {
+            return Err(Error::Synthesis);
+        }
+
+        layouter.assign_region(
+            || format!("range check {} bits", num_bits),
+            |mut region| {
+                self.config.start_selector.enable(&mut region, 0)?;
+
+                let mut z = Value::known(F::ZERO);
+                region.assign_advice(|| "z_0", self.config.value, 0, || z)?;
+
+                let mut value_cell = None;
+                for i in 0..num_bits {
+                    self.config.bit_selector.enable(&mut region, i)?;
+
+                    // The last cell holds the value itself, so an out-of-range
+                    // value (even one wider than 128 bits) leaves a non-boolean
+                    // final step
+                    z = if i + 1 == num_bits {
+                        value
+                    } else {
+                        let bit = value.map(|v| {
+                            F::from(((field_to_u128(&v).unwrap_or_default() >> (num_bits - 1 - i)) & 1) as u64)
+                        });
+                        z * Value::known(F::from(2)) + bit
+                    };
+                    let cell = region.assign_advice(|| format!("z_{}", i + 1), self.config.value, i + 1, || z)?;
+                    value_cell = Some(cell);
+                }
+
+                Ok(value_cell.expect("num_bits is non-zero"))
+            },
+        )
+    }
+
+    /// Range check an already-assigned cell by copying it into the decomposition
+    pub fn copy_range_check(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: &AssignedCell<F>,
+        num_bits: usize,
+    ) -> Result<AssignedCell<F>, Error> {
+        let checked = self.assign_range_check(
+            layouter.namespace(|| "range check"),
+            value.value().copied(),
+            num_bits,
+        )?;
+        layouter.assign_region(
+            || "bind range checked value",
+            |mut region| region.constrain_equal(checked.cell(), value.cell()),
+        )?;
+
+        Ok(checked)
+    }
+}
+
 /// Mobile-optimized trust score circuit
 /// Uses fewer constraints and smaller field operations
 pub mod mobile_trust_score {
     use super::*;
-    use crate::circuits::trust_score::{TrustScoreCircuit, TrustScoreConfig};
+    use crate::circuits::trust_score::{TrustScoreChip, TrustScoreConfig};
     use halo2_proofs::{
         circuit::SimpleFloorPlanner,
-        plonk::{Circuit, Instance},
+        plonk::Circuit,
     };
 
     /// Mobile-optimized version of trust score circuit
@@ -94,15 +235,11 @@ pub mod mobile_trust_score {
             let trust_score = meta.advice_column();
             let threshold = meta.advice_column();
             let result = meta.advice_column();
+            let decomposition = meta.advice_column();
+            let bounds = [(); 2].map(|_| meta.advice_column());
             let instance = meta.instance_column();
 
-            TrustScoreConfig {
-                trust_score,
-                threshold,
-                result,
-                instance,
-                selector: meta.selector(),
-            }
+            TrustScoreChip::configure(meta, trust_score, threshold, result, decomposition, bounds, instance)
         }
 
         fn synthesize(
@@ -112,29 +249,237 @@ pub mod mobile_trust_score {
         ) -> Result<(), Error> {
             // Use the same synthesis as regular trust score circuit
             // The optimization comes from using smaller k parameter
-            use crate::circuits::trust_score::TrustScoreChip;
-            
             let chip = TrustScoreChip::construct(config.clone());
-            let result_cell = chip.assign_comparison(
+            let assigned = chip.assign_comparison(
                 layouter.namespace(|| "trust score check"),
                 self.trust_score,
                 self.threshold,
             )?;
 
-            // Expose the result as public input
+            // Expose the result and threshold as public inputs
             layouter.constrain_instance(
-                result_cell.cell(),
+                assigned.result.cell(),
                 config.instance,
                 0,
             )?;
+            layouter.constrain_instance(
+                assigned.threshold.cell(),
+                config.instance,
+                1,
+            )?;
 
             Ok(())
         }
     }
 }
 
+/// Trust score circuit with the threshold in a fixed column
+///
+/// The threshold is set at keygen rather than witnessed, so the circuit uses
+/// one advice column fewer than `TrustScoreCircuit` at the cost of needing
+/// keys per threshold. Suits lenders with a single standing policy.
+pub mod fixed_threshold {
+    use super::*;
+    use crate::circuits::lending::known;
+    use crate::circuits::loan_history::field_to_u64_truncating;
+    use crate::circuits::trust_score::{utils::public_inputs, TrustScoreChip, TrustScoreConfig};
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        plonk::Circuit,
+    };
+
+    /// Proves a private trust score is at least a threshold fixed at keygen
+    ///
+    /// Instance layout: `[result, threshold]`. The threshold cell is fixed,
+    /// so binding it to the instance only lets verifiers confirm which
+    /// policy the keys were made for.
+    #[derive(Clone, Debug)]
+    pub struct FixedThresholdTrustScoreCircuit<F: PrimeField> {
+        /// Private input: the actual trust score
+        pub trust_score: Value<F>,
+        /// Fixed at keygen: the threshold to compare against
+        pub threshold: Value<F>,
+    }
+
+    impl<F: PrimeField> FixedThresholdTrustScoreCircuit<F> {
+        pub fn new(trust_score: Option<u64>, threshold: u64) -> Self {
+            Self {
+                trust_score: match trust_score {
+                    Some(score) => Value::known(F::from(score)),
+                    None => Value::unknown(),
+                },
+                threshold: Value::known(F::from(threshold)),
+            }
+        }
+
+        /// Public inputs `[result, threshold]`, with the result computed
+        /// from the known trust score (0 without a witness)
+        pub fn expected_public_inputs(&self) -> Vec<F> {
+            let threshold = field_to_u64_truncating(&known(self.threshold).unwrap_or(F::ZERO));
+            let result = known(self.trust_score).is_some_and(|score| field_to_u64_truncating(&score) >= threshold);
+
+            public_inputs::<F>(result, threshold)[..2].to_vec()
+        }
+    }
+
+    impl<F: PrimeField> Circuit<F> for FixedThresholdTrustScoreCircuit<F> {
+        type Config = TrustScoreConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                trust_score: Value::unknown(),
+                threshold: self.threshold,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let trust_score = meta.advice_column();
+            let threshold = meta.fixed_column();
+            let result = meta.advice_column();
+            let decomposition = meta.advice_column();
+            let bounds = [(); 2].map(|_| meta.advice_column());
+            let instance = meta.instance_column();
+
+            TrustScoreChip::configure(meta, trust_score, threshold, result, decomposition, bounds, instance)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            let chip = TrustScoreChip::construct(config.clone());
+            let assigned = chip.assign_comparison(
+                layouter.namespace(|| "trust score comparison"),
+                self.trust_score,
+                self.threshold,
+            )?;
+
+            layouter.constrain_instance(assigned.result.cell(), config.instance, 0)?;
+            layouter.constrain_instance(assigned.threshold.cell(), config.instance, 1)
+        }
+    }
+}
+
 /// Performance utilities for mobile optimization
 pub mod performance {
+    use super::batch_processing::{get_optimal_batch_size, should_use_batch_processing};
+    use crate::api::MAX_K;
+    use crate::error::ZkError;
+    use halo2_proofs::{
+        dev::{CircuitCost, CircuitGates, MockProver},
+        plonk::{Circuit, ConstraintSystem},
+    };
+    use pasta_curves::{Eq, Fp};
+    use std::str::FromStr;
+
+    /// Measured size of a circuit, as opposed to the estimates below
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct CircuitStats {
+        pub advice_columns: usize,
+        pub fixed_columns: usize,
+        pub instance_columns: usize,
+        /// Number of custom gates
+        pub gates: usize,
+        /// Smallest `k` the circuit lays out in, or `None` if it does not fit in the `k` measured
+        pub min_k: Option<u32>,
+    }
+
+    impl CircuitStats {
+        /// Stats as a JSON object with camelCase keys
+        pub fn to_json(&self) -> String {
+            serde_json::json!({
+                "adviceColumns": self.advice_columns,
+                "fixedColumns": self.fixed_columns,
+                "instanceColumns": self.instance_columns,
+                "gates": self.gates,
+                "minK": self.min_k,
+            })
+            .to_string()
+        }
+    }
+
+    /// Measure a circuit's columns and gates, and the smallest `k` up to `k` it fits in
+    ///
+    /// The layout is found by synthesizing with the `MockProver`, so the
+    /// circuit needs a witness; any witness will do, as the layout does not
+    /// depend on it. `min_k` is also `None` if synthesis fails.
+    pub fn circuit_stats<C: Circuit<Fp>>(circuit: &C, k: u32) -> CircuitStats {
+        let mut cs = ConstraintSystem::<Fp>::default();
+        C::configure(&mut cs);
+
+        // halo2 keeps the column counts crate-private; the pinned constraint
+        // system (also hashed into verifying keys) prints them
+        let pinned = format!("{:?}", cs.pinned());
+        let count = |name: &str| debug_field(&pinned, name).unwrap_or_default();
+        let advice_columns = count("num_advice_columns");
+        let fixed_columns = count("num_fixed_columns");
+        let instance_columns = count("num_instance_columns");
+
+        // Likewise, `CircuitGates` only exposes its gate count through its summary
+        let gates = CircuitGates::collect::<Fp, C>()
+            .to_string()
+            .lines()
+            .find_map(|line| line.strip_prefix("Total gates: "))
+            .and_then(|count| count.parse().ok())
+            .unwrap_or_default();
+
+        let min_k = probe_min_k(circuit, &cs, instance_columns, k);
+
+        CircuitStats {
+            advice_columns,
+            fixed_columns,
+            instance_columns,
+            gates,
+            min_k,
+        }
+    }
+
+    /// Smallest circuit size `k` a circuit lays out in
+    ///
+    /// Probes increasing `k` with the `MockProver`, so like `circuit_stats`
+    /// this needs a witness. Only layout is checked, not the witness, so pick
+    /// keys with this rather than guessing `k`. Panics if the circuit does
+    /// not fit in any `k` up to `MAX_K`.
+    pub fn min_k_for<C: Circuit<Fp>>(circuit: &C) -> u32 {
+        let mut cs = ConstraintSystem::<Fp>::default();
+        C::configure(&mut cs);
+        let instance_columns = debug_field(&format!("{:?}", cs.pinned()), "num_instance_columns").unwrap_or_default();
+        probe_min_k(circuit, &cs, instance_columns, MAX_K).unwrap_or_else(|| panic!("Circuit does not fit in k = {}", MAX_K))
+    }
+
+    /// Smallest `k` up to `max_k` that `circuit`, configured as `cs`, lays out in
+    fn probe_min_k<C: Circuit<Fp>>(circuit: &C, cs: &ConstraintSystem<Fp>, instance_columns: usize, max_k: u32) -> Option<u32> {
+        // The layout fits once synthesis no longer runs out of usable rows
+        let min_rows = cs.minimum_rows();
+        (1..=max_k)
+            .filter(|&k| (1usize << k) >= min_rows)
+            .find(|&k| MockProver::run(k, circuit, vec![vec![]; instance_columns]).is_ok())
+    }
+
+    /// Rows a circuit's layout occupies at circuit size `k`
+    ///
+    /// Measured with halo2's `CircuitCost`, which needs a witness like
+    /// `circuit_stats` does. Panics if the circuit does not fit in `k`.
+    pub fn rows_used<C: Circuit<Fp> + std::fmt::Debug>(circuit: &C, k: u32) -> usize {
+        // `CircuitCost` keeps its measurements private; its Debug output lists them
+        let cost = format!("{:?}", CircuitCost::<Eq, C>::measure(k, circuit));
+        debug_field(&cost, "max_rows").expect("CircuitCost reports max_rows")
+    }
+
+    /// Rows available to a circuit's layout at size `k`, after the rows
+    /// halo2 reserves for blinding
+    pub fn usable_rows<C: Circuit<Fp>>(k: u32) -> usize {
+        let mut cs = ConstraintSystem::<Fp>::default();
+        C::configure(&mut cs);
+        (1usize << k) - (cs.blinding_factors() + 1)
+    }
+
+    /// Read the number printed after `name: ` in a Debug dump
+    fn debug_field(debug: &str, name: &str) -> Option<usize> {
+        debug
+            .split_once(&format!("{}: ", name))
+            .and_then(|(_, rest)| rest.split(|c: char| !c.is_ascii_digit()).next())
+            .and_then(|digits| digits.parse().ok())
+    }
+
     /// Recommended circuit size parameters for different device types
     pub struct CircuitSizeRecommendations;
 
@@ -166,7 +511,10 @@ pub mod performance {
     }
 
     /// Device type classification for optimization
-    #[derive(Debug, Clone, Copy)]
+    ///
+    /// Crosses the FFI boundary as a stable id: 0 = `HighEndMobile`,
+    /// 1 = `MidRangeMobile`, 2 = `LowEndMobile`, 3 = `Desktop`.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     pub enum DeviceType {
         HighEndMobile,
         MidRangeMobile,
@@ -174,6 +522,98 @@ pub mod performance {
         Desktop,
     }
 
+    impl DeviceType {
+        /// Every device type, in id order
+        pub const ALL: [DeviceType; 4] = [
+            DeviceType::HighEndMobile,
+            DeviceType::MidRangeMobile,
+            DeviceType::LowEndMobile,
+            DeviceType::Desktop,
+        ];
+
+        /// The device type's FFI id
+        pub fn as_u8(self) -> u8 {
+            match self {
+                DeviceType::HighEndMobile => 0,
+                DeviceType::MidRangeMobile => 1,
+                DeviceType::LowEndMobile => 2,
+                DeviceType::Desktop => 3,
+            }
+        }
+    }
+
+    impl TryFrom<u8> for DeviceType {
+        type Error = ZkError;
+
+        fn try_from(id: u8) -> Result<Self, ZkError> {
+            Self::ALL
+                .get(id as usize)
+                .copied()
+                .ok_or_else(|| ZkError::InvalidInput(format!("Unknown device type id: {}", id)))
+        }
+    }
+
+    impl FromStr for DeviceType {
+        type Err = ZkError;
+
+        fn from_str(name: &str) -> Result<Self, ZkError> {
+            match name.trim().to_ascii_lowercase().replace('-', "_").as_str() {
+                "high_end_mobile" => Ok(DeviceType::HighEndMobile),
+                "mid_range_mobile" => Ok(DeviceType::MidRangeMobile),
+                "low_end_mobile" => Ok(DeviceType::LowEndMobile),
+                "desktop" => Ok(DeviceType::Desktop),
+                other => Err(ZkError::InvalidInput(format!("Unknown device type: {}", other))),
+            }
+        }
+    }
+
+    /// Proving configuration for a device, combining the separate recommendations
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct DeviceProfile {
+        pub device: DeviceType,
+        /// Recommended circuit size
+        pub k: u32,
+        /// Proofs to generate per batch
+        pub batch_size: usize,
+        /// Estimated time to generate one proof at `k`
+        pub estimated_proof_time_ms: u64,
+        /// Estimated memory to generate one proof at `k`
+        pub estimated_memory_mb: u64,
+    }
+
+    impl DeviceProfile {
+        /// Whether a workload of `num_proofs` proofs should be split into batches
+        pub fn use_batching(&self, num_proofs: usize) -> bool {
+            should_use_batch_processing(num_proofs, self.device)
+        }
+
+        /// Profile as a JSON object with camelCase keys, advising on batching
+        /// for a workload of `num_proofs` proofs
+        pub fn to_json(&self, num_proofs: usize) -> String {
+            serde_json::json!({
+                "k": self.k,
+                "batchSize": self.batch_size,
+                "estimatedProofTimeMs": self.estimated_proof_time_ms,
+                "estimatedMemoryMb": self.estimated_memory_mb,
+                "useBatching": self.use_batching(num_proofs),
+            })
+            .to_string()
+        }
+    }
+
+    /// Recommended circuit size, batch size and per-proof cost for a device
+    pub fn configure_for_device(device: DeviceType) -> DeviceProfile {
+        let k = get_recommended_k(device);
+
+        DeviceProfile {
+            device,
+            k,
+            batch_size: get_optimal_batch_size(device),
+            estimated_proof_time_ms: estimate_proof_time_ms(k, device),
+            estimated_memory_mb: estimate_memory_usage_mb(k),
+        }
+    }
+
     /// Get recommended circuit size for device type
     pub fn get_recommended_k(device_type: DeviceType) -> u32 {
         match device_type {
@@ -189,6 +629,28 @@ pub mod performance {
         k <= CircuitSizeRecommendations::HIGH_END_MOBILE
     }
 
+    /// Size in bytes of each point and scalar in a pasta IPA proof
+    const PROOF_ELEMENT_BYTES: usize = 32;
+
+    /// Points and scalars a trust-score-sized circuit writes regardless of
+    /// `k` and its instance columns: advice commitments and evaluations,
+    /// fixed and permutation evaluations, the vanishing argument and the
+    /// multiopen argument. Calibrated against `measure_proof_size_bytes`.
+    const CIRCUIT_PROOF_ELEMENTS: usize = 48;
+
+    /// Estimate the serialized size of a proof for circuit size `k` with
+    /// `num_instance` instance columns
+    ///
+    /// The IPA opening grows with `k` (one `L`/`R` pair per halving round)
+    /// and each instance column adds one evaluation; everything else assumes
+    /// a circuit shaped like the trust score circuit, so wider circuits
+    /// (more advice columns, lookups) produce larger proofs.
+    pub fn estimate_proof_size_bytes(k: u32, num_instance: usize) -> usize {
+        // s commitment, L and R per round, then the final c and f scalars
+        let opening = 1 + 2 * k as usize + 2;
+        (CIRCUIT_PROOF_ELEMENTS + num_instance + opening) * PROOF_ELEMENT_BYTES
+    }
+
     /// Memory usage estimation in MB for a given circuit size
     pub fn estimate_memory_usage_mb(k: u32) -> u64 {
         // Rough estimation: each constraint uses about 32 bytes
@@ -239,7 +701,58 @@ mod tests {
     use super::performance::*;
     use super::mobile_trust_score::*;
     use pasta_curves::Fp;
-    use halo2_proofs::plonk::Circuit;
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        plonk::Circuit,
+    };
+
+    /// Range checks a single witness at a fixed width
+    #[derive(Clone)]
+    struct RangeCheckTestCircuit<const BITS: usize> {
+        value: Value<Fp>,
+    }
+
+    impl<const BITS: usize> Circuit<Fp> for RangeCheckTestCircuit<BITS> {
+        type Config = RangeCheckConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { value: Value::unknown() }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let value = meta.advice_column();
+            RangeCheckChip::configure(meta, value, BITS)
+        }
+
+        fn synthesize(&self, config: Self::Config, layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let chip = RangeCheckChip::construct(config);
+            chip.assign_range_check(layouter, self.value, BITS)?;
+            Ok(())
+        }
+    }
+
+    fn check_range<const BITS: usize>(value: u64) -> bool {
+        let circuit = RangeCheckTestCircuit::<BITS> { value: Value::known(Fp::from(value)) };
+        MockProver::run(6, &circuit, vec![]).unwrap().verify().is_ok()
+    }
+
+    #[test]
+    fn test_range_check_trust_score_width() {
+        assert!(check_range::<7>(0));
+        assert!(check_range::<7>(100));
+        assert!(check_range::<7>((1 << 7) - 1));
+        assert!(!check_range::<7>(1 << 7));
+    }
+
+    #[test]
+    fn test_range_check_income_width() {
+        assert!(check_range::<40>(75_000));
+        assert!(check_range::<40>((1 << 40) - 1));
+        assert!(!check_range::<40>(1 << 40));
+        assert!(!check_range::<40>(u64::MAX));
+    }
 
     #[test]
     fn test_mobile_trust_score_circuit() {
@@ -249,11 +762,43 @@ mod tests {
 
         let circuit = MobileTrustScoreCircuit::<Fp>::new(Some(trust_score), threshold);
         let circuit_without_witnesses = circuit.without_witnesses();
-        
-        // Just verify the circuits can be created
-        // This tests the basic structure and configuration
-        let _ = circuit;
-        let _ = circuit_without_witnesses;
+
+        // Keys generate without the witness
+        crate::circuits::lending::assert_keygen(min_k_for(&circuit), &circuit_without_witnesses);
+    }
+
+    #[test]
+    fn test_fixed_threshold_trust_score() {
+        use super::fixed_threshold::FixedThresholdTrustScoreCircuit;
+        use crate::circuits::trust_score::utils::public_inputs;
+
+        for (trust_score, result) in [(85u64, true), (70, true), (69, false)] {
+            let circuit = FixedThresholdTrustScoreCircuit::<Fp>::new(Some(trust_score), 70);
+            let instance = public_inputs::<Fp>(result, 70)[..2].to_vec();
+            assert_eq!(circuit.expected_public_inputs(), instance);
+            MockProver::run(4, &circuit, vec![instance]).unwrap().assert_satisfied();
+
+            // Neither a forged result nor another threshold verifies
+            let forged = public_inputs::<Fp>(!result, 70)[..2].to_vec();
+            assert!(MockProver::run(4, &circuit, vec![forged]).unwrap().verify().is_err());
+            let other = public_inputs::<Fp>(result, 60)[..2].to_vec();
+            assert!(MockProver::run(4, &circuit, vec![other]).unwrap().verify().is_err());
+        }
+
+        let circuit = FixedThresholdTrustScoreCircuit::<Fp>::new(None, 70);
+        crate::circuits::lending::assert_keygen(4, &circuit.without_witnesses());
+    }
+
+    #[test]
+    fn test_fixed_threshold_saves_advice_column() {
+        use super::fixed_threshold::FixedThresholdTrustScoreCircuit;
+        use crate::circuits::trust_score::TrustScoreCircuit;
+
+        let advice = circuit_stats(&TrustScoreCircuit::<Fp>::new(Some(85), 70), 10);
+        let fixed = circuit_stats(&FixedThresholdTrustScoreCircuit::<Fp>::new(Some(85), 70), 10);
+        assert_eq!(fixed.advice_columns, advice.advice_columns - 1);
+        assert_eq!(fixed.fixed_columns, advice.fixed_columns + 1);
+        assert_eq!(fixed.min_k, advice.min_k);
     }
 
     #[test]
@@ -316,10 +861,84 @@ mod tests {
         assert!(k_high < k_desktop);
     }
 
+    #[test]
+    fn test_configure_for_device() {
+        let low_end = configure_for_device(DeviceType::LowEndMobile);
+        let desktop = configure_for_device(DeviceType::Desktop);
+
+        assert!(low_end.k < desktop.k);
+        assert!(low_end.batch_size < desktop.batch_size);
+        assert!(low_end.estimated_memory_mb < desktop.estimated_memory_mb);
+        assert_eq!(low_end.k, get_recommended_k(DeviceType::LowEndMobile));
+
+        assert!(!low_end.use_batching(1));
+        assert!(low_end.use_batching(5));
+        assert!(!desktop.use_batching(5));
+
+        assert_eq!("low-end-mobile".parse(), Ok(DeviceType::LowEndMobile));
+        assert_eq!(" Desktop ".parse(), Ok(DeviceType::Desktop));
+        assert!(matches!("tablet".parse::<DeviceType>(), Err(crate::error::ZkError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_device_type_ids() {
+        for (id, device) in DeviceType::ALL.into_iter().enumerate() {
+            assert_eq!(device.as_u8() as usize, id);
+            assert_eq!(DeviceType::try_from(device.as_u8()), Ok(device));
+        }
+        assert_eq!(DeviceType::Desktop.as_u8(), 3);
+
+        assert!(matches!(DeviceType::try_from(9), Err(crate::error::ZkError::InvalidInput(_))));
+    }
+
     #[test]
     fn test_should_use_batch_processing() {
         assert!(!batch_processing::should_use_batch_processing(1, DeviceType::LowEndMobile));
         assert!(batch_processing::should_use_batch_processing(5, DeviceType::LowEndMobile));
         assert!(batch_processing::should_use_batch_processing(15, DeviceType::Desktop));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_circuit_stats() {
+        use crate::circuits::lending::LendingCircuit;
+        use crate::circuits::loan_history::LoanHistoryCircuit;
+        use crate::circuits::trust_score::TrustScoreCircuit;
+
+        // Trust score, threshold and result, plus the comparison and score bound decompositions
+        let stats = circuit_stats(&TrustScoreCircuit::<Fp>::new(Some(85), 70), 10);
+        assert_eq!(stats.advice_columns, 6);
+        assert_eq!(stats.fixed_columns, 0);
+        assert_eq!(stats.instance_columns, 1);
+        assert_eq!(stats.gates, 2);
+        assert_eq!(stats.min_k, Some(TrustScoreCircuit::<Fp>::recommended_k()));
+        assert_eq!(
+            stats.to_json(),
+            r#"{"adviceColumns":6,"fixedColumns":0,"gates":2,"instanceColumns":1,"minK":4}"#
+        );
+
+        // The recommended size fits the on-time check's second pass
+        let stats = circuit_stats(&LoanHistoryCircuit::<Fp>::new_with_on_time(Some(10), Some(9), Some(8), 8000, 7000, 3), 10);
+        assert_eq!(stats.min_k, Some(LoanHistoryCircuit::<Fp>::recommended_k()));
+        let stats = circuit_stats(&LoanHistoryCircuit::<Fp>::new(Some(10), Some(9), 8000, 3), 10);
+        assert_eq!(stats.min_k, Some(7));
+
+        // Too small to lay out at all
+        assert_eq!(circuit_stats(&LoanHistoryCircuit::<Fp>::new(Some(10), Some(9), 8000, 3), 5).min_k, None);
+    }
+
+    #[test]
+    fn test_min_k_for() {
+        use crate::circuits::lending::LendingCircuit;
+        use crate::circuits::loan_history::LoanHistoryCircuit;
+        use crate::circuits::trust_score::TrustScoreCircuit;
+        use crate::keys::TRUST_SCORE_K;
+
+        let trust_score = TrustScoreCircuit::<Fp>::new(Some(85), 70);
+        assert!(min_k_for(&trust_score) <= TRUST_SCORE_K);
+        assert_eq!(min_k_for(&trust_score), circuit_stats(&trust_score, 10).min_k.unwrap());
+
+        let loan_history = LoanHistoryCircuit::<Fp>::new(Some(10), Some(9), 8000, 3);
+        assert!(min_k_for(&loan_history) <= LoanHistoryCircuit::<Fp>::recommended_k());
+        assert!(usable_rows::<LoanHistoryCircuit<Fp>>(min_k_for(&loan_history)) >= rows_used(&loan_history, 10));
+    }
+}
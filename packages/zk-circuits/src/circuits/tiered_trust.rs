@@ -0,0 +1,395 @@
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Fixed, Selector},
+    poly::Rotation,
+};
+use ff::PrimeField;
+use std::marker::PhantomData;
+
+use super::AssignedCell;
+use super::lending::known;
+use super::loan_history::field_to_u64_truncating;
+use super::trust_score::{TrustScoreChip, TrustScoreConfig};
+
+/// Configuration for the tiered trust score circuit
+#[derive(Clone, Debug)]
+pub struct TieredTrustConfig {
+    /// Advice column for each tier's comparison result
+    pub result: Column<Advice>,
+    /// Running count of cleared tiers, ending in the total cleared
+    pub cleared: Column<Advice>,
+    /// Advice column for `threshold_{i+1} >= threshold_i`
+    pub ascending: Column<Advice>,
+    /// Inverse of the cleared count (0 when nothing is cleared)
+    pub inverse: Column<Advice>,
+    /// Index of the highest cleared tier, or the number of tiers if none
+    pub tier: Column<Advice>,
+    /// Number of tiers, fixed by the circuit's shape
+    pub num_tiers: Column<Fixed>,
+    /// Selector for the per-tier counting gate
+    pub count_selector: Selector,
+    /// Selector for the first counting row (the count starts at zero)
+    pub start_selector: Selector,
+    /// Selector requiring consecutive thresholds to be ascending
+    pub ascending_selector: Selector,
+    /// Selector for the final row deriving the tier from the count
+    pub tier_selector: Selector,
+    /// Comparison shared with the trust score circuit, used per tier and per
+    /// pair of consecutive thresholds
+    pub comparison: TrustScoreConfig,
+}
+
+/// Cells assigned by the tiered check
+pub struct AssignedTieredTrust<F: PrimeField> {
+    /// Threshold cells, in tier order
+    pub thresholds: Vec<AssignedCell<F>>,
+    /// Index of the highest cleared tier, or the number of tiers if none
+    pub tier: AssignedCell<F>,
+}
+
+/// Chip finding the highest of several ascending thresholds a score clears
+pub struct TieredTrustChip<F: PrimeField> {
+    config: TieredTrustConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: PrimeField> TieredTrustChip<F> {
+    pub fn construct(config: TieredTrustConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 5],
+        num_tiers: Column<Fixed>,
+        comparison: TrustScoreConfig,
+    ) -> TieredTrustConfig {
+        let [result, cleared, ascending, inverse, tier] = advice;
+        let count_selector = meta.selector();
+        let start_selector = meta.selector();
+        let ascending_selector = meta.selector();
+        let tier_selector = meta.selector();
+
+        meta.enable_equality(result);
+        meta.enable_equality(ascending);
+        meta.enable_equality(tier);
+
+        // Each row adds one comparison result (already boolean) to the count
+        meta.create_gate("tiered_trust_count", |meta| {
+            let s = meta.query_selector(count_selector);
+            let result = meta.query_advice(result, Rotation::cur());
+            let cleared_next = meta.query_advice(cleared, Rotation::next());
+            let cleared = meta.query_advice(cleared, Rotation::cur());
+
+            vec![s * (cleared_next - cleared - result)]
+        });
+
+        meta.create_gate("tiered_trust_count_start", |meta| {
+            let s = meta.query_selector(start_selector);
+            let cleared = meta.query_advice(cleared, Rotation::cur());
+
+            vec![s * cleared]
+        });
+
+        meta.create_gate("tiered_trust_ascending", |meta| {
+            let s = meta.query_selector(ascending_selector);
+            let ascending = meta.query_advice(ascending, Rotation::cur());
+
+            vec![s * (Expression::Constant(F::ONE) - ascending)]
+        });
+
+        // With ascending thresholds the cleared tiers are a prefix, so the
+        // highest one is `cleared - 1`; an is-zero on the count selects the
+        // sentinel when no tier is cleared
+        meta.create_gate("tiered_trust_tier", |meta| {
+            let s = meta.query_selector(tier_selector);
+            let cleared = meta.query_advice(cleared, Rotation::cur());
+            let inverse = meta.query_advice(inverse, Rotation::cur());
+            let tier = meta.query_advice(tier, Rotation::cur());
+            let num_tiers = meta.query_fixed(num_tiers);
+
+            let one = Expression::Constant(F::ONE);
+            let none_cleared = one.clone() - cleared.clone() * inverse;
+
+            vec![
+                s.clone() * (cleared.clone() * none_cleared.clone()),
+                s * (tier - (none_cleared.clone() * num_tiers + (one.clone() - none_cleared) * (cleared - one))),
+            ]
+        });
+
+        TieredTrustConfig {
+            result,
+            cleared,
+            ascending,
+            inverse,
+            tier,
+            num_tiers,
+            count_selector,
+            start_selector,
+            ascending_selector,
+            tier_selector,
+            comparison,
+        }
+    }
+
+    /// Compare `trust_score` against each threshold and find the highest
+    /// tier cleared
+    ///
+    /// Each consecutive pair of thresholds is compared as well, so the
+    /// thresholds must be ascending for the circuit to be satisfied.
+    pub fn assign_tiers(
+        &self,
+        mut layouter: impl Layouter<F>,
+        trust_score: Value<F>,
+        thresholds: &[Value<F>],
+    ) -> Result<AssignedTieredTrust<F>, Error> {
+        let comparison = TrustScoreChip::construct(self.config.comparison.clone());
+        let tiers = thresholds
+            .iter()
+            .enumerate()
+            .map(|(i, &threshold)| {
+                comparison.assign_comparison(layouter.namespace(|| format!("tier {}", i)), trust_score, threshold)
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        let pairs = thresholds
+            .windows(2)
+            .enumerate()
+            .map(|(i, pair)| {
+                comparison.assign_comparison(layouter.namespace(|| format!("tiers {} and {}", i, i + 1)), pair[1], pair[0])
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        let num_tiers = tiers.len();
+        let tier = layouter.assign_region(
+            || "tiered trust",
+            |mut region| {
+                self.config.start_selector.enable(&mut region, 0)?;
+
+                let mut cleared = Value::known(F::ZERO);
+                for (i, compared) in tiers.iter().enumerate() {
+                    self.config.count_selector.enable(&mut region, i)?;
+                    region.constrain_equal(tiers[0].trust_score.cell(), compared.trust_score.cell())?;
+
+                    region.assign_advice(|| "cleared", self.config.cleared, i, || cleared)?;
+                    let result = compared.result.copy_advice(|| "result", &mut region, self.config.result, i)?;
+                    cleared = cleared + result.value().copied();
+                }
+
+                // Tie each pair comparison to the thresholds of its tiers
+                for (i, pair) in pairs.iter().enumerate() {
+                    self.config.ascending_selector.enable(&mut region, i)?;
+                    region.constrain_equal(pair.trust_score.cell(), tiers[i + 1].threshold.cell())?;
+                    region.constrain_equal(pair.threshold.cell(), tiers[i].threshold.cell())?;
+                    pair.result.copy_advice(|| "ascending", &mut region, self.config.ascending, i)?;
+                }
+
+                self.config.tier_selector.enable(&mut region, num_tiers)?;
+                region.assign_advice(|| "cleared", self.config.cleared, num_tiers, || cleared)?;
+                region.assign_advice(
+                    || "inverse",
+                    self.config.inverse,
+                    num_tiers,
+                    || cleared.map(|cleared| cleared.invert().unwrap_or(F::ZERO)),
+                )?;
+                region.assign_fixed(|| "num tiers", self.config.num_tiers, num_tiers, || Value::known(F::from(num_tiers as u64)))?;
+
+                let tier = cleared.map(|cleared| {
+                    if cleared == F::ZERO {
+                        F::from(num_tiers as u64)
+                    } else {
+                        cleared - F::ONE
+                    }
+                });
+                region.assign_advice(|| "tier", self.config.tier, num_tiers, || tier)
+            },
+        )?;
+
+        Ok(AssignedTieredTrust {
+            thresholds: tiers.into_iter().map(|compared| compared.threshold).collect(),
+            tier,
+        })
+    }
+}
+
+/// Circuit proving the highest of several trust score tiers a borrower clears
+///
+/// Lets a lender with tiered rates (e.g. thresholds 50/70/90) learn which
+/// tier applies from one proof, without learning the score. The thresholds
+/// are public and must be ascending; clearing no tier yields the sentinel
+/// index `N`. Instance layout: `[tier, threshold_0, .., threshold_{N-1}]`.
+#[derive(Clone, Debug)]
+pub struct TieredTrustCircuit<F: PrimeField> {
+    /// Private input: the actual trust score
+    pub trust_score: Value<F>,
+    /// Public inputs: the tier thresholds, ascending
+    pub thresholds: Vec<Value<F>>,
+}
+
+impl<F: PrimeField> TieredTrustCircuit<F> {
+    pub fn new(trust_score: Option<u64>, thresholds: &[u64]) -> Self {
+        assert!(!thresholds.is_empty(), "A tiered proof needs at least one tier");
+
+        Self {
+            trust_score: trust_score.map(|score| Value::known(F::from(score))).unwrap_or_else(Value::unknown),
+            thresholds: thresholds.iter().map(|&threshold| Value::known(F::from(threshold))).collect(),
+        }
+    }
+
+    /// Public inputs `[tier, threshold_0, ..]`, with the tier computed from
+    /// the known trust score (the sentinel without a witness)
+    pub fn expected_public_inputs(&self) -> Vec<F> {
+        let thresholds: Vec<u64> = self
+            .thresholds
+            .iter()
+            .map(|&threshold| field_to_u64_truncating(&known(threshold).unwrap_or(F::ZERO)))
+            .collect();
+        let tier = match known(self.trust_score) {
+            Some(score) => utils::highest_tier(field_to_u64_truncating(&score), &thresholds),
+            None => thresholds.len() as u64,
+        };
+
+        utils::public_inputs(tier, &thresholds)
+    }
+}
+
+impl<F: PrimeField> Circuit<F> for TieredTrustCircuit<F> {
+    type Config = TieredTrustConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            trust_score: Value::unknown(),
+            thresholds: self.thresholds.clone(),
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let trust_score = meta.advice_column();
+        let threshold = meta.advice_column();
+        let result = meta.advice_column();
+        let decomposition = meta.advice_column();
+        let bounds = [(); 2].map(|_| meta.advice_column());
+        let instance = meta.instance_column();
+        let comparison = TrustScoreChip::configure(meta, trust_score, threshold, result, decomposition, bounds, instance);
+
+        let advice = [(); 5].map(|_| meta.advice_column());
+        let num_tiers = meta.fixed_column();
+        TieredTrustChip::configure(meta, advice, num_tiers, comparison)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = TieredTrustChip::construct(config.clone());
+
+        let assigned = chip.assign_tiers(layouter.namespace(|| "tiered trust"), self.trust_score, &self.thresholds)?;
+
+        let instance = config.comparison.instance;
+        layouter.constrain_instance(assigned.tier.cell(), instance, 0)?;
+        for (i, threshold) in assigned.thresholds.iter().enumerate() {
+            layouter.constrain_instance(threshold.cell(), instance, 1 + i)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Utility functions for tiered trust score proofs
+pub mod utils {
+    use ff::PrimeField;
+
+    /// Index of the highest threshold `trust_score` meets, or
+    /// `thresholds.len()` if it meets none
+    pub fn highest_tier(trust_score: u64, thresholds: &[u64]) -> u64 {
+        thresholds
+            .iter()
+            .rposition(|&threshold| trust_score >= threshold)
+            .unwrap_or(thresholds.len()) as u64
+    }
+
+    /// Build the public inputs in the circuit's instance order:
+    /// `[tier, threshold_0, .., threshold_{N-1}]`
+    pub fn public_inputs<F: PrimeField>(tier: u64, thresholds: &[u64]) -> Vec<F> {
+        std::iter::once(F::from(tier))
+            .chain(thresholds.iter().map(|&threshold| F::from(threshold)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::utils::*;
+    use halo2_proofs::dev::MockProver;
+    use pasta_curves::Fp;
+
+    const K: u32 = 6;
+    const TIERS: [u64; 3] = [50, 70, 90];
+
+    fn run(trust_score: u64, thresholds: &[u64], tier: u64) -> MockProver<Fp> {
+        let circuit = TieredTrustCircuit::<Fp>::new(Some(trust_score), thresholds);
+        MockProver::run(K, &circuit, vec![public_inputs(tier, thresholds)]).unwrap()
+    }
+
+    #[test]
+    fn test_clears_tier_one_of_three() {
+        assert_eq!(highest_tier(75, &TIERS), 1);
+
+        run(75, &TIERS, 1).assert_satisfied();
+        for wrong in [0, 2, 3] {
+            assert!(run(75, &TIERS, wrong).verify().is_err());
+        }
+    }
+
+    #[test]
+    fn test_clears_no_tier() {
+        assert_eq!(highest_tier(40, &TIERS), 3);
+
+        run(40, &TIERS, 3).assert_satisfied();
+        assert!(run(40, &TIERS, 0).verify().is_err());
+    }
+
+    #[test]
+    fn test_tier_boundaries() {
+        run(50, &TIERS, 0).assert_satisfied();
+        run(90, &TIERS, 2).assert_satisfied();
+        run(100, &TIERS, 2).assert_satisfied();
+        run(60, &[60], 0).assert_satisfied();
+        run(59, &[60], 1).assert_satisfied();
+    }
+
+    #[test]
+    fn test_thresholds_must_ascend() {
+        // Swapping tiers would let a score of 75 claim the top tier
+        let thresholds = [50, 90, 70];
+        assert!(run(75, &thresholds, 2).verify().is_err());
+        assert!(run(75, &thresholds, highest_tier(75, &thresholds)).verify().is_err());
+    }
+
+    #[test]
+    fn test_thresholds_bound_to_instance() {
+        let circuit = TieredTrustCircuit::<Fp>::new(Some(75), &TIERS);
+        assert_eq!(circuit.expected_public_inputs(), public_inputs(1, &TIERS));
+
+        // A proof against 50/70/90 does not pass for 50/60/70
+        let prover = MockProver::run(K, &circuit, vec![public_inputs(2, &[50, 60, 70])]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_circuit_without_witnesses() {
+        use crate::circuits::lending::assert_keygen;
+
+        let circuit = TieredTrustCircuit::<Fp>::new(None, &TIERS);
+        let circuit_without_witnesses = circuit.without_witnesses();
+
+        assert_eq!(circuit_without_witnesses.thresholds.len(), 3);
+        assert_eq!(circuit_without_witnesses.expected_public_inputs(), public_inputs(3, &TIERS));
+        assert_keygen(K, &circuit_without_witnesses);
+    }
+}
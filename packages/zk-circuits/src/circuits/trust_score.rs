@@ -1,24 +1,73 @@
 use halo2_proofs::{
     circuit::{Layouter, SimpleFloorPlanner, Value},
-    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Instance, Selector},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Fixed, Instance, Selector},
     poly::Rotation,
 };
 use ff::PrimeField;
 use std::marker::PhantomData;
 
+use super::AssignedCell;
+use super::lending::{known, InstanceKind, InstanceSpec, LendingCircuit};
+use super::loan_history::field_to_u64_truncating;
+use crate::error::ZkError;
+
+/// Bit width of trust scores and thresholds (scores range over 0..=100)
+pub const TRUST_SCORE_BITS: usize = 7;
+
+/// Highest trust score the platform assigns
+pub const MAX_TRUST_SCORE: u64 = 100;
+
+/// Column holding the threshold a trust score is compared against
+///
+/// An advice threshold is witnessed per proof. A fixed threshold is set at
+/// keygen, so it is part of the verifying key and saves an advice column;
+/// circuits composing several comparisons against the same policy can use it
+/// to shrink their witness.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ThresholdColumn {
+    Advice(Column<Advice>),
+    Fixed(Column<Fixed>),
+}
+
+impl From<Column<Advice>> for ThresholdColumn {
+    fn from(column: Column<Advice>) -> Self {
+        ThresholdColumn::Advice(column)
+    }
+}
+
+impl From<Column<Fixed>> for ThresholdColumn {
+    fn from(column: Column<Fixed>) -> Self {
+        ThresholdColumn::Fixed(column)
+    }
+}
+
 /// Configuration for the trust score circuit
 #[derive(Clone, Debug)]
 pub struct TrustScoreConfig {
     /// Advice column for the trust score (private input)
     pub trust_score: Column<Advice>,
-    /// Advice column for the threshold (public input)
-    pub threshold: Column<Advice>,
+    /// Advice or fixed column for the threshold (public input)
+    pub threshold: ThresholdColumn,
     /// Advice column for the comparison result
     pub result: Column<Advice>,
+    /// Running sum decomposing `trust_score - threshold + 2^N`
+    pub decomposition: Column<Advice>,
+    /// Running sums decomposing `trust_score` and `MAX_TRUST_SCORE - trust_score`,
+    /// bounding the score to `0..=MAX_TRUST_SCORE`
+    pub bounds: [Column<Advice>; 2],
     /// Instance column for public inputs/outputs
     pub instance: Column<Instance>,
     /// Selector for the comparison gate
     pub selector: Selector,
+    /// Selector for each step of the bit decomposition
+    pub bit_selector: Selector,
+}
+
+/// Cells assigned by the trust score comparison
+pub struct AssignedTrustScore<F: PrimeField> {
+    pub trust_score: AssignedCell<F>,
+    pub threshold: AssignedCell<F>,
+    pub result: AssignedCell<F>,
 }
 
 /// Chip for trust score comparison operations
@@ -35,59 +84,113 @@ impl<F: PrimeField> TrustScoreChip<F> {
         }
     }
 
+    /// Configure the comparison
+    ///
+    /// `threshold` is an advice column, or a fixed column for a threshold
+    /// fixed at keygen (see `ThresholdColumn`).
     pub fn configure(
         meta: &mut ConstraintSystem<F>,
         trust_score: Column<Advice>,
-        threshold: Column<Advice>,
+        threshold: impl Into<ThresholdColumn>,
         result: Column<Advice>,
+        decomposition: Column<Advice>,
+        bounds: [Column<Advice>; 2],
         instance: Column<Instance>,
     ) -> TrustScoreConfig {
+        let threshold = threshold.into();
         let selector = meta.selector();
+        let bit_selector = meta.selector();
 
         // Enable equality constraints for public inputs/outputs
         meta.enable_equality(trust_score);
-        meta.enable_equality(threshold);
+        match threshold {
+            ThresholdColumn::Advice(column) => meta.enable_equality(column),
+            ThresholdColumn::Fixed(column) => meta.enable_equality(column),
+        }
         meta.enable_equality(result);
         meta.enable_equality(instance);
 
         // Create the comparison gate
         // This gate checks if trust_score >= threshold
+        //
+        // diff = trust_score - threshold + 2^N is decomposed into N + 1 bits,
+        // most significant first, in the decomposition column. The running sum
+        // starts at 0 and ends at diff, so diff lies in [0, 2^(N+1)), and its
+        // top bit is set exactly when trust_score >= threshold.
         meta.create_gate("trust_score_comparison", |meta| {
             let s = meta.query_selector(selector);
-            let _trust_score = meta.query_advice(trust_score, Rotation::cur());
-            let _threshold = meta.query_advice(threshold, Rotation::cur());
+            let trust_score = meta.query_advice(trust_score, Rotation::cur());
+            let threshold = match threshold {
+                ThresholdColumn::Advice(column) => meta.query_advice(column, Rotation::cur()),
+                ThresholdColumn::Fixed(column) => meta.query_fixed(column),
+            };
             let result = meta.query_advice(result, Rotation::cur());
-
-            // We need to prove that:
-            // - result is boolean (0 or 1)
-            // - If result = 1, then trust_score >= threshold
-            // - If result = 0, then trust_score < threshold
-            // 
-            // For simplicity in this mock implementation, we'll just ensure result is boolean
-            // A full implementation would need range checks and more complex comparison logic
+            let z_first = meta.query_advice(decomposition, Rotation::cur());
+            let msb = meta.query_advice(decomposition, Rotation::next());
+            let z_last = meta.query_advice(decomposition, Rotation((TRUST_SCORE_BITS + 1) as i32));
+
+            let offset = Expression::Constant(F::from(1u64 << TRUST_SCORE_BITS));
+
+            // Both bounds are decomposed into N + 1 bits over the same rows, so
+            // trust_score and MAX - trust_score both lie in [0, 2^(N+1)), which
+            // together mean 0 <= trust_score <= MAX
+            let [score_bits, headroom_bits] = bounds;
+            let score_first = meta.query_advice(score_bits, Rotation::cur());
+            let score_last = meta.query_advice(score_bits, Rotation((TRUST_SCORE_BITS + 1) as i32));
+            let headroom_first = meta.query_advice(headroom_bits, Rotation::cur());
+            let headroom_last = meta.query_advice(headroom_bits, Rotation((TRUST_SCORE_BITS + 1) as i32));
+            let max = Expression::Constant(F::from(MAX_TRUST_SCORE));
 
             vec![
                 // Ensure result is boolean (0 or 1)
-                s * (result.clone() * (result - Expression::Constant(F::ONE))),
+                s.clone() * (result.clone() * (result.clone() - Expression::Constant(F::ONE))),
+                s.clone() * z_first,
+                s.clone() * (msb - result),
+                s.clone() * (z_last - (trust_score.clone() - threshold + offset)),
+                s.clone() * score_first,
+                s.clone() * (score_last - trust_score.clone()),
+                s.clone() * headroom_first,
+                s * (headroom_last - (max - trust_score)),
             ]
         });
 
+        meta.create_gate("trust_score_comparison_bit", |meta| {
+            let s = meta.query_selector(bit_selector);
+
+            [decomposition, bounds[0], bounds[1]]
+                .into_iter()
+                .map(|column| {
+                    let z_next = meta.query_advice(column, Rotation::next());
+                    let z_cur = meta.query_advice(column, Rotation::cur());
+                    let bit = z_next - z_cur * Expression::Constant(F::from(2));
+
+                    s.clone() * (bit.clone() * (bit - Expression::Constant(F::ONE)))
+                })
+                .collect::<Vec<_>>()
+        });
+
         TrustScoreConfig {
             trust_score,
             threshold,
             result,
+            decomposition,
+            bounds,
             instance,
             selector,
+            bit_selector,
         }
     }
 
     /// Assign the trust score comparison
+    ///
+    /// Returns the input cells alongside the result so callers can expose the
+    /// threshold publicly or bind the score to other constraints.
     pub fn assign_comparison(
         &self,
         mut layouter: impl Layouter<F>,
         trust_score: Value<F>,
         threshold: Value<F>,
-    ) -> Result<AssignedCell<F>, Error> {
+    ) -> Result<AssignedTrustScore<F>, Error> {
         layouter.assign_region(
             || "trust score comparison",
             |mut region| {
@@ -95,37 +198,56 @@ impl<F: PrimeField> TrustScoreChip<F> {
                 self.config.selector.enable(&mut region, 0)?;
 
                 // Assign trust score (private input)
-                let _trust_score_cell = region.assign_advice(
+                let trust_score_cell = region.assign_advice(
                     || "trust score",
                     self.config.trust_score,
                     0,
                     || trust_score,
                 )?;
 
-                // Assign threshold (public input)
-                let _threshold_cell = region.assign_advice(
-                    || "threshold",
-                    self.config.threshold,
-                    0,
-                    || threshold,
-                )?;
+                // Assign threshold (public input), fixed at keygen in fixed mode
+                let threshold_cell = match self.config.threshold {
+                    ThresholdColumn::Advice(column) => region.assign_advice(|| "threshold", column, 0, || threshold)?,
+                    ThresholdColumn::Fixed(column) => region.assign_fixed(|| "threshold", column, 0, || threshold)?,
+                };
 
-                // Calculate and assign result
-                // For the mock prover, we need to calculate the expected result
-                let result_value = trust_score.zip(threshold).map(|(score, thresh)| {
-                    // Convert field elements to u64 for comparison
-                    // This is a simplification for the mock prover
-                    let score_bytes = score.to_repr();
-                    let thresh_bytes = thresh.to_repr();
-                    
-                    // Compare the byte representations (little-endian)
-                    if score_bytes.as_ref() >= thresh_bytes.as_ref() {
-                        F::ONE
-                    } else {
-                        F::ZERO
-                    }
+                // Witness the bits of trust_score - threshold + 2^N
+                let diff = trust_score.zip(threshold).map(|(score, thresh)| {
+                    (field_to_u64_truncating(&score) as u128 + (1u128 << TRUST_SCORE_BITS))
+                        .wrapping_sub(field_to_u64_truncating(&thresh) as u128)
                 });
 
+                // A score above the maximum leaves no valid headroom witness
+                let score = trust_score.map(|score| field_to_u64_truncating(&score) as u128);
+                let headroom = score.map(|score| (MAX_TRUST_SCORE as u128).wrapping_sub(score));
+
+                let num_bits = TRUST_SCORE_BITS + 1;
+                let mut z = Value::known(F::ZERO);
+                let mut bounds = [Value::known(F::ZERO); 2];
+                region.assign_advice(|| "z_0", self.config.decomposition, 0, || z)?;
+                for (column, bound) in self.config.bounds.into_iter().zip(bounds) {
+                    region.assign_advice(|| "bound_0", column, 0, || bound)?;
+                }
+
+                let bit_of = |value: Value<u128>, i: usize| value.map(|v| F::from(((v >> (num_bits - 1 - i)) & 1) as u64));
+
+                let mut result_value = Value::known(F::ZERO);
+                for i in 0..num_bits {
+                    self.config.bit_selector.enable(&mut region, i)?;
+                    let bit = bit_of(diff, i);
+                    if i == 0 {
+                        // The top bit is the comparison result
+                        result_value = bit;
+                    }
+                    z = z * Value::known(F::from(2)) + bit;
+                    region.assign_advice(|| format!("z_{}", i + 1), self.config.decomposition, i + 1, || z)?;
+
+                    for ((column, bound), value) in self.config.bounds.into_iter().zip(bounds.iter_mut()).zip([score, headroom]) {
+                        *bound = *bound * Value::known(F::from(2)) + bit_of(value, i);
+                        region.assign_advice(|| format!("bound_{}", i + 1), column, i + 1, || *bound)?;
+                    }
+                }
+
                 let result_cell = region.assign_advice(
                     || "comparison result",
                     self.config.result,
@@ -133,22 +255,42 @@ impl<F: PrimeField> TrustScoreChip<F> {
                     || result_value,
                 )?;
 
-                Ok(result_cell)
+                Ok(AssignedTrustScore {
+                    trust_score: trust_score_cell,
+                    threshold: threshold_cell,
+                    result: result_cell,
+                })
             },
         )
     }
 }
 
 /// The main trust score circuit
+///
+/// Proves whether a private trust score is at least a public threshold.
+/// Instance layout: `[result, threshold, epoch]`, where `result` is 1 if
+/// `trust_score >= threshold` and 0 otherwise. The threshold is bound to the
+/// instance, so verifiers must pass both values; passing the threshold alone
+/// fails verification.
 #[derive(Clone, Debug)]
 pub struct TrustScoreCircuit<F: PrimeField> {
     /// Private input: the actual trust score
     pub trust_score: Value<F>,
     /// Public input: the threshold to compare against (typically 70)
     pub threshold: Value<F>,
+    /// Public input: the epoch the proof is made for (0 unless set)
+    ///
+    /// Only bound to the instance, not checked against any clock: verifiers
+    /// decide which epochs are current. Instance columns are zero-padded, so
+    /// `[result, threshold]` alone verifies a proof made for epoch 0.
+    pub epoch: Value<F>,
 }
 
 impl<F: PrimeField> TrustScoreCircuit<F> {
+    /// Build the circuit without validating the inputs
+    ///
+    /// A score or threshold above `MAX_TRUST_SCORE` makes the circuit
+    /// unsatisfiable; use `new_checked` to reject them up front.
     pub fn new(trust_score: Option<u64>, threshold: u64) -> Self {
         Self {
             trust_score: if let Some(score) = trust_score {
@@ -157,8 +299,37 @@ impl<F: PrimeField> TrustScoreCircuit<F> {
                 Value::unknown()
             },
             threshold: Value::known(F::from(threshold)),
+            epoch: Value::known(F::ZERO),
         }
     }
+
+    /// `new` for a proof that expires with `epoch`
+    ///
+    /// Lenders that want proofs to go stale (e.g. with epochs counting days)
+    /// verify against the current epoch, so an old proof stops verifying.
+    pub fn new_at_epoch(trust_score: Option<u64>, threshold: u64, epoch: u64) -> Self {
+        Self {
+            epoch: Value::known(F::from(epoch)),
+            ..Self::new(trust_score, threshold)
+        }
+    }
+
+    /// `new`, rejecting a score or threshold above `MAX_TRUST_SCORE`
+    pub fn new_checked(trust_score: Option<u64>, threshold: u64) -> Result<Self, ZkError> {
+        utils::check_trust_score_range(trust_score.unwrap_or(0), threshold)?;
+
+        Ok(Self::new(trust_score, threshold))
+    }
+
+    /// Public inputs `[result, threshold, epoch]`, with the result computed
+    /// from the known trust score (0 without a witness)
+    pub fn expected_public_inputs(&self) -> Vec<F> {
+        let threshold = known(self.threshold).unwrap_or(F::ZERO);
+        let result = known(self.trust_score).is_some_and(|score| field_to_u64_truncating(&score) >= field_to_u64_truncating(&threshold));
+        let epoch = field_to_u64_truncating(&known(self.epoch).unwrap_or(F::ZERO));
+
+        utils::public_inputs_at_epoch(result, field_to_u64_truncating(&threshold), epoch)
+    }
 }
 
 impl<F: PrimeField> Circuit<F> for TrustScoreCircuit<F> {
@@ -169,6 +340,7 @@ impl<F: PrimeField> Circuit<F> for TrustScoreCircuit<F> {
         Self {
             trust_score: Value::unknown(),
             threshold: self.threshold,
+            epoch: self.epoch,
         }
     }
 
@@ -176,9 +348,11 @@ impl<F: PrimeField> Circuit<F> for TrustScoreCircuit<F> {
         let trust_score = meta.advice_column();
         let threshold = meta.advice_column();
         let result = meta.advice_column();
+        let decomposition = meta.advice_column();
+        let bounds = [(); 2].map(|_| meta.advice_column());
         let instance = meta.instance_column();
 
-        TrustScoreChip::configure(meta, trust_score, threshold, result, instance)
+        TrustScoreChip::configure(meta, trust_score, threshold, result, decomposition, bounds, instance)
     }
 
     fn synthesize(
@@ -189,32 +363,113 @@ impl<F: PrimeField> Circuit<F> for TrustScoreCircuit<F> {
         let chip = TrustScoreChip::construct(config.clone());
 
         // Assign the comparison
-        let result_cell = chip.assign_comparison(
+        let assigned = chip.assign_comparison(
             layouter.namespace(|| "trust score comparison"),
             self.trust_score,
             self.threshold,
         )?;
 
-        // Expose the threshold as public input (instance 0)
+        // Expose the result as public input (instance 0)
         layouter.constrain_instance(
-            result_cell.cell(),
+            assigned.result.cell(),
             config.instance,
             0,
         )?;
 
+        // Expose the threshold as public input (instance 1)
+        layouter.constrain_instance(
+            assigned.threshold.cell(),
+            config.instance,
+            1,
+        )?;
+
+        // Expose the epoch as public input (instance 2); nothing else uses it.
+        // It shares the threshold's column, which this circuit always witnesses
+        let epoch_column = match config.threshold {
+            ThresholdColumn::Advice(column) => column,
+            ThresholdColumn::Fixed(_) => config.trust_score,
+        };
+        let epoch = layouter.assign_region(
+            || "epoch",
+            |mut region| region.assign_advice(|| "epoch", epoch_column, 0, || self.epoch),
+        )?;
+        layouter.constrain_instance(epoch.cell(), config.instance, 2)?;
+
         Ok(())
     }
 }
 
-/// Helper type for assigned cells
-pub type AssignedCell<F> = halo2_proofs::circuit::AssignedCell<F, F>;
+impl<F: PrimeField> LendingCircuit<F> for TrustScoreCircuit<F> {
+    fn public_inputs(&self) -> Vec<F> {
+        self.expected_public_inputs()
+    }
+
+    fn recommended_k() -> u32 {
+        4
+    }
+
+    fn instance_layout() -> Vec<InstanceSpec> {
+        InstanceSpec::layout(&[
+            ("result", InstanceKind::Bool),
+            ("threshold", InstanceKind::Integer),
+            ("epoch", InstanceKind::Integer),
+        ])
+    }
+}
+
+/// Utility functions for trust score proofs
+pub mod utils {
+    use super::MAX_TRUST_SCORE;
+    use crate::error::ZkError;
+    use ff::PrimeField;
+
+    /// Reject a trust score or threshold above `MAX_TRUST_SCORE`
+    pub fn check_trust_score_range(trust_score: u64, threshold: u64) -> Result<(), ZkError> {
+        for (name, value) in [("trust score", trust_score), ("threshold", threshold)] {
+            if value > MAX_TRUST_SCORE {
+                return Err(ZkError::InvalidInput(format!(
+                    "{} {} exceeds the maximum of {}",
+                    name, value, MAX_TRUST_SCORE
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Build the public inputs for a proof made at epoch 0
+    pub fn public_inputs<F: PrimeField>(result: bool, threshold: u64) -> Vec<F> {
+        public_inputs_at_epoch(result, threshold, 0)
+    }
+
+    /// Build the public inputs in the circuit's instance order:
+    /// `[result, threshold, epoch]`
+    pub fn public_inputs_at_epoch<F: PrimeField>(result: bool, threshold: u64, epoch: u64) -> Vec<F> {
+        vec![if result { F::ONE } else { F::ZERO }, F::from(threshold), F::from(epoch)]
+    }
+
+    /// Public inputs of an honest proof for `trust_score` against `threshold`
+    pub fn public_inputs_for_trust_score<F: PrimeField>(trust_score: u64, threshold: u64) -> Vec<F> {
+        public_inputs(trust_score >= threshold, threshold)
+    }
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use super::utils::{public_inputs, public_inputs_for_trust_score};
     use halo2_proofs::dev::MockProver;
     use pasta_curves::Fp;
-    use ff::Field;
+
+    #[test]
+    fn test_public_inputs_for_trust_score() {
+        for (trust_score, threshold) in [(85u64, 70u64), (70, 70), (69, 70), (0, 0)] {
+            let expected = public_inputs_for_trust_score::<Fp>(trust_score, threshold);
+            let circuit = TrustScoreCircuit::<Fp>::new(Some(trust_score), threshold);
+            assert_eq!(circuit.expected_public_inputs(), expected);
+            MockProver::run(4, &circuit, vec![expected]).unwrap().assert_satisfied();
+        }
+    }
 
     #[test]
     fn test_trust_score_above_threshold() {
@@ -224,13 +479,29 @@ mod tests {
 
         let circuit = TrustScoreCircuit::<Fp>::new(Some(trust_score), threshold);
         
-        // The public input should be 1 (true) since 85 >= 70
-        let public_inputs = vec![Fp::one()];
+        // The result should be 1 (true) since 85 >= 70
+        let public_inputs = public_inputs(true, threshold);
 
         let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
         prover.assert_satisfied();
     }
 
+    /// The same circuit over the bn256 scalar field a future KZG backend would use
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_trust_score_over_bn256() {
+        use halo2curves::bn256::Fr;
+
+        for (trust_score, result) in [(85u64, true), (65, false), (70, true)] {
+            let circuit = TrustScoreCircuit::<Fr>::new(Some(trust_score), 70);
+            let prover = MockProver::run(4, &circuit, vec![public_inputs(result, 70)]).unwrap();
+            prover.assert_satisfied();
+
+            let wrong = MockProver::run(4, &circuit, vec![public_inputs(!result, 70)]).unwrap();
+            assert!(wrong.verify().is_err());
+        }
+    }
+
     #[test]
     fn test_trust_score_below_threshold() {
         let k = 4;
@@ -239,8 +510,8 @@ mod tests {
 
         let circuit = TrustScoreCircuit::<Fp>::new(Some(trust_score), threshold);
         
-        // The public input should be 0 (false) since 65 < 70
-        let public_inputs = vec![Fp::zero()];
+        // The result should be 0 (false) since 65 < 70
+        let public_inputs = public_inputs(false, threshold);
 
         let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
         prover.assert_satisfied();
@@ -254,24 +525,208 @@ mod tests {
 
         let circuit = TrustScoreCircuit::<Fp>::new(Some(trust_score), threshold);
         
-        // The public input should be 1 (true) since 70 >= 70
-        let public_inputs = vec![Fp::one()];
+        // The result should be 1 (true) since 70 >= 70
+        let public_inputs = public_inputs(true, threshold);
 
         let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
         prover.assert_satisfied();
     }
 
+    #[test]
+    fn test_trust_score_just_below_threshold() {
+        let circuit = TrustScoreCircuit::<Fp>::new(Some(69), 70);
+
+        let prover = MockProver::run(4, &circuit, vec![public_inputs(true, 70)]).unwrap();
+        assert!(prover.verify().is_err());
+
+        let prover = MockProver::run(4, &circuit, vec![public_inputs(false, 70)]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    /// Circuit whose prover claims `result = 1` regardless of the comparison
+    #[derive(Clone)]
+    struct ForgedResultCircuit {
+        trust_score: u64,
+        threshold: u64,
+    }
+
+    impl Circuit<Fp> for ForgedResultCircuit {
+        type Config = TrustScoreConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            TrustScoreCircuit::<Fp>::configure(meta)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let (result, threshold) = layouter.assign_region(
+                || "forged comparison",
+                |mut region| {
+                    config.selector.enable(&mut region, 0)?;
+                    region.assign_advice(|| "trust score", config.trust_score, 0, || Value::known(Fp::from(self.trust_score)))?;
+                    let ThresholdColumn::Advice(threshold_column) = config.threshold else {
+                        unreachable!("the trust score circuit witnesses its threshold");
+                    };
+                    let threshold =
+                        region.assign_advice(|| "threshold", threshold_column, 0, || Value::known(Fp::from(self.threshold)))?;
+
+                    // Honest decomposition of the difference, forged result
+                    let diff = self.trust_score + (1 << TRUST_SCORE_BITS) - self.threshold;
+                    let mut z = 0u64;
+                    region.assign_advice(|| "z_0", config.decomposition, 0, || Value::known(Fp::zero()))?;
+                    for i in 0..=TRUST_SCORE_BITS {
+                        config.bit_selector.enable(&mut region, i)?;
+                        z = 2 * z + ((diff >> (TRUST_SCORE_BITS - i)) & 1);
+                        region.assign_advice(|| "z", config.decomposition, i + 1, || Value::known(Fp::from(z)))?;
+                    }
+
+                    // Honest score bounds
+                    let headroom = MAX_TRUST_SCORE - self.trust_score;
+                    for (column, value) in config.bounds.into_iter().zip([self.trust_score, headroom]) {
+                        let mut z = 0u64;
+                        region.assign_advice(|| "bound_0", column, 0, || Value::known(Fp::zero()))?;
+                        for i in 0..=TRUST_SCORE_BITS {
+                            z = 2 * z + ((value >> (TRUST_SCORE_BITS - i)) & 1);
+                            region.assign_advice(|| "bound", column, i + 1, || Value::known(Fp::from(z)))?;
+                        }
+                    }
+
+                    let result = region.assign_advice(|| "result", config.result, 0, || Value::known(Fp::one()))?;
+                    Ok((result, threshold))
+                },
+            )?;
+            layouter.constrain_instance(result.cell(), config.instance, 0)?;
+            layouter.constrain_instance(threshold.cell(), config.instance, 1)
+        }
+    }
+
+    #[test]
+    fn test_forged_result_rejected() {
+        let circuit = ForgedResultCircuit { trust_score: 65, threshold: 70 };
+
+        let prover = MockProver::run(4, &circuit, vec![public_inputs(true, 70)]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // The same assignment is accepted when the claim is true
+        let circuit = ForgedResultCircuit { trust_score: 70, threshold: 70 };
+        let prover = MockProver::run(4, &circuit, vec![public_inputs(true, 70)]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn test_forged_result_diagnosed() {
+        use crate::circuits::lending::diagnose;
+
+        let circuit = ForgedResultCircuit { trust_score: 65, threshold: 70 };
+        let diagnostics = diagnose(4, &circuit, vec![public_inputs(true, 70)]).unwrap();
+        assert!(!diagnostics.satisfied);
+
+        let failure = diagnostics.failures.iter().find(|failure| failure.kind == "constraint").unwrap();
+        assert_eq!(failure.gate.as_deref(), Some("trust_score_comparison"));
+        assert_eq!(failure.region.as_deref(), Some("forged comparison"));
+        assert_eq!(failure.row, Some(0));
+    }
+
+    #[test]
+    fn test_score_above_maximum_rejected() {
+        assert!(matches!(TrustScoreCircuit::<Fp>::new_checked(Some(150), 70), Err(ZkError::InvalidInput(_))));
+        assert!(matches!(TrustScoreCircuit::<Fp>::new_checked(Some(85), 150), Err(ZkError::InvalidInput(_))));
+
+        // Unchecked, the bound on the score still makes the circuit unsatisfiable
+        let circuit = TrustScoreCircuit::<Fp>::new(Some(150), 70);
+        let prover = MockProver::run(4, &circuit, vec![public_inputs(true, 70)]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_score_at_maximum_accepted() {
+        let circuit = TrustScoreCircuit::<Fp>::new_checked(Some(MAX_TRUST_SCORE), 70).unwrap();
+        assert_eq!(circuit.expected_public_inputs(), public_inputs(true, 70));
+
+        let prover = MockProver::run(4, &circuit, vec![public_inputs(true, 70)]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn test_every_instance_constrained() {
+        use crate::circuits::lending::assert_circuit;
+
+        for (trust_score, result) in [(85, true), (65, false), (70, true)] {
+            let circuit = TrustScoreCircuit::<Fp>::new(Some(trust_score), 70);
+            assert_circuit(4, &circuit, vec![public_inputs(result, 70)]);
+        }
+    }
+
+    #[test]
+    fn test_instance_layout() {
+        // Score 85 against threshold 70: the instance is exactly [1, 70, 0]
+        let circuit = TrustScoreCircuit::<Fp>::new(Some(85), 70);
+        let instance = vec![Fp::one(), Fp::from(70), Fp::zero()];
+        assert_eq!(circuit.expected_public_inputs(), instance);
+        MockProver::run(4, &circuit, vec![instance]).unwrap().assert_satisfied();
+
+        // Score 65 against 70 gives [0, 70], with the zero epoch left to padding
+        let circuit = TrustScoreCircuit::<Fp>::new(Some(65), 70);
+        MockProver::run(4, &circuit, vec![vec![Fp::zero(), Fp::from(70)]]).unwrap().assert_satisfied();
+
+        // The threshold alone, or the values swapped, is not a valid instance
+        for instance in [vec![Fp::from(70)], vec![Fp::from(70), Fp::one()]] {
+            let circuit = TrustScoreCircuit::<Fp>::new(Some(85), 70);
+            assert!(MockProver::run(4, &circuit, vec![instance]).unwrap().verify().is_err());
+        }
+    }
+
+    #[test]
+    fn test_instance_layout_metadata() {
+        use crate::circuits::lending::{InstanceKind, InstanceSpec};
+
+        // Threshold and epoch follow the result, as in `expected_public_inputs`
+        assert_eq!(
+            TrustScoreCircuit::<Fp>::instance_layout(),
+            vec![
+                InstanceSpec { index: 0, name: "result", kind: InstanceKind::Bool, optional: false },
+                InstanceSpec { index: 1, name: "threshold", kind: InstanceKind::Integer, optional: false },
+                InstanceSpec { index: 2, name: "epoch", kind: InstanceKind::Integer, optional: false },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_epoch_bound_to_instance() {
+        use super::utils::public_inputs_at_epoch;
+
+        let circuit = TrustScoreCircuit::<Fp>::new_at_epoch(Some(85), 70, 12);
+        assert_eq!(circuit.expected_public_inputs(), public_inputs_at_epoch(true, 70, 12));
+        assert!(MockProver::run(4, &circuit, vec![public_inputs_at_epoch(true, 70, 13)]).unwrap().verify().is_err());
+        assert!(MockProver::run(4, &circuit, vec![public_inputs(true, 70)]).unwrap().verify().is_err());
+
+        crate::circuits::lending::assert_circuit(4, &circuit, vec![public_inputs_at_epoch(true, 70, 12)]);
+    }
+
+    #[test]
+    fn test_threshold_bound_to_instance() {
+        // A proof made against threshold 50 does not pass as one against 80
+        let circuit = TrustScoreCircuit::<Fp>::new(Some(60), 50);
+
+        let prover = MockProver::run(4, &circuit, vec![public_inputs(true, 80)]).unwrap();
+        assert!(prover.verify().is_err());
+
+        let prover = MockProver::run(4, &circuit, vec![public_inputs(true, 50)]).unwrap();
+        prover.assert_satisfied();
+    }
+
     #[test]
     fn test_circuit_without_witnesses() {
-        let k = 4;
+        use crate::circuits::lending::assert_keygen;
+
         let threshold = 70u64;
 
         let circuit = TrustScoreCircuit::<Fp>::new(None, threshold);
-        let circuit_without_witnesses = circuit.without_witnesses();
-
-        // Should be able to create the circuit structure without witnesses
-        // We can't directly test if Value is unknown, but we can verify the circuit compiles
-        let _ = circuit_without_witnesses;
+        assert_keygen(TrustScoreCircuit::<Fp>::recommended_k(), &circuit.without_witnesses());
     }
 }
 
@@ -0,0 +1,307 @@
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Selector},
+    poly::Rotation,
+};
+use ff::PrimeField;
+use std::marker::PhantomData;
+
+use super::AssignedCell;
+use super::lending::{known, InstanceKind, InstanceSpec, LendingCircuit};
+use super::loan_history::field_to_u64_truncating;
+use super::trust_score::{TrustScoreChip, TrustScoreConfig};
+
+/// Configuration for the trust score band circuit
+#[derive(Clone, Debug)]
+pub struct TrustScoreBandConfig {
+    /// Advice column for `trust_score >= low`
+    pub above_low: Column<Advice>,
+    /// Advice column for `high >= trust_score`
+    pub below_high: Column<Advice>,
+    /// Advice column for the band result (1 if `low <= trust_score <= high`)
+    pub in_band: Column<Advice>,
+    /// Selector for the band gate
+    pub selector: Selector,
+    /// Comparison shared with the trust score circuit, used for both bounds
+    pub comparison: TrustScoreConfig,
+}
+
+/// Cells assigned by the band check
+pub struct AssignedTrustScoreBand<F: PrimeField> {
+    pub trust_score: AssignedCell<F>,
+    pub low: AssignedCell<F>,
+    pub high: AssignedCell<F>,
+    pub in_band: AssignedCell<F>,
+}
+
+/// Chip checking a trust score against both ends of a band
+pub struct TrustScoreBandChip<F: PrimeField> {
+    config: TrustScoreBandConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: PrimeField> TrustScoreBandChip<F> {
+    pub fn construct(config: TrustScoreBandConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 3],
+        comparison: TrustScoreConfig,
+    ) -> TrustScoreBandConfig {
+        let [above_low, below_high, in_band] = advice;
+        let selector = meta.selector();
+
+        meta.enable_equality(above_low);
+        meta.enable_equality(below_high);
+        meta.enable_equality(in_band);
+
+        // Both comparison results are boolean, so their product is the AND
+        meta.create_gate("trust_score_band", |meta| {
+            let s = meta.query_selector(selector);
+            let above_low = meta.query_advice(above_low, Rotation::cur());
+            let below_high = meta.query_advice(below_high, Rotation::cur());
+            let in_band = meta.query_advice(in_band, Rotation::cur());
+
+            vec![s * (in_band - above_low * below_high)]
+        });
+
+        TrustScoreBandConfig {
+            above_low,
+            below_high,
+            in_band,
+            selector,
+            comparison,
+        }
+    }
+
+    /// Assign the band check
+    ///
+    /// Runs the trust score comparison twice, as `trust_score >= low` and as
+    /// `high >= trust_score`, with the score cells tied together.
+    pub fn assign_band(
+        &self,
+        mut layouter: impl Layouter<F>,
+        trust_score: Value<F>,
+        low: Value<F>,
+        high: Value<F>,
+    ) -> Result<AssignedTrustScoreBand<F>, Error> {
+        let comparison = TrustScoreChip::construct(self.config.comparison.clone());
+        let lower = comparison.assign_comparison(layouter.namespace(|| "score >= low"), trust_score, low)?;
+        let upper = comparison.assign_comparison(layouter.namespace(|| "high >= score"), high, trust_score)?;
+
+        let in_band = layouter.assign_region(
+            || "trust score band",
+            |mut region| {
+                self.config.selector.enable(&mut region, 0)?;
+                region.constrain_equal(lower.trust_score.cell(), upper.threshold.cell())?;
+
+                let above_low = lower.result.copy_advice(|| "above low", &mut region, self.config.above_low, 0)?;
+                let below_high = upper.result.copy_advice(|| "below high", &mut region, self.config.below_high, 0)?;
+
+                region.assign_advice(
+                    || "in band",
+                    self.config.in_band,
+                    0,
+                    || above_low.value().copied() * below_high.value().copied(),
+                )
+            },
+        )?;
+
+        Ok(AssignedTrustScoreBand {
+            trust_score: lower.trust_score,
+            low: lower.threshold,
+            high: upper.trust_score,
+            in_band,
+        })
+    }
+}
+
+/// Circuit proving a trust score lies in the band `[low, high]`
+///
+/// Lets a lender check a score tier (e.g. 60–80) without learning the score.
+/// The bounds must fit in `TRUST_SCORE_BITS` like thresholds do, and `high`
+/// is compared as a score so cannot exceed `MAX_TRUST_SCORE`; a band with
+/// `high < low` is empty. Instance layout: `[in_band, low, high]`.
+#[derive(Clone, Debug)]
+pub struct TrustScoreBandCircuit<F: PrimeField> {
+    /// Private input: the actual trust score
+    pub trust_score: Value<F>,
+    /// Public input: the lowest score in the band
+    pub low: Value<F>,
+    /// Public input: the highest score in the band
+    pub high: Value<F>,
+}
+
+impl<F: PrimeField> TrustScoreBandCircuit<F> {
+    pub fn new(trust_score: Option<u64>, low: u64, high: u64) -> Self {
+        Self {
+            trust_score: trust_score.map(|score| Value::known(F::from(score))).unwrap_or_else(Value::unknown),
+            low: Value::known(F::from(low)),
+            high: Value::known(F::from(high)),
+        }
+    }
+
+    /// Public inputs `[in_band, low, high]`, with the result computed from
+    /// the known trust score (0 without a witness)
+    pub fn expected_public_inputs(&self) -> Vec<F> {
+        let low = field_to_u64_truncating(&known(self.low).unwrap_or(F::ZERO));
+        let high = field_to_u64_truncating(&known(self.high).unwrap_or(F::ZERO));
+        let in_band = known(self.trust_score).is_some_and(|score| (low..=high).contains(&field_to_u64_truncating(&score)));
+
+        utils::public_inputs(in_band, low, high)
+    }
+}
+
+impl<F: PrimeField> Circuit<F> for TrustScoreBandCircuit<F> {
+    type Config = TrustScoreBandConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            trust_score: Value::unknown(),
+            low: self.low,
+            high: self.high,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let trust_score = meta.advice_column();
+        let threshold = meta.advice_column();
+        let result = meta.advice_column();
+        let decomposition = meta.advice_column();
+        let bounds = [(); 2].map(|_| meta.advice_column());
+        let instance = meta.instance_column();
+        let comparison = TrustScoreChip::configure(meta, trust_score, threshold, result, decomposition, bounds, instance);
+
+        let advice = [(); 3].map(|_| meta.advice_column());
+        TrustScoreBandChip::configure(meta, advice, comparison)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = TrustScoreBandChip::construct(config.clone());
+
+        let assigned = chip.assign_band(
+            layouter.namespace(|| "trust score band"),
+            self.trust_score,
+            self.low,
+            self.high,
+        )?;
+
+        let instance = config.comparison.instance;
+        layouter.constrain_instance(assigned.in_band.cell(), instance, 0)?;
+        layouter.constrain_instance(assigned.low.cell(), instance, 1)?;
+        layouter.constrain_instance(assigned.high.cell(), instance, 2)?;
+
+        Ok(())
+    }
+}
+
+impl<F: PrimeField> LendingCircuit<F> for TrustScoreBandCircuit<F> {
+    fn public_inputs(&self) -> Vec<F> {
+        self.expected_public_inputs()
+    }
+
+    fn recommended_k() -> u32 {
+        5
+    }
+
+    fn instance_layout() -> Vec<InstanceSpec> {
+        InstanceSpec::layout(&[
+            ("in_band", InstanceKind::Bool),
+            ("low", InstanceKind::Integer),
+            ("high", InstanceKind::Integer),
+        ])
+    }
+}
+
+/// Utility functions for trust score band proofs
+pub mod utils {
+    use ff::PrimeField;
+
+    /// Build the public inputs in the circuit's instance order: `[in_band, low, high]`
+    pub fn public_inputs<F: PrimeField>(in_band: bool, low: u64, high: u64) -> Vec<F> {
+        vec![if in_band { F::ONE } else { F::ZERO }, F::from(low), F::from(high)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::utils::public_inputs;
+    use halo2_proofs::dev::MockProver;
+    use pasta_curves::Fp;
+
+    const K: u32 = 5;
+
+    fn run(trust_score: u64, low: u64, high: u64, in_band: bool) -> MockProver<Fp> {
+        let circuit = TrustScoreBandCircuit::<Fp>::new(Some(trust_score), low, high);
+        MockProver::run(K, &circuit, vec![public_inputs(in_band, low, high)]).unwrap()
+    }
+
+    #[test]
+    fn test_score_at_low() {
+        run(60, 60, 80, true).assert_satisfied();
+        assert!(run(60, 60, 80, false).verify().is_err());
+    }
+
+    #[test]
+    fn test_score_at_high() {
+        run(80, 60, 80, true).assert_satisfied();
+        assert!(run(80, 60, 80, false).verify().is_err());
+    }
+
+    #[test]
+    fn test_score_below_low() {
+        run(59, 60, 80, false).assert_satisfied();
+        assert!(run(59, 60, 80, true).verify().is_err());
+    }
+
+    #[test]
+    fn test_score_above_high() {
+        run(81, 60, 80, false).assert_satisfied();
+        assert!(run(81, 60, 80, true).verify().is_err());
+    }
+
+    #[test]
+    fn test_bounds_bound_to_instance() {
+        let circuit = TrustScoreBandCircuit::<Fp>::new(Some(70), 60, 80);
+        assert_eq!(circuit.expected_public_inputs(), public_inputs(true, 60, 80));
+
+        // A proof for the 60-80 band does not pass for 75-80
+        let prover = MockProver::run(K, &circuit, vec![public_inputs(true, 75, 80)]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // An empty band rejects every score
+        run(70, 80, 60, false).assert_satisfied();
+    }
+
+    #[test]
+    fn test_every_instance_constrained() {
+        use crate::circuits::lending::assert_circuit;
+
+        for (trust_score, in_band) in [(70, true), (59, false), (81, false)] {
+            let circuit = TrustScoreBandCircuit::<Fp>::new(Some(trust_score), 60, 80);
+            assert_circuit(K, &circuit, vec![public_inputs(in_band, 60, 80)]);
+        }
+    }
+
+    #[test]
+    fn test_circuit_without_witnesses() {
+        use crate::circuits::lending::assert_keygen;
+
+        let circuit = TrustScoreBandCircuit::<Fp>::new(None, 60, 80);
+        let circuit_without_witnesses = circuit.without_witnesses();
+
+        assert_eq!(circuit_without_witnesses.expected_public_inputs(), public_inputs(false, 60, 80));
+        assert_keygen(K, &circuit_without_witnesses);
+    }
+}
@@ -0,0 +1,517 @@
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Instance, Selector},
+    poly::Rotation,
+};
+use ff::PrimeField;
+use std::marker::PhantomData;
+
+use super::AssignedCell;
+use super::lending::known;
+use super::loan_history::{field_to_u128, field_to_u64_truncating, LOAN_HISTORY_BITS, SUCCESS_RATE_SCALE};
+use super::optimizations::{RangeCheckChip, RangeCheckConfig};
+
+/// Bit width of each loan amount
+pub const WEIGHTED_AMOUNT_BITS: usize = 40;
+
+/// Bit width of the amount totals and the division remainders (up to 256 loans)
+pub const WEIGHTED_TOTAL_BITS: usize = 48;
+
+/// Configuration for the amount-weighted loan history circuit
+#[derive(Clone, Debug)]
+pub struct WeightedLoanHistoryConfig {
+    /// Advice column for each loan amount (private input)
+    pub amount: Column<Advice>,
+    /// Advice column for each loan's repaid bit (private input)
+    pub repaid: Column<Advice>,
+    /// Running sum of the amounts, ending in the total lent
+    pub total: Column<Advice>,
+    /// Running sum of `repaid * amount`, ending in the total repaid
+    pub repaid_total: Column<Advice>,
+    /// Advice column for the repaid share of the total (percentage * 100)
+    pub weighted_rate: Column<Advice>,
+    /// Advice column for the inverse of the total (0 when nothing was lent)
+    pub total_inverse: Column<Advice>,
+    /// Advice column for the division remainder
+    pub remainder: Column<Advice>,
+    /// Advice column for `total - 1 - remainder`, proving `remainder < total`
+    pub remainder_gap: Column<Advice>,
+    /// Advice column for the minimum rate threshold (public input)
+    pub min_rate: Column<Advice>,
+    /// Advice column for the `weighted_rate >= min_rate` bit
+    pub above_min: Column<Advice>,
+    /// Low N bits of `weighted_rate - min_rate + 2^N`
+    pub rate_remainder: Column<Advice>,
+    /// Advice column for the result (1 if meets threshold, 0 if not)
+    pub result: Column<Advice>,
+    /// Instance column for public inputs/outputs
+    pub instance: Column<Instance>,
+    /// Selector for the per-loan accumulation gate
+    pub loan_selector: Selector,
+    /// Selector for the first accumulation row (running sums start at zero)
+    pub start_selector: Selector,
+    /// Selector for the final row (division and threshold comparison)
+    pub rate_selector: Selector,
+    /// Range check for the amounts and remainders
+    pub range_check: RangeCheckConfig,
+}
+
+/// Cells assigned by the weighted loan history verification
+pub struct AssignedWeightedLoanHistory<F: PrimeField> {
+    pub min_rate: AssignedCell<F>,
+    pub result: AssignedCell<F>,
+}
+
+/// Chip for amount-weighted loan history verification
+pub struct WeightedLoanHistoryChip<F: PrimeField> {
+    config: WeightedLoanHistoryConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: PrimeField> WeightedLoanHistoryChip<F> {
+    pub fn construct(config: WeightedLoanHistoryConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 13],
+        instance: Column<Instance>,
+    ) -> WeightedLoanHistoryConfig {
+        let [amount, repaid, total, repaid_total, weighted_rate, total_inverse, remainder, remainder_gap, min_rate, above_min, rate_remainder, result, range_column] =
+            advice;
+        let loan_selector = meta.selector();
+        let start_selector = meta.selector();
+        let rate_selector = meta.selector();
+        let range_check = RangeCheckChip::configure(meta, range_column, WEIGHTED_TOTAL_BITS);
+
+        // Enable equality constraints for public inputs/outputs
+        meta.enable_equality(amount);
+        meta.enable_equality(remainder);
+        meta.enable_equality(remainder_gap);
+        meta.enable_equality(min_rate);
+        meta.enable_equality(rate_remainder);
+        meta.enable_equality(result);
+        meta.enable_equality(instance);
+
+        // Each loan row adds its amount to the total, and to the repaid total
+        // when its repaid bit is set
+        meta.create_gate("weighted_loan_accumulation", |meta| {
+            let s = meta.query_selector(loan_selector);
+            let amount = meta.query_advice(amount, Rotation::cur());
+            let repaid = meta.query_advice(repaid, Rotation::cur());
+            let total_next = meta.query_advice(total, Rotation::next());
+            let total = meta.query_advice(total, Rotation::cur());
+            let repaid_total_next = meta.query_advice(repaid_total, Rotation::next());
+            let repaid_total = meta.query_advice(repaid_total, Rotation::cur());
+
+            vec![
+                // Ensure repaid is boolean (0 or 1)
+                s.clone() * (repaid.clone() * (repaid.clone() - Expression::Constant(F::ONE))),
+                s.clone() * (total_next - total - amount.clone()),
+                s * (repaid_total_next - repaid_total - repaid * amount),
+            ]
+        });
+
+        meta.create_gate("weighted_loan_accumulation_start", |meta| {
+            let s = meta.query_selector(start_selector);
+            let total = meta.query_advice(total, Rotation::cur());
+            let repaid_total = meta.query_advice(repaid_total, Rotation::cur());
+
+            vec![s.clone() * total, s * repaid_total]
+        });
+
+        // The same constrained division as the loan history circuit, over the
+        // amount totals: weighted_rate * total + remainder == repaid_total * 10000
+        // with remainder < total, and a zero rate when nothing was lent. The
+        // repaid total never exceeds the total, so the rate is at most 100%.
+        meta.create_gate("weighted_loan_rate", |meta| {
+            let s = meta.query_selector(rate_selector);
+            let total = meta.query_advice(total, Rotation::cur());
+            let repaid_total = meta.query_advice(repaid_total, Rotation::cur());
+            let weighted_rate = meta.query_advice(weighted_rate, Rotation::cur());
+            let total_inverse = meta.query_advice(total_inverse, Rotation::cur());
+            let remainder = meta.query_advice(remainder, Rotation::cur());
+            let remainder_gap = meta.query_advice(remainder_gap, Rotation::cur());
+            let min_rate = meta.query_advice(min_rate, Rotation::cur());
+            let above_min = meta.query_advice(above_min, Rotation::cur());
+            let rate_remainder = meta.query_advice(rate_remainder, Rotation::cur());
+            let result = meta.query_advice(result, Rotation::cur());
+
+            let one = Expression::Constant(F::ONE);
+            let scale = Expression::Constant(F::from(SUCCESS_RATE_SCALE));
+            let offset = Expression::Constant(F::from(1u64 << LOAN_HISTORY_BITS));
+            let nothing_lent = one.clone() - total.clone() * total_inverse;
+            let has_total = one.clone() - nothing_lent.clone();
+
+            vec![
+                s.clone() * (above_min.clone() * (above_min.clone() - one.clone())),
+                // is-zero gadget on the total
+                s.clone() * (total.clone() * nothing_lent.clone()),
+                // Division with remainder when anything was lent
+                s.clone() * (has_total.clone()
+                    * (weighted_rate.clone() * total.clone() + remainder.clone() - repaid_total * scale)),
+                // remainder < total, i.e. total - 1 - remainder >= 0
+                s.clone() * (remainder_gap - (total - one - remainder.clone() + nothing_lent.clone())),
+                // Nothing lent: rate and remainder are zero
+                s.clone() * (nothing_lent.clone() * weighted_rate.clone()),
+                s.clone() * (nothing_lent * remainder),
+                // weighted_rate >= min_rate
+                s.clone() * (weighted_rate - min_rate + offset.clone()
+                    - (above_min.clone() * offset + rate_remainder)),
+                s * (result - above_min * has_total),
+            ]
+        });
+
+        WeightedLoanHistoryConfig {
+            amount,
+            repaid,
+            total,
+            repaid_total,
+            weighted_rate,
+            total_inverse,
+            remainder,
+            remainder_gap,
+            min_rate,
+            above_min,
+            rate_remainder,
+            result,
+            instance,
+            loan_selector,
+            start_selector,
+            rate_selector,
+            range_check,
+        }
+    }
+
+    /// Assign the per-loan accumulation and the weighted rate check
+    pub fn assign_weighted_loan_history(
+        &self,
+        mut layouter: impl Layouter<F>,
+        loans: &[(Value<F>, Value<F>)],
+        min_rate: Value<F>,
+    ) -> Result<AssignedWeightedLoanHistory<F>, Error> {
+        for (amount, _) in loans {
+            amount.error_if_known_and(|a| field_to_u128(a).is_none_or(|a| a >> WEIGHTED_AMOUNT_BITS != 0))?;
+        }
+        min_rate.error_if_known_and(|rate| field_to_u128(rate).is_none())?;
+
+        let (assigned, amounts, range_checked) = layouter.assign_region(
+            || "weighted loan history",
+            |mut region| {
+                self.config.start_selector.enable(&mut region, 0)?;
+
+                let mut total = Value::known(F::ZERO);
+                let mut repaid_total = Value::known(F::ZERO);
+                let mut amounts = Vec::with_capacity(loans.len());
+
+                for (i, &(amount, repaid)) in loans.iter().enumerate() {
+                    self.config.loan_selector.enable(&mut region, i)?;
+
+                    region.assign_advice(|| "total", self.config.total, i, || total)?;
+                    region.assign_advice(|| "repaid total", self.config.repaid_total, i, || repaid_total)?;
+                    amounts.push(region.assign_advice(|| "amount", self.config.amount, i, || amount)?);
+                    region.assign_advice(|| "repaid", self.config.repaid, i, || repaid)?;
+
+                    total = total + amount;
+                    repaid_total = repaid_total + repaid * amount;
+                }
+
+                // Final row: the totals, the division and the threshold comparison
+                let last = loans.len();
+                self.config.rate_selector.enable(&mut region, last)?;
+
+                region.assign_advice(|| "total", self.config.total, last, || total)?;
+                region.assign_advice(|| "repaid total", self.config.repaid_total, last, || repaid_total)?;
+
+                let witness = utils::RateWitness::compute(total, repaid_total, min_rate);
+                region.assign_advice(|| "weighted rate", self.config.weighted_rate, last, || witness.weighted_rate)?;
+                region.assign_advice(
+                    || "total inverse",
+                    self.config.total_inverse,
+                    last,
+                    || total.map(|total| total.invert().unwrap_or(F::ZERO)),
+                )?;
+                let remainder = region.assign_advice(|| "remainder", self.config.remainder, last, || witness.remainder)?;
+                let remainder_gap = region.assign_advice(
+                    || "remainder gap",
+                    self.config.remainder_gap,
+                    last,
+                    || witness.remainder_gap,
+                )?;
+                let min_rate = region.assign_advice(|| "minimum rate", self.config.min_rate, last, || min_rate)?;
+                region.assign_advice(|| "above min", self.config.above_min, last, || witness.above_min)?;
+                let rate_remainder = region.assign_advice(
+                    || "rate remainder",
+                    self.config.rate_remainder,
+                    last,
+                    || witness.rate_remainder,
+                )?;
+                let result = region.assign_advice(|| "verification result", self.config.result, last, || witness.result)?;
+
+                Ok((
+                    AssignedWeightedLoanHistory { min_rate, result },
+                    amounts,
+                    [(remainder, WEIGHTED_TOTAL_BITS), (remainder_gap, WEIGHTED_TOTAL_BITS), (rate_remainder, LOAN_HISTORY_BITS)],
+                ))
+            },
+        )?;
+
+        let range_chip = RangeCheckChip::construct(self.config.range_check.clone());
+        for (i, cell) in amounts.iter().enumerate() {
+            range_chip.copy_range_check(layouter.namespace(|| format!("amount {}", i)), cell, WEIGHTED_AMOUNT_BITS)?;
+        }
+        for (i, (cell, bits)) in range_checked.iter().enumerate() {
+            range_chip.copy_range_check(layouter.namespace(|| format!("range check {}", i)), cell, *bits)?;
+        }
+
+        Ok(assigned)
+    }
+}
+
+/// Circuit proving the amount-weighted repayment rate meets a threshold
+///
+/// Counting loans equally treats a repaid micro-loan like a repaid mortgage;
+/// here each loan counts by its amount, and the circuit proves
+/// `sum(repaid amounts) * 10000 / sum(amounts) >= min_rate`. The amounts and
+/// repaid bits stay private and the number of loans is fixed by the circuit.
+/// A history with nothing lent rates 0 and never passes. Instance layout:
+/// `[result, min_rate]`.
+#[derive(Clone, Debug)]
+pub struct WeightedLoanHistoryCircuit<F: PrimeField> {
+    /// Private input: `(amount, repaid)` for each loan, repaid as 0 or 1
+    pub loans: Vec<(Value<F>, Value<F>)>,
+    /// Public input: the minimum weighted rate (as percentage * 100)
+    pub min_rate: Value<F>,
+}
+
+impl<F: PrimeField> WeightedLoanHistoryCircuit<F> {
+    pub fn new(loans: Option<Vec<(u64, bool)>>, num_loans: usize, min_rate: u64) -> Self {
+        let loans = match loans {
+            Some(loans) => {
+                assert_eq!(loans.len(), num_loans, "Loan list length must match the circuit's loan count");
+                loans
+                    .iter()
+                    .map(|&(amount, repaid)| (Value::known(F::from(amount)), Value::known(F::from(repaid as u64))))
+                    .collect()
+            }
+            None => vec![(Value::unknown(), Value::unknown()); num_loans],
+        };
+
+        Self {
+            loans,
+            min_rate: Value::known(F::from(min_rate)),
+        }
+    }
+
+    /// Public inputs `[result, min_rate]`, with the result computed from the
+    /// known loans (0 without a witness)
+    pub fn expected_public_inputs(&self) -> Vec<F> {
+        let min_rate = known(self.min_rate).unwrap_or(F::ZERO);
+        let loans: Option<Vec<_>> = self
+            .loans
+            .iter()
+            .map(|&(amount, repaid)| {
+                Some((field_to_u64_truncating(&known(amount)?), known(repaid)? == F::ONE))
+            })
+            .collect();
+        let result = loans.is_some_and(|loans| utils::meets_weighted_rate_threshold(&loans, field_to_u64_truncating(&min_rate)));
+
+        utils::public_inputs(result, min_rate)
+    }
+}
+
+impl<F: PrimeField> Circuit<F> for WeightedLoanHistoryCircuit<F> {
+    type Config = WeightedLoanHistoryConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            loans: vec![(Value::unknown(), Value::unknown()); self.loans.len()],
+            min_rate: self.min_rate,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [(); 13].map(|_| meta.advice_column());
+        let instance = meta.instance_column();
+
+        WeightedLoanHistoryChip::configure(meta, advice, instance)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = WeightedLoanHistoryChip::construct(config.clone());
+
+        let assigned = chip.assign_weighted_loan_history(
+            layouter.namespace(|| "weighted loan history"),
+            &self.loans,
+            self.min_rate,
+        )?;
+
+        layouter.constrain_instance(assigned.result.cell(), config.instance, 0)?;
+        layouter.constrain_instance(assigned.min_rate.cell(), config.instance, 1)?;
+
+        Ok(())
+    }
+}
+
+/// Utility functions for weighted loan history verification
+pub mod utils {
+    use super::*;
+
+    /// Every value witnessed on the weighted rate row
+    pub(super) struct RateWitness<F: PrimeField> {
+        pub weighted_rate: Value<F>,
+        pub remainder: Value<F>,
+        pub remainder_gap: Value<F>,
+        pub above_min: Value<F>,
+        pub rate_remainder: Value<F>,
+        pub result: Value<F>,
+    }
+
+    impl<F: PrimeField> RateWitness<F> {
+        /// Compute the honest witness from the amount totals
+        pub fn compute(total: Value<F>, repaid_total: Value<F>, min_rate: Value<F>) -> Self {
+            let values = total.zip(repaid_total).zip(min_rate).map(|((total, repaid_total), min_rate)| {
+                // Amounts are checked to fit in WEIGHTED_AMOUNT_BITS, so the totals fit in u128
+                let total = field_to_u128(&total).unwrap_or_default();
+                let scaled = field_to_u128(&repaid_total).unwrap_or_default() * SUCCESS_RATE_SCALE as u128;
+
+                let rate = scaled.checked_div(total).unwrap_or(0);
+                let remainder = scaled.checked_rem(total).unwrap_or(0);
+                let gap = total.saturating_sub(1 + remainder);
+
+                // `a - b + 2^N` has its top bit set iff a >= b
+                let diff = rate
+                    .wrapping_add(1u128 << LOAN_HISTORY_BITS)
+                    .wrapping_sub(field_to_u128(&min_rate).unwrap_or_default());
+                let above = diff >> LOAN_HISTORY_BITS == 1;
+                let rate_remainder = diff & ((1u128 << LOAN_HISTORY_BITS) - 1);
+
+                (rate, remainder, gap, above, rate_remainder, total != 0 && above)
+            });
+            let to_field = |b: bool| if b { F::ONE } else { F::ZERO };
+
+            Self {
+                weighted_rate: values.map(|v| F::from_u128(v.0)),
+                remainder: values.map(|v| F::from_u128(v.1)),
+                remainder_gap: values.map(|v| F::from_u128(v.2)),
+                above_min: values.map(|v| to_field(v.3)),
+                rate_remainder: values.map(|v| F::from_u128(v.4)),
+                result: values.map(|v| to_field(v.5)),
+            }
+        }
+    }
+
+    /// Repaid share of the total amount lent, as percentage * 100
+    ///
+    /// Returns 0 when nothing was lent (no loans, or only zero amounts).
+    pub fn calculate_weighted_rate(loans: &[(u64, bool)]) -> u64 {
+        let total: u128 = loans.iter().map(|&(amount, _)| amount as u128).sum();
+        let repaid: u128 = loans.iter().filter(|&&(_, repaid)| repaid).map(|&(amount, _)| amount as u128).sum();
+
+        (repaid * SUCCESS_RATE_SCALE as u128).checked_div(total).unwrap_or(0) as u64
+    }
+
+    /// Check if the weighted rate meets `min_rate`; a history with nothing
+    /// lent never does
+    pub fn meets_weighted_rate_threshold(loans: &[(u64, bool)], min_rate: u64) -> bool {
+        loans.iter().any(|&(amount, _)| amount > 0) && calculate_weighted_rate(loans) >= min_rate
+    }
+
+    /// Build the public inputs in the circuit's instance order: `[result, min_rate]`
+    pub fn public_inputs<F: PrimeField>(result: bool, min_rate: F) -> Vec<F> {
+        vec![if result { F::ONE } else { F::ZERO }, min_rate]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::utils::*;
+    use halo2_proofs::dev::MockProver;
+    use pasta_curves::Fp;
+
+    const K: u32 = 9;
+    const NUM_LOANS: usize = 4;
+
+    fn run(loans: Vec<(u64, bool)>, min_rate: u64, result: bool) -> MockProver<Fp> {
+        let circuit = WeightedLoanHistoryCircuit::<Fp>::new(Some(loans), NUM_LOANS, min_rate);
+        MockProver::run(K, &circuit, vec![public_inputs(result, Fp::from(min_rate))]).unwrap()
+    }
+
+    #[test]
+    fn test_mixed_portfolio() {
+        // 3 of 4 loans repaid, but the defaulted one is the largest: 3500 of 10000 lent
+        let loans = vec![(500, true), (1000, true), (2000, true), (6500, false)];
+        assert_eq!(calculate_weighted_rate(&loans), 3500);
+
+        run(loans.clone(), 3500, true).assert_satisfied();
+        run(loans.clone(), 3501, false).assert_satisfied();
+
+        // Counting loans equally would give 75%, which the weighted rate does not reach
+        assert!(run(loans, 7500, true).verify().is_err());
+    }
+
+    #[test]
+    fn test_large_repaid_loan_dominates() {
+        let loans = vec![(1_000_000, true), (100, false), (200, false), (300, false)];
+        assert_eq!(calculate_weighted_rate(&loans), 9994);
+
+        run(loans.clone(), 9900, true).assert_satisfied();
+        assert!(run(loans, 9900, false).verify().is_err());
+    }
+
+    #[test]
+    fn test_nothing_lent() {
+        let loans = vec![(0, true), (0, false), (0, true), (0, true)];
+        assert_eq!(calculate_weighted_rate(&loans), 0);
+        assert!(!meets_weighted_rate_threshold(&loans, 0));
+
+        // Even a zero threshold is not met without anything lent
+        run(loans.clone(), 0, false).assert_satisfied();
+        assert!(run(loans, 0, true).verify().is_err());
+    }
+
+    #[test]
+    fn test_expected_public_inputs() {
+        let loans = vec![(400, true), (300, true), (200, false), (100, true)];
+        for min_rate in [7000u64, 8000, 8001] {
+            let circuit = WeightedLoanHistoryCircuit::<Fp>::new(Some(loans.clone()), NUM_LOANS, min_rate);
+            let expected = circuit.expected_public_inputs();
+            assert_eq!(expected, public_inputs(min_rate <= 8000, Fp::from(min_rate)));
+            MockProver::run(K, &circuit, vec![expected]).unwrap().assert_satisfied();
+        }
+
+        // The threshold in the instance is bound to the one compared against
+        let circuit = WeightedLoanHistoryCircuit::<Fp>::new(Some(loans), NUM_LOANS, 8000);
+        let prover = MockProver::run(K, &circuit, vec![public_inputs(true, Fp::from(9000u64))]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_oversized_amount_rejected() {
+        let loans = vec![(1 << WEIGHTED_AMOUNT_BITS, true), (1, true), (1, true), (1, true)];
+        let circuit = WeightedLoanHistoryCircuit::<Fp>::new(Some(loans), NUM_LOANS, 5000);
+        assert!(MockProver::run(K, &circuit, vec![public_inputs(true, Fp::from(5000u64))]).is_err());
+    }
+
+    #[test]
+    fn test_circuit_without_witnesses() {
+        use crate::circuits::lending::assert_keygen;
+
+        let circuit = WeightedLoanHistoryCircuit::<Fp>::new(None, NUM_LOANS, 8000);
+        let circuit_without_witnesses = circuit.without_witnesses();
+
+        assert_eq!(circuit_without_witnesses.loans.len(), NUM_LOANS);
+        assert_eq!(circuit_without_witnesses.expected_public_inputs(), public_inputs(false, Fp::from(8000u64)));
+        assert_keygen(K, &circuit_without_witnesses);
+    }
+}
@@ -0,0 +1,310 @@
+//! Self-describing proof envelope
+//!
+//! Raw transcript bytes say nothing about which circuit or format produced
+//! them, so a proof handed to the wrong verifier just fails to verify. Proofs
+//! leaving the bindings are wrapped as:
+//!
+//! ```text
+//! magic (4) | format version (1) | circuit tag (1) | proof length (4, LE) | proof
+//! ```
+//!
+//! and the header is checked before any verification work.
+
+use crate::error::ZkError;
+use std::fmt;
+
+/// Leading bytes of every proof envelope
+pub const PROOF_MAGIC: [u8; 4] = *b"MCZK";
+
+/// Envelope format version written by `encode_proof`
+///
+/// Version 2 proofs bind their transcript to the circuit's domain, so
+/// version 1 proofs no longer verify and are rejected up front.
+pub const PROOF_FORMAT_VERSION: u8 = 2;
+
+/// Number of bytes before the proof in an envelope
+pub const HEADER_LEN: usize = PROOF_MAGIC.len() + 1 + 1 + 4;
+
+/// Circuit a proof was generated for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitTag {
+    TrustScore = 1,
+    IncomeRange = 2,
+    LoanHistory = 3,
+    Identity = 4,
+    Composite = 5,
+}
+
+impl TryFrom<u8> for CircuitTag {
+    type Error = ZkError;
+
+    fn try_from(tag: u8) -> Result<Self, ZkError> {
+        match tag {
+            1 => Ok(CircuitTag::TrustScore),
+            2 => Ok(CircuitTag::IncomeRange),
+            3 => Ok(CircuitTag::LoanHistory),
+            4 => Ok(CircuitTag::Identity),
+            5 => Ok(CircuitTag::Composite),
+            _ => Err(ZkError::UnknownCircuitTag(tag)),
+        }
+    }
+}
+
+impl fmt::Display for CircuitTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CircuitTag::TrustScore => write!(f, "trust_score"),
+            CircuitTag::IncomeRange => write!(f, "income_range"),
+            CircuitTag::LoanHistory => write!(f, "loan_history"),
+            CircuitTag::Identity => write!(f, "identity"),
+            CircuitTag::Composite => write!(f, "composite"),
+        }
+    }
+}
+
+/// What an envelope says about its proof
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProofMetadata {
+    pub circuit: CircuitTag,
+    /// Envelope format version
+    pub version: u8,
+    /// Length of the raw proof, without the header
+    pub proof_len: usize,
+}
+
+/// Describe a proof envelope without verifying it
+///
+/// Only the header is read; no verifying key is involved, so a proof that
+/// inspects cleanly may still fail verification. The envelope does not carry
+/// public inputs (verifiers supply them), so they are not reported.
+pub fn inspect_proof(envelope: &[u8]) -> Result<ProofMetadata, ZkError> {
+    let (circuit, proof) = decode_proof(envelope)?;
+
+    Ok(ProofMetadata {
+        circuit,
+        version: envelope[4],
+        proof_len: proof.len(),
+    })
+}
+
+/// Wrap raw proof bytes in an envelope for `circuit`
+pub fn encode_proof(circuit: CircuitTag, proof: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(HEADER_LEN + proof.len());
+    bytes.extend_from_slice(&PROOF_MAGIC);
+    bytes.push(PROOF_FORMAT_VERSION);
+    bytes.push(circuit as u8);
+    bytes.extend_from_slice(&(proof.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(proof);
+    bytes
+}
+
+/// Validate an envelope's header and return its circuit and raw proof bytes
+///
+/// Fails with `UnsupportedProofVersion` or `UnknownCircuitTag` for envelopes
+/// this build cannot read, and a serialization error for anything that is not
+/// a complete envelope.
+pub fn decode_proof(envelope: &[u8]) -> Result<(CircuitTag, &[u8]), ZkError> {
+    if envelope.len() < HEADER_LEN {
+        return Err(ZkError::Serialization(format!(
+            "Proof envelope is {} bytes, shorter than its {} byte header",
+            envelope.len(),
+            HEADER_LEN
+        )));
+    }
+
+    let (header, proof) = envelope.split_at(HEADER_LEN);
+    if header[..4] != PROOF_MAGIC {
+        return Err(ZkError::Serialization("Not a proof envelope".into()));
+    }
+    if header[4] != PROOF_FORMAT_VERSION {
+        return Err(ZkError::UnsupportedProofVersion(header[4]));
+    }
+    let circuit = CircuitTag::try_from(header[5])?;
+
+    let len = u32::from_le_bytes([header[6], header[7], header[8], header[9]]) as usize;
+    if proof.len() != len {
+        return Err(ZkError::Serialization(format!(
+            "Proof envelope declares {} proof bytes but carries {}",
+            len,
+            proof.len()
+        )));
+    }
+
+    Ok((circuit, proof))
+}
+
+/// Standard base64 alphabet (RFC 4648, with padding)
+const BASE64_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Padded standard base64 of an envelope, for JSON transport
+pub fn proof_to_base64(envelope: &[u8]) -> String {
+    let mut text = String::with_capacity(envelope.len().div_ceil(3) * 4);
+    for chunk in envelope.chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (i, &byte)| bits | (byte as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                text.push(BASE64_ALPHABET[(bits >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                text.push('=');
+            }
+        }
+    }
+    text
+}
+
+/// Read an envelope written by `proof_to_base64`
+///
+/// Only padded standard base64 is accepted; anything else, including
+/// whitespace and nonzero trailing bits, is a serialization error.
+pub fn proof_from_base64(text: &str) -> Result<Vec<u8>, ZkError> {
+    let malformed = || ZkError::Serialization("Proof is not valid base64".into());
+    if !text.len().is_multiple_of(4) {
+        return Err(malformed());
+    }
+
+    let text = text.as_bytes();
+    let padding = text.iter().rev().take_while(|&&c| c == b'=').count();
+    if padding > 2 {
+        return Err(malformed());
+    }
+
+    let mut bytes = Vec::with_capacity(text.len() / 4 * 3);
+    for (n, chunk) in text.chunks(4).enumerate() {
+        let last = n + 1 == text.len() / 4;
+        let digits = if last { 4 - padding } else { 4 };
+
+        let mut bits = 0u32;
+        for (i, &c) in chunk[..digits].iter().enumerate() {
+            let value = BASE64_ALPHABET.iter().position(|&a| a == c).ok_or_else(malformed)?;
+            bits |= (value as u32) << (18 - 6 * i);
+        }
+
+        let len = digits * 6 / 8;
+        if bits & (0xff_ffff >> (8 * len)) != 0 {
+            return Err(malformed());
+        }
+        bytes.extend((0..len).map(|i| (bits >> (16 - 8 * i)) as u8));
+    }
+    Ok(bytes)
+}
+
+/// Lowercase hex of an envelope, for JSON transport
+pub fn proof_to_hex(envelope: &[u8]) -> String {
+    envelope.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Read an envelope written by `proof_to_hex`
+///
+/// Either case is accepted; an odd length or a non-hex character is a
+/// serialization error.
+pub fn proof_from_hex(text: &str) -> Result<Vec<u8>, ZkError> {
+    let malformed = || ZkError::Serialization("Proof is not valid hex".into());
+    if !text.len().is_multiple_of(2) {
+        return Err(malformed());
+    }
+
+    let digit = |c: u8| (c as char).to_digit(16).ok_or_else(malformed);
+    text.as_bytes()
+        .chunks(2)
+        .map(|pair| Ok((digit(pair[0])? << 4 | digit(pair[1])?) as u8))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let envelope = encode_proof(CircuitTag::LoanHistory, &[1, 2, 3]);
+        assert_eq!(envelope.len(), HEADER_LEN + 3);
+        assert_eq!(decode_proof(&envelope), Ok((CircuitTag::LoanHistory, &[1u8, 2, 3][..])));
+
+        let empty = encode_proof(CircuitTag::TrustScore, &[]);
+        assert_eq!(decode_proof(&empty), Ok((CircuitTag::TrustScore, &[][..])));
+    }
+
+    #[test]
+    fn test_truncated_envelope() {
+        let envelope = encode_proof(CircuitTag::TrustScore, &[7; 32]);
+
+        // Cut inside the proof and inside the header
+        for len in [envelope.len() - 1, HEADER_LEN, HEADER_LEN - 1, 0] {
+            assert!(matches!(decode_proof(&envelope[..len]), Err(ZkError::Serialization(_))));
+        }
+
+        let mut padded = envelope.clone();
+        padded.push(0);
+        assert!(matches!(decode_proof(&padded), Err(ZkError::Serialization(_))));
+
+        let mut foreign = envelope;
+        foreign[0] ^= 0xff;
+        assert!(matches!(decode_proof(&foreign), Err(ZkError::Serialization(_))));
+    }
+
+    #[test]
+    fn test_inspect_proof() {
+        let envelope = encode_proof(CircuitTag::Identity, &[7; 32]);
+        let metadata = inspect_proof(&envelope).unwrap();
+        assert_eq!(
+            metadata,
+            ProofMetadata {
+                circuit: CircuitTag::Identity,
+                version: PROOF_FORMAT_VERSION,
+                proof_len: 32,
+            }
+        );
+        assert_eq!(metadata.circuit.to_string(), "identity");
+
+        // Garbage proof bytes still inspect: nothing is verified
+        assert!(inspect_proof(&encode_proof(CircuitTag::TrustScore, &[0; 3])).is_ok());
+        assert!(matches!(inspect_proof(&envelope[1..]), Err(ZkError::Serialization(_))));
+    }
+
+    #[test]
+    fn test_unknown_version_and_tag() {
+        let mut envelope = encode_proof(CircuitTag::TrustScore, &[7; 32]);
+        envelope[5] = 0x2a;
+        assert_eq!(decode_proof(&envelope), Err(ZkError::UnknownCircuitTag(0x2a)));
+
+        envelope[4] = PROOF_FORMAT_VERSION + 1;
+        assert_eq!(decode_proof(&envelope), Err(ZkError::UnsupportedProofVersion(PROOF_FORMAT_VERSION + 1)));
+    }
+
+    #[test]
+    fn test_base64_encoding() {
+        // RFC 4648 test vectors
+        for (bytes, text) in [
+            (&b""[..], ""),
+            (b"f", "Zg=="),
+            (b"fo", "Zm8="),
+            (b"foo", "Zm9v"),
+            (b"foob", "Zm9vYg=="),
+            (b"fooba", "Zm9vYmE="),
+            (b"foobar", "Zm9vYmFy"),
+        ] {
+            assert_eq!(proof_to_base64(bytes), text);
+            assert_eq!(proof_from_base64(text).unwrap(), bytes);
+        }
+
+        let envelope = encode_proof(CircuitTag::TrustScore, &(0..=255).collect::<Vec<u8>>());
+        assert_eq!(proof_from_base64(&proof_to_base64(&envelope)).unwrap(), envelope);
+
+        for malformed in ["Zg=", "Zg===", "Z===", "Zh==", "Zm9v!mFy", "Zg==Zm8=", "Zm9v Yg==", "===="] {
+            assert!(matches!(proof_from_base64(malformed), Err(ZkError::Serialization(_))), "{}", malformed);
+        }
+    }
+
+    #[test]
+    fn test_hex_encoding() {
+        let envelope = encode_proof(CircuitTag::TrustScore, &[0x00, 0xab, 0xff]);
+        let text = proof_to_hex(&envelope);
+        assert!(text.ends_with("00abff"));
+        assert_eq!(proof_from_hex(&text).unwrap(), envelope);
+        assert_eq!(proof_from_hex(&text.to_uppercase()).unwrap(), envelope);
+
+        for malformed in ["abc", "zz", "+1", "0x00"] {
+            assert!(matches!(proof_from_hex(malformed), Err(ZkError::Serialization(_))), "{}", malformed);
+        }
+    }
+}
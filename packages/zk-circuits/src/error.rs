@@ -0,0 +1,141 @@
+//! Error types shared by the proving, verification and FFI layers
+
+use std::fmt;
+
+/// Errors surfaced by the ZK proof system
+///
+/// Each variant has a stable integer code (see `ZkError::code`) so callers
+/// across the FFI boundary can tell failures apart without parsing messages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ZkError {
+    /// The ZK system was used before its keys were set up
+    NotInitialized,
+    /// Key generation failed for the given circuit
+    KeygenFailed(String),
+    /// Caller-supplied data was malformed or out of range
+    InvalidInput(String),
+    /// Proof creation failed
+    ProofFailed(String),
+    /// A proof or circuit could not be verified
+    VerifyFailed(String),
+    /// Params, keys or proofs could not be (de)serialized
+    Serialization(String),
+    /// A proof referenced a key version that is not registered
+    UnknownKeyVersion(u32),
+    /// The loaded verifying key does not match the pinned fingerprint
+    KeyVersionMismatch { expected: String, actual: String },
+    /// A proof envelope uses a format version this build cannot read
+    UnsupportedProofVersion(u8),
+    /// A proof envelope names a circuit this build does not know
+    UnknownCircuitTag(u8),
+    /// Params and keys were made for different circuit sizes, or a verifying
+    /// key does not belong to the proving key it is used with
+    KeyMismatch(String),
+    /// Proving was cancelled before it finished
+    Cancelled,
+}
+
+impl ZkError {
+    /// Stable code for this kind of error; 0 is reserved for success
+    pub fn code(&self) -> i32 {
+        match self {
+            ZkError::NotInitialized => 1,
+            ZkError::KeygenFailed(_) => 2,
+            ZkError::ProofFailed(_) => 3,
+            ZkError::VerifyFailed(_) => 4,
+            ZkError::InvalidInput(_) => 5,
+            ZkError::Serialization(_) => 6,
+            ZkError::UnknownKeyVersion(_) => 7,
+            ZkError::KeyVersionMismatch { .. } => 8,
+            ZkError::UnsupportedProofVersion(_) => 9,
+            ZkError::UnknownCircuitTag(_) => 10,
+            ZkError::KeyMismatch(_) => 11,
+            ZkError::Cancelled => 12,
+        }
+    }
+}
+
+impl fmt::Display for ZkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ZkError::NotInitialized => write!(f, "ZK system not initialized"),
+            ZkError::KeygenFailed(msg) => write!(f, "Key generation failed: {}", msg),
+            ZkError::InvalidInput(msg) => write!(f, "Invalid input: {}", msg),
+            ZkError::ProofFailed(msg) => write!(f, "Proof generation failed: {}", msg),
+            ZkError::VerifyFailed(msg) => write!(f, "Verification failed: {}", msg),
+            ZkError::Serialization(msg) => write!(f, "Serialization error: {}", msg),
+            ZkError::UnknownKeyVersion(version) => {
+                write!(f, "No verifying key registered for version {}", version)
+            }
+            ZkError::KeyVersionMismatch { expected, actual } => write!(
+                f,
+                "Verifying key fingerprint mismatch: pinned {}, loaded {}",
+                expected, actual
+            ),
+            ZkError::UnsupportedProofVersion(version) => {
+                write!(f, "Unsupported proof format version {}", version)
+            }
+            ZkError::UnknownCircuitTag(tag) => write!(f, "Unknown circuit tag {} in proof", tag),
+            ZkError::KeyMismatch(msg) => write!(f, "Key mismatch: {}", msg),
+            ZkError::Cancelled => write!(f, "Proving cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for ZkError {}
+
+/// Thrown to JavaScript with the code in the message, e.g. `[ZK1] ZK system not initialized`
+#[cfg(feature = "node")]
+impl From<ZkError> for napi::Error {
+    fn from(error: ZkError) -> Self {
+        let status = match error {
+            ZkError::InvalidInput(_) => napi::Status::InvalidArg,
+            _ => napi::Status::GenericFailure,
+        };
+        napi::Error::new(status, format!("[ZK{}] {}", error.code(), error))
+    }
+}
+
+/// A field element did not fit the integer type it was read as
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldConversionError;
+
+impl fmt::Display for FieldConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Field element does not fit in 64 bits")
+    }
+}
+
+impl std::error::Error for FieldConversionError {}
+
+impl From<FieldConversionError> for ZkError {
+    fn from(error: FieldConversionError) -> Self {
+        ZkError::InvalidInput(error.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_codes_are_distinct() {
+        let errors = [
+            ZkError::NotInitialized,
+            ZkError::KeygenFailed(String::new()),
+            ZkError::ProofFailed(String::new()),
+            ZkError::VerifyFailed(String::new()),
+            ZkError::InvalidInput(String::new()),
+            ZkError::Serialization(String::new()),
+            ZkError::UnknownKeyVersion(0),
+            ZkError::KeyVersionMismatch { expected: String::new(), actual: String::new() },
+            ZkError::UnsupportedProofVersion(0),
+            ZkError::UnknownCircuitTag(0),
+            ZkError::KeyMismatch(String::new()),
+            ZkError::Cancelled,
+        ];
+        let codes: std::collections::BTreeSet<_> = errors.iter().map(ZkError::code).collect();
+        assert_eq!(codes.len(), errors.len());
+        assert_eq!(codes, (1..=12).collect());
+    }
+}
@@ -1,149 +1,890 @@
+use crate::api::{check_circuit_size, init_system, CancellationToken, ZkSystem};
+use crate::circuits::optimizations::batch_processing::get_optimal_batch_size;
+use crate::circuits::composite::CompositeLendingCircuit;
+use crate::circuits::identity::IdentityCircuit;
+use crate::circuits::identity::utils::hash_identity;
+use crate::circuits::income_range::IncomeRangeCircuit;
+use crate::circuits::lending::{dry_run_with_diagnostics, run_mock, InstanceSpec, LendingCircuit};
+use crate::circuits::loan_history::LoanHistoryCircuit;
+use crate::circuits::optimizations::performance::{
+    circuit_stats, configure_for_device, estimate_proof_size_bytes, get_recommended_k, DeviceType,
+};
 use crate::circuits::trust_score::TrustScoreCircuit;
-use halo2_proofs::{
-    dev::MockProver,
-    plonk::{create_proof, keygen_pk, keygen_vk, verify_proof, ProvingKey, VerifyingKey, SingleVerifier},
-    poly::commitment::Params,
-    transcript::{Blake2bRead, Blake2bWrite, Challenge255},
+use crate::circuits::{
+    committed_trust_score::CommittedTrustScoreCircuit, distinct_identities::DistinctIdentitiesCircuit,
+    eligibility::EligibilityCircuit, income_growth::IncomeGrowthCircuit, trust_score_band::TrustScoreBandCircuit,
 };
-use pasta_curves::{Fp, EqAffine};
-use ff::Field;
-use rand::rngs::OsRng;
+use crate::envelope::{proof_from_base64, proof_from_hex, proof_to_base64, proof_to_hex};
+use crate::error::ZkError;
+use crate::keys::TRUST_SCORE_K;
+use crate::prover::{fp_from_le_bytes, BatchMode, BatchVerdict, TrustScoreClaim};
+use crate::validation::{validate_income_range_inputs, validate_loan_history_inputs, validate_threshold, validate_trust_inputs};
+use halo2_proofs::dev::MockProver;
+use pasta_curves::Fp;
 use std::ffi::CString;
 use std::os::raw::{c_char, c_int};
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Mutex, OnceLock};
 use napi_derive::napi;
-use napi::{Result, Error, Status};
+use napi::threadsafe_function::{ErrorStrategy, ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi::{bindgen_prelude::External, Either, Result};
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
 
 /// Result structure for proof operations
+///
+/// `error_code` is 0 on success, otherwise the failing `ZkError::code`.
+/// `magic` is `PROOF_RESULT_MAGIC` while the result is live and cleared by
+/// `free_proof_result`.
 #[repr(C)]
 pub struct ProofResult {
     pub success: bool,
     pub proof_data: *mut u8,
     pub proof_len: usize,
     pub error_message: *mut c_char,
+    pub error_code: c_int,
+    pub magic: u32,
 }
 
+/// Sentinel marking a live `ProofResult` ("MCPR")
+pub const PROOF_RESULT_MAGIC: u32 = 0x4d43_5052;
+
+/// Addresses of results handed out by `generate_trust_proof` and not yet
+/// freed, so a stale pointer is recognized without being dereferenced
+static LIVE_PROOF_RESULTS: Mutex<BTreeSet<usize>> = Mutex::new(BTreeSet::new());
+
+/// Addresses of handles handed out by `new_prover_handle` and not yet
+/// destroyed, so a stale pointer is recognized without being dereferenced
+static LIVE_PROVER_HANDLES: Mutex<BTreeSet<usize>> = Mutex::new(BTreeSet::new());
+
 /// Parameters for trust score proof generation
 #[repr(C)]
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "crate::inputs::TrustScoreInputs", into = "crate::inputs::TrustScoreInputs")
+)]
 pub struct TrustScoreParams {
     pub trust_score: u64,
     pub threshold: u64,
 }
 
-/// Setup parameters for the circuit (simplified for demo)
-static mut SETUP_PARAMS: Option<Params<EqAffine>> = None;
-static mut PROVING_KEY: Option<ProvingKey<EqAffine>> = None;
-static mut VERIFYING_KEY: Option<VerifyingKey<EqAffine>> = None;
+#[cfg(feature = "serde")]
+impl From<crate::inputs::TrustScoreInputs> for TrustScoreParams {
+    fn from(inputs: crate::inputs::TrustScoreInputs) -> Self {
+        Self {
+            trust_score: inputs.trust_score,
+            threshold: inputs.threshold,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<TrustScoreParams> for crate::inputs::TrustScoreInputs {
+    fn from(params: TrustScoreParams) -> Self {
+        Self {
+            trust_score: params.trust_score,
+            threshold: params.threshold,
+        }
+    }
+}
+
+pub use crate::api::MAX_K;
+
+/// Setup state, written once by `initialize_zk_system` or `load_keys` and
+/// shared read-only by every proving and verifying call (including from
+/// Node.js worker threads)
+static ZK_SYSTEM: OnceLock<ZkSystem> = OnceLock::new();
+
+/// Borrow the setup state held in `system`
+fn keys_in(system: &OnceLock<ZkSystem>) -> std::result::Result<&ZkSystem, ZkError> {
+    system.get().ok_or(ZkError::NotInitialized)
+}
+
+/// Borrow the initialized setup state
+fn zk_system() -> std::result::Result<&'static ZkSystem, ZkError> {
+    keys_in(&ZK_SYSTEM)
+}
+
+/// Install a system as the setup state, failing if it is already set
+fn install_keys(system: ZkSystem) -> std::result::Result<(), ZkError> {
+    // A concurrent initializer may have won the race since any earlier check
+    ZK_SYSTEM
+        .set(system)
+        .map_err(|_| ZkError::InvalidInput("ZK system already initialized".into()))
+}
+
+/// Route the crate's keygen, prove and verify spans to `subscriber`
+///
+/// Nothing is installed by default, so spans are dropped until the host sets
+/// a subscriber. This sets the process-wide default and fails if one is
+/// already set.
+#[cfg(feature = "tracing")]
+pub fn set_tracing_subscriber<S>(subscriber: S) -> std::result::Result<(), ZkError>
+where
+    S: tracing::Subscriber + Send + Sync + 'static,
+{
+    tracing::subscriber::set_global_default(subscriber)
+        .map_err(|e| ZkError::InvalidInput(format!("Tracing subscriber already set: {}", e)))
+}
+
+/// Check a requested circuit size, defaulting to the desktop recommendation
+fn circuit_size(k: Option<u32>) -> std::result::Result<u32, ZkError> {
+    check_circuit_size(k.unwrap_or_else(|| get_recommended_k(DeviceType::Desktop)))
+}
 
-/// Initialize the ZK proof system with setup parameters
+/// Initialize the ZK proof system with setup parameters for circuit size `k`
+///
+/// `k` defaults to the desktop recommendation. Proofs only verify against
+/// keys generated for the same `k`. Fails if `k` is out of range or the
+/// system has already been initialized.
 #[napi]
-pub fn initialize_zk_system() -> Result<bool> {
-    unsafe {
-        // Create setup parameters (in production, these would be from a trusted setup)
-        let k = 4; // Circuit size parameter
-        let params = Params::<EqAffine>::new(k);
-        
-        // Create a dummy circuit for key generation
-        let circuit = TrustScoreCircuit::<Fp>::new(Some(75), 70);
-        
-        // Generate verification key
-        let vk = keygen_vk(&params, &circuit)
-            .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to generate VK: {:?}", e)))?;
-        
-        // Generate proving key
-        let pk = keygen_pk(&params, vk.clone(), &circuit)
-            .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to generate PK: {:?}", e)))?;
-        
-        SETUP_PARAMS = Some(params);
-        PROVING_KEY = Some(pk);
-        VERIFYING_KEY = Some(vk);
-        
-        Ok(true)
-    }
-}
-
-/// Generate a trust score proof
+pub fn initialize_zk_system(k: Option<u32>) -> Result<bool> {
+    init_keys(k)?;
+    Ok(true)
+}
+
+/// Generate and install keys for circuit size `k`
+fn init_keys(k: Option<u32>) -> std::result::Result<(), ZkError> {
+    let k = circuit_size(k)?;
+    if ZK_SYSTEM.get().is_some() {
+        return Err(ZkError::InvalidInput("ZK system already initialized".into()));
+    }
+
+    install_keys(init_system(k)?)
+}
+
+/// Save the initialized params and keys to `path`
+pub fn save_keys(path: &str) -> std::result::Result<(), ZkError> {
+    zk_system()?.save(path)
+}
+
+/// Initialize the ZK proof system from a key file written by `save_keys`
+///
+/// Skips regenerating the setup parameters. Fails if the system has already
+/// been initialized or the file is not a compatible key file.
+pub fn load_keys(path: &str) -> std::result::Result<(), ZkError> {
+    if ZK_SYSTEM.get().is_some() {
+        return Err(ZkError::InvalidInput("ZK system already initialized".into()));
+    }
+
+    install_keys(ZkSystem::load(path)?)
+}
+
+/// Save the initialized params and keys to a file
+#[napi]
+pub fn save_zk_keys(path: String) -> Result<bool> {
+    save_keys(&path)?;
+    Ok(true)
+}
+
+/// Initialize the ZK proof system from a saved key file
+#[napi]
+pub fn load_zk_keys(path: String) -> Result<bool> {
+    load_keys(&path)?;
+    Ok(true)
+}
+
+/// Borrow the setup state, initializing it first if no caller has yet
+fn zk_system_or_init() -> std::result::Result<&'static ZkSystem, ZkError> {
+    if let Some(system) = ZK_SYSTEM.get() {
+        return Ok(system);
+    }
+
+    // Losing an initialization race to another caller is fine
+    if let Err(e) = init_keys(None) {
+        return ZK_SYSTEM.get().ok_or(e);
+    }
+    zk_system()
+}
+
+/// Trust score proving keys owned by the caller rather than the global
+/// setup state
+///
+/// A server proving many inputs creates one handle and reuses it, without
+/// touching the shared state on each call; handles for different `k` can
+/// coexist. Proofs from a handle verify against any system set up for the
+/// same `k`. Opaque to JavaScript and C callers.
+pub struct ProverHandle {
+    /// The handle's keys; `None` once freed
+    system: Option<ZkSystem>,
+}
+
+impl ProverHandle {
+    /// Generate keys for circuit size `k` (the desktop recommendation by default)
+    fn new(k: Option<u32>) -> std::result::Result<Self, ZkError> {
+        let k = circuit_size(k)?;
+
+        Ok(Self { system: Some(init_system(k)?) })
+    }
+
+    /// Borrow the handle's keys, failing once it has been freed
+    fn system(&self) -> std::result::Result<&ZkSystem, ZkError> {
+        self.system
+            .as_ref()
+            .ok_or_else(|| ZkError::InvalidInput("Prover handle has been freed".into()))
+    }
+}
+
+/// Create a prover handle with its own keys for circuit size `k`
+///
+/// `k` defaults as for `initialize_zk_system`, which need not have been
+/// called. Key generation happens once, here.
+#[napi]
+pub fn create_prover_handle(k: Option<u32>) -> Result<External<ProverHandle>> {
+    Ok(External::new(ProverHandle::new(k)?))
+}
+
+/// Generate a trust score proof envelope with a handle's keys
+#[napi]
+pub fn prove_with_handle(handle: External<ProverHandle>, trust_score: u32, threshold: u32) -> Result<Vec<u8>> {
+    validate_trust_inputs(trust_score as u64, threshold as u64)?;
+    Ok(handle.system()?.prove_trust_score(trust_score as u64, threshold as u64)?)
+}
+
+/// Release a handle's keys without waiting for garbage collection
+///
+/// Proving with the handle afterwards fails; freeing it again does nothing.
+#[napi]
+pub fn free_prover_handle(mut handle: External<ProverHandle>) {
+    handle.system = None;
+}
+
+/// Generate a trust score proof, wrapped in a proof envelope
 #[napi]
 pub fn generate_trust_score_proof(trust_score: u32, threshold: u32) -> Result<Vec<u8>> {
-    unsafe {
-        let params = SETUP_PARAMS.as_ref()
-            .ok_or_else(|| Error::new(Status::GenericFailure, "ZK system not initialized"))?;
-        let pk = PROVING_KEY.as_ref()
-            .ok_or_else(|| Error::new(Status::GenericFailure, "Proving key not available"))?;
-        
-        // Create the circuit with the actual trust score
-        let circuit = TrustScoreCircuit::<Fp>::new(Some(trust_score as u64), threshold as u64);
-        
-        // Determine the expected public input (result of comparison)
-        let public_input = if trust_score >= threshold {
-            Fp::one()
-        } else {
-            Fp::zero()
-        };
-        
-        // Create proof
-        let mut transcript = Blake2bWrite::<Vec<u8>, EqAffine, Challenge255<_>>::init(vec![]);
-        
-        create_proof(
-            params,
-            pk,
-            &[circuit],
-            &[&[&[public_input]]],
-            OsRng,
-            &mut transcript,
-        ).map_err(|e| Error::new(Status::GenericFailure, format!("Failed to create proof: {:?}", e)))?;
-        
-        Ok(transcript.finalize())
-    }
-}
-
-/// Verify a trust score proof
-#[napi]
-pub fn verify_trust_score_proof(proof_data: Vec<u8>, threshold: u32, expected_result: bool) -> Result<bool> {
-    unsafe {
-        let params = SETUP_PARAMS.as_ref()
-            .ok_or_else(|| Error::new(Status::GenericFailure, "ZK system not initialized"))?;
-        let vk = VERIFYING_KEY.as_ref()
-            .ok_or_else(|| Error::new(Status::GenericFailure, "Verifying key not available"))?;
-        
-        // Expected public input based on the result
-        let public_input = if expected_result {
-            Fp::one()
-        } else {
-            Fp::zero()
-        };
-        
-        // Verify proof
-        let mut transcript = Blake2bRead::<&[u8], EqAffine, Challenge255<_>>::init(&proof_data[..]);
-        let strategy = SingleVerifier::new(params);
-        
-        let verification_result = verify_proof(
-            params,
-            vk,
-            strategy,
-            &[&[&[public_input]]],
-            &mut transcript,
-        );
-        
-        Ok(verification_result.is_ok())
+    validate_trust_inputs(trust_score as u64, threshold as u64)?;
+    Ok(zk_system()?.prove_trust_score(trust_score as u64, threshold as u64)?)
+}
+
+/// `generate_trust_score_proof` as padded standard base64
+#[napi]
+pub fn generate_trust_score_proof_base64(trust_score: u32, threshold: u32) -> Result<String> {
+    Ok(proof_to_base64(&generate_trust_score_proof(trust_score, threshold)?))
+}
+
+/// `generate_trust_score_proof` as lowercase hex
+#[napi]
+pub fn generate_trust_score_proof_hex(trust_score: u32, threshold: u32) -> Result<String> {
+    Ok(proof_to_hex(&generate_trust_score_proof(trust_score, threshold)?))
+}
+
+/// Cancellation tokens handed out by `create_cancellation_token` and not yet
+/// freed, by id
+///
+/// JavaScript refers to tokens by id because async bindings cannot take
+/// `External` arguments across the await.
+static CANCELLATION_TOKENS: Mutex<BTreeMap<u32, CancellationToken>> = Mutex::new(BTreeMap::new());
+
+/// Id of the next cancellation token; 0 is never handed out
+static NEXT_CANCELLATION_TOKEN: AtomicU32 = AtomicU32::new(1);
+
+/// The token registered as `id`, or a token nobody can cancel for `None`
+fn cancellation_token(id: Option<u32>) -> std::result::Result<CancellationToken, ZkError> {
+    let Some(id) = id else {
+        return Ok(CancellationToken::new());
+    };
+
+    let tokens = CANCELLATION_TOKENS.lock().unwrap_or_else(|e| e.into_inner());
+    tokens
+        .get(&id)
+        .cloned()
+        .ok_or_else(|| ZkError::InvalidInput(format!("Unknown cancellation token {}", id)))
+}
+
+/// Create a token for cancelling async proofs, returning its id
+///
+/// Pass the id to `generate_trust_score_proof_async` or
+/// `generate_trust_score_proofs_batch_async`, and to `cancel_proofs` to stop
+/// them. Free it with `free_cancellation_token` once done.
+#[napi]
+pub fn create_cancellation_token() -> u32 {
+    let id = NEXT_CANCELLATION_TOKEN.fetch_add(1, Ordering::Relaxed);
+    CANCELLATION_TOKENS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(id, CancellationToken::new());
+    id
+}
+
+/// Cancel every proof started with the token `id`
+///
+/// A proof cannot be interrupted part way through `create_proof`, so
+/// cancellation takes effect between proofs: calls using the token reject
+/// with a cancellation error (`[ZK12]`) instead of starting or returning
+/// further proofs.
+#[napi]
+pub fn cancel_proofs(id: u32) -> Result<()> {
+    cancellation_token(Some(id))?.cancel();
+    Ok(())
+}
+
+/// Forget the token `id`; proofs already holding it can still be cancelled
+/// through it, but its id no longer can be passed to new calls
+#[napi]
+pub fn free_cancellation_token(id: u32) {
+    CANCELLATION_TOKENS.lock().unwrap_or_else(|e| e.into_inner()).remove(&id);
+}
+
+/// Generate a trust score proof without blocking the Node.js event loop
+///
+/// Proving runs on a blocking worker thread of the napi tokio runtime. The
+/// keys are shared with it as a `'static` borrow of the process-wide setup
+/// state, which is immutable once installed, so concurrent calls need no
+/// locking or copying.
+///
+/// With `cancel_token`, cancelling the token before proving starts, or while
+/// it runs, rejects with a cancellation error rather than returning the proof.
+#[napi]
+pub async fn generate_trust_score_proof_async(trust_score: u32, threshold: u32, cancel_token: Option<u32>) -> Result<Vec<u8>> {
+    validate_trust_inputs(trust_score as u64, threshold as u64)?;
+    let system = zk_system()?;
+    let cancel = cancellation_token(cancel_token)?;
+    let proof = napi::tokio::task::spawn_blocking(move || {
+        cancel.check()?;
+        let proof = system.prove_trust_score(trust_score as u64, threshold as u64)?;
+        cancel.check()?;
+        Ok::<_, ZkError>(proof)
+    })
+    .await
+    .map_err(|e| ZkError::ProofFailed(format!("Proving task failed: {}", e)))??;
+
+    Ok(proof)
+}
+
+/// Generate a trust score proof per input on a worker thread, stopping early
+/// if `cancel_token` is cancelled
+///
+/// Proofs are made one after another, and the token is checked between
+/// them, so a cancelled batch rejects with a cancellation error once the
+/// proof in flight finishes. Any invalid input rejects the whole batch
+/// before proving starts.
+#[napi]
+pub async fn generate_trust_score_proofs_batch_async(inputs: Vec<TrustScoreInput>, cancel_token: Option<u32>) -> Result<Vec<Vec<u8>>> {
+    let inputs: Vec<_> = inputs
+        .iter()
+        .map(|input| (input.trust_score as u64, input.threshold as u64))
+        .collect();
+    for &(trust_score, threshold) in &inputs {
+        validate_trust_inputs(trust_score, threshold)?;
     }
+    let system = zk_system()?;
+    let cancel = cancellation_token(cancel_token)?;
+
+    let proofs = napi::tokio::task::spawn_blocking(move || {
+        system.prove_trust_scores_cancellable(&inputs, &cancel).collect::<std::result::Result<Vec<_>, _>>()
+    })
+    .await
+    .map_err(|e| ZkError::ProofFailed(format!("Proving task failed: {}", e)))??;
+
+    Ok(proofs)
+}
+
+/// Generate a trust score proof that expires with `epoch`, wrapped in a
+/// proof envelope
+///
+/// Verify it with `verify_trust_score_proof_at_epoch`; it does not verify
+/// with `verify_trust_score_proof` unless `epoch` is 0.
+#[napi]
+pub fn generate_trust_score_proof_at_epoch(trust_score: u32, threshold: u32, epoch: u32) -> Result<Vec<u8>> {
+    validate_trust_inputs(trust_score as u64, threshold as u64)?;
+    Ok(zk_system()?.prove_trust_score_at_epoch(trust_score as u64, threshold as u64, epoch as u64)?)
+}
+
+/// Prove a trust score with proving randomness seeded from `seed`
+fn prove_seeded(system: &ZkSystem, trust_score: u64, threshold: u64, seed: u64) -> std::result::Result<Vec<u8>, ZkError> {
+    system.prove_trust_score_with_rng(trust_score, threshold, ChaCha20Rng::seed_from_u64(seed))
+}
+
+/// Generate a reproducible trust score proof (for testing)
+///
+/// The same seed and inputs always give the same envelope bytes. The proof
+/// randomness is derived from `seed`, so these proofs are not zero-knowledge
+/// to anyone who knows it; use `generate_trust_score_proof` outside tests.
+/// `seed` arrives as a JavaScript number, so it must be a non-negative
+/// integer (BigInt would need a newer N-API version than we target).
+#[napi]
+pub fn generate_trust_score_proof_seeded(trust_score: u32, threshold: u32, seed: i64) -> Result<Vec<u8>> {
+    validate_trust_inputs(trust_score as u64, threshold as u64)?;
+    let seed = u64::try_from(seed)
+        .map_err(|_| ZkError::InvalidInput(format!("Seed {} must not be negative", seed)))?;
+
+    Ok(prove_seeded(zk_system()?, trust_score as u64, threshold as u64, seed)?)
+}
+
+/// Trust score proof request from JavaScript
+#[napi(object)]
+pub struct TrustScoreInput {
+    pub trust_score: u32,
+    pub threshold: u32,
+}
+
+/// Outcome of one item in a proof batch: a proof or an error
+#[napi(object)]
+pub struct BatchProofResult {
+    pub proof: Option<Vec<u8>>,
+    pub error: Option<String>,
+}
+
+/// Outcome of a dry run: whether a proof could be made, and what it would show
+#[napi(object)]
+#[derive(Debug)]
+pub struct DryRunResult {
+    /// The circuit's constraints hold for the inputs, so a proof can be made
+    pub satisfied: bool,
+    /// The statement's result (e.g. income in range); false if not satisfied
+    pub result: bool,
+}
+
+/// Description of a proof envelope, read without verifying the proof
+#[napi(object)]
+pub struct ProofInfo {
+    /// Circuit name, e.g. `trust_score`
+    pub circuit: String,
+    pub version: u32,
+    pub proof_len: u32,
+}
+
+/// Describe a proof envelope (circuit and format version) without verifying it
+///
+/// Needs no initialized system. Succeeding says nothing about validity; use
+/// the verify functions for that.
+#[napi]
+pub fn inspect_proof(proof: Vec<u8>) -> Result<ProofInfo> {
+    let metadata = crate::envelope::inspect_proof(&proof)?;
+
+    Ok(ProofInfo {
+        circuit: metadata.circuit.to_string(),
+        version: metadata.version as u32,
+        proof_len: metadata.proof_len as u32,
+    })
+}
+
+/// Generate one proof per input, sharing the initialized params and proving key
+///
+/// Inputs are proven `get_optimal_batch_size(device_type)` at a time, in
+/// parallel within each batch. A failing input only fails its own entry.
+pub fn prove_trust_scores_batch(
+    inputs: &[TrustScoreParams],
+    device_type: DeviceType,
+) -> std::result::Result<Vec<std::result::Result<Vec<u8>, ZkError>>, ZkError> {
+    prove_trust_scores_batch_with_progress(inputs, device_type, |_, _| {})
+}
+
+/// `prove_trust_scores_batch`, calling `on_progress(done, total)` as each
+/// result is collected
+///
+/// Results are collected in input order, so `done` counts up from 1 to
+/// `total` with one call per input, failed inputs included. The callback runs
+/// on the calling thread between proofs, so it should return quickly.
+pub fn prove_trust_scores_batch_with_progress(
+    inputs: &[TrustScoreParams],
+    device_type: DeviceType,
+    mut on_progress: impl FnMut(usize, usize),
+) -> std::result::Result<Vec<std::result::Result<Vec<u8>, ZkError>>, ZkError> {
+    let system = zk_system()?;
+
+    let mut results = Vec::with_capacity(inputs.len());
+    for batch in inputs.chunks(get_optimal_batch_size(device_type).max(1)) {
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = batch
+                .iter()
+                .map(|input| {
+                    scope.spawn(move || system.prove_trust_score(input.trust_score, input.threshold))
+                })
+                .collect();
+
+            for handle in handles {
+                results.push(
+                    handle
+                        .join()
+                        .unwrap_or_else(|_| Err(ZkError::ProofFailed("Proof generation panicked".into()))),
+                );
+                on_progress(results.len(), inputs.len());
+            }
+        });
+    }
+
+    Ok(results)
+}
+
+/// Generate trust score proofs for several inputs in one call
+///
+/// Returns one entry per input, in order; an invalid input reports its error
+/// without aborting the rest of the batch. Proving runs on the blocking
+/// thread pool, and `on_progress` is queued `(done, total)` after each result
+/// without waiting for JavaScript to run it.
+#[napi]
+pub async fn generate_trust_score_proofs_batch(
+    inputs: Vec<TrustScoreInput>,
+    on_progress: Option<ThreadsafeFunction<(u32, u32), ErrorStrategy::Fatal>>,
+) -> Result<Vec<BatchProofResult>> {
+    batch_proof_results(inputs, move |done, total| {
+        if let Some(on_progress) = &on_progress {
+            on_progress.call((done as u32, total as u32), ThreadsafeFunctionCallMode::NonBlocking);
+        }
+    })
+    .await
+}
+
+/// Body of `generate_trust_score_proofs_batch`, generic over the callback so
+/// it can run without a JavaScript environment
+async fn batch_proof_results(
+    inputs: Vec<TrustScoreInput>,
+    on_progress: impl FnMut(usize, usize) + Send + 'static,
+) -> Result<Vec<BatchProofResult>> {
+    let inputs: Vec<_> = inputs
+        .iter()
+        .map(|input| TrustScoreParams {
+            trust_score: input.trust_score as u64,
+            threshold: input.threshold as u64,
+        })
+        .collect();
+
+    let results = napi::tokio::task::spawn_blocking(move || {
+        prove_trust_scores_batch_with_progress(&inputs, DeviceType::Desktop, on_progress)
+    })
+    .await
+    .map_err(|e| ZkError::ProofFailed(format!("Proving task failed: {}", e)))??;
+
+    Ok(results
+        .into_iter()
+        .map(|result| match result {
+            Ok(proof) => BatchProofResult { proof: Some(proof), error: None },
+            Err(e) => BatchProofResult { proof: None, error: Some(e.to_string()) },
+        })
+        .collect())
+}
+
+/// Generate a single proof covering several trust score inputs, wrapped in
+/// a proof envelope
+///
+/// Much cheaper than a proof per input, but the inputs can only be verified
+/// together, with `verify_trust_scores_combined`. Fails if any input is
+/// invalid or there are none.
+#[napi]
+pub fn generate_trust_scores_combined_proof(inputs: Vec<TrustScoreInput>) -> Result<Vec<u8>> {
+    let inputs: Vec<_> = inputs
+        .iter()
+        .map(|input| (input.trust_score as u64, input.threshold as u64))
+        .collect();
+    for &(trust_score, threshold) in &inputs {
+        validate_trust_inputs(trust_score, threshold)?;
+    }
+
+    Ok(zk_system()?.prove_trust_scores_combined(&inputs)?)
+}
+
+/// Verify a trust score proof envelope
+///
+/// The envelope header is validated first: unknown format versions and
+/// circuit tags, or proofs of another circuit, fail with an error rather than
+/// returning `false`, and a buffer too short for a header (such as an empty
+/// one, which `verify_trust_proof` also rejects) is invalid input. With `vk_fingerprint`, the loaded verifying key is checked against it first
+/// and a mismatch fails with a key mismatch error before any verification work.
+#[napi]
+pub fn verify_trust_score_proof(
+    proof_data: Vec<u8>,
+    threshold: u32,
+    expected_result: bool,
+    vk_fingerprint: Option<String>,
+) -> Result<bool> {
+    validate_threshold(threshold as u64)?;
+    let system = zk_system()?;
+    if let Some(pinned) = vk_fingerprint {
+        system.check_fingerprint(&pinned)?;
+    }
+
+    Ok(system.verify_trust_score(&proof_data, threshold as u64, expected_result)?)
+}
+
+/// `verify_trust_score_proof` for a proof in base64
+///
+/// Malformed base64 fails with a serialization error.
+#[napi]
+pub fn verify_trust_score_proof_base64(
+    proof: String,
+    threshold: u32,
+    expected_result: bool,
+    vk_fingerprint: Option<String>,
+) -> Result<bool> {
+    verify_trust_score_proof(proof_from_base64(&proof)?, threshold, expected_result, vk_fingerprint)
+}
+
+/// `verify_trust_score_proof` for a proof in hex
+///
+/// Malformed hex fails with a serialization error.
+#[napi]
+pub fn verify_trust_score_proof_hex(
+    proof: String,
+    threshold: u32,
+    expected_result: bool,
+    vk_fingerprint: Option<String>,
+) -> Result<bool> {
+    verify_trust_score_proof(proof_from_hex(&proof)?, threshold, expected_result, vk_fingerprint)
+}
+
+/// Verify a trust score proof against `threshold` and return whether it
+/// attests a pass
+///
+/// The result is read from the proof rather than supplied by the caller, so
+/// it cannot be claimed falsely. Fails with a verification error if the
+/// proof is not valid for `threshold` with either result.
+#[napi]
+pub fn verify_trust_score_proof_outcome(proof_data: Vec<u8>, threshold: u32) -> Result<bool> {
+    validate_threshold(threshold as u64)?;
+    Ok(zk_system()?.verify_trust_score_outcome(&proof_data, threshold as u64)?.passed())
+}
+
+/// Verify a trust score proof made no more than `max_age` epochs before
+/// `current_epoch`
+///
+/// `max_age` defaults to 0, accepting only proofs made for `current_epoch`.
+/// Proofs made for any other epoch return `false`.
+#[napi]
+pub fn verify_trust_score_proof_at_epoch(
+    proof_data: Vec<u8>,
+    threshold: u32,
+    expected_result: bool,
+    current_epoch: u32,
+    max_age: Option<u32>,
+) -> Result<bool> {
+    validate_threshold(threshold as u64)?;
+    let current_epoch = current_epoch as u64;
+    let oldest = current_epoch.saturating_sub(max_age.unwrap_or(0) as u64);
+
+    Ok(zk_system()?.verify_trust_score_in_epochs(&proof_data, threshold as u64, expected_result, oldest..=current_epoch)?)
+}
+
+/// Verify a proof against caller-supplied public inputs
+///
+/// `public_inputs` holds one entry per instance column, each a run of 32-byte
+/// little-endian field elements; trust score proofs have a single column,
+/// `[result, threshold, epoch]`. Fails if the envelope or a column cannot be
+/// read, or the result is not 0 or 1.
+#[napi]
+pub fn verify_proof_raw(proof: Vec<u8>, public_inputs: Vec<Vec<u8>>) -> Result<bool> {
+    Ok(zk_system()?.verify_proof_raw(&proof, &public_inputs)?)
+}
+
+/// Pair up the parallel batch arrays as claims, checking their lengths and thresholds
+fn batch_claims<'a>(
+    proofs: &'a [Vec<u8>],
+    thresholds: &[u32],
+    expected_results: &[bool],
+) -> std::result::Result<Vec<TrustScoreClaim<'a>>, ZkError> {
+    if proofs.len() != thresholds.len() || proofs.len() != expected_results.len() {
+        return Err(ZkError::InvalidInput(format!(
+            "Batch inputs differ in length: {} proofs, {} thresholds, {} expected results",
+            proofs.len(),
+            thresholds.len(),
+            expected_results.len()
+        )));
+    }
+    for &threshold in thresholds {
+        validate_threshold(threshold as u64)?;
+    }
+
+    Ok(proofs
+        .iter()
+        .zip(thresholds)
+        .zip(expected_results)
+        .map(|((proof, &threshold), &expected_result)| TrustScoreClaim {
+            proof,
+            threshold: threshold as u64,
+            expected_result,
+        })
+        .collect())
+}
+
+/// Verify several trust score proofs in one batch
+///
+/// `proofs`, `thresholds` and `expected_results` are parallel arrays and must
+/// have equal lengths. Returns whether each proof verified, in order; an
+/// envelope that cannot be read is reported as not verifying.
+#[napi]
+pub fn verify_trust_score_proofs_batch(
+    proofs: Vec<Vec<u8>>,
+    thresholds: Vec<u32>,
+    expected_results: Vec<bool>,
+) -> Result<Vec<bool>> {
+    let claims = batch_claims(&proofs, &thresholds, &expected_results)?;
+
+    Ok(zk_system()?.verify_trust_scores_batch(&claims)?)
+}
+
+/// Verify several trust score proofs, each against its own threshold, in
+/// the given batch mode
+///
+/// Takes the same parallel arrays as `verify_trust_score_proofs_batch`.
+/// `mode` is `"all_or_nothing"`, returning a single boolean that is `true`
+/// only if every proof verifies, or `"report"`, returning one boolean per
+/// proof in order.
+#[napi]
+pub fn verify_trust_score_proofs_batch_mode(
+    proofs: Vec<Vec<u8>>,
+    thresholds: Vec<u32>,
+    expected_results: Vec<bool>,
+    mode: String,
+) -> Result<Either<bool, Vec<bool>>> {
+    let mode: BatchMode = mode.parse()?;
+    let claims = batch_claims(&proofs, &thresholds, &expected_results)?;
+
+    Ok(match zk_system()?.verify_trust_scores_batch_mode(&claims, mode)? {
+        BatchVerdict::All(verified) => Either::A(verified),
+        BatchVerdict::Report(verified) => Either::B(verified),
+    })
+}
+
+/// Verify a proof from `generate_trust_scores_combined_proof`
+///
+/// `thresholds` and `expected_results` are parallel arrays with one entry per
+/// input, in input order. Returns `true` only if every claim holds.
+#[napi]
+pub fn verify_trust_scores_combined(proof_data: Vec<u8>, thresholds: Vec<u32>, expected_results: Vec<bool>) -> Result<bool> {
+    if thresholds.len() != expected_results.len() {
+        return Err(ZkError::InvalidInput(format!(
+            "Combined claims differ in length: {} thresholds, {} expected results",
+            thresholds.len(),
+            expected_results.len()
+        ))
+        .into());
+    }
+    for &threshold in &thresholds {
+        validate_threshold(threshold as u64)?;
+    }
+
+    let claims: Vec<_> = thresholds
+        .iter()
+        .zip(&expected_results)
+        .map(|(&threshold, &expected_result)| (threshold as u64, expected_result))
+        .collect();
+
+    Ok(zk_system()?.verify_trust_scores_combined(&proof_data, &claims)?)
+}
+
+/// Blake2b fingerprint of the loaded verifying key
+pub fn verifying_key_fingerprint() -> std::result::Result<[u8; 32], ZkError> {
+    Ok(zk_system()?.verifying_key_fingerprint_bytes())
+}
+
+/// Fingerprint of the loaded verifying key as hex, for pinning in verifier config
+#[napi]
+pub fn get_verifying_key_fingerprint() -> Result<String> {
+    Ok(zk_system()?.verifying_key_fingerprint())
+}
+
+/// Verify a trust score proof, first checking the loaded verifying key
+/// against a pinned fingerprint
+///
+/// Fails with a key mismatch error rather than verifying against the wrong key.
+#[napi]
+pub fn verify_trust_score_proof_pinned(
+    proof_data: Vec<u8>,
+    threshold: u32,
+    expected_result: bool,
+    vk_fingerprint: String,
+) -> Result<bool> {
+    verify_trust_score_proof(proof_data, threshold, expected_result, Some(vk_fingerprint))
+}
+
+/// Measured columns, gates and minimum `k` of a circuit, as JSON
+///
+/// `circuit` is one of `trust_score`, `income_range`, `loan_history`,
+/// `identity` or `composite`; `minK` is null if it needs more than `MAX_K`.
+#[napi]
+pub fn get_circuit_stats(circuit: String) -> Result<String> {
+    // Layouts do not depend on the witness, so placeholder values measure them
+    let stats = match circuit.as_str() {
+        "trust_score" => circuit_stats(&TrustScoreCircuit::<Fp>::new(Some(0), 0), MAX_K),
+        "income_range" => circuit_stats(&IncomeRangeCircuit::<Fp>::new(Some(0), 0, 0), MAX_K),
+        "loan_history" => circuit_stats(&LoanHistoryCircuit::<Fp>::new(Some(0), Some(0), 0, 0), MAX_K),
+        "identity" => circuit_stats(&IdentityCircuit::<Fp>::new(Some(Fp::zero()), Some(Fp::zero()), Fp::zero(), 0, 0), MAX_K),
+        "composite" => circuit_stats(
+            &CompositeLendingCircuit::<Fp>::new(
+                Some(Fp::zero()),
+                TrustScoreCircuit::new(Some(0), 0),
+                IncomeRangeCircuit::new(Some(0), 0, 0),
+                LoanHistoryCircuit::new(Some(0), Some(0), 0, 0),
+            ),
+            MAX_K,
+        ),
+        other => return Err(ZkError::InvalidInput(format!("Unknown circuit: {}", other)).into()),
+    };
+
+    Ok(stats.to_json())
+}
+
+/// What each public input of a circuit holds, as a JSON array of
+/// `{ index, name, kind, optional }` objects in instance order
+///
+/// `kind` is `bool`, `integer` or `field`, and `optional` slots are only
+/// present for some constructions of the circuit. `circuit` is one of
+/// `trust_score`, `trust_score_band`, `committed_trust_score`,
+/// `income_range`, `income_growth`, `loan_history`, `identity`,
+/// `distinct_identities`, `eligibility` or `composite`.
+#[napi]
+pub fn get_instance_layout(circuit: String) -> Result<String> {
+    let layout = match circuit.as_str() {
+        "trust_score" => TrustScoreCircuit::<Fp>::instance_layout(),
+        "trust_score_band" => TrustScoreBandCircuit::<Fp>::instance_layout(),
+        "committed_trust_score" => CommittedTrustScoreCircuit::<Fp>::instance_layout(),
+        "income_range" => IncomeRangeCircuit::<Fp>::instance_layout(),
+        "income_growth" => IncomeGrowthCircuit::<Fp>::instance_layout(),
+        "loan_history" => LoanHistoryCircuit::<Fp>::instance_layout(),
+        "identity" => IdentityCircuit::<Fp>::instance_layout(),
+        "distinct_identities" => DistinctIdentitiesCircuit::<Fp>::instance_layout(),
+        "eligibility" => EligibilityCircuit::<Fp>::instance_layout(),
+        "composite" => CompositeLendingCircuit::<Fp>::instance_layout(),
+        other => return Err(ZkError::InvalidInput(format!("Unknown circuit: {}", other)).into()),
+    };
+
+    Ok(InstanceSpec::to_json(&layout))
+}
+
+/// Proving configuration for a device class, as JSON
+///
+/// `device` is one of `high_end_mobile`, `mid_range_mobile`, `low_end_mobile`
+/// or `desktop`, or its id (0 to 3 in that order); `useBatching` advises on a
+/// workload of `num_proofs` proofs.
+#[napi]
+pub fn get_device_profile(device: Either<String, u32>, num_proofs: u32) -> Result<String> {
+    let device = match device {
+        Either::A(name) => name.parse()?,
+        Either::B(id) => u8::try_from(id)
+            .map_err(|_| ZkError::InvalidInput(format!("Unknown device type id: {}", id)))
+            .and_then(DeviceType::try_from)?,
+    };
+    Ok(configure_for_device(device).to_json(num_proofs as usize))
+}
+
+/// Estimated serialized size in bytes of a proof for circuit size `k` with
+/// `num_instance` instance columns
+///
+/// Assumes a circuit shaped like the trust score circuit; compare with
+/// `measure_trust_score_proof_size` to calibrate.
+#[napi]
+pub fn get_estimated_proof_size(k: u32, num_instance: u32) -> u32 {
+    estimate_proof_size_bytes(k, num_instance as usize) as u32
+}
+
+/// Length in bytes of a trust score proof from the initialized system,
+/// without the envelope
+#[napi]
+pub fn measure_trust_score_proof_size() -> Result<u32> {
+    Ok(zk_system()?.measure_proof_size_bytes()? as u32)
 }
 
 /// Test the trust score circuit with mock prover (for testing)
 #[napi]
 pub fn test_trust_score_circuit(trust_score: u32, threshold: u32) -> Result<bool> {
-    let k = 4;
+    validate_trust_inputs(trust_score as u64, threshold as u64)?;
+    let k = TRUST_SCORE_K;
     let circuit = TrustScoreCircuit::<Fp>::new(Some(trust_score as u64), threshold as u64);
     
-    // Determine expected result
-    let expected_result = if trust_score >= threshold {
-        Fp::one()
-    } else {
-        Fp::zero()
-    };
-    
-    let public_inputs = vec![expected_result];
-    
-    match MockProver::run(k, &circuit, vec![public_inputs]) {
+    match MockProver::run(k, &circuit, vec![circuit.expected_public_inputs()]) {
         Ok(prover) => {
             match prover.verify() {
                 Ok(_) => Ok(true),
@@ -154,134 +895,1093 @@ pub fn test_trust_score_circuit(trust_score: u32, threshold: u32) -> Result<bool
             }
         }
         Err(e) => {
-            Err(Error::new(Status::GenericFailure, format!("Failed to run mock prover: {:?}", e)))
+            Err(ZkError::ProofFailed(format!("Failed to run mock prover: {:?}", e)).into())
         }
     }
 }
 
-// C-compatible FFI functions for direct integration
-extern "C" {
-    fn free(ptr: *mut std::ffi::c_void);
+/// Run the trust score circuit through the mock prover and report every
+/// constraint it fails
+///
+/// Returns JSON `{ satisfied, failures }`, each failure naming its `kind`,
+/// `gate`, `region` and `row` as reported by halo2, for debugging witnesses
+/// that `test_trust_score_circuit` only reports as unsatisfied. Unlike the
+/// other bindings it does not validate its inputs, so out-of-range values
+/// show the constraints they break.
+#[napi]
+pub fn dry_run_trust_score_with_diagnostics(trust_score: u32, threshold: u32) -> Result<String> {
+    let circuit = TrustScoreCircuit::<Fp>::new(Some(trust_score as u64), threshold as u64);
+    Ok(dry_run_with_diagnostics(&circuit)?.to_json())
+}
+
+/// Run a circuit through the `MockProver` against its own public inputs
+fn dry_run<C: LendingCircuit<Fp>>(circuit: C) -> Result<DryRunResult> {
+    let satisfied = match run_mock(&circuit) {
+        Ok(()) => true,
+        Err(ZkError::VerifyFailed(_)) => false,
+        Err(e) => return Err(e.into()),
+    };
+
+    Ok(DryRunResult {
+        satisfied,
+        result: satisfied && circuit.public_inputs()[0] == Fp::one(),
+    })
+}
+
+/// Read a field element argument encoded by `fp_to_le_bytes`
+fn field_argument(name: &str, bytes: &[u8]) -> std::result::Result<Fp, ZkError> {
+    let bytes = bytes
+        .try_into()
+        .map_err(|_| ZkError::InvalidInput(format!("{} must be one 32 byte field element", name)))?;
+    fp_from_le_bytes(bytes)
+}
+
+/// Check whether an income lies in `[minRange, maxRange]` without proving it
+///
+/// Runs the circuit through the mock prover, so it is cheap and needs no
+/// initialized system. The values arrive as JavaScript numbers and must be
+/// non-negative integers, and the range must not be inverted.
+#[napi]
+pub fn dry_run_income_range(income: i64, min_range: i64, max_range: i64) -> Result<DryRunResult> {
+    let [income, min_range, max_range] = [("Income", income), ("Minimum range", min_range), ("Maximum range", max_range)]
+        .map(|(name, value)| u128::try_from(value).map_err(|_| ZkError::InvalidInput(format!("{} {} must not be negative", name, value))));
+
+    let (income, min_range, max_range) = (income?, min_range?, max_range?);
+    validate_income_range_inputs(income, min_range, max_range)?;
+
+    dry_run(IncomeRangeCircuit::<Fp>::new(Some(income), min_range, max_range))
+}
+
+/// Check whether identity data opens a commitment without proving it
+///
+/// `nonce` and `commitment` are 32-byte little-endian field elements, as in
+/// an instance column; the identity data is hashed as for the commitment.
+/// Needs no initialized system.
+#[napi]
+pub fn dry_run_identity(
+    identity_data: Vec<u8>,
+    nonce: Vec<u8>,
+    commitment: Vec<u8>,
+    domain_separator: u32,
+    context_id: u32,
+) -> Result<DryRunResult> {
+    let nonce = field_argument("Nonce", &nonce)?;
+    let commitment = field_argument("Commitment", &commitment)?;
+
+    dry_run(IdentityCircuit::new(
+        Some(hash_identity(&identity_data)),
+        Some(nonce),
+        commitment,
+        domain_separator as u64,
+        context_id as u64,
+    ))
+}
+
+/// Check whether a loan history meets a minimum success rate without proving it
+///
+/// `min_success_rate` is in basis points (percentage * 100). A history with
+/// more repayments than loans is invalid input. Needs no initialized system.
+#[napi]
+pub fn dry_run_loan_history(
+    num_loans: u32,
+    successful_repayments: u32,
+    min_success_rate: u32,
+    min_loans: u32,
+) -> Result<DryRunResult> {
+    validate_loan_history_inputs(num_loans as u64, successful_repayments as u64, min_success_rate as u64, min_loans as u64)?;
+
+    dry_run(LoanHistoryCircuit::<Fp>::new(
+        Some(num_loans as u64),
+        Some(successful_repayments as u64),
+        min_success_rate as u64,
+        min_loans as u64,
+    ))
 }
 
+// C-compatible FFI functions for direct integration
+
 /// C-compatible function to generate trust score proof
+///
+/// The proof is wrapped in a proof envelope, as for `generate_trust_score_proof`.
+///
+/// Initializes the ZK system on first use. On failure `success` is false,
+/// `error_code` holds the `ZkError` code and `error_message` describes the
+/// error. Release the result with `free_proof_result`.
 #[no_mangle]
 pub extern "C" fn generate_trust_proof(
     trust_score: u64,
     threshold: u64,
 ) -> *mut ProofResult {
-    let result = Box::new(ProofResult {
+    proof_result(zk_system_or_init().and_then(|system| system.prove_trust_score(trust_score, threshold)))
+}
+
+/// Box a proof (or its error) as a live `ProofResult`
+fn proof_result(proof: std::result::Result<Vec<u8>, ZkError>) -> *mut ProofResult {
+    let mut result = Box::new(ProofResult {
         success: false,
         proof_data: std::ptr::null_mut(),
         proof_len: 0,
         error_message: std::ptr::null_mut(),
+        error_code: 0,
+        magic: PROOF_RESULT_MAGIC,
     });
-    
-    // For this demo, we'll use the mock prover approach
-    let k = 4;
-    let circuit = TrustScoreCircuit::<Fp>::new(Some(trust_score), threshold);
-    
-    let expected_result = if trust_score >= threshold {
-        Fp::one()
-    } else {
-        Fp::zero()
-    };
-    
-    let public_inputs = vec![expected_result];
-    
-    match MockProver::run(k, &circuit, vec![public_inputs]) {
-        Ok(prover) => {
-            match prover.verify() {
-                Ok(_) => {
-                    // Create a dummy proof for demonstration
-                    let proof_data = b"mock_proof_data".to_vec();
-                    let proof_len = proof_data.len();
-                    
-                    let mut result = result;
-                    result.success = true;
-                    result.proof_len = proof_len;
-                    
-                    // Allocate memory for proof data
-                    let proof_ptr = unsafe {
-                        libc::malloc(proof_len) as *mut u8
-                    };
-                    
-                    if !proof_ptr.is_null() {
-                        unsafe {
-                            std::ptr::copy_nonoverlapping(proof_data.as_ptr(), proof_ptr, proof_len);
-                        }
-                        result.proof_data = proof_ptr;
-                    }
-                    
-                    Box::into_raw(result)
-                }
-                Err(e) => {
-                    let error_msg = CString::new(format!("Circuit verification failed: {:?}", e))
-                        .unwrap_or_else(|_| CString::new("Unknown error").unwrap());
-                    let mut result = result;
-                    result.error_message = error_msg.into_raw();
-                    Box::into_raw(result)
+
+    match proof {
+        Ok(proof_data) => {
+            // Allocate memory for proof data
+            let proof_len = proof_data.len();
+            let proof_ptr = unsafe {
+                libc::malloc(proof_len) as *mut u8
+            };
+
+            if proof_ptr.is_null() {
+                let error = ZkError::ProofFailed("Failed to allocate proof buffer".into());
+                result.error_message = error_message(&error.to_string());
+                result.error_code = error.code();
+            } else {
+                unsafe {
+                    std::ptr::copy_nonoverlapping(proof_data.as_ptr(), proof_ptr, proof_len);
                 }
+                result.success = true;
+                result.proof_data = proof_ptr;
+                result.proof_len = proof_len;
             }
         }
-        Err(e) => {
-            let error_msg = CString::new(format!("Mock prover failed: {:?}", e))
-                .unwrap_or_else(|_| CString::new("Unknown error").unwrap());
-            let mut result = result;
-            result.error_message = error_msg.into_raw();
-            Box::into_raw(result)
+        Err(error) => {
+            result.error_message = error_message(&error.to_string());
+            result.error_code = error.code();
+        }
+    }
+
+    let result = Box::into_raw(result);
+    LIVE_PROOF_RESULTS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(result as usize);
+    result
+}
+
+/// C-compatible function to create a prover handle for circuit size `k`
+///
+/// Returns null if `k` is out of range or key generation fails. Prove with
+/// `generate_trust_proof_with_handle` and release the handle with
+/// `destroy_prover_handle`.
+#[no_mangle]
+pub extern "C" fn new_prover_handle(k: u32) -> *mut ProverHandle {
+    match ProverHandle::new(Some(k)) {
+        Ok(handle) => {
+            let handle = Box::into_raw(Box::new(handle));
+            LIVE_PROVER_HANDLES
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .insert(handle as usize);
+            handle
         }
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// C-compatible function to generate a trust score proof with a handle's keys
+///
+/// As `generate_trust_proof`, but never touches the global setup state. A
+/// null or destroyed handle fails with an invalid input error. Release the
+/// result with `free_proof_result`.
+///
+/// # Safety
+///
+/// `handle` must be null or a pointer returned by `new_prover_handle`, and
+/// must not be destroyed while this call runs.
+#[no_mangle]
+pub unsafe extern "C" fn generate_trust_proof_with_handle(
+    handle: *const ProverHandle,
+    trust_score: u64,
+    threshold: u64,
+) -> *mut ProofResult {
+    let live = LIVE_PROVER_HANDLES
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .contains(&(handle as usize));
+    if !live {
+        return proof_result(Err(ZkError::InvalidInput("Not a live prover handle".into())));
+    }
+
+    let handle = unsafe { &*handle };
+    proof_result(handle.system().and_then(|system| system.prove_trust_score(trust_score, threshold)))
+}
+
+/// Destroy a prover handle, releasing its keys
+///
+/// Destroying a handle twice, or a pointer `new_prover_handle` never
+/// returned, is logged and otherwise ignored.
+///
+/// # Safety
+///
+/// `handle` must be null or a pointer returned by `new_prover_handle`.
+#[no_mangle]
+pub unsafe extern "C" fn destroy_prover_handle(handle: *mut ProverHandle) {
+    if handle.is_null() {
+        return;
     }
+
+    let live = LIVE_PROVER_HANDLES
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(&(handle as usize));
+    if !live {
+        eprintln!("destroy_prover_handle: ignoring {:p}, not a live prover handle", handle);
+        return;
+    }
+
+    drop(unsafe { Box::from_raw(handle) });
+}
+
+/// Allocate a C string for `ProofResult::error_message`
+fn error_message(message: &str) -> *mut c_char {
+    CString::new(message)
+        .unwrap_or_else(|_| CString::new("Unknown error").unwrap())
+        .into_raw()
 }
 
 /// C-compatible function to verify trust score proof
+///
+/// Returns 1 only if the proof verifies for `threshold` and `expected_result`,
+/// and 0 for invalid, truncated or null input. Initializes the ZK system on
+/// first use.
+///
+/// # Safety
+///
+/// `proof_data` must be null or point to `proof_len` readable bytes.
 #[no_mangle]
-pub extern "C" fn verify_trust_proof(
+pub unsafe extern "C" fn verify_trust_proof(
     proof_data: *const u8,
     proof_len: usize,
-    _threshold: u64,
-    _expected_result: bool,
+    threshold: u64,
+    expected_result: bool,
 ) -> c_int {
     if proof_data.is_null() || proof_len == 0 {
         return 0; // false
     }
-    
-    // For this demo, we'll just check if the proof data matches our expected format
-    let expected_proof = b"mock_proof_data";
-    
-    if proof_len == expected_proof.len() {
-        let proof_slice = unsafe {
-            std::slice::from_raw_parts(proof_data, proof_len)
-        };
-        
-        if proof_slice == expected_proof {
-            return 1; // true
-        }
+
+    let proof_slice = unsafe {
+        std::slice::from_raw_parts(proof_data, proof_len)
+    };
+
+    // Never unwind across the FFI boundary
+    let verified = std::panic::catch_unwind(|| {
+        zk_system_or_init()
+            .ok()
+            .and_then(|system| system.verify_trust_score(proof_slice, threshold, expected_result).ok())
+            .unwrap_or(false)
+    });
+
+    match verified {
+        Ok(true) => 1,
+        _ => 0,
     }
-    
-    0 // false
 }
 
 /// Free memory allocated by proof generation
+///
+/// Freeing a result twice, or a pointer `generate_trust_proof` never
+/// returned, is logged and otherwise ignored.
+///
+/// # Safety
+///
+/// `result` must be null or a pointer returned by `generate_trust_proof`.
 #[no_mangle]
-pub extern "C" fn free_proof_result(result: *mut ProofResult) {
+pub unsafe extern "C" fn free_proof_result(result: *mut ProofResult) {
     if result.is_null() {
         return;
     }
-    
+
+    let live = LIVE_PROOF_RESULTS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(&(result as usize));
+    if !live || unsafe { (*result).magic } != PROOF_RESULT_MAGIC {
+        eprintln!("free_proof_result: ignoring {:p}, not a live proof result", result);
+        return;
+    }
+
     unsafe {
-        let result = Box::from_raw(result);
-        
+        let mut result = Box::from_raw(result);
+        result.magic = 0;
+
         // Free proof data if allocated
         if !result.proof_data.is_null() {
             libc::free(result.proof_data as *mut std::ffi::c_void);
+            result.proof_data = std::ptr::null_mut();
+            result.proof_len = 0;
         }
-        
+
         // Free error message if allocated
         if !result.error_message.is_null() {
             let _ = CString::from_raw(result.error_message);
+            result.error_message = std::ptr::null_mut();
         }
-        
+
         // result is automatically dropped here
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::envelope::{decode_proof, encode_proof, CircuitTag, HEADER_LEN};
+    use crate::api::trust_score_proof;
+    use crate::prover::encode_instance_column;
+    use crate::keys::TrustScoreKeys;
+    use napi::{Error, Status};
+
+    /// Initialize the shared system, tolerating initialization by another test
+    fn ensure_initialized() {
+        let _ = initialize_zk_system(Some(TRUST_SCORE_K));
+        assert!(ZK_SYSTEM.get().is_some());
+    }
+
+    #[test]
+    fn test_initialize_twice_fails() {
+        ensure_initialized();
+        assert!(initialize_zk_system(Some(TRUST_SCORE_K)).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_trust_score_params_json() {
+        let params: TrustScoreParams = serde_json::from_str(r#"{"trustScore":85,"threshold":70}"#).unwrap();
+        assert_eq!((params.trust_score, params.threshold), (85, 70));
+        assert_eq!(serde_json::to_string(&params).unwrap(), r#"{"trustScore":85,"threshold":70}"#);
+
+        let err = serde_json::from_str::<TrustScoreParams>(r#"{"trustScore":500,"threshold":70}"#).unwrap_err();
+        assert!(err.to_string().contains("trust score 500 exceeds the maximum of 100"));
+    }
+
+    #[test]
+    fn test_circuit_size_validation() {
+        assert_eq!(circuit_size(None), Ok(get_recommended_k(DeviceType::Desktop)));
+        assert_eq!(circuit_size(Some(TRUST_SCORE_K)), Ok(TRUST_SCORE_K));
+        assert_eq!(circuit_size(Some(MAX_K)), Ok(MAX_K));
+        assert!(circuit_size(Some(TRUST_SCORE_K - 1)).is_err());
+        assert!(circuit_size(Some(MAX_K + 1)).is_err());
+
+        // Out-of-range sizes are rejected before any setup work
+        assert_eq!(initialize_zk_system(Some(MAX_K + 1)).unwrap_err().status, Status::InvalidArg);
+    }
+
+    #[test]
+    fn test_proofs_rejected_across_circuit_sizes() {
+        ensure_initialized();
+        let proof = generate_trust_score_proof(85, 70).unwrap();
+        assert!(verify_trust_score_proof(proof.clone(), 70, true, None).unwrap());
+
+        // Keys for another k neither accept this proof nor produce ones we accept
+        let other = init_system(TRUST_SCORE_K + 1).unwrap();
+        assert!(!other.verify_trust_score(&proof, 70, true).unwrap());
+
+        let other_proof = other.prove_trust_score(85, 70).unwrap();
+        assert!(other.verify_trust_score(&other_proof, 70, true).unwrap());
+        assert!(!verify_trust_score_proof(other_proof, 70, true, None).unwrap());
+    }
+
+    #[test]
+    fn test_trust_score_range_validated() {
+        ensure_initialized();
+
+        let err = generate_trust_score_proof(150, 70).unwrap_err();
+        assert_eq!(err.status, Status::InvalidArg);
+        assert!(err.reason.contains("trust score 150 exceeds the maximum of 100"));
+        assert!(generate_trust_score_proof(85, 150).is_err());
+
+        let proof = generate_trust_score_proof(100, 70).unwrap();
+        assert!(verify_trust_score_proof(proof, 70, true, None).unwrap());
+    }
+
+    #[test]
+    fn test_save_keys_then_reject_reload() {
+        ensure_initialized();
+        let path = std::env::temp_dir().join(format!("zk-ffi-keys-{}.bin", std::process::id()));
+        let path = path.to_str().unwrap();
+
+        assert!(save_zk_keys(path.to_string()).unwrap());
+        let saved = TrustScoreKeys::load(path).unwrap();
+        assert_eq!(crate::registry::vk_fingerprint(&saved.verifying_key), get_verifying_key_fingerprint().unwrap());
+
+        // Keys cannot be swapped under a running system
+        assert_eq!(load_keys(path), Err(ZkError::InvalidInput("ZK system already initialized".into())));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_concurrent_proof_generation() {
+        ensure_initialized();
+
+        let handles: Vec<_> = (0..4u32)
+            .map(|i| std::thread::spawn(move || {
+                let score = 60 + i * 10;
+                (score >= 70, generate_trust_score_proof(score, 70).unwrap())
+            }))
+            .collect();
+
+        for handle in handles {
+            let (expected, proof) = handle.join().unwrap();
+            assert!(verify_trust_score_proof(proof, 70, expected, None).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_async_proofs_in_flight_concurrently() {
+        ensure_initialized();
+        let runtime = napi::tokio::runtime::Runtime::new().unwrap();
+
+        // Both tasks are spawned before either is awaited
+        let above = runtime.spawn(generate_trust_score_proof_async(85, 70, None));
+        let below = runtime.spawn(generate_trust_score_proof_async(65, 70, None));
+        let (above, below) = runtime.block_on(async { (above.await.unwrap(), below.await.unwrap()) });
+
+        assert!(verify_trust_score_proof(above.unwrap(), 70, true, None).unwrap());
+        assert!(verify_trust_score_proof(below.unwrap(), 70, false, None).unwrap());
+
+        // Input errors still come back through the promise
+        let err = runtime.block_on(generate_trust_score_proof_async(500, 70, None)).unwrap_err();
+        assert_eq!(err.status, Status::InvalidArg);
+    }
+
+    #[test]
+    fn test_async_proofs_cancelled() {
+        ensure_initialized();
+        let runtime = napi::tokio::runtime::Runtime::new().unwrap();
+        let token = create_cancellation_token();
+        cancel_proofs(token).unwrap();
+
+        let err = runtime.block_on(generate_trust_score_proof_async(85, 70, Some(token))).unwrap_err();
+        assert!(err.reason.starts_with("[ZK12]"));
+
+        let inputs = || (0..3).map(|_| TrustScoreInput { trust_score: 85, threshold: 70 }).collect();
+        let err = runtime.block_on(generate_trust_score_proofs_batch_async(inputs(), Some(token))).unwrap_err();
+        assert!(err.reason.starts_with("[ZK12]"));
+        free_cancellation_token(token);
+
+        // Without a token the batch runs to completion
+        let proofs = runtime.block_on(generate_trust_score_proofs_batch_async(inputs(), None)).unwrap();
+        assert_eq!(proofs.len(), 3);
+    }
+
+    #[test]
+    fn test_seeded_proofs_are_reproducible() {
+        ensure_initialized();
+        let system = zk_system().unwrap();
+
+        let proof = prove_seeded(system, 85, 70, 42).unwrap();
+        assert_eq!(proof, prove_seeded(system, 85, 70, 42).unwrap());
+        assert_ne!(proof, prove_seeded(system, 85, 70, 43).unwrap());
+        assert!(verify_trust_score_proof(proof.clone(), 70, true, None).unwrap());
+
+        let seeded = generate_trust_score_proof_seeded(85, 70, 42).unwrap();
+        assert_eq!(seeded, proof);
+
+        let err = generate_trust_score_proof_seeded(85, 70, -1).unwrap_err();
+        assert_eq!(err.status, Status::InvalidArg);
+    }
+
+    #[test]
+    fn test_proof_size() {
+        ensure_initialized();
+        let k = zk_system().unwrap().keys().params.k();
+
+        let measured = measure_trust_score_proof_size().unwrap();
+        let proof = generate_trust_score_proof(85, 70).unwrap();
+        assert_eq!(proof.len(), HEADER_LEN + measured as usize);
+        assert_eq!(get_estimated_proof_size(k, 1), measured);
+    }
+
+    #[test]
+    fn test_inspect_proof() {
+        ensure_initialized();
+        let proof = generate_trust_score_proof(85, 70).unwrap();
+
+        let info = inspect_proof(proof.clone()).unwrap();
+        assert_eq!(info.circuit, "trust_score");
+        assert_eq!(info.version, crate::envelope::PROOF_FORMAT_VERSION as u32);
+        assert_eq!(info.proof_len as usize, proof.len() - HEADER_LEN);
+
+        assert!(inspect_proof(proof[..HEADER_LEN - 1].to_vec()).is_err());
+    }
+
+    #[test]
+    fn test_verify_proof_raw() {
+        ensure_initialized();
+        let proof = generate_trust_score_proof(85, 70).unwrap();
+
+        let public_inputs = |result: u64| vec![encode_instance_column(&[Fp::from(result), Fp::from(70)])];
+        assert!(verify_proof_raw(proof.clone(), public_inputs(1)).unwrap());
+        assert!(!verify_proof_raw(proof.clone(), public_inputs(0)).unwrap());
+
+        let err = verify_proof_raw(proof, vec![vec![1, 2, 3]]).unwrap_err();
+        assert_eq!(err.status, Status::GenericFailure);
+    }
+
+    #[test]
+    fn test_batch_proof_generation() {
+        ensure_initialized();
+        let inputs = [
+            TrustScoreParams { trust_score: 85, threshold: 70 },
+            TrustScoreParams { trust_score: 65, threshold: 70 },
+            TrustScoreParams { trust_score: 500, threshold: 70 },
+            TrustScoreParams { trust_score: 70, threshold: 70 },
+        ];
+
+        // Batches of one exercise the chunking across several batches
+        let results = prove_trust_scores_batch(&inputs, DeviceType::LowEndMobile).unwrap();
+        assert_eq!(results.len(), inputs.len());
+
+        let proof = |i: usize| results[i].as_ref().unwrap().clone();
+        assert!(verify_trust_score_proof(proof(0), 70, true, None).unwrap());
+        assert!(verify_trust_score_proof(proof(1), 70, false, None).unwrap());
+        assert!(!verify_trust_score_proof(proof(1), 70, true, None).unwrap());
+        assert!(matches!(results[2], Err(ZkError::InvalidInput(_))));
+        assert!(verify_trust_score_proof(proof(3), 70, true, None).unwrap());
+
+        let runtime = napi::tokio::runtime::Runtime::new().unwrap();
+        let batch = vec![
+            TrustScoreInput { trust_score: 85, threshold: 70 },
+            TrustScoreInput { trust_score: 200, threshold: 70 },
+        ];
+        let (sender, progress) = std::sync::mpsc::channel();
+        let results = runtime
+            .block_on(batch_proof_results(batch, move |done, total| sender.send((done, total)).unwrap()))
+            .unwrap();
+        assert_eq!(progress.iter().collect::<Vec<_>>(), vec![(1, 2), (2, 2)]);
+        assert!(results[0].proof.is_some() && results[0].error.is_none());
+        assert!(results[1].proof.is_none() && results[1].error.is_some());
+    }
+
+    #[test]
+    fn test_batch_progress_reported_in_order() {
+        ensure_initialized();
+        let inputs: Vec<_> = [85, 65, 500, 70, 90]
+            .into_iter()
+            .map(|trust_score| TrustScoreParams { trust_score, threshold: 70 })
+            .collect();
+
+        // Batches of one and a batch of all five
+        for device_type in [DeviceType::LowEndMobile, DeviceType::Desktop] {
+            let mut calls = Vec::new();
+            let results = prove_trust_scores_batch_with_progress(&inputs, device_type, |done, total| calls.push((done, total))).unwrap();
+            assert_eq!(results.len(), inputs.len());
+            assert_eq!(calls, (1..=inputs.len()).map(|done| (done, inputs.len())).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn test_batch_verification_matches_individual() {
+        ensure_initialized();
+
+        let mut proofs = Vec::new();
+        let mut thresholds = Vec::new();
+        let mut expected = Vec::new();
+        for i in 0..10u32 {
+            let score = 50 + i * 5;
+            proofs.push(generate_trust_score_proof(score, 70).unwrap());
+            thresholds.push(70);
+            expected.push(score >= 70);
+        }
+
+        let batched = verify_trust_score_proofs_batch(proofs.clone(), thresholds.clone(), expected.clone()).unwrap();
+        assert_eq!(batched, vec![true; 10]);
+
+        // Break two claims: a wrong expected result and a tampered proof
+        expected[3] = !expected[3];
+        proofs[7][HEADER_LEN] ^= 0x01;
+        let batched = verify_trust_score_proofs_batch(proofs.clone(), thresholds.clone(), expected.clone()).unwrap();
+
+        let individual: Vec<bool> = (0..10)
+            .map(|i| verify_trust_score_proof(proofs[i].clone(), thresholds[i], expected[i], None).unwrap())
+            .collect();
+
+        assert_eq!(batched, individual);
+        assert_eq!(batched.iter().filter(|&&ok| !ok).count(), 2);
+
+        let err = verify_trust_score_proofs_batch(proofs, thresholds, vec![true]).unwrap_err();
+        assert_eq!(err.status, Status::InvalidArg);
+    }
+
+    #[test]
+    fn test_batch_verification_modes() {
+        ensure_initialized();
+
+        let thresholds = vec![50, 70, 90];
+        let mut proofs: Vec<_> = thresholds.iter().map(|&threshold| generate_trust_score_proof(95, threshold).unwrap()).collect();
+        let expected = vec![true; 3];
+        let verify = |proofs: &Vec<Vec<u8>>, mode: &str| {
+            verify_trust_score_proofs_batch_mode(proofs.clone(), thresholds.clone(), expected.clone(), mode.into()).unwrap()
+        };
+
+        assert!(matches!(verify(&proofs, "all_or_nothing"), Either::A(true)));
+        assert!(matches!(verify(&proofs, "report"), Either::B(ref verified) if verified == &vec![true; 3]));
+
+        proofs[1][HEADER_LEN] ^= 0x01;
+        assert!(matches!(verify(&proofs, "all_or_nothing"), Either::A(false)));
+        assert!(matches!(verify(&proofs, "report"), Either::B(ref verified) if verified == &vec![true, false, true]));
+
+        let err = verify_trust_score_proofs_batch_mode(proofs, thresholds, expected, "first_failure".into()).unwrap_err();
+        assert_eq!(err.status, Status::InvalidArg);
+        assert!(err.reason.contains("Unknown batch mode"));
+    }
+
+    #[test]
+    fn test_c_generate_round_trip() {
+        ensure_initialized();
+        let result = generate_trust_proof(85, 70);
+        assert!(!result.is_null());
+
+        unsafe {
+            let proof_result = &*result;
+            assert!(proof_result.success);
+            assert!(proof_result.error_message.is_null());
+            assert_eq!(proof_result.error_code, 0);
+            assert!(proof_result.proof_len > 0);
+
+            let proof_data = proof_result.proof_data;
+            let proof_len = proof_result.proof_len;
+            assert_eq!(verify_trust_proof(proof_data, proof_len, 70, true), 1);
+            assert_eq!(verify_trust_proof(proof_data, proof_len, 70, false), 0);
+            assert_eq!(verify_trust_proof(proof_data, proof_len, 80, true), 0);
+
+            free_proof_result(result);
+        }
+    }
+
+    #[test]
+    fn test_prover_handle_proves_many() {
+        ensure_initialized();
+        let handle = new_prover_handle(TRUST_SCORE_K);
+        assert!(!handle.is_null());
+
+        for i in 0..100u64 {
+            let (trust_score, threshold) = (i, 50);
+            unsafe {
+                let result = generate_trust_proof_with_handle(handle, trust_score, threshold);
+                assert!((*result).success);
+
+                // Handle proofs verify against the global keys for the same k
+                if i % 25 == 0 {
+                    let proof = std::slice::from_raw_parts((*result).proof_data, (*result).proof_len);
+                    assert!(zk_system().unwrap().verify_trust_score(proof, threshold, trust_score >= threshold).unwrap());
+                }
+                free_proof_result(result);
+            }
+        }
+
+        unsafe {
+            destroy_prover_handle(handle);
+            destroy_prover_handle(handle);
+
+            let result = generate_trust_proof_with_handle(handle, 85, 70);
+            assert!(!(*result).success);
+            assert_eq!((*result).error_code, ZkError::InvalidInput(String::new()).code());
+            free_proof_result(result);
+        }
+        assert!(new_prover_handle(MAX_K + 1).is_null());
+    }
+
+    #[test]
+    fn test_napi_prover_handle() {
+        ensure_initialized();
+        let handle = create_prover_handle(Some(TRUST_SCORE_K)).unwrap();
+        let proof = prove_with_handle(handle, 85, 70).unwrap();
+        assert!(zk_system().unwrap().verify_trust_score(&proof, 70, true).unwrap());
+
+        assert!(create_prover_handle(Some(MAX_K + 1)).is_err());
+    }
+
+    #[test]
+    fn test_free_proof_result_twice() {
+        ensure_initialized();
+        for (trust_score, success) in [(85, true), (500, false)] {
+            let result = generate_trust_proof(trust_score, 70);
+            unsafe {
+                assert_eq!((*result).success, success);
+                assert_eq!((*result).magic, PROOF_RESULT_MAGIC);
+
+                free_proof_result(result);
+                free_proof_result(result);
+            }
+            assert!(!LIVE_PROOF_RESULTS.lock().unwrap().contains(&(result as usize)));
+        }
+
+        // A result the library did not allocate is left alone
+        let mut foreign = ProofResult {
+            success: false,
+            proof_data: std::ptr::null_mut(),
+            proof_len: 0,
+            error_message: std::ptr::null_mut(),
+            error_code: 0,
+            magic: PROOF_RESULT_MAGIC,
+        };
+        unsafe {
+            free_proof_result(&mut foreign);
+            free_proof_result(std::ptr::null_mut());
+        }
+        assert_eq!(foreign.magic, PROOF_RESULT_MAGIC);
+    }
+
+    #[test]
+    fn test_dry_run_income_range() {
+        let dry_run = |income, min_range, max_range| {
+            let result = dry_run_income_range(income, min_range, max_range).unwrap();
+            assert!(result.satisfied);
+            result.result
+        };
+
+        assert!(dry_run(50000, 30000, 80000));
+        assert!(!dry_run(25000, 30000, 80000));
+        assert!(!dry_run(90000, 30000, 80000));
+        assert!(dry_run(30000, 30000, 80000));
+        assert!(dry_run(80000, 30000, 80000));
+        assert!(dry_run(256, 255, 80000));
+
+        let err = dry_run_income_range(-1, 30000, 80000).unwrap_err();
+        assert!(err.reason.contains("Income -1 must not be negative"));
+    }
+
+    #[test]
+    fn test_dry_run_trust_score_with_diagnostics() {
+        let json: serde_json::Value = serde_json::from_str(&dry_run_trust_score_with_diagnostics(85, 70).unwrap()).unwrap();
+        assert_eq!(json["satisfied"], true);
+        assert_eq!(json["failures"], serde_json::json!([]));
+
+        // A score above the maximum fails the bound gate
+        let json: serde_json::Value = serde_json::from_str(&dry_run_trust_score_with_diagnostics(150, 70).unwrap()).unwrap();
+        assert_eq!(json["satisfied"], false);
+        assert!(json["failures"].as_array().unwrap().iter().any(|failure| failure["gate"].is_string()));
+    }
+
+    #[test]
+    fn test_dry_run_identity() {
+        use crate::circuits::identity::utils::create_commitment;
+        use crate::prover::encode_instance_column;
+
+        let field = |value: Fp| encode_instance_column(&[value]);
+        let nonce = Fp::from(12345u64);
+        let commitment = create_commitment(b"user123@example.com", nonce, 1);
+
+        let dry_run = |data: &[u8], nonce: Fp, context_id| {
+            let result = dry_run_identity(data.to_vec(), field(nonce), field(commitment), 2024, context_id).unwrap();
+            assert!(result.satisfied);
+            result.result
+        };
+
+        assert!(dry_run(b"user123@example.com", nonce, 1));
+        assert!(!dry_run(b"wrong_user", nonce, 1));
+        assert!(!dry_run(b"user123@example.com", nonce + Fp::one(), 1));
+        assert!(!dry_run(b"user123@example.com", nonce, 2));
+
+        assert!(dry_run_identity(b"user".to_vec(), vec![0; 31], field(commitment), 2024, 1).is_err());
+        assert!(dry_run_identity(b"user".to_vec(), vec![0xff; 32], field(commitment), 2024, 1).is_err());
+    }
+
+    #[test]
+    fn test_dry_run_loan_history() {
+        let dry_run = |num_loans, repayments, min_success_rate, min_loans| {
+            dry_run_loan_history(num_loans, repayments, min_success_rate, min_loans).unwrap()
+        };
+
+        assert!(dry_run(10, 9, 8000, 1).result);
+        assert!(!dry_run(10, 6, 8000, 1).result);
+        assert!(!dry_run(0, 0, 8000, 1).result);
+        assert!(dry_run(5, 5, 9000, 1).result);
+        assert!(!dry_run(3, 2, 6667, 1).result);
+        assert!(dry_run(3, 2, 6666, 1).result);
+        assert!(!dry_run(1, 1, 9000, 3).result);
+
+        // More repayments than loans cannot be proven at all
+        let err = dry_run_loan_history(3, 5, 8000, 1).unwrap_err();
+        assert_eq!(err.status, Status::InvalidArg);
+        assert!(err.reason.contains("successful repayments 5 exceed the number of loans 3"));
+    }
+
+    #[test]
+    fn test_inputs_validated_before_use() {
+        fn invalid<T>(result: Result<T>) -> String {
+            let err = result.err().unwrap();
+            assert_eq!(err.status, Status::InvalidArg);
+            assert!(err.reason.starts_with("[ZK5] Invalid input: "), "{}", err.reason);
+            err.reason
+        }
+
+        // Rejected before the system is consulted, so these need no setup
+        assert!(invalid(generate_trust_score_proof(101, 70)).contains("trust score 101 exceeds the maximum of 100"));
+        assert!(invalid(generate_trust_score_proof_at_epoch(85, 101, 1)).contains("threshold 101"));
+        assert!(invalid(verify_trust_score_proof(vec![], 101, true, None)).contains("threshold 101"));
+        assert!(invalid(verify_trust_scores_combined(vec![], vec![70, 101], vec![true, true])).contains("threshold 101"));
+        assert!(invalid(test_trust_score_circuit(101, 70)).contains("trust score 101"));
+        assert!(test_trust_score_circuit(100, 100).unwrap());
+
+        assert!(invalid(dry_run_income_range(5, 10, 1)).contains("min range 10 is above max range 1"));
+        assert!(dry_run_income_range(i64::MAX, 0, i64::MAX).unwrap().result);
+
+        assert!(invalid(dry_run_loan_history(1 << 16, 0, 8000, 1)).contains("number of loans 65536"));
+        assert!(invalid(dry_run_loan_history(10, 9, 8000, 1 << 16)).contains("minimum loans 65536"));
+        assert!(invalid(dry_run_loan_history(10, 9, 10_001, 1)).contains("minimum success rate 10001"));
+        assert!(dry_run_loan_history(65_535, 65_535, 10_000, 65_535).unwrap().result);
+    }
+
+    #[test]
+    fn test_error_codes() {
+        // A system that was never set up reports NotInitialized
+        let err = keys_in(&OnceLock::new()).err().unwrap();
+        assert_eq!(err, ZkError::NotInitialized);
+        assert_eq!(err.code(), 1);
+
+        let err = Error::from(err);
+        assert_eq!(err.status, Status::GenericFailure);
+        assert_eq!(err.reason, "[ZK1] ZK system not initialized");
+
+        // C callers get the code alongside the message
+        ensure_initialized();
+        let result = generate_trust_proof(500, 70);
+        unsafe {
+            let proof_result = &*result;
+            assert!(!proof_result.success);
+            assert_eq!(proof_result.error_code, ZkError::InvalidInput(String::new()).code());
+            free_proof_result(result);
+        }
+    }
+
+    #[test]
+    fn test_c_verify_rejects_tampered_proofs() {
+        ensure_initialized();
+        let proof = generate_trust_score_proof(85, 70).unwrap();
+        unsafe {
+            assert_eq!(verify_trust_proof(proof.as_ptr(), proof.len(), 70, true), 1);
+        }
+
+        for index in [0, proof.len() / 2, proof.len() - 1] {
+            let mut tampered = proof.clone();
+            tampered[index] ^= 0x01;
+            unsafe {
+                assert_eq!(verify_trust_proof(tampered.as_ptr(), tampered.len(), 70, true), 0);
+            }
+        }
+
+        unsafe {
+            // Truncated, empty and null buffers are rejected without panicking
+            assert_eq!(verify_trust_proof(proof.as_ptr(), proof.len() / 2, 70, true), 0);
+            assert_eq!(verify_trust_proof(proof.as_ptr(), 0, 70, true), 0);
+            assert_eq!(verify_trust_proof(std::ptr::null(), proof.len(), 70, true), 0);
+        }
+    }
+
+    #[test]
+    fn test_verify_with_pinned_fingerprint() {
+        ensure_initialized();
+        let fingerprint = get_verifying_key_fingerprint().unwrap();
+        let proof = generate_trust_score_proof(85, 70).unwrap();
+
+        assert!(verify_trust_score_proof_pinned(proof.clone(), 70, true, fingerprint.clone()).unwrap());
+
+        // A wrong pin fails loudly instead of returning a verification result
+        let wrong = "00".repeat(32);
+        let err = verify_trust_score_proof_pinned(proof, 70, true, wrong.clone()).unwrap_err();
+        assert!(err.reason.contains("fingerprint mismatch"));
+
+        // The proof only verifies against the threshold it was made for
+        let proof = generate_trust_score_proof(85, 70).unwrap();
+        assert!(!verify_trust_score_proof(proof, 80, true, None).unwrap());
+
+        let system = zk_system().unwrap();
+        assert_eq!(system.check_fingerprint(&fingerprint.to_uppercase()), Ok(()));
+        assert_eq!(
+            system.check_fingerprint(&wrong),
+            Err(ZkError::KeyVersionMismatch { expected: wrong, actual: fingerprint })
+        );
+    }
+
+    #[test]
+    fn test_proof_expires_with_epoch() {
+        ensure_initialized();
+        let proof = generate_trust_score_proof_at_epoch(85, 70, 100).unwrap();
+
+        assert!(verify_trust_score_proof_at_epoch(proof.clone(), 70, true, 100, None).unwrap());
+        assert!(!verify_trust_score_proof_at_epoch(proof.clone(), 70, false, 100, None).unwrap());
+
+        // Stale once the verifier moves on, unless still within the allowed age
+        assert!(!verify_trust_score_proof_at_epoch(proof.clone(), 70, true, 101, None).unwrap());
+        assert!(verify_trust_score_proof_at_epoch(proof.clone(), 70, true, 101, Some(1)).unwrap());
+        assert!(!verify_trust_score_proof_at_epoch(proof.clone(), 70, true, 102, Some(1)).unwrap());
+        assert!(!verify_trust_score_proof(proof, 70, true, None).unwrap());
+
+        // Plain proofs are made for epoch 0
+        let proof = generate_trust_score_proof(85, 70).unwrap();
+        assert!(verify_trust_score_proof_at_epoch(proof, 70, true, 0, None).unwrap());
+    }
+
+    #[test]
+    fn test_verify_with_optional_fingerprint() {
+        ensure_initialized();
+        let bytes = verifying_key_fingerprint().unwrap();
+        let fingerprint = get_verifying_key_fingerprint().unwrap();
+        assert_eq!(fingerprint, bytes.iter().map(|b| format!("{:02x}", b)).collect::<String>());
+
+        let proof = generate_trust_score_proof(85, 70).unwrap();
+        assert!(verify_trust_score_proof(proof, 70, true, Some(fingerprint)).unwrap());
+
+        // The mismatch is reported before the proof bytes are even looked at
+        let err = verify_trust_score_proof(vec![0xff; 8], 70, true, Some("00".repeat(32))).unwrap_err();
+        assert!(err.reason.starts_with("[ZK8]"));
+    }
+
+    #[test]
+    fn test_text_proof_round_trip() {
+        ensure_initialized();
+
+        let proof = generate_trust_score_proof_base64(85, 70).unwrap();
+        assert!(verify_trust_score_proof_base64(proof.clone(), 70, true, None).unwrap());
+        assert!(!verify_trust_score_proof_base64(proof, 70, false, None).unwrap());
+
+        let proof = generate_trust_score_proof_hex(85, 70).unwrap();
+        assert!(verify_trust_score_proof_hex(proof.clone(), 70, true, None).unwrap());
+
+        // The same bytes either way
+        let bytes = proof_from_hex(&proof).unwrap();
+        assert!(verify_trust_score_proof_base64(proof_to_base64(&bytes), 70, true, None).unwrap());
+
+        for err in [
+            verify_trust_score_proof_base64("not base64!".into(), 70, true, None).unwrap_err(),
+            verify_trust_score_proof_hex(format!("{}zz", proof), 70, true, None).unwrap_err(),
+        ] {
+            assert!(err.reason.starts_with("[ZK6]"), "{}", err.reason);
+        }
+    }
+
+    #[test]
+    fn test_verify_rejects_short_proofs() {
+        ensure_initialized();
+
+        for proof in [vec![], vec![0; 3]] {
+            let err = verify_trust_score_proof(proof.clone(), 70, true, None).unwrap_err();
+            assert_eq!(err.status, napi::Status::InvalidArg);
+            assert!(err.reason.starts_with("[ZK5]"));
+            assert!(verify_proof_raw(proof.clone(), vec![]).is_err());
+            assert_eq!(unsafe { verify_trust_proof(proof.as_ptr(), proof.len(), 70, true) }, 0);
+        }
+    }
+
+    #[test]
+    fn test_tampered_proof_does_not_verify() {
+        ensure_initialized();
+        let proof = generate_trust_score_proof(85, 70).unwrap();
+
+        // A flipped transcript byte is an invalid proof, not an error
+        let mut tampered = proof.clone();
+        tampered[HEADER_LEN + (proof.len() - HEADER_LEN) / 2] ^= 0x01;
+        assert!(!verify_trust_score_proof(tampered, 70, true, None).unwrap());
+
+        // A flipped magic byte is a parse error
+        let mut tampered = proof;
+        tampered[0] ^= 0x01;
+        let err = verify_trust_score_proof(tampered, 70, true, None).unwrap_err();
+        assert!(err.reason.starts_with("[ZK6]"), "{}", err.reason);
+    }
+
+    #[test]
+    fn test_verify_checks_envelope_header() {
+        ensure_initialized();
+        let proof = generate_trust_score_proof(85, 70).unwrap();
+        assert_eq!(decode_proof(&proof).unwrap().0, CircuitTag::TrustScore);
+
+        let err = verify_trust_score_proof(proof[..proof.len() - 1].to_vec(), 70, true, None).unwrap_err();
+        assert!(err.reason.starts_with("[ZK6]"));
+
+        let mut unknown = proof.clone();
+        unknown[5] = 0xee;
+        let err = verify_trust_score_proof(unknown, 70, true, None).unwrap_err();
+        assert_eq!(err.reason, "[ZK10] Unknown circuit tag 238 in proof");
+
+        let mut future = proof.clone();
+        future[4] += 1;
+        let err = verify_trust_score_proof(future, 70, true, None).unwrap_err();
+        assert!(err.reason.starts_with("[ZK9]"));
+
+        // A well-formed envelope for another circuit is not a trust score proof
+        let other = encode_proof(CircuitTag::IncomeRange, trust_score_proof(&proof).unwrap());
+        let err = verify_trust_score_proof(other, 70, true, None).unwrap_err();
+        assert_eq!(err.status, Status::InvalidArg);
+    }
+
+    #[test]
+    fn test_device_profile_json() {
+        let profile: serde_json::Value = serde_json::from_str(&get_device_profile(Either::A("low_end_mobile".into()), 3).unwrap()).unwrap();
+        assert_eq!(profile["k"], get_recommended_k(DeviceType::LowEndMobile));
+        assert_eq!(profile["batchSize"], 1);
+        assert_eq!(profile["useBatching"], true);
+
+        let profile: serde_json::Value = serde_json::from_str(&get_device_profile(Either::A("desktop".into()), 3).unwrap()).unwrap();
+        assert_eq!(profile["useBatching"], false);
+
+        // Ids select the same profiles as names
+        for device in DeviceType::ALL {
+            let by_id = get_device_profile(Either::B(device.as_u8() as u32), 3).unwrap();
+            assert_eq!(by_id, configure_for_device(device).to_json(3));
+        }
+
+        assert_eq!(get_device_profile(Either::A("tablet".into()), 1).unwrap_err().status, Status::InvalidArg);
+        for id in [9, 256] {
+            let err = get_device_profile(Either::B(id), 1).unwrap_err();
+            assert_eq!(err.status, Status::InvalidArg);
+            assert!(err.reason.contains(&format!("Unknown device type id: {}", id)));
+        }
+    }
+
+    #[test]
+    fn test_circuit_stats_json() {
+        let stats: serde_json::Value = serde_json::from_str(&get_circuit_stats("trust_score".into()).unwrap()).unwrap();
+        assert_eq!(stats["adviceColumns"], 6);
+        assert_eq!(stats["minK"], TRUST_SCORE_K);
+
+        for circuit in ["income_range", "loan_history", "identity", "composite"] {
+            let stats: serde_json::Value = serde_json::from_str(&get_circuit_stats(circuit.into()).unwrap()).unwrap();
+            assert!(stats["minK"].as_u64().is_some(), "{} has no minimum k", circuit);
+        }
+
+        let err = get_circuit_stats("mystery".into()).unwrap_err();
+        assert_eq!(err.status, Status::InvalidArg);
+    }
+
+    #[test]
+    fn test_instance_layout_json() {
+        let layout: serde_json::Value = serde_json::from_str(&get_instance_layout("trust_score".into()).unwrap()).unwrap();
+        let names: Vec<_> = layout.as_array().unwrap().iter().map(|slot| slot["name"].as_str().unwrap()).collect();
+        assert_eq!(names, ["result", "threshold", "epoch"]);
+        assert_eq!(layout[0]["kind"], "bool");
+
+        let layout: serde_json::Value = serde_json::from_str(&get_instance_layout("identity".into()).unwrap()).unwrap();
+        assert_eq!(layout[3], serde_json::json!({ "index": 3, "name": "commitment", "kind": "field", "optional": false }));
+
+        let layout: serde_json::Value = serde_json::from_str(&get_instance_layout("loan_history".into()).unwrap()).unwrap();
+        assert_eq!(layout[4]["name"], "success_rate");
+        assert_eq!(layout[4]["optional"], true);
+
+        let layout: serde_json::Value = serde_json::from_str(&get_instance_layout("composite".into()).unwrap()).unwrap();
+        assert_eq!(layout.as_array().unwrap().len(), 7);
+
+        let err = get_instance_layout("mystery".into()).unwrap_err();
+        assert_eq!(err.status, Status::InvalidArg);
+    }
+
+    #[test]
+    fn test_cancellation_tokens() {
+        let id = create_cancellation_token();
+        let token = cancellation_token(Some(id)).unwrap();
+        assert!(!token.is_cancelled());
+
+        cancel_proofs(id).unwrap();
+        assert!(token.is_cancelled());
+        assert!(cancellation_token(Some(id)).unwrap().is_cancelled());
+
+        // A freed id is unknown, and no id means a token nobody cancels
+        free_cancellation_token(id);
+        assert_eq!(cancel_proofs(id).unwrap_err().status, Status::InvalidArg);
+        assert!(!cancellation_token(None).unwrap().is_cancelled());
+    }
+}
@@ -0,0 +1,291 @@
+//! Serializable circuit inputs
+//!
+//! Plain data transfer objects for persisting or transmitting circuit inputs
+//! as JSON, convertible into the `Value`-based circuits. Deserialization
+//! validates ranges up front, so a malformed payload is rejected with a
+//! descriptive error instead of producing an unsatisfiable proof, using the
+//! same checks as the bindings (see `validation`).
+
+use crate::circuits::identity::IdentityCircuit;
+use crate::circuits::income_range::IncomeRangeCircuit;
+use crate::circuits::loan_history::LoanHistoryCircuit;
+use crate::circuits::trust_score::TrustScoreCircuit;
+use crate::error::ZkError;
+use crate::validation::{validate_income_range_inputs, validate_loan_history_inputs, validate_trust_inputs};
+use ff::PrimeField;
+use halo2_proofs::circuit::Value;
+use serde::{Deserialize, Serialize};
+
+pub use crate::circuits::trust_score::MAX_TRUST_SCORE;
+
+/// Inputs of a trust score proof
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", try_from = "RawTrustScoreInputs")]
+pub struct TrustScoreInputs {
+    pub trust_score: u64,
+    pub threshold: u64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawTrustScoreInputs {
+    trust_score: u64,
+    threshold: u64,
+}
+
+impl TryFrom<RawTrustScoreInputs> for TrustScoreInputs {
+    type Error = ZkError;
+
+    fn try_from(raw: RawTrustScoreInputs) -> Result<Self, ZkError> {
+        validate_trust_inputs(raw.trust_score, raw.threshold)?;
+
+        Ok(Self {
+            trust_score: raw.trust_score,
+            threshold: raw.threshold,
+        })
+    }
+}
+
+impl<F: PrimeField> From<TrustScoreInputs> for TrustScoreCircuit<F> {
+    fn from(inputs: TrustScoreInputs) -> Self {
+        TrustScoreCircuit::new(Some(inputs.trust_score), inputs.threshold)
+    }
+}
+
+/// Inputs of an income range proof
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", try_from = "RawIncomeRangeInputs")]
+pub struct IncomeRangeInputs {
+    pub income: u128,
+    pub min_range: u128,
+    pub max_range: u128,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawIncomeRangeInputs {
+    income: u128,
+    min_range: u128,
+    max_range: u128,
+}
+
+impl TryFrom<RawIncomeRangeInputs> for IncomeRangeInputs {
+    type Error = ZkError;
+
+    fn try_from(raw: RawIncomeRangeInputs) -> Result<Self, ZkError> {
+        validate_income_range_inputs(raw.income, raw.min_range, raw.max_range)?;
+
+        Ok(Self {
+            income: raw.income,
+            min_range: raw.min_range,
+            max_range: raw.max_range,
+        })
+    }
+}
+
+impl<F: PrimeField> From<IncomeRangeInputs> for IncomeRangeCircuit<F> {
+    fn from(inputs: IncomeRangeInputs) -> Self {
+        IncomeRangeCircuit::new(Some(inputs.income), inputs.min_range, inputs.max_range)
+    }
+}
+
+/// Inputs of a loan history proof
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", try_from = "RawLoanHistoryInputs")]
+pub struct LoanHistoryInputs {
+    pub num_loans: u64,
+    pub successful_repayments: u64,
+    /// Minimum success rate as percentage * 100
+    pub min_success_rate: u64,
+    /// Minimum number of loans for the success rate to count
+    pub min_loans: u64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawLoanHistoryInputs {
+    num_loans: u64,
+    successful_repayments: u64,
+    min_success_rate: u64,
+    min_loans: u64,
+}
+
+impl TryFrom<RawLoanHistoryInputs> for LoanHistoryInputs {
+    type Error = ZkError;
+
+    fn try_from(raw: RawLoanHistoryInputs) -> Result<Self, ZkError> {
+        validate_loan_history_inputs(raw.num_loans, raw.successful_repayments, raw.min_success_rate, raw.min_loans)?;
+
+        Ok(Self {
+            num_loans: raw.num_loans,
+            successful_repayments: raw.successful_repayments,
+            min_success_rate: raw.min_success_rate,
+            min_loans: raw.min_loans,
+        })
+    }
+}
+
+impl<F: PrimeField> From<LoanHistoryInputs> for LoanHistoryCircuit<F> {
+    fn from(inputs: LoanHistoryInputs) -> Self {
+        LoanHistoryCircuit::new(
+            Some(inputs.num_loans),
+            Some(inputs.successful_repayments),
+            inputs.min_success_rate,
+            inputs.min_loans,
+        )
+    }
+}
+
+/// Inputs of an identity proof
+///
+/// Field elements are hex strings of their little-endian canonical encoding.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", try_from = "RawIdentityInputs")]
+pub struct IdentityInputs {
+    pub identity_hash: String,
+    pub nonce: String,
+    pub commitment: String,
+    pub domain_separator: u64,
+    pub context_id: u64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawIdentityInputs {
+    identity_hash: String,
+    nonce: String,
+    commitment: String,
+    domain_separator: u64,
+    context_id: u64,
+}
+
+impl TryFrom<RawIdentityInputs> for IdentityInputs {
+    type Error = ZkError;
+
+    fn try_from(raw: RawIdentityInputs) -> Result<Self, ZkError> {
+        for (name, value) in [("identity hash", &raw.identity_hash), ("nonce", &raw.nonce), ("commitment", &raw.commitment)] {
+            if value.len() != 64 || !value.bytes().all(|b| b.is_ascii_hexdigit()) {
+                return Err(ZkError::InvalidInput(format!("{} must be 64 hex characters", name)));
+            }
+        }
+
+        Ok(Self {
+            identity_hash: raw.identity_hash,
+            nonce: raw.nonce,
+            commitment: raw.commitment,
+            domain_separator: raw.domain_separator,
+            context_id: raw.context_id,
+        })
+    }
+}
+
+impl IdentityInputs {
+    /// Describe field elements in the serialized encoding
+    pub fn new<F: PrimeField>(identity_hash: F, nonce: F, commitment: F, domain_separator: u64, context_id: u64) -> Self {
+        Self {
+            identity_hash: field_to_hex(&identity_hash),
+            nonce: field_to_hex(&nonce),
+            commitment: field_to_hex(&commitment),
+            domain_separator,
+            context_id,
+        }
+    }
+}
+
+impl<F: PrimeField> TryFrom<IdentityInputs> for IdentityCircuit<F> {
+    type Error = ZkError;
+
+    /// Fails if an element is not canonical for `F`
+    fn try_from(inputs: IdentityInputs) -> Result<Self, ZkError> {
+        Ok(IdentityCircuit::new_with_fields(
+            Value::known(field_from_hex(&inputs.identity_hash)?),
+            Value::known(field_from_hex(&inputs.nonce)?),
+            Value::known(field_from_hex(&inputs.commitment)?),
+            Value::known(F::from(inputs.domain_separator)),
+            Value::known(F::from(inputs.context_id)),
+        ))
+    }
+}
+
+/// Hex encoding of a field element's canonical representation
+fn field_to_hex<F: PrimeField>(field: &F) -> String {
+    field.to_repr().as_ref().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Parse a field element written by `field_to_hex`
+fn field_from_hex<F: PrimeField>(hex: &str) -> Result<F, ZkError> {
+    let mut repr = F::Repr::default();
+    let bytes = repr.as_mut();
+    if hex.len() != bytes.len() * 2 {
+        return Err(ZkError::InvalidInput(format!(
+            "Field element must be {} hex characters",
+            bytes.len() * 2
+        )));
+    }
+
+    for (byte, pair) in bytes.iter_mut().zip(hex.as_bytes().chunks(2)) {
+        let pair = std::str::from_utf8(pair).map_err(|_| ZkError::InvalidInput("Field element is not hex".into()))?;
+        *byte = u8::from_str_radix(pair, 16).map_err(|_| ZkError::InvalidInput("Field element is not hex".into()))?;
+    }
+
+    Option::from(F::from_repr(repr))
+        .ok_or_else(|| ZkError::InvalidInput("Field element is not canonical".into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuits::identity::utils::commit;
+    use crate::circuits::lending::run_mock;
+    use pasta_curves::Fp;
+
+    #[test]
+    fn test_round_trip_through_json() {
+        let trust = TrustScoreInputs { trust_score: 85, threshold: 70 };
+        let json = serde_json::to_string(&trust).unwrap();
+        assert_eq!(json, r#"{"trustScore":85,"threshold":70}"#);
+        assert_eq!(serde_json::from_str::<TrustScoreInputs>(&json).unwrap(), trust);
+        run_mock(&TrustScoreCircuit::<Fp>::from(trust)).unwrap();
+
+        let income = IncomeRangeInputs { income: (1 << 64) + 5, min_range: 1 << 64, max_range: 1 << 65 };
+        let json = serde_json::to_string(&income).unwrap();
+        assert_eq!(serde_json::from_str::<IncomeRangeInputs>(&json).unwrap(), income);
+        run_mock(&IncomeRangeCircuit::<Fp>::from(income)).unwrap();
+
+        let loans = LoanHistoryInputs { num_loans: 10, successful_repayments: 9, min_success_rate: 8000, min_loans: 3 };
+        let json = serde_json::to_string(&loans).unwrap();
+        assert_eq!(serde_json::from_str::<LoanHistoryInputs>(&json).unwrap(), loans);
+        run_mock(&LoanHistoryCircuit::<Fp>::from(loans)).unwrap();
+
+        let (identity_hash, nonce) = (Fp::from(1111u64), Fp::from(42u64));
+        let identity = IdentityInputs::new(identity_hash, nonce, commit(identity_hash, nonce, 3), 7, 3);
+        let json = serde_json::to_string(&identity).unwrap();
+        let decoded = serde_json::from_str::<IdentityInputs>(&json).unwrap();
+        assert_eq!(decoded, identity);
+        run_mock(&IdentityCircuit::<Fp>::try_from(decoded).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_out_of_range_values_rejected() {
+        let err = serde_json::from_str::<TrustScoreInputs>(r#"{"trustScore":101,"threshold":70}"#).unwrap_err();
+        assert!(err.to_string().contains("trust score 101 exceeds the maximum of 100"));
+
+        let err = serde_json::from_str::<IncomeRangeInputs>(r#"{"income":5,"minRange":10,"maxRange":1}"#).unwrap_err();
+        assert!(err.to_string().contains("min range 10 is above max range 1"));
+
+        let err = serde_json::from_str::<LoanHistoryInputs>(
+            r#"{"numLoans":3,"successfulRepayments":4,"minSuccessRate":8000,"minLoans":1}"#,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("exceed the number of loans"));
+
+        let mut identity = serde_json::to_value(IdentityInputs::new(Fp::one(), Fp::one(), Fp::one(), 7, 3)).unwrap();
+        identity["nonce"] = "xyz".into();
+        assert!(serde_json::from_value::<IdentityInputs>(identity.clone()).is_err());
+
+        // Well-formed hex that is not a canonical field element
+        identity["nonce"] = "ff".repeat(32).into();
+        let decoded = serde_json::from_value::<IdentityInputs>(identity).unwrap();
+        assert!(IdentityCircuit::<Fp>::try_from(decoded).is_err());
+    }
+}
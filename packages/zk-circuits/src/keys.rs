@@ -0,0 +1,255 @@
+//! Trust score key material and its on-disk format
+//!
+//! `halo2_proofs` 0.3 cannot encode proving or verifying keys, so a key file
+//! stores the setup `Params` (the costly part to regenerate) together with the
+//! fingerprint of the verifying key derived from them. Loading re-derives the
+//! keys with `keygen_vk`/`keygen_pk` and checks the fingerprint, so a file
+//! written for a different circuit is rejected instead of silently producing
+//! unverifiable proofs.
+//!
+//! Layout: `MAGIC | version (1 byte) | vk fingerprint (64 hex bytes) | params`.
+
+use crate::circuits::trust_score::TrustScoreCircuit;
+use crate::error::ZkError;
+use crate::registry::vk_fingerprint;
+use crate::trace::traced;
+#[cfg(feature = "prover")]
+use halo2_proofs::plonk::{keygen_pk, ProvingKey};
+use halo2_proofs::{
+    plonk::{keygen_vk, VerifyingKey},
+    poly::commitment::Params,
+};
+use pasta_curves::{EqAffine, Fp};
+use std::fs;
+
+/// Circuit size of the trust score keys used by the bindings
+pub const TRUST_SCORE_K: u32 = 4;
+
+/// Magic header identifying a trust score key file
+pub const KEY_FILE_MAGIC: &[u8; 8] = b"ZKTSKEYS";
+
+/// Current key file format version
+pub const KEY_FILE_VERSION: u8 = 1;
+
+/// Length of the hex-encoded verifying key fingerprint
+const FINGERPRINT_LEN: usize = 64;
+
+/// Largest circuit size accepted when reading serialized params
+const MAX_PARAMS_K: u32 = 24;
+
+/// Size of a compressed `EqAffine` point
+const POINT_LEN: usize = 32;
+
+/// Setup parameters and keys for the trust score circuit
+///
+/// Without the `prover` feature only the verifying key is derived.
+pub struct TrustScoreKeys {
+    pub params: Params<EqAffine>,
+    #[cfg(feature = "prover")]
+    pub proving_key: ProvingKey<EqAffine>,
+    pub verifying_key: VerifyingKey<EqAffine>,
+}
+
+impl TrustScoreKeys {
+    /// Create fresh setup parameters for circuit size `k` and derive the keys
+    pub fn generate(k: u32) -> Result<Self, ZkError> {
+        // In production, these would be from a trusted setup
+        Self::from_params(Params::<EqAffine>::new(k))
+    }
+
+    /// Derive the trust score keys from existing params
+    pub fn from_params(params: Params<EqAffine>) -> Result<Self, ZkError> {
+        let circuit = TrustScoreCircuit::<Fp>::new(None, 0);
+
+        let keys = traced!("keygen", "trust_score", params.k(), {
+            let verifying_key = keygen_vk(&params, &circuit)
+                .map_err(|e| ZkError::KeygenFailed(format!("Failed to generate VK: {:?}", e)))?;
+            #[cfg(feature = "prover")]
+            let proving_key = keygen_pk(&params, verifying_key.clone(), &circuit)
+                .map_err(|e| ZkError::KeygenFailed(format!("Failed to generate PK: {:?}", e)))?;
+
+            Self {
+                params,
+                #[cfg(feature = "prover")]
+                proving_key,
+                verifying_key,
+            }
+        });
+
+        Ok(keys)
+    }
+
+    /// Encode the keys in the key file format
+    pub fn to_bytes(&self) -> Result<Vec<u8>, ZkError> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(KEY_FILE_MAGIC);
+        bytes.push(KEY_FILE_VERSION);
+        bytes.extend_from_slice(vk_fingerprint(&self.verifying_key).as_bytes());
+        self.params
+            .write(&mut bytes)
+            .map_err(|e| ZkError::Serialization(format!("Failed to write params: {}", e)))?;
+
+        Ok(bytes)
+    }
+
+    /// Decode a key file, re-deriving and checking the keys
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ZkError> {
+        let header_len = KEY_FILE_MAGIC.len() + 1 + FINGERPRINT_LEN;
+        if bytes.len() < header_len || &bytes[..KEY_FILE_MAGIC.len()] != KEY_FILE_MAGIC {
+            return Err(ZkError::Serialization("Not a trust score key file (bad magic header)".into()));
+        }
+
+        let version = bytes[KEY_FILE_MAGIC.len()];
+        if version != KEY_FILE_VERSION {
+            return Err(ZkError::Serialization(format!(
+                "Unsupported key file version {} (expected {})",
+                version, KEY_FILE_VERSION
+            )));
+        }
+
+        let fingerprint = std::str::from_utf8(&bytes[KEY_FILE_MAGIC.len() + 1..header_len])
+            .map_err(|_| ZkError::Serialization("Key file fingerprint is not valid hex".into()))?;
+
+        let keys = Self::from_params(read_params(&bytes[header_len..])?)?;
+        let actual = vk_fingerprint(&keys.verifying_key);
+        if actual != fingerprint {
+            return Err(ZkError::KeyVersionMismatch {
+                expected: fingerprint.to_string(),
+                actual,
+            });
+        }
+
+        Ok(keys)
+    }
+
+    /// Write the keys to `path`
+    pub fn save(&self, path: &str) -> Result<(), ZkError> {
+        fs::write(path, self.to_bytes()?)
+            .map_err(|e| ZkError::Serialization(format!("Failed to write {}: {}", path, e)))
+    }
+
+    /// Read keys previously written with `save`
+    pub fn load(path: &str) -> Result<Self, ZkError> {
+        let bytes = fs::read(path)
+            .map_err(|e| ZkError::Serialization(format!("Failed to read {}: {}", path, e)))?;
+        Self::from_bytes(&bytes)
+    }
+}
+
+/// Read serialized params, checking the declared size before allocating
+///
+/// `Params::read` trusts the leading `k`, so a corrupt header would otherwise
+/// overflow or trigger a huge allocation.
+pub(crate) fn read_params(bytes: &[u8]) -> Result<Params<EqAffine>, ZkError> {
+    let k = bytes
+        .get(..4)
+        .map(|k| u32::from_le_bytes([k[0], k[1], k[2], k[3]]))
+        .ok_or_else(|| ZkError::Serialization("Params are truncated".into()))?;
+    if k > MAX_PARAMS_K {
+        return Err(ZkError::Serialization(format!("Params declare unsupported k = {}", k)));
+    }
+
+    // k, then g and g_lagrange (2^k points each), then w and u
+    let expected_len = 4 + (2 * (1usize << k) + 2) * POINT_LEN;
+    if bytes.len() != expected_len {
+        return Err(ZkError::Serialization(format!(
+            "Params for k = {} should be {} bytes, got {}",
+            k,
+            expected_len,
+            bytes.len()
+        )));
+    }
+
+    Params::<EqAffine>::read(&mut &bytes[..])
+        .map_err(|e| ZkError::Serialization(format!("Failed to read params: {}", e)))
+}
+
+/// Read params for circuit size `k` from `path` through a memory map
+///
+/// The file is never copied into a buffer of its own: its pages are read in
+/// by the OS as the points are decoded, which avoids holding the file twice
+/// (raw and decoded) while loading a large setup. The decoded params are
+/// still owned in memory. The declared `k` and the file length are checked
+/// before any points are decoded. The file must not be modified while it is
+/// being loaded.
+#[cfg(feature = "mmap")]
+pub fn load_params_mmap(path: &str, k: u32) -> Result<Params<EqAffine>, ZkError> {
+    let file = fs::File::open(path)
+        .map_err(|e| ZkError::Serialization(format!("Failed to open {}: {}", path, e)))?;
+    // SAFETY: the map is only read, and callers must not modify the file while it is loaded
+    let map = unsafe { memmap2::Mmap::map(&file) }
+        .map_err(|e| ZkError::Serialization(format!("Failed to map {}: {}", path, e)))?;
+
+    let declared = map.get(..4).map(|k| u32::from_le_bytes([k[0], k[1], k[2], k[3]]));
+    if declared != Some(k) {
+        return Err(ZkError::Serialization(format!("{} does not hold params for k = {}", path, k)));
+    }
+
+    read_params(&map)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "prover")]
+    use crate::prover::{prove_trust_score, verify_trust_score};
+
+    #[cfg(feature = "prover")]
+    #[test]
+    fn test_save_load_and_verify() {
+        let keys = TrustScoreKeys::generate(4).unwrap();
+        let path = std::env::temp_dir().join(format!("zk-keys-{}.bin", std::process::id()));
+        let path = path.to_str().unwrap();
+        keys.save(path).unwrap();
+        let loaded = TrustScoreKeys::load(path).unwrap();
+        fs::remove_file(path).unwrap();
+
+        // Prove with the original keys, verify with the reloaded ones
+        let proof = prove_trust_score(&keys.params, &keys.proving_key, 85, 70).unwrap();
+        assert!(verify_trust_score(&loaded.params, &loaded.verifying_key, &proof, 70, true).unwrap());
+        assert!(!verify_trust_score(&loaded.params, &loaded.verifying_key, &proof, 70, false).unwrap());
+    }
+
+    #[cfg(all(feature = "prover", feature = "mmap"))]
+    #[test]
+    fn test_load_params_mmap() {
+        let keys = TrustScoreKeys::generate(4).unwrap();
+        let path = std::env::temp_dir().join(format!("zk-params-{}.bin", std::process::id()));
+        let path = path.to_str().unwrap();
+        let mut bytes = Vec::new();
+        keys.params.write(&mut bytes).unwrap();
+        fs::write(path, &bytes).unwrap();
+
+        let loaded = TrustScoreKeys::from_params(load_params_mmap(path, 4).unwrap()).unwrap();
+        let wrong_k = load_params_mmap(path, 5);
+        fs::write(path, &bytes[..bytes.len() - 1]).unwrap();
+        let truncated = load_params_mmap(path, 4);
+        fs::remove_file(path).unwrap();
+
+        let proof = prove_trust_score(&keys.params, &keys.proving_key, 85, 70).unwrap();
+        assert!(verify_trust_score(&loaded.params, &loaded.verifying_key, &proof, 70, true).unwrap());
+        assert!(matches!(wrong_k, Err(ZkError::Serialization(_))));
+        assert!(matches!(truncated, Err(ZkError::Serialization(_))));
+    }
+
+    #[test]
+    fn test_incompatible_key_files_rejected() {
+        let bytes = TrustScoreKeys::generate(4).unwrap().to_bytes().unwrap();
+
+        let mut bad_magic = bytes.clone();
+        bad_magic[0] ^= 0xff;
+        assert!(matches!(TrustScoreKeys::from_bytes(&bad_magic), Err(ZkError::Serialization(_))));
+
+        let mut bad_version = bytes.clone();
+        bad_version[KEY_FILE_MAGIC.len()] = KEY_FILE_VERSION + 1;
+        assert!(matches!(TrustScoreKeys::from_bytes(&bad_version), Err(ZkError::Serialization(_))));
+
+        // A fingerprint from another circuit means the keys no longer match
+        let mut stale = bytes.clone();
+        stale[KEY_FILE_MAGIC.len() + 1] = if stale[KEY_FILE_MAGIC.len() + 1] == b'0' { b'1' } else { b'0' };
+        assert!(matches!(TrustScoreKeys::from_bytes(&stale), Err(ZkError::KeyVersionMismatch { .. })));
+
+        assert!(matches!(TrustScoreKeys::from_bytes(&bytes[..bytes.len() - 1]), Err(ZkError::Serialization(_))));
+        assert!(matches!(TrustScoreKeys::from_bytes(b"ZK"), Err(ZkError::Serialization(_))));
+    }
+}
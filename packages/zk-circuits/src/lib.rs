@@ -2,15 +2,43 @@
 //! 
 //! This crate provides privacy-preserving circuits for:
 //! - Trust score verification without revealing actual scores
+//! - Trust score band (tier) proofs against public lower and upper bounds
+//! - Counts of how many borrowers in a pool pass their trust thresholds
+//! - The highest of several ascending trust score tiers a borrower clears
+//! - Trust scores bound to a Poseidon commitment other proofs can reference
 //! - Income range proofs without exposing exact amounts
+//! - Range proofs over Pedersen income commitments that aggregate homomorphically
+//! - Income growth between two periods against a public minimum rate
 //! - Identity verification with commitment schemes
+//! - Allowlist membership of identity commitments via Merkle paths
+//! - Distinctness of two committed identities, for co-signer checks
 //! - Loan history verification with privacy protection
+//! - Loan repayment rates weighted by loan amount
+//! - Income histogram bucket proofs for aggregate analytics
+//! - Income band proofs revealing only the matched band index
+//! - Savings-group membership and pooled fund coverage
+//! - Combined trust, income and loan history checks in a single proof
+//! - Loan eligibility against a trust threshold and income range, revealing only the outcome
 
+pub mod api;
 pub mod circuits;
+pub mod envelope;
+pub mod error;
+#[cfg(feature = "node")]
 pub mod ffi;
+#[cfg(feature = "serde")]
+pub mod inputs;
+pub mod keys;
+pub mod prover;
+pub mod registry;
+mod trace;
+pub mod validation;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
 // Re-export main circuit types for easy access
 pub use circuits::*;
+pub use error::{FieldConversionError, ZkError};
 
 // Common types used across circuits
 pub use halo2_proofs::{
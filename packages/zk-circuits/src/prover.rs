@@ -0,0 +1,1155 @@
+//! Proof generation and verification for the trust score circuit
+//!
+//! Pure-Rust entry points taking explicit key references, shared by the napi,
+//! C FFI and WASM bindings so that global state stays at those edges.
+//!
+//! Every transcript first absorbs the domain of the circuit it is for (see
+//! `transcript_domain`), so a proof only verifies under the circuit type it
+//! was created for.
+
+#[cfg(feature = "prover")]
+use crate::circuits::trust_score::{utils::check_trust_score_range, TrustScoreCircuit};
+use crate::circuits::trust_score::utils::{public_inputs, public_inputs_at_epoch};
+use crate::envelope::CircuitTag;
+use crate::error::ZkError;
+#[cfg(feature = "prover")]
+use crate::registry::vk_fingerprint_bytes;
+use crate::trace::traced;
+use ff::{Field, FromUniformBytes, PrimeField};
+#[cfg(feature = "prover")]
+use halo2_proofs::{
+    plonk::{create_proof, Circuit, ProvingKey},
+    transcript::Blake2bWrite,
+};
+use halo2_proofs::{
+    plonk::{verify_proof, Error, SingleVerifier, VerificationStrategy, VerifyingKey},
+    poly::commitment::{Guard, Params, MSM},
+    transcript::{Blake2bRead, Challenge255, EncodedChallenge, Transcript},
+};
+use pasta_curves::{EqAffine, Fp};
+use rand::rngs::OsRng;
+#[cfg(feature = "prover")]
+use rand::{CryptoRng, RngCore};
+use std::fmt;
+#[cfg(feature = "prover")]
+use std::io::Write;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+/// Transcript domain of a circuit type's proofs
+///
+/// A Blake2b hash of the circuit's name, absorbed into the transcript before
+/// any proof data by both prover and verifier.
+pub fn transcript_domain(circuit: CircuitTag) -> Fp {
+    let hash = blake2b_simd::Params::new()
+        .hash_length(64)
+        .personal(b"ZkCircuitDomain_")
+        .hash(circuit.to_string().as_bytes());
+
+    Fp::from_uniform_bytes(hash.as_array())
+}
+
+/// Absorb `circuit`'s domain into a fresh transcript
+fn bind_domain<T: Transcript<EqAffine, Challenge255<EqAffine>>>(transcript: &mut T, circuit: CircuitTag) -> Result<(), ZkError> {
+    transcript
+        .common_scalar(transcript_domain(circuit))
+        .map_err(|e| ZkError::ProofFailed(format!("Failed to bind transcript domain: {}", e)))
+}
+
+/// Size `k` of the evaluation domain a verifying key was generated for
+///
+/// halo2 keeps the domain's `k` private, but its generator `omega` is a
+/// primitive `2^k`-th root of unity, so `k` squarings first bring it to one.
+fn domain_k(vk: &VerifyingKey<EqAffine>) -> u32 {
+    let mut omega = vk.get_domain().get_omega();
+    let mut k = 0;
+    while omega != Fp::ONE && k < Fp::S {
+        omega = omega.square();
+        k += 1;
+    }
+    k
+}
+
+/// Check that `params` and `vk` were made for the same circuit size
+///
+/// The verifying half of `assert_keys_compatible`, for callers that hold no
+/// proving key.
+pub fn assert_params_compatible(params: &Params<EqAffine>, vk: &VerifyingKey<EqAffine>) -> Result<(), ZkError> {
+    let keys_k = domain_k(vk);
+    if keys_k != params.k() {
+        return Err(ZkError::KeyMismatch(format!(
+            "params are for k = {} but the keys are for k = {}",
+            params.k(),
+            keys_k
+        )));
+    }
+
+    Ok(())
+}
+
+/// Check that `params`, `pk` and `vk` belong together before proving or
+/// verifying with them
+///
+/// The params and keys must be for the same `k` (so their domains have the
+/// same size), and `vk` must be the verifying key `pk` was generated with.
+/// Mismatched keys otherwise fail deep inside halo2 with an opaque error.
+#[cfg(feature = "prover")]
+pub fn assert_keys_compatible(
+    params: &Params<EqAffine>,
+    pk: &ProvingKey<EqAffine>,
+    vk: &VerifyingKey<EqAffine>,
+) -> Result<(), ZkError> {
+    assert_params_compatible(params, vk)?;
+
+    let pk_vk = pk.get_vk();
+    if std::ptr::eq(pk_vk, vk) {
+        return Ok(());
+    }
+    if domain_k(pk_vk) != domain_k(vk) {
+        return Err(ZkError::KeyMismatch(format!(
+            "proving key is for k = {} but the verifying key is for k = {}",
+            domain_k(pk_vk),
+            domain_k(vk)
+        )));
+    }
+    if vk_fingerprint_bytes(pk_vk) != vk_fingerprint_bytes(vk) {
+        return Err(ZkError::KeyMismatch("verifying key was not generated with the proving key".into()));
+    }
+
+    Ok(())
+}
+
+/// Prove `circuit` in the transcript domain of `tag`, streaming the proof into `writer`
+#[cfg(feature = "prover")]
+pub fn prove_in_domain<C: Circuit<Fp>, W: Write>(
+    tag: CircuitTag,
+    writer: W,
+    params: &Params<EqAffine>,
+    pk: &ProvingKey<EqAffine>,
+    circuit: C,
+    instances: &[Vec<Fp>],
+    rng: impl RngCore + CryptoRng,
+) -> Result<W, ZkError> {
+    prove_many_in_domain(tag, writer, params, pk, &[circuit], &[instances.to_vec()], rng)
+}
+
+/// `prove_in_domain` for several circuits under the same key, in one proof
+///
+/// `instances` holds each circuit's instance columns, in circuit order. The
+/// proof only verifies against all of them together.
+#[cfg(feature = "prover")]
+pub fn prove_many_in_domain<C: Circuit<Fp>, W: Write>(
+    tag: CircuitTag,
+    writer: W,
+    params: &Params<EqAffine>,
+    pk: &ProvingKey<EqAffine>,
+    circuits: &[C],
+    instances: &[Vec<Vec<Fp>>],
+    rng: impl RngCore + CryptoRng,
+) -> Result<W, ZkError> {
+    assert_keys_compatible(params, pk, pk.get_vk())?;
+
+    let columns: Vec<Vec<&[Fp]>> = instances
+        .iter()
+        .map(|circuit| circuit.iter().map(Vec::as_slice).collect())
+        .collect();
+    let columns: Vec<&[&[Fp]]> = columns.iter().map(Vec::as_slice).collect();
+    let mut transcript = Blake2bWrite::<W, EqAffine, Challenge255<_>>::init(writer);
+    bind_domain(&mut transcript, tag)?;
+
+    traced!("prove", tag, params.k(), create_proof(
+        params,
+        pk,
+        circuits,
+        &columns,
+        rng,
+        &mut transcript,
+    ))
+    .map_err(|e| ZkError::ProofFailed(format!("Failed to create proof: {:?}", e)))?;
+
+    Ok(transcript.finalize())
+}
+
+/// Verify a proof created by `prove_in_domain` for the same `tag`
+///
+/// Returns `Ok(false)` for a proof that does not verify, including one made
+/// for another circuit type.
+pub fn verify_in_domain(
+    tag: CircuitTag,
+    params: &Params<EqAffine>,
+    vk: &VerifyingKey<EqAffine>,
+    proof: &[u8],
+    instances: &[Vec<Fp>],
+) -> Result<bool, ZkError> {
+    verify_many_in_domain(tag, params, vk, proof, &[instances.to_vec()])
+}
+
+/// Verify a proof created by `prove_many_in_domain` for the same `tag`
+///
+/// Returns `Ok(false)` for a proof that does not verify, including one made
+/// for a different number of circuits, and `KeyMismatch` if `params` and
+/// `vk` are for different circuit sizes.
+pub fn verify_many_in_domain(
+    tag: CircuitTag,
+    params: &Params<EqAffine>,
+    vk: &VerifyingKey<EqAffine>,
+    proof: &[u8],
+    instances: &[Vec<Vec<Fp>>],
+) -> Result<bool, ZkError> {
+    assert_params_compatible(params, vk)?;
+    if proof.is_empty() {
+        return Err(ZkError::InvalidInput("Proof is empty".into()));
+    }
+
+    let columns: Vec<Vec<&[Fp]>> = instances
+        .iter()
+        .map(|circuit| circuit.iter().map(Vec::as_slice).collect())
+        .collect();
+    let columns: Vec<&[&[Fp]]> = columns.iter().map(Vec::as_slice).collect();
+    let mut transcript = Blake2bRead::<&[u8], EqAffine, Challenge255<_>>::init(proof);
+    bind_domain(&mut transcript, tag)?;
+    let strategy = SingleVerifier::new(params);
+
+    let verification_result = traced!("verify", tag, params.k(), verify_proof(
+        params,
+        vk,
+        strategy,
+        &columns,
+        &mut transcript,
+    ));
+
+    Ok(verification_result.is_ok())
+}
+
+/// Create a proof that `trust_score` is (or is not) at least `threshold`
+///
+/// The proof's public inputs are `[result, threshold, 0]`, for epoch 0. Both
+/// values must be at most `MAX_TRUST_SCORE`; larger ones are rejected before
+/// proving.
+#[cfg(feature = "prover")]
+pub fn prove_trust_score(
+    params: &Params<EqAffine>,
+    pk: &ProvingKey<EqAffine>,
+    trust_score: u64,
+    threshold: u64,
+) -> Result<Vec<u8>, ZkError> {
+    prove_trust_score_with_rng(params, pk, trust_score, threshold, OsRng)
+}
+
+/// `prove_trust_score` with the blinding randomness drawn from `rng`
+///
+/// The same `rng` state and inputs always produce the same proof bytes, which
+/// makes proofs reproducible in tests. Production callers should use
+/// `prove_trust_score`: a predictable `rng` gives up zero knowledge.
+#[cfg(feature = "prover")]
+pub fn prove_trust_score_with_rng(
+    params: &Params<EqAffine>,
+    pk: &ProvingKey<EqAffine>,
+    trust_score: u64,
+    threshold: u64,
+    rng: impl RngCore + CryptoRng,
+) -> Result<Vec<u8>, ZkError> {
+    prove_trust_score_to_writer(Vec::new(), params, pk, trust_score, threshold, rng)
+}
+
+/// `prove_trust_score_with_rng`, streaming the proof into `writer`
+///
+/// The transcript is written to `writer` as proving proceeds instead of
+/// being buffered, so a file or socket sink keeps memory flat across a batch
+/// of proofs. The bytes are the raw proof, without a proof envelope. Returns
+/// the writer; a write error fails the proof with `ProofFailed`, and the
+/// sink may then hold a partial proof.
+#[cfg(feature = "prover")]
+pub fn prove_trust_score_to_writer<W: Write>(
+    writer: W,
+    params: &Params<EqAffine>,
+    pk: &ProvingKey<EqAffine>,
+    trust_score: u64,
+    threshold: u64,
+    rng: impl RngCore + CryptoRng,
+) -> Result<W, ZkError> {
+    prove_trust_score_at_epoch_to_writer(writer, params, pk, trust_score, threshold, 0, rng)
+}
+
+/// `prove_trust_score` for a proof that only verifies at `epoch`
+#[cfg(feature = "prover")]
+pub fn prove_trust_score_at_epoch(
+    params: &Params<EqAffine>,
+    pk: &ProvingKey<EqAffine>,
+    trust_score: u64,
+    threshold: u64,
+    epoch: u64,
+) -> Result<Vec<u8>, ZkError> {
+    prove_trust_score_at_epoch_to_writer(Vec::new(), params, pk, trust_score, threshold, epoch, OsRng)
+}
+
+#[cfg(feature = "prover")]
+fn prove_trust_score_at_epoch_to_writer<W: Write>(
+    writer: W,
+    params: &Params<EqAffine>,
+    pk: &ProvingKey<EqAffine>,
+    trust_score: u64,
+    threshold: u64,
+    epoch: u64,
+    rng: impl RngCore + CryptoRng,
+) -> Result<W, ZkError> {
+    check_trust_score_range(trust_score, threshold)?;
+
+    // Create the circuit with the actual trust score
+    let circuit = TrustScoreCircuit::<Fp>::new_at_epoch(Some(trust_score), threshold, epoch);
+
+    // Public inputs: the comparison result, the threshold it was made against and the epoch
+    let public_inputs = public_inputs_at_epoch::<Fp>(trust_score >= threshold, threshold, epoch);
+
+    prove_in_domain(CircuitTag::TrustScore, writer, params, pk, circuit, &[public_inputs], rng)
+}
+
+/// Prove `(trust_score, threshold)` inputs on a pool of `num_threads` workers
+///
+/// For server-side bulk proving. Each worker holds its own handle to `pk`;
+/// a `num_threads` of 0 uses one worker per core. Returns one entry per
+/// input, in input order, and an invalid input only fails its own entry.
+/// Fails as a whole only if the thread pool cannot be started.
+#[cfg(all(feature = "prover", feature = "parallel"))]
+pub fn generate_trust_score_proofs_parallel(
+    params: &Params<EqAffine>,
+    pk: std::sync::Arc<ProvingKey<EqAffine>>,
+    inputs: &[(u64, u64)],
+    num_threads: usize,
+) -> Result<Vec<Result<Vec<u8>, ZkError>>, ZkError> {
+    use rayon::prelude::*;
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(num_threads)
+        .build()
+        .map_err(|e| ZkError::ProofFailed(format!("Failed to start proving threads: {}", e)))?;
+
+    Ok(pool.install(|| {
+        inputs
+            .par_iter()
+            .map_init(
+                || std::sync::Arc::clone(&pk),
+                |pk, &(trust_score, threshold)| prove_trust_score(params, pk, trust_score, threshold),
+            )
+            .collect()
+    }))
+}
+
+/// Prove several `(trust_score, threshold)` inputs in a single proof
+///
+/// All the circuits go through one `create_proof`, which is much cheaper
+/// than a proof per input, but the proof can only be checked as a whole by
+/// `verify_trust_scores_combined`, with claims in the same order. Every input
+/// is range checked first, and an empty batch is invalid input.
+#[cfg(feature = "prover")]
+pub fn prove_trust_scores_combined(
+    params: &Params<EqAffine>,
+    pk: &ProvingKey<EqAffine>,
+    inputs: &[(u64, u64)],
+) -> Result<Vec<u8>, ZkError> {
+    if inputs.is_empty() {
+        return Err(ZkError::InvalidInput("No trust scores to prove".into()));
+    }
+    for &(trust_score, threshold) in inputs {
+        check_trust_score_range(trust_score, threshold)?;
+    }
+
+    let circuits: Vec<_> = inputs
+        .iter()
+        .map(|&(trust_score, threshold)| TrustScoreCircuit::<Fp>::new(Some(trust_score), threshold))
+        .collect();
+    let instances: Vec<_> = inputs
+        .iter()
+        .map(|&(trust_score, threshold)| vec![public_inputs::<Fp>(trust_score >= threshold, threshold)])
+        .collect();
+
+    prove_many_in_domain(CircuitTag::TrustScore, Vec::new(), params, pk, &circuits, &instances, OsRng)
+}
+
+/// Check a proof from `prove_trust_scores_combined` against one
+/// `(threshold, expected_result)` claim per input, in input order
+///
+/// Returns `Ok(false)` unless every claim holds; a proof made for a
+/// different number of inputs does not verify.
+pub fn verify_trust_scores_combined(
+    params: &Params<EqAffine>,
+    vk: &VerifyingKey<EqAffine>,
+    proof: &[u8],
+    claims: &[(u64, bool)],
+) -> Result<bool, ZkError> {
+    if claims.is_empty() {
+        return Err(ZkError::InvalidInput("No trust score claims to verify".into()));
+    }
+
+    let instances: Vec<_> = claims
+        .iter()
+        .map(|&(threshold, expected_result)| vec![public_inputs::<Fp>(expected_result, threshold)])
+        .collect();
+
+    verify_many_in_domain(CircuitTag::TrustScore, params, vk, proof, &instances)
+}
+
+/// Check a trust score proof made at any epoch in `epochs`
+///
+/// Tries each epoch in turn, so keep the window small (such as the current
+/// and previous epoch). Returns `Ok(false)` for a proof that does not verify
+/// at any of them, including one made for an epoch outside the window.
+pub fn verify_trust_score_in_epochs(
+    params: &Params<EqAffine>,
+    vk: &VerifyingKey<EqAffine>,
+    proof: &[u8],
+    threshold: u64,
+    expected_result: bool,
+    epochs: RangeInclusive<u64>,
+) -> Result<bool, ZkError> {
+    if proof.is_empty() {
+        return Err(ZkError::InvalidInput("Proof is empty".into()));
+    }
+
+    for epoch in epochs {
+        let public_inputs = public_inputs_at_epoch::<Fp>(expected_result, threshold, epoch);
+        if verify_with_instances(params, vk, proof, &[public_inputs])? {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// What a trust score proof attests: whether the score clears its threshold
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrustScoreOutcome {
+    Pass,
+    Fail,
+}
+
+impl TrustScoreOutcome {
+    /// Whether the score is at least the threshold
+    pub fn passed(self) -> bool {
+        self == TrustScoreOutcome::Pass
+    }
+}
+
+/// Check a trust score proof against `threshold` and report the result it attests
+///
+/// Unlike `verify_trust_score`, the caller supplies no expected result: the
+/// circuit binds the result to the comparison, so at most one of the two
+/// possible results verifies. Only proofs made for epoch 0 verify. A proof
+/// that verifies with neither result fails with `VerifyFailed`.
+pub fn verify_trust_score_outcome(
+    params: &Params<EqAffine>,
+    vk: &VerifyingKey<EqAffine>,
+    proof: &[u8],
+    threshold: u64,
+) -> Result<TrustScoreOutcome, ZkError> {
+    if verify_trust_score(params, vk, proof, threshold, true)? {
+        Ok(TrustScoreOutcome::Pass)
+    } else if verify_trust_score(params, vk, proof, threshold, false)? {
+        Ok(TrustScoreOutcome::Fail)
+    } else {
+        Err(ZkError::VerifyFailed(format!(
+            "Proof does not verify against threshold {} with either result",
+            threshold
+        )))
+    }
+}
+
+/// Check a trust score proof against `threshold` and the claimed result
+///
+/// Only proofs made for epoch 0 verify. Returns `Ok(false)` for a proof that
+/// does not verify.
+pub fn verify_trust_score(
+    params: &Params<EqAffine>,
+    vk: &VerifyingKey<EqAffine>,
+    proof: &[u8],
+    threshold: u64,
+    expected_result: bool,
+) -> Result<bool, ZkError> {
+    if proof.is_empty() {
+        return Err(ZkError::InvalidInput("Proof is empty".into()));
+    }
+
+    // Expected public inputs: the result and the threshold it must be proven against
+    let public_inputs = public_inputs::<Fp>(expected_result, threshold);
+
+    verify_with_instances(params, vk, proof, &[public_inputs])
+}
+
+/// Verify a trust score proof against caller-supplied instance columns
+///
+/// Unlike `verify_trust_score`, which builds `[result, threshold, epoch]` itself,
+/// the columns are used as given, so callers control the instance layout.
+/// Columns that do not match the circuit's make the proof fail to verify.
+pub fn verify_with_instances(
+    params: &Params<EqAffine>,
+    vk: &VerifyingKey<EqAffine>,
+    proof: &[u8],
+    instances: &[Vec<Fp>],
+) -> Result<bool, ZkError> {
+    verify_in_domain(CircuitTag::TrustScore, params, vk, proof, instances)
+}
+
+/// Canonical encoding of a field element: 32 bytes, least significant first
+///
+/// Every instance value crossing the bindings uses this encoding. Clients
+/// holding a big-endian value (such as a JavaScript `BigInt` written out
+/// most significant byte first, or a Swift or Kotlin `BigInteger`) must
+/// reverse its bytes and left-pad it to 32.
+pub fn fp_to_le_bytes(value: &Fp) -> [u8; 32] {
+    value.to_repr()
+}
+
+/// Read a field element written by `fp_to_le_bytes`
+///
+/// Fails for a non-canonical encoding, one whose value is not below the
+/// field modulus, rather than silently reducing it, so each field element
+/// has exactly one accepted encoding.
+pub fn fp_from_le_bytes(bytes: &[u8; 32]) -> Result<Fp, ZkError> {
+    Option::from(Fp::from_repr(*bytes))
+        .ok_or_else(|| ZkError::Serialization("Field element is not canonical (not below the field modulus)".into()))
+}
+
+/// Serialize an instance column as consecutive `fp_to_le_bytes` encodings
+pub fn encode_instance_column(column: &[Fp]) -> Vec<u8> {
+    column.iter().flat_map(fp_to_le_bytes).collect()
+}
+
+/// Read an instance column written by `encode_instance_column`
+///
+/// Fails if the length is not a multiple of 32 bytes or an element is not
+/// canonical (not below the field modulus).
+pub fn decode_instance_column(bytes: &[u8]) -> Result<Vec<Fp>, ZkError> {
+    if !bytes.len().is_multiple_of(32) {
+        return Err(ZkError::Serialization(format!(
+            "Instance column is {} bytes, not a whole number of 32 byte field elements",
+            bytes.len()
+        )));
+    }
+
+    bytes
+        .chunks_exact(32)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let chunk = chunk.try_into().expect("chunks are 32 bytes");
+            fp_from_le_bytes(chunk)
+                .map_err(|_| ZkError::Serialization(format!("Instance element {} is not a canonical field element", i)))
+        })
+        .collect()
+}
+
+/// Verification strategy handing back each proof's MSM, so a batch is
+/// checked with a single evaluation
+struct BatchStrategy<'params> {
+    msm: MSM<'params, EqAffine>,
+}
+
+impl<'params> VerificationStrategy<'params, EqAffine> for BatchStrategy<'params> {
+    type Output = MSM<'params, EqAffine>;
+
+    fn process<E: EncodedChallenge<EqAffine>>(
+        self,
+        f: impl FnOnce(MSM<'params, EqAffine>) -> Result<Guard<'params, EqAffine, E>, Error>,
+    ) -> Result<Self::Output, Error> {
+        Ok(f(self.msm)?.use_challenges())
+    }
+}
+
+/// A trust score proof with the statement it should be checked against
+pub struct TrustScoreClaim<'a> {
+    pub proof: &'a [u8],
+    pub threshold: u64,
+    pub expected_result: bool,
+}
+
+/// How a `TrustScoreVerifier` checks the proofs fed to it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VerifyStrategy {
+    /// Check each proof in full as it arrives
+    #[default]
+    Single,
+    /// Fold each proof's MSM into an accumulator and check them all at
+    /// `finalize`, paying for a single multi-scalar multiplication
+    Accumulate,
+}
+
+/// Verifier for a stream of trust score proofs with a chosen strategy
+///
+/// Feed proofs with `verify` over any stretch of time, then call `finalize`
+/// for whether every one verified. With `Accumulate`, a failing proof only
+/// shows at `finalize` and is not identified; re-check the proofs singly (as
+/// `verify_trust_scores_batch` does) to find it.
+pub struct TrustScoreVerifier<'params> {
+    params: &'params Params<EqAffine>,
+    vk: &'params VerifyingKey<EqAffine>,
+    strategy: VerifyStrategy,
+    /// Accumulated MSM, or `None` once a proof has failed
+    accumulator: Option<MSM<'params, EqAffine>>,
+}
+
+impl<'params> TrustScoreVerifier<'params> {
+    pub fn new(params: &'params Params<EqAffine>, vk: &'params VerifyingKey<EqAffine>, strategy: VerifyStrategy) -> Self {
+        Self {
+            params,
+            vk,
+            strategy,
+            accumulator: Some(params.empty_msm()),
+        }
+    }
+
+    /// Check a trust score proof against `threshold` and the claimed result
+    ///
+    /// With `Single`, returns whether the proof verified. With `Accumulate`,
+    /// `Ok(true)` only means the proof was folded in; `Ok(false)` means it
+    /// already failed on its own. An empty proof is an error either way.
+    pub fn verify(&mut self, proof: &[u8], threshold: u64, expected_result: bool) -> Result<bool, ZkError> {
+        assert_params_compatible(self.params, self.vk)?;
+        if proof.is_empty() {
+            return Err(ZkError::InvalidInput("Proof is empty".into()));
+        }
+
+        let verified = match self.strategy {
+            VerifyStrategy::Single => verify_trust_score(self.params, self.vk, proof, threshold, expected_result)?,
+            VerifyStrategy::Accumulate => {
+                let public_inputs = public_inputs::<Fp>(expected_result, threshold);
+                match self.accumulator.as_mut() {
+                    Some(acc) => accumulate(self.params, self.vk, acc, proof, &public_inputs),
+                    None => accumulate(self.params, self.vk, &mut self.params.empty_msm(), proof, &public_inputs),
+                }
+            }
+        };
+
+        if !verified {
+            self.accumulator = None;
+        }
+        Ok(verified)
+    }
+
+    /// Whether every proof fed to `verify` verified (true if there were none)
+    pub fn finalize(self) -> bool {
+        self.accumulator.is_some_and(|msm| msm.eval())
+    }
+}
+
+/// Fold a trust score proof's MSM into `acc`, returning false if the proof
+/// fails before the final MSM check
+fn accumulate<'params>(
+    params: &'params Params<EqAffine>,
+    vk: &VerifyingKey<EqAffine>,
+    acc: &mut MSM<'params, EqAffine>,
+    proof: &[u8],
+    public_inputs: &[Fp],
+) -> bool {
+    let mut transcript = Blake2bRead::<&[u8], EqAffine, Challenge255<_>>::init(proof);
+    if bind_domain(&mut transcript, CircuitTag::TrustScore).is_err() {
+        return false;
+    }
+
+    let strategy = BatchStrategy { msm: params.empty_msm() };
+    match verify_proof(params, vk, strategy, &[&[public_inputs]], &mut transcript) {
+        Ok(msm) => {
+            // A random factor keeps one proof's MSM from cancelling another's
+            acc.scale(Fp::random(OsRng));
+            acc.add_msm(&msm);
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+/// Whether every claim verifies, folding the proofs into a single MSM check
+///
+/// Stops at the first proof that fails on its own, including an empty one;
+/// a proof that only fails the final MSM check is caught at the end, without
+/// saying which one it was.
+fn verify_all_accumulated<'a>(
+    params: &Params<EqAffine>,
+    vk: &VerifyingKey<EqAffine>,
+    claims: impl IntoIterator<Item = &'a TrustScoreClaim<'a>>,
+) -> Result<bool, ZkError> {
+    let mut batch = TrustScoreVerifier::new(params, vk, VerifyStrategy::Accumulate);
+    for claim in claims {
+        if claim.proof.is_empty() || !batch.verify(claim.proof, claim.threshold, claim.expected_result)? {
+            return Ok(false);
+        }
+    }
+
+    Ok(batch.finalize())
+}
+
+/// Check several trust score proofs, amortizing the MSM work across them
+///
+/// The proofs are first checked together, each proof's MSM scaled by a
+/// random factor and summed. Only if the batch fails is each proof
+/// re-checked on its own to find the bad ones, so the common all-valid case
+/// pays for a single multi-scalar multiplication. Empty proofs are reported
+/// as invalid.
+pub fn verify_trust_scores_batch(
+    params: &Params<EqAffine>,
+    vk: &VerifyingKey<EqAffine>,
+    claims: &[TrustScoreClaim<'_>],
+) -> Result<Vec<bool>, ZkError> {
+    let non_empty = claims.iter().filter(|claim| !claim.proof.is_empty());
+    if verify_all_accumulated(params, vk, non_empty)? {
+        return Ok(claims.iter().map(|claim| !claim.proof.is_empty()).collect());
+    }
+
+    claims
+        .iter()
+        .map(|claim| {
+            if claim.proof.is_empty() {
+                return Ok(false);
+            }
+            verify_trust_score(params, vk, claim.proof, claim.threshold, claim.expected_result)
+        })
+        .collect()
+}
+
+/// What a batch verification reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BatchMode {
+    /// A single verdict for the whole batch, stopping early once a proof is
+    /// known to fail
+    AllOrNothing,
+    /// Whether each proof verified, in order
+    #[default]
+    Report,
+}
+
+impl fmt::Display for BatchMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BatchMode::AllOrNothing => write!(f, "all_or_nothing"),
+            BatchMode::Report => write!(f, "report"),
+        }
+    }
+}
+
+impl FromStr for BatchMode {
+    type Err = ZkError;
+
+    fn from_str(name: &str) -> Result<Self, ZkError> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "all_or_nothing" => Ok(BatchMode::AllOrNothing),
+            "report" => Ok(BatchMode::Report),
+            other => Err(ZkError::InvalidInput(format!("Unknown batch mode: {}", other))),
+        }
+    }
+}
+
+/// Result of a batch verification, shaped by its `BatchMode`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BatchVerdict {
+    /// Whether every proof verified (`BatchMode::AllOrNothing`)
+    All(bool),
+    /// Whether each proof verified, in order (`BatchMode::Report`)
+    Report(Vec<bool>),
+}
+
+/// Check several trust score proofs, reporting as `mode` asks
+///
+/// `AllOrNothing` pays for a single MSM check when every proof is valid and
+/// returns as soon as one fails on its own, without finding the others.
+/// `Report` is `verify_trust_scores_batch`. An empty batch verifies.
+pub fn verify_trust_scores_batch_mode(
+    params: &Params<EqAffine>,
+    vk: &VerifyingKey<EqAffine>,
+    claims: &[TrustScoreClaim<'_>],
+    mode: BatchMode,
+) -> Result<BatchVerdict, ZkError> {
+    match mode {
+        BatchMode::AllOrNothing => Ok(BatchVerdict::All(verify_all_accumulated(params, vk, claims)?)),
+        BatchMode::Report => Ok(BatchVerdict::Report(verify_trust_scores_batch(params, vk, claims)?)),
+    }
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use super::*;
+    use crate::circuits::trust_score::MAX_TRUST_SCORE;
+    use crate::keys::{TrustScoreKeys, TRUST_SCORE_K};
+
+    #[test]
+    fn test_prove_and_verify() {
+        let keys = TrustScoreKeys::generate(TRUST_SCORE_K).unwrap();
+
+        let proof = prove_trust_score(&keys.params, &keys.proving_key, 85, 70).unwrap();
+        assert!(verify_trust_score(&keys.params, &keys.verifying_key, &proof, 70, true).unwrap());
+        assert!(!verify_trust_score(&keys.params, &keys.verifying_key, &proof, 70, false).unwrap());
+        assert!(!verify_trust_score(&keys.params, &keys.verifying_key, &proof, 80, true).unwrap());
+
+        let proof = prove_trust_score(&keys.params, &keys.proving_key, 65, 70).unwrap();
+        assert!(verify_trust_score(&keys.params, &keys.verifying_key, &proof, 70, false).unwrap());
+    }
+
+    #[test]
+    fn test_verify_outcome_without_hint() {
+        let keys = TrustScoreKeys::generate(TRUST_SCORE_K).unwrap();
+        let outcome = |trust_score, threshold| {
+            let proof = prove_trust_score(&keys.params, &keys.proving_key, trust_score, threshold).unwrap();
+            verify_trust_score_outcome(&keys.params, &keys.verifying_key, &proof, 70)
+        };
+
+        assert_eq!(outcome(85, 70), Ok(TrustScoreOutcome::Pass));
+        assert_eq!(outcome(65, 70), Ok(TrustScoreOutcome::Fail));
+        assert!(matches!(outcome(85, 80), Err(ZkError::VerifyFailed(_))));
+        assert!(matches!(
+            verify_trust_score_outcome(&keys.params, &keys.verifying_key, &[], 70),
+            Err(ZkError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_seeded_proofs_are_reproducible() {
+        use rand_chacha::ChaCha20Rng;
+        use rand::SeedableRng;
+
+        let keys = TrustScoreKeys::generate(TRUST_SCORE_K).unwrap();
+        let prove = |seed| {
+            prove_trust_score_with_rng(&keys.params, &keys.proving_key, 85, 70, ChaCha20Rng::seed_from_u64(seed)).unwrap()
+        };
+
+        let proof = prove(7);
+        assert_eq!(proof, prove(7));
+        assert_ne!(proof, prove(8));
+        assert!(verify_trust_score(&keys.params, &keys.verifying_key, &proof, 70, true).unwrap());
+    }
+
+    #[test]
+    fn test_verify_with_instances() {
+        let keys = TrustScoreKeys::generate(TRUST_SCORE_K).unwrap();
+        let proof = prove_trust_score(&keys.params, &keys.proving_key, 85, 70).unwrap();
+
+        let verify = |instances: &[Vec<Fp>]| verify_with_instances(&keys.params, &keys.verifying_key, &proof, instances).unwrap();
+        assert!(verify(&[vec![Fp::one(), Fp::from(70)]]));
+        assert!(!verify(&[vec![Fp::one(), Fp::from(80)]]));
+        assert!(!verify(&[vec![Fp::one()]]));
+        assert!(!verify(&[]));
+    }
+
+    #[test]
+    fn test_combined_proof() {
+        let keys = TrustScoreKeys::generate(TRUST_SCORE_K).unwrap();
+        let proof = prove_trust_scores_combined(&keys.params, &keys.proving_key, &[(85, 70), (65, 70), (90, 90)]).unwrap();
+
+        let verify = |claims: &[(u64, bool)]| verify_trust_scores_combined(&keys.params, &keys.verifying_key, &proof, claims).unwrap();
+        assert!(verify(&[(70, true), (70, false), (90, true)]));
+
+        // Every claim must hold, in order, and for every input
+        assert!(!verify(&[(70, true), (70, true), (90, true)]));
+        assert!(!verify(&[(70, false), (70, true), (90, true)]));
+        assert!(!verify(&[(70, true), (70, false)]));
+        assert!(!verify_trust_score(&keys.params, &keys.verifying_key, &proof, 70, true).unwrap());
+
+        assert!(matches!(
+            prove_trust_scores_combined(&keys.params, &keys.proving_key, &[]),
+            Err(ZkError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            prove_trust_scores_combined(&keys.params, &keys.proving_key, &[(85, 70), (150, 70)]),
+            Err(ZkError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            verify_trust_scores_combined(&keys.params, &keys.verifying_key, &proof, &[]),
+            Err(ZkError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_field_byte_encoding() {
+        for value in [Fp::zero(), Fp::one(), Fp::from(70), Fp::from(u64::MAX), -Fp::one()] {
+            assert_eq!(fp_from_le_bytes(&fp_to_le_bytes(&value)), Ok(value));
+        }
+
+        // Least significant byte first
+        let mut bytes = [0u8; 32];
+        bytes[0] = 0x46;
+        bytes[1] = 0x01;
+        assert_eq!(fp_to_le_bytes(&Fp::from(0x0146)), bytes);
+
+        // The modulus itself, one past the largest element, encodes zero non-canonically
+        let mut modulus = fp_to_le_bytes(&-Fp::one());
+        modulus[0] += 1;
+        assert!(matches!(fp_from_le_bytes(&modulus), Err(ZkError::Serialization(_))));
+        assert!(matches!(fp_from_le_bytes(&[0xff; 32]), Err(ZkError::Serialization(_))));
+    }
+
+    #[test]
+    fn test_instance_column_encoding() {
+        let column = vec![Fp::one(), Fp::from(70), -Fp::one()];
+        let bytes = encode_instance_column(&column);
+        assert_eq!(bytes.len(), 96);
+        assert_eq!(decode_instance_column(&bytes), Ok(column));
+
+        assert!(matches!(decode_instance_column(&bytes[..95]), Err(ZkError::Serialization(_))));
+        assert!(matches!(decode_instance_column(&[0xff; 32]), Err(ZkError::Serialization(_))));
+        assert_eq!(decode_instance_column(&[]), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_malformed_proofs() {
+        let keys = TrustScoreKeys::generate(TRUST_SCORE_K).unwrap();
+
+        assert!(matches!(
+            verify_trust_score(&keys.params, &keys.verifying_key, &[], 70, true),
+            Err(ZkError::InvalidInput(_))
+        ));
+        assert!(!verify_trust_score(&keys.params, &keys.verifying_key, &[7u8; 40], 70, true).unwrap());
+    }
+
+    #[test]
+    fn test_batch_verification() {
+        let keys = TrustScoreKeys::generate(TRUST_SCORE_K).unwrap();
+        let above = prove_trust_score(&keys.params, &keys.proving_key, 85, 70).unwrap();
+        let below = prove_trust_score(&keys.params, &keys.proving_key, 65, 70).unwrap();
+
+        let claim = |proof, expected_result| TrustScoreClaim { proof, threshold: 70, expected_result };
+        let valid = [claim(&above, true), claim(&below, false)];
+        assert_eq!(verify_trust_scores_batch(&keys.params, &keys.verifying_key, &valid).unwrap(), vec![true, true]);
+
+        // One bad claim does not hide the valid ones
+        let mixed = [claim(&above, true), claim(&below, true), claim(&[], true)];
+        assert_eq!(
+            verify_trust_scores_batch(&keys.params, &keys.verifying_key, &mixed).unwrap(),
+            vec![true, false, false]
+        );
+
+        assert!(verify_trust_scores_batch(&keys.params, &keys.verifying_key, &[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_batch_modes() {
+        let keys = TrustScoreKeys::generate(TRUST_SCORE_K).unwrap();
+        let above = prove_trust_score(&keys.params, &keys.proving_key, 85, 70).unwrap();
+        let below = prove_trust_score(&keys.params, &keys.proving_key, 65, 70).unwrap();
+        let verify = |claims: &[TrustScoreClaim<'_>], mode| {
+            verify_trust_scores_batch_mode(&keys.params, &keys.verifying_key, claims, mode).unwrap()
+        };
+
+        let claim = |proof, expected_result| TrustScoreClaim { proof, threshold: 70, expected_result };
+        let valid = [claim(&above, true), claim(&below, false), claim(&above, true)];
+        assert_eq!(verify(&valid, BatchMode::AllOrNothing), BatchVerdict::All(true));
+        assert_eq!(verify(&valid, BatchMode::Report), BatchVerdict::Report(vec![true, true, true]));
+
+        // One proof claiming the wrong result
+        let one_failing = [claim(&above, true), claim(&below, true), claim(&above, true)];
+        assert_eq!(verify(&one_failing, BatchMode::AllOrNothing), BatchVerdict::All(false));
+        assert_eq!(verify(&one_failing, BatchMode::Report), BatchVerdict::Report(vec![true, false, true]));
+
+        let truncated = [claim(&above, true), claim(&above[..above.len() / 2], true)];
+        assert_eq!(verify(&truncated, BatchMode::AllOrNothing), BatchVerdict::All(false));
+        assert_eq!(verify(&[claim(&[], true)], BatchMode::AllOrNothing), BatchVerdict::All(false));
+        assert_eq!(verify(&[], BatchMode::AllOrNothing), BatchVerdict::All(true));
+
+        assert_eq!("all_or_nothing".parse::<BatchMode>().unwrap(), BatchMode::AllOrNothing);
+        assert_eq!(" Report ".parse::<BatchMode>().unwrap(), BatchMode::Report);
+        assert_eq!(BatchMode::AllOrNothing.to_string().parse::<BatchMode>().unwrap(), BatchMode::AllOrNothing);
+        assert!("first_failure".parse::<BatchMode>().is_err());
+    }
+
+    #[test]
+    fn test_out_of_range_inputs_rejected() {
+        let keys = TrustScoreKeys::generate(TRUST_SCORE_K).unwrap();
+
+        assert!(matches!(
+            prove_trust_score(&keys.params, &keys.proving_key, 150, 70),
+            Err(ZkError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            prove_trust_score(&keys.params, &keys.proving_key, 85, MAX_TRUST_SCORE + 1),
+            Err(ZkError::InvalidInput(_))
+        ));
+
+        let proof = prove_trust_score(&keys.params, &keys.proving_key, MAX_TRUST_SCORE, 70).unwrap();
+        assert!(verify_trust_score(&keys.params, &keys.verifying_key, &proof, 70, true).unwrap());
+    }
+
+    #[test]
+    fn test_proofs_bound_to_circuit_domain() {
+        use crate::circuits::income_range::IncomeRangeCircuit;
+        use halo2_proofs::plonk::{keygen_pk, keygen_vk};
+
+        let params = Params::<EqAffine>::new(8);
+        let circuit = IncomeRangeCircuit::<Fp>::new(Some(50_000), 30_000, 80_000);
+        let vk = keygen_vk(&params, &circuit).unwrap();
+        let pk = keygen_pk(&params, vk.clone(), &circuit).unwrap();
+        let instances = vec![circuit.expected_public_inputs()];
+
+        let proof = prove_in_domain(CircuitTag::IncomeRange, Vec::new(), &params, &pk, circuit, &instances, OsRng).unwrap();
+        assert!(verify_in_domain(CircuitTag::IncomeRange, &params, &vk, &proof, &instances).unwrap());
+
+        // The same proof, key and inputs are rejected in another circuit's domain
+        assert!(!verify_in_domain(CircuitTag::TrustScore, &params, &vk, &proof, &instances).unwrap());
+        assert!(!verify_with_instances(&params, &vk, &proof, &instances).unwrap());
+
+        // A trust score proof does not verify against the income range key
+        let keys = TrustScoreKeys::generate(8).unwrap();
+        let trust_proof = prove_trust_score(&keys.params, &keys.proving_key, 85, 70).unwrap();
+        assert!(!verify_in_domain(CircuitTag::IncomeRange, &params, &vk, &trust_proof, &instances).unwrap());
+        assert_ne!(transcript_domain(CircuitTag::TrustScore), transcript_domain(CircuitTag::IncomeRange));
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parallel_proofs_match_sequential() {
+        use std::sync::Arc;
+
+        let keys = TrustScoreKeys::generate(TRUST_SCORE_K).unwrap();
+        let pk = Arc::new(keys.proving_key.clone());
+        let inputs = [(85, 70), (65, 70), (MAX_TRUST_SCORE + 1, 70), (70, 70), (0, 0), (40, 50)];
+
+        // Proofs are randomized, so compare what each one proves
+        let sequential: Vec<_> = inputs
+            .iter()
+            .map(|&(score, threshold)| prove_trust_score(&keys.params, &keys.proving_key, score, threshold))
+            .collect();
+        let parallel = generate_trust_score_proofs_parallel(&keys.params, pk.clone(), &inputs, 3).unwrap();
+        assert_eq!(parallel.len(), inputs.len());
+
+        for ((&(score, threshold), sequential), parallel) in inputs.iter().zip(&sequential).zip(&parallel) {
+            match (sequential, parallel) {
+                (Ok(_), Ok(proof)) => {
+                    assert!(verify_trust_score(&keys.params, &keys.verifying_key, proof, threshold, score >= threshold).unwrap());
+                }
+                (Err(expected), Err(actual)) => assert_eq!(expected, actual),
+                _ => panic!("({}, {}) proved differently in parallel", score, threshold),
+            }
+        }
+
+        // A single worker proves everything in order too
+        let single = generate_trust_score_proofs_parallel(&keys.params, pk, &inputs[..2], 1).unwrap();
+        assert!(verify_trust_score(&keys.params, &keys.verifying_key, single[0].as_ref().unwrap(), 70, true).unwrap());
+        assert!(verify_trust_score(&keys.params, &keys.verifying_key, single[1].as_ref().unwrap(), 70, false).unwrap());
+    }
+
+    #[test]
+    fn test_mismatched_keys_rejected() {
+        use crate::circuits::optimizations::mobile_trust_score::MobileTrustScoreCircuit;
+        use halo2_proofs::plonk::keygen_vk;
+
+        let keys = TrustScoreKeys::generate(TRUST_SCORE_K).unwrap();
+        let other = TrustScoreKeys::generate(TRUST_SCORE_K + 1).unwrap();
+        let expected = format!(
+            "Key mismatch: params are for k = {} but the keys are for k = {}",
+            TRUST_SCORE_K + 1,
+            TRUST_SCORE_K
+        );
+        fn mismatch<T>(result: Result<T, ZkError>) -> String {
+            match result {
+                Err(err @ ZkError::KeyMismatch(_)) => err.to_string(),
+                Err(err) => panic!("expected a key mismatch, got {}", err),
+                Ok(_) => panic!("expected a key mismatch"),
+            }
+        }
+
+        assert_eq!(mismatch(prove_trust_score(&other.params, &keys.proving_key, 85, 70)), expected);
+
+        let proof = prove_trust_score(&keys.params, &keys.proving_key, 85, 70).unwrap();
+        assert_eq!(mismatch(verify_trust_score(&other.params, &keys.verifying_key, &proof, 70, true)), expected);
+        let claims = [TrustScoreClaim { proof: &proof, threshold: 70, expected_result: true }];
+        assert_eq!(mismatch(verify_trust_scores_batch(&other.params, &keys.verifying_key, &claims)), expected);
+
+        assert_keys_compatible(&keys.params, &keys.proving_key, &keys.verifying_key).unwrap();
+        assert_eq!(
+            mismatch(assert_keys_compatible(&keys.params, &other.proving_key, &keys.verifying_key)),
+            format!(
+                "Key mismatch: proving key is for k = {} but the verifying key is for k = {}",
+                TRUST_SCORE_K + 1,
+                TRUST_SCORE_K
+            )
+        );
+
+        // Same size, but generated for another circuit
+        let foreign_vk = keygen_vk(&keys.params, &MobileTrustScoreCircuit::<Fp>::new(None, 70)).unwrap();
+        assert_eq!(
+            mismatch(assert_keys_compatible(&keys.params, &keys.proving_key, &foreign_vk)),
+            "Key mismatch: verifying key was not generated with the proving key"
+        );
+    }
+
+    #[test]
+    fn test_proof_bound_to_epoch() {
+        use crate::circuits::trust_score::utils::public_inputs_at_epoch;
+
+        let keys = TrustScoreKeys::generate(TRUST_SCORE_K).unwrap();
+        let verify = |proof: &[u8], epochs| {
+            verify_trust_score_in_epochs(&keys.params, &keys.verifying_key, proof, 70, true, epochs).unwrap()
+        };
+        let proof = prove_trust_score_at_epoch(&keys.params, &keys.proving_key, 85, 70, 7).unwrap();
+
+        assert!(verify(&proof, 7..=7));
+        assert!(!verify(&proof, 8..=8), "a proof for epoch 7 verified at epoch 8");
+        assert!(verify(&proof, 6..=8));
+        assert!(!verify_trust_score(&keys.params, &keys.verifying_key, &proof, 70, true).unwrap());
+        assert!(verify_with_instances(&keys.params, &keys.verifying_key, &proof, &[public_inputs_at_epoch(true, 70, 7)]).unwrap());
+
+        // Proofs without an epoch are made for epoch 0, which `[result, threshold]` still verifies
+        let proof = prove_trust_score(&keys.params, &keys.proving_key, 85, 70).unwrap();
+        assert!(verify(&proof, 0..=0));
+        assert!(verify_with_instances(&keys.params, &keys.verifying_key, &proof, &[vec![Fp::one(), Fp::from(70)]]).unwrap());
+    }
+
+    #[test]
+    fn test_accumulating_verifier() {
+        let keys = TrustScoreKeys::generate(TRUST_SCORE_K).unwrap();
+        let prove = |score| prove_trust_score(&keys.params, &keys.proving_key, score, 70).unwrap();
+        let proofs = [(prove(85), true), (prove(65), false), (prove(70), true)];
+
+        for strategy in [VerifyStrategy::Single, VerifyStrategy::Accumulate] {
+            let verifier = || TrustScoreVerifier::new(&keys.params, &keys.verifying_key, strategy);
+
+            let mut all_valid = verifier();
+            for (proof, expected_result) in &proofs {
+                assert!(all_valid.verify(proof, 70, *expected_result).unwrap());
+            }
+            assert!(all_valid.finalize(), "{:?} rejected three valid proofs", strategy);
+
+            // One wrong claim among the three rejects the whole set
+            let mut one_invalid = verifier();
+            for (i, (proof, expected_result)) in proofs.iter().enumerate() {
+                let verified = one_invalid.verify(proof, 70, *expected_result != (i == 1)).unwrap();
+                assert_eq!(verified, strategy == VerifyStrategy::Accumulate || i != 1);
+            }
+            assert!(!one_invalid.finalize(), "{:?} accepted a wrong claim", strategy);
+
+            assert!(verifier().finalize());
+            assert!(matches!(verifier().verify(&[], 70, true), Err(ZkError::InvalidInput(_))));
+        }
+
+        // A malformed proof fails as soon as it is fed in
+        let mut verifier = TrustScoreVerifier::new(&keys.params, &keys.verifying_key, VerifyStrategy::Accumulate);
+        assert!(!verifier.verify(&proofs[0].0[..64], 70, true).unwrap());
+        assert!(verifier.verify(&proofs[0].0, 70, true).unwrap());
+        assert!(!verifier.finalize());
+    }
+
+    #[test]
+    fn test_prove_to_writer_matches_in_memory() {
+        use rand::SeedableRng;
+        use rand_chacha::ChaCha20Rng;
+        use std::io::Cursor;
+
+        let keys = TrustScoreKeys::generate(TRUST_SCORE_K).unwrap();
+        let rng = || ChaCha20Rng::seed_from_u64(7);
+
+        let in_memory = prove_trust_score_with_rng(&keys.params, &keys.proving_key, 85, 70, rng()).unwrap();
+        let cursor = prove_trust_score_to_writer(Cursor::new(Vec::new()), &keys.params, &keys.proving_key, 85, 70, rng()).unwrap();
+        assert_eq!(cursor.into_inner(), in_memory);
+
+        // The streamed bytes start after whatever the sink already holds
+        let mut prefixed = Cursor::new(b"header".to_vec());
+        prefixed.set_position(6);
+        let streamed = prove_trust_score_to_writer(prefixed, &keys.params, &keys.proving_key, 85, 70, rng()).unwrap().into_inner();
+        assert_eq!(&streamed[6..], &in_memory[..]);
+        assert!(verify_trust_score(&keys.params, &keys.verifying_key, &streamed[6..], 70, true).unwrap());
+    }
+
+    #[cfg(feature = "tracing")]
+    #[tracing_test::traced_test]
+    #[test]
+    fn test_prove_span_records_k() {
+        let keys = TrustScoreKeys::generate(TRUST_SCORE_K).unwrap();
+        prove_trust_score(&keys.params, &keys.proving_key, 85, 70).unwrap();
+
+        assert!(logs_contain(&format!("prove{{k={} circuit=trust_score}}", TRUST_SCORE_K)));
+        assert!(logs_contain("elapsed_ms="));
+    }
+}
@@ -0,0 +1,208 @@
+//! Verifying-key registry for rolling circuit upgrades
+//!
+//! When a circuit is upgraded, proofs generated against the previous verifying
+//! key are still in flight. The registry keeps every active `(version, key)`
+//! pair and dispatches each proof to the key named by its embedded version, so
+//! old and new proofs both verify until the old version is retired.
+//!
+//! `halo2_proofs` 0.3 has no verifying-key encoding, so a registered key is
+//! described by its serialized `Params` and re-derived with `keygen_vk`.
+
+use crate::circuits::trust_score::TrustScoreCircuit;
+use crate::envelope::CircuitTag;
+use crate::error::ZkError;
+use crate::keys::read_params;
+use crate::prover::verify_in_domain;
+use crate::trace::traced;
+use halo2_proofs::{
+    plonk::{keygen_vk, VerifyingKey},
+    poly::commitment::Params,
+};
+use pasta_curves::{EqAffine, Fp};
+use std::collections::BTreeMap;
+
+/// Number of bytes used for the key version prefix
+pub const VERSION_PREFIX_LEN: usize = 4;
+
+/// A verifying key together with the params it was generated from
+struct RegisteredKey {
+    params: Params<EqAffine>,
+    vk: VerifyingKey<EqAffine>,
+}
+
+/// Registry dispatching proof verification by key version
+#[derive(Default)]
+pub struct VerifierRegistry {
+    keys: BTreeMap<u32, RegisteredKey>,
+}
+
+impl VerifierRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the trust score verifying key derived from serialized params
+    ///
+    /// Re-registering an existing version replaces its key.
+    pub fn register_vk(&mut self, version: u32, bytes: &[u8]) -> Result<(), ZkError> {
+        let params = read_params(bytes)?;
+
+        let circuit = TrustScoreCircuit::<Fp>::new(None, 0);
+        let vk = traced!("keygen", "trust_score", params.k(), keygen_vk(&params, &circuit))
+            .map_err(|e| ZkError::KeygenFailed(format!("{:?}", e)))?;
+
+        self.keys.insert(version, RegisteredKey { params, vk });
+        Ok(())
+    }
+
+    /// Stop accepting proofs for a version; returns whether it was registered
+    pub fn retire_vk(&mut self, version: u32) -> bool {
+        self.keys.remove(&version).is_some()
+    }
+
+    /// Versions currently accepted, in ascending order
+    pub fn versions(&self) -> Vec<u32> {
+        self.keys.keys().copied().collect()
+    }
+
+    /// Verify a versioned proof against the key matching its embedded version
+    pub fn verify(&self, versioned_proof: &[u8], public_inputs: &[Fp]) -> Result<bool, ZkError> {
+        let (version, proof) = split_version(versioned_proof)
+            .ok_or_else(|| ZkError::InvalidInput("Proof is missing its version prefix".into()))?;
+        let key = self
+            .keys
+            .get(&version)
+            .ok_or(ZkError::UnknownKeyVersion(version))?;
+
+        verify_in_domain(CircuitTag::TrustScore, &key.params, &key.vk, proof, &[public_inputs.to_vec()])
+    }
+}
+
+/// Stable Blake2b fingerprint of a verifying key
+///
+/// Hashes the pinned representation (domain, constraint system and fixed
+/// commitments), so two keys share a fingerprint exactly when they accept the
+/// same proofs.
+pub fn vk_fingerprint_bytes(vk: &VerifyingKey<EqAffine>) -> [u8; 32] {
+    let hash = blake2b_simd::Params::new()
+        .hash_length(32)
+        .personal(b"ZkCircuitVkFprnt")
+        .hash(format!("{:?}", vk.pinned()).as_bytes());
+
+    let mut fingerprint = [0u8; 32];
+    fingerprint.copy_from_slice(hash.as_bytes());
+    fingerprint
+}
+
+/// Fingerprint of a verifying key, as lowercase hex
+pub fn vk_fingerprint(vk: &VerifyingKey<EqAffine>) -> String {
+    vk_fingerprint_bytes(vk).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Prefix proof bytes with the key version they were generated against
+pub fn embed_version(version: u32, proof: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(VERSION_PREFIX_LEN + proof.len());
+    bytes.extend_from_slice(&version.to_le_bytes());
+    bytes.extend_from_slice(proof);
+    bytes
+}
+
+/// Split a versioned proof into its key version and the raw proof bytes
+pub fn split_version(versioned_proof: &[u8]) -> Option<(u32, &[u8])> {
+    if versioned_proof.len() < VERSION_PREFIX_LEN {
+        return None;
+    }
+
+    let (prefix, proof) = versioned_proof.split_at(VERSION_PREFIX_LEN);
+    let version = u32::from_le_bytes(prefix.try_into().ok()?);
+    Some((version, proof))
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use super::*;
+    use crate::circuits::trust_score::utils::public_inputs;
+    use crate::prover::prove_trust_score;
+    use halo2_proofs::plonk::keygen_pk;
+
+    /// Public inputs of a passing proof against threshold 70
+    fn trust_inputs() -> Vec<Fp> {
+        public_inputs(true, 70)
+    }
+
+    /// Serialize params for `k` and produce a passing trust score proof with them
+    fn params_and_proof(k: u32) -> (Vec<u8>, Vec<u8>) {
+        let params = Params::<EqAffine>::new(k);
+        let circuit = TrustScoreCircuit::<Fp>::new(Some(85), 70);
+        let vk = keygen_vk(&params, &circuit).unwrap();
+        let pk = keygen_pk(&params, vk, &circuit).unwrap();
+
+        let proof = prove_trust_score(&params, &pk, 85, 70).unwrap();
+
+        let mut params_bytes = vec![];
+        params.write(&mut params_bytes).unwrap();
+        (params_bytes, proof)
+    }
+
+    #[test]
+    fn test_old_and_new_versions_verify_during_rollout() {
+        let (old_params, old_proof) = params_and_proof(4);
+        let (new_params, new_proof) = params_and_proof(5);
+
+        let mut registry = VerifierRegistry::new();
+        registry.register_vk(1, &old_params).unwrap();
+        registry.register_vk(2, &new_params).unwrap();
+        assert_eq!(registry.versions(), vec![1, 2]);
+
+        assert!(registry.verify(&embed_version(1, &old_proof), &trust_inputs()).unwrap());
+        assert!(registry.verify(&embed_version(2, &new_proof), &trust_inputs()).unwrap());
+
+        // A proof dispatched to the wrong key does not verify
+        assert!(!registry.verify(&embed_version(2, &old_proof), &trust_inputs()).unwrap());
+    }
+
+    #[test]
+    fn test_retired_version_is_rejected() {
+        let (old_params, old_proof) = params_and_proof(4);
+
+        let mut registry = VerifierRegistry::new();
+        registry.register_vk(1, &old_params).unwrap();
+        assert!(registry.retire_vk(1));
+        assert!(!registry.retire_vk(1));
+
+        assert_eq!(
+            registry.verify(&embed_version(1, &old_proof), &trust_inputs()),
+            Err(ZkError::UnknownKeyVersion(1))
+        );
+    }
+
+    #[test]
+    fn test_malformed_inputs() {
+        let mut registry = VerifierRegistry::new();
+        assert!(matches!(
+            registry.register_vk(1, b"not params"),
+            Err(ZkError::Serialization(_))
+        ));
+        assert!(matches!(
+            registry.verify(&[0, 1], &trust_inputs()),
+            Err(ZkError::InvalidInput(_))
+        ));
+        assert_eq!(split_version(&embed_version(7, b"proof")), Some((7, &b"proof"[..])));
+    }
+
+    #[test]
+    fn test_vk_fingerprint_tracks_params() {
+        let circuit = TrustScoreCircuit::<Fp>::new(None, 0);
+        let vk_a = keygen_vk(&Params::<EqAffine>::new(4), &circuit).unwrap();
+        let vk_b = keygen_vk(&Params::<EqAffine>::new(4), &circuit).unwrap();
+        let vk_c = keygen_vk(&Params::<EqAffine>::new(5), &circuit).unwrap();
+
+        assert_eq!(vk_fingerprint(&vk_a).len(), 64);
+        assert_eq!(vk_fingerprint(&vk_a), vk_fingerprint(&vk_b));
+        assert_ne!(vk_fingerprint(&vk_a), vk_fingerprint(&vk_c));
+
+        // Different circuit sizes never share raw fingerprint bytes either
+        assert_ne!(vk_fingerprint_bytes(&vk_a), vk_fingerprint_bytes(&vk_c));
+        assert!(vk_fingerprint(&vk_c).starts_with(&format!("{:02x}", vk_fingerprint_bytes(&vk_c)[0])));
+    }
+}
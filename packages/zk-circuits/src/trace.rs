@@ -0,0 +1,23 @@
+//! Optional `tracing` spans around keygen, proving and verification
+//!
+//! With the `tracing` feature, `traced!` runs its body inside an info span
+//! named after the operation (`keygen`, `prove` or `verify`) that records the
+//! circuit size `k` and the circuit type, and emits an `elapsed_ms` event
+//! when the body finishes. The crate never installs a subscriber itself; the
+//! host sets one (see `ffi::set_tracing_subscriber`). Without the feature the
+//! macro expands to the body alone.
+
+macro_rules! traced {
+    ($name:literal, $circuit:expr, $k:expr, $body:expr) => {{
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!($name, k = $k, circuit = %$circuit).entered();
+        #[cfg(feature = "tracing")]
+        let started = std::time::Instant::now();
+        let output = $body;
+        #[cfg(feature = "tracing")]
+        tracing::info!(elapsed_ms = started.elapsed().as_secs_f64() * 1000.0, "{} finished", $name);
+        output
+    }};
+}
+
+pub(crate) use traced;
@@ -0,0 +1,225 @@
+//! Domain checks for circuit inputs
+//!
+//! Callers across the FFI boundary pass plain integers with no guarantee they
+//! are in range, and out-of-range values either wrap into the field or make
+//! the circuit unsatisfiable. Each binding runs the matching check first, so
+//! every circuit's bounds are enforced the same way everywhere. Failures are
+//! `ZkError::InvalidInput`, thrown to JavaScript as `[ZK5] Invalid input: ...`.
+
+use crate::circuits::income_range::INCOME_BITS;
+use crate::circuits::lending::{InstanceKind, InstanceSpec};
+use crate::circuits::loan_history::{try_field_to_u64, LOAN_HISTORY_BITS, SUCCESS_RATE_SCALE};
+use crate::circuits::trust_score::utils::check_trust_score_range;
+use crate::circuits::trust_score::MAX_TRUST_SCORE;
+use crate::error::ZkError;
+use ff::PrimeField;
+
+/// Reject a trust score or threshold above `MAX_TRUST_SCORE`
+pub fn validate_trust_inputs(trust_score: u64, threshold: u64) -> Result<(), ZkError> {
+    check_trust_score_range(trust_score, threshold)
+}
+
+/// Reject a threshold above `MAX_TRUST_SCORE`, for verifiers that never see a score
+pub fn validate_threshold(threshold: u64) -> Result<(), ZkError> {
+    if threshold > MAX_TRUST_SCORE {
+        return Err(ZkError::InvalidInput(format!(
+            "threshold {} exceeds the maximum of {}",
+            threshold, MAX_TRUST_SCORE
+        )));
+    }
+
+    Ok(())
+}
+
+/// Reject income range inputs wider than `INCOME_BITS` or an inverted range
+pub fn validate_income_range_inputs(income: u128, min_range: u128, max_range: u128) -> Result<(), ZkError> {
+    let limit = 1u128 << INCOME_BITS;
+    for (name, value) in [("income", income), ("min range", min_range), ("max range", max_range)] {
+        if value >= limit {
+            return Err(ZkError::InvalidInput(format!(
+                "{} {} does not fit in {} bits",
+                name, value, INCOME_BITS
+            )));
+        }
+    }
+    if min_range > max_range {
+        return Err(ZkError::InvalidInput(format!(
+            "min range {} is above max range {}",
+            min_range, max_range
+        )));
+    }
+
+    Ok(())
+}
+
+/// Reject loan counts wider than `LOAN_HISTORY_BITS`, more repayments than
+/// loans, or a success rate above `SUCCESS_RATE_SCALE`
+pub fn validate_loan_history_inputs(
+    num_loans: u64,
+    successful_repayments: u64,
+    min_success_rate: u64,
+    min_loans: u64,
+) -> Result<(), ZkError> {
+    for (name, value) in [("number of loans", num_loans), ("minimum loans", min_loans)] {
+        if value >= 1 << LOAN_HISTORY_BITS {
+            return Err(ZkError::InvalidInput(format!(
+                "{} {} does not fit in {} bits",
+                name, value, LOAN_HISTORY_BITS
+            )));
+        }
+    }
+    if successful_repayments > num_loans {
+        return Err(ZkError::InvalidInput(format!(
+            "successful repayments {} exceed the number of loans {}",
+            successful_repayments, num_loans
+        )));
+    }
+    if min_success_rate > SUCCESS_RATE_SCALE {
+        return Err(ZkError::InvalidInput(format!(
+            "minimum success rate {} exceeds {}",
+            min_success_rate, SUCCESS_RATE_SCALE
+        )));
+    }
+
+    Ok(())
+}
+
+/// Reject instance values outside the domain of their slot in `layout`
+///
+/// `Bool` slots such as an expected `result` must be 0 or 1, and `Integer`
+/// slots must fit in 64 bits. Without this a verifier passing 2 as the
+/// expected result would only see the proof fail, not learn why. Values
+/// past the end of the layout are left for verification to reject.
+pub fn validate_instance<F: PrimeField>(layout: &[InstanceSpec], instance: &[F]) -> Result<(), ZkError> {
+    for (spec, value) in layout.iter().zip(instance) {
+        match spec.kind {
+            InstanceKind::Bool if *value != F::ZERO && *value != F::ONE => {
+                return Err(ZkError::InvalidInput(format!("{} must be 0 or 1", spec.name)));
+            }
+            InstanceKind::Integer if try_field_to_u64(value).is_err() => {
+                return Err(ZkError::InvalidInput(format!("{} does not fit in 64 bits", spec.name)));
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Reject an index slot above `max`, such as a tier or bucket index
+fn validate_index<F: PrimeField>(name: &str, index: &F, max: usize) -> Result<(), ZkError> {
+    match try_field_to_u64(index) {
+        Ok(index) if index <= max as u64 => Ok(()),
+        _ => Err(ZkError::InvalidInput(format!("{} must be at most {}", name, max))),
+    }
+}
+
+/// Reject a trust tier instance `[tier, threshold_0, ..]` whose tier is not
+/// one of the `0..=N` a circuit with `N` thresholds can prove
+pub fn validate_tier_instance<F: PrimeField>(instance: &[F]) -> Result<(), ZkError> {
+    let (tier, thresholds) = instance
+        .split_first()
+        .ok_or_else(|| ZkError::InvalidInput("Tier instance is empty".into()))?;
+    validate_index("tier", tier, thresholds.len())
+}
+
+/// Reject an income bucket instance `[bucket_index, boundary_0, ..]` whose
+/// index is not one of the `n + 1` buckets `n` boundaries make
+pub fn validate_bucket_instance<F: PrimeField>(instance: &[F]) -> Result<(), ZkError> {
+    let (bucket, boundaries) = instance
+        .split_first()
+        .ok_or_else(|| ZkError::InvalidInput("Bucket instance is empty".into()))?;
+    validate_index("bucket index", bucket, boundaries.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuits::income_range::IncomeRangeCircuit;
+    use crate::circuits::lending::run_mock;
+    use crate::circuits::loan_history::LoanHistoryCircuit;
+    use crate::circuits::trust_score::TrustScoreCircuit;
+    use pasta_curves::Fp;
+
+    fn message(result: Result<(), ZkError>) -> String {
+        let err = result.unwrap_err();
+        assert_eq!(err.code(), 5);
+        err.to_string()
+    }
+
+    #[test]
+    fn test_trust_inputs() {
+        for (trust_score, threshold) in [(0, 0), (85, 70), (100, 100)] {
+            validate_trust_inputs(trust_score, threshold).unwrap();
+            validate_threshold(threshold).unwrap();
+            run_mock(&TrustScoreCircuit::<Fp>::new(Some(trust_score), threshold)).unwrap();
+        }
+
+        assert_eq!(
+            message(validate_trust_inputs(101, 70)),
+            "Invalid input: trust score 101 exceeds the maximum of 100"
+        );
+        assert!(message(validate_trust_inputs(85, 101)).contains("threshold 101"));
+        assert_eq!(
+            message(validate_threshold(u64::MAX)),
+            format!("Invalid input: threshold {} exceeds the maximum of 100", u64::MAX)
+        );
+    }
+
+    #[test]
+    fn test_income_range_inputs() {
+        let max = (1u128 << INCOME_BITS) - 1;
+        for (income, min_range, max_range) in [(50_000, 30_000, 80_000), (0, 0, 0), (max, 0, max)] {
+            validate_income_range_inputs(income, min_range, max_range).unwrap();
+            run_mock(&IncomeRangeCircuit::<Fp>::new(Some(income), min_range, max_range)).unwrap();
+        }
+
+        assert!(message(validate_income_range_inputs(max + 1, 0, max)).contains("income"));
+        assert!(message(validate_income_range_inputs(5, 0, max + 1)).contains("max range"));
+        assert!(message(validate_income_range_inputs(5, 10, 1)).contains("min range 10 is above max range 1"));
+    }
+
+    #[test]
+    fn test_loan_history_inputs() {
+        let max = (1u64 << LOAN_HISTORY_BITS) - 1;
+        for (num_loans, repayments, min_success_rate, min_loans) in [(10, 9, 8000, 3), (0, 0, 0, 0), (max, max, SUCCESS_RATE_SCALE, max)] {
+            validate_loan_history_inputs(num_loans, repayments, min_success_rate, min_loans).unwrap();
+            run_mock(&LoanHistoryCircuit::<Fp>::new(Some(num_loans), Some(repayments), min_success_rate, min_loans)).unwrap();
+        }
+
+        assert!(message(validate_loan_history_inputs(max + 1, 0, 8000, 1)).contains("number of loans"));
+        assert!(message(validate_loan_history_inputs(10, 9, 8000, max + 1)).contains("minimum loans"));
+        assert!(message(validate_loan_history_inputs(3, 5, 8000, 1)).contains("exceed the number of loans 3"));
+        assert!(message(validate_loan_history_inputs(10, 9, 10_001, 1)).contains("minimum success rate 10001"));
+    }
+
+    #[test]
+    fn test_instance_domains() {
+        use crate::circuits::lending::LendingCircuit;
+
+        let layout = TrustScoreCircuit::<Fp>::instance_layout();
+        for result in [0u64, 1] {
+            validate_instance(&layout, &[Fp::from(result), Fp::from(70), Fp::zero()]).unwrap();
+        }
+
+        // An expected result of 2 is a domain error, not just a failed proof
+        assert_eq!(
+            message(validate_instance(&layout, &[Fp::from(2), Fp::from(70)])),
+            "Invalid input: result must be 0 or 1"
+        );
+        assert!(message(validate_instance(&layout, &[Fp::one(), -Fp::one()])).contains("threshold does not fit in 64 bits"));
+    }
+
+    #[test]
+    fn test_index_domains() {
+        // Two thresholds make tiers 0 to 2; two boundaries make buckets 0 to 2
+        for index in [0u64, 1, 2] {
+            validate_tier_instance(&[Fp::from(index), Fp::from(50), Fp::from(80)]).unwrap();
+            validate_bucket_instance(&[Fp::from(index), Fp::from(20_000), Fp::from(60_000)]).unwrap();
+        }
+
+        assert!(message(validate_tier_instance(&[Fp::from(3), Fp::from(50), Fp::from(80)])).contains("tier must be at most 2"));
+        assert!(message(validate_bucket_instance(&[-Fp::one(), Fp::from(20_000)])).contains("bucket index must be at most 1"));
+        assert!(message(validate_tier_instance::<Fp>(&[])).contains("empty"));
+    }
+}
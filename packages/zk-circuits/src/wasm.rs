@@ -0,0 +1,79 @@
+//! WebAssembly bindings for browser and mobile web clients
+//!
+//! Exposes the trust score prover and verifier through `wasm_bindgen`, without
+//! the Node-only napi layer. Keys are generated on first use and kept for the
+//! lifetime of the module instance.
+//!
+//! Build with the `wasm` feature and without the default `node` feature:
+//!
+//! ```text
+//! wasm-pack build --target web -- --no-default-features --features wasm
+//! ```
+//!
+//! Run the headless browser tests in `tests/wasm.rs` with:
+//!
+//! ```text
+//! wasm-pack test --headless --chrome -- --no-default-features --features wasm
+//! ```
+
+use crate::api::ZkSystem;
+use crate::keys::{TrustScoreKeys, TRUST_SCORE_K};
+use std::sync::OnceLock;
+use wasm_bindgen::prelude::*;
+
+/// System shared by every call into this module instance
+static SYSTEM: OnceLock<ZkSystem> = OnceLock::new();
+
+/// Borrow the system, generating its keys on first use
+fn system() -> Result<&'static ZkSystem, JsError> {
+    if let Some(system) = SYSTEM.get() {
+        return Ok(system);
+    }
+
+    let keys = TrustScoreKeys::generate(TRUST_SCORE_K).map_err(|e| JsError::new(&e.to_string()))?;
+    Ok(SYSTEM.get_or_init(|| ZkSystem::from_keys(keys)))
+}
+
+/// Generate a proof envelope that `score` is (or is not) at least `threshold`
+///
+/// The envelope is the same format the Node bindings produce, so either side
+/// can verify proofs made by the other.
+#[wasm_bindgen]
+pub fn wasm_generate_trust_proof(score: u32, threshold: u32) -> Result<Vec<u8>, JsError> {
+    system()?
+        .prove_trust_score(score as u64, threshold as u64)
+        .map_err(|e| JsError::new(&e.to_string()))
+}
+
+/// Verify a trust score proof envelope for `threshold` and the claimed result
+///
+/// Bare transcripts without an envelope header are rejected.
+#[wasm_bindgen]
+pub fn wasm_verify_trust_proof(proof: &[u8], threshold: u32, expected: bool) -> bool {
+    system()
+        .ok()
+        .and_then(|system| system.verify_trust_score(proof, threshold as u64, expected).ok())
+        .unwrap_or(false)
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use crate::envelope::{decode_proof, encode_proof, CircuitTag};
+
+    #[test]
+    fn test_proofs_are_interchangeable_with_api_envelopes() {
+        let envelope = wasm_generate_trust_proof(85, 70).unwrap();
+        let (circuit, proof) = decode_proof(&envelope).unwrap();
+        assert_eq!(circuit, CircuitTag::TrustScore);
+
+        // The module's proofs verify through the api, and re-wrapping the
+        // transcript yields an envelope the module accepts
+        let system = ZkSystem::from_keys(TrustScoreKeys::generate(TRUST_SCORE_K).unwrap());
+        assert!(system.verify_trust_score(&envelope, 70, true).unwrap());
+        assert!(wasm_verify_trust_proof(&encode_proof(CircuitTag::TrustScore, proof), 70, true));
+
+        assert!(!wasm_verify_trust_proof(proof, 70, true));
+        assert!(!wasm_verify_trust_proof(&envelope, 70, false));
+    }
+}
@@ -0,0 +1,161 @@
+//! Golden vectors pinning proof and verification behaviour
+//!
+//! Each circuit's vectors list inputs with the public inputs an honest
+//! prover exposes and whether the `MockProver` accepts them, and each
+//! circuit's verifying key fingerprint pins its constraint system. A halo2
+//! upgrade or a constraint change that alters any of them fails here; update
+//! the vectors deliberately, noting why they changed.
+//!
+//! Run without default features: the napi bindings only link when loaded
+//! into Node.js, so a test binary cannot include them. `npm test` runs this
+//! after the default `cargo test`:
+//!
+//! ```sh
+//! cargo test --no-default-features --features prover --test golden
+//! ```
+
+#![cfg(not(feature = "node"))]
+
+use ff::PrimeField;
+use halo2_proofs::{dev::MockProver, plonk::keygen_vk, poly::commitment::Params};
+use pasta_curves::{EqAffine, Fp};
+use zk_circuits::circuits::identity::utils::commit;
+use zk_circuits::registry::vk_fingerprint;
+use zk_circuits::{IdentityCircuit, IncomeRangeCircuit, LendingCircuit, LoanHistoryCircuit, TrustScoreCircuit};
+
+/// Field element from its 32-byte little-endian canonical encoding, as hex
+fn fp(hex: &str) -> Fp {
+    let mut repr = [0u8; 32];
+    for (byte, pair) in repr.iter_mut().zip(hex.as_bytes().chunks(2)) {
+        *byte = u8::from_str_radix(std::str::from_utf8(pair).unwrap(), 16).unwrap();
+    }
+    Fp::from_repr(repr).expect("golden field elements are canonical")
+}
+
+/// Instance of small integers, such as results and thresholds
+fn ints(values: &[u64]) -> Vec<Fp> {
+    values.iter().map(|&value| Fp::from(value)).collect()
+}
+
+/// Assert `circuit` exposes `instance` and the `MockProver` accepts it exactly when `satisfied`
+fn assert_vector<C: LendingCircuit<Fp>>(name: &str, circuit: &C, instance: Vec<Fp>, satisfied: bool) {
+    assert_eq!(circuit.public_inputs(), instance, "{}: public inputs changed", name);
+
+    let prover = MockProver::run(C::recommended_k(), circuit, vec![instance]).unwrap();
+    assert_eq!(prover.verify().is_ok(), satisfied, "{}: MockProver verdict changed", name);
+}
+
+/// Assert the verifying key of `circuit` at its recommended `k` has `fingerprint`
+fn assert_fingerprint<C: LendingCircuit<Fp>>(name: &str, circuit: &C, fingerprint: &str) {
+    let params = Params::<EqAffine>::new(C::recommended_k());
+    let vk = keygen_vk(&params, &circuit.without_witnesses()).unwrap();
+    assert_eq!(vk_fingerprint(&vk), fingerprint, "{}: verifying key changed", name);
+}
+
+#[test]
+fn test_trust_score_vectors() {
+    // (trust_score, threshold, [result, threshold, epoch], satisfied)
+    let vectors = [
+        (85, 70, [1, 70, 0], true),
+        (70, 70, [1, 70, 0], true),
+        (69, 70, [0, 70, 0], true),
+        (0, 0, [1, 0, 0], true),
+        (100, 100, [1, 100, 0], true),
+        (0, 100, [0, 100, 0], true),
+        // Above the maximum score: no valid witness exists
+        (101, 70, [1, 70, 0], false),
+    ];
+
+    for (trust_score, threshold, instance, satisfied) in vectors {
+        let name = format!("trust score {} against {}", trust_score, threshold);
+        assert_vector(&name, &TrustScoreCircuit::new(Some(trust_score), threshold), ints(&instance), satisfied);
+    }
+}
+
+#[test]
+fn test_income_range_vectors() {
+    // (income, min_range, max_range, in_range)
+    let vectors = [
+        (50_000, 30_000, 80_000, 1),
+        (30_000, 30_000, 80_000, 1),
+        (80_000, 30_000, 80_000, 1),
+        (80_001, 30_000, 80_000, 0),
+        (29_999, 30_000, 80_000, 0),
+        (0, 0, 0, 1),
+    ];
+
+    for (income, min_range, max_range, in_range) in vectors {
+        let name = format!("income {} in {}..={}", income, min_range, max_range);
+        let circuit = IncomeRangeCircuit::new(Some(income), min_range, max_range);
+        assert_vector(&name, &circuit, ints(&[in_range, min_range as u64, max_range as u64]), true);
+    }
+}
+
+#[test]
+fn test_loan_history_vectors() {
+    // (num_loans, successful_repayments, min_success_rate, min_loans, result, satisfied)
+    let vectors = [
+        (10, 9, 8_000, 3, 1, true),
+        // Exactly the minimum rate
+        (10, 8, 8_000, 3, 1, true),
+        (10, 7, 8_000, 3, 0, true),
+        // Too few loans, however well repaid
+        (2, 2, 8_000, 3, 0, true),
+        (0, 0, 0, 0, 0, true),
+        (10, 10, 10_000, 10, 1, true),
+        // More repayments than loans: no valid witness exists
+        (5, 6, 0, 0, 1, false),
+    ];
+
+    for (num_loans, repayments, min_success_rate, min_loans, result, satisfied) in vectors {
+        let name = format!("{} of {} loans repaid", repayments, num_loans);
+        let circuit = LoanHistoryCircuit::new(Some(num_loans), Some(repayments), min_success_rate, min_loans);
+        assert_vector(&name, &circuit, ints(&[result, min_success_rate, 0, min_loans]), satisfied);
+    }
+}
+
+#[test]
+fn test_identity_vectors() {
+    let identity_hash = Fp::from(1111u64);
+    let nonce = Fp::from(42u64);
+    let commitment = commit(identity_hash, nonce, 3);
+    assert_eq!(commitment, fp("cc8fad1660ec39364981ca903b58332b8c3f0af9fbc31ad4b433927ada8f5820"));
+
+    let nullifier = fp("f18952af1205b4629c331a794ddef9b9ad7b6b7db00d80dc6b630375a13b9932");
+    let instance = |result: u64, context_id: u64| vec![Fp::from(result), nullifier, Fp::from(7), commitment, Fp::from(context_id)];
+
+    let circuit = IdentityCircuit::new(Some(identity_hash), Some(nonce), commitment, 7, 3);
+    assert_vector("matching opening", &circuit, instance(1, 3), true);
+
+    let circuit = IdentityCircuit::new(Some(identity_hash), Some(nonce + Fp::one()), commitment, 7, 3);
+    assert_vector("wrong nonce", &circuit, instance(0, 3), true);
+
+    let circuit = IdentityCircuit::new(Some(identity_hash), Some(nonce), commitment, 7, 4);
+    assert_vector("wrong context", &circuit, instance(0, 4), true);
+}
+
+#[test]
+fn test_verifying_key_fingerprints() {
+    assert_fingerprint(
+        "trust score",
+        &TrustScoreCircuit::<Fp>::new(None, 70),
+        "84c54d4a803d094ebcfa2cb6e66dc2cae72ac8ca4580d168e7ac82b1849fef69",
+    );
+    assert_fingerprint(
+        "income range",
+        &IncomeRangeCircuit::<Fp>::new(None, 30_000, 80_000),
+        "6f16d0be483ec6276af16a14340fea83857d38d889a940b8475ce586e44572d2",
+    );
+    assert_fingerprint(
+        "loan history",
+        &LoanHistoryCircuit::<Fp>::new(None, None, 8_000, 3),
+        "222726575d0b5a7ddd5673a4b0d2f752a9f05895e77fab4df2e3989476db1439",
+    );
+
+    let commitment = commit(Fp::from(1111u64), Fp::from(42u64), 3);
+    assert_fingerprint(
+        "identity",
+        &IdentityCircuit::new(None, None, commitment, 7, 3),
+        "16d1539bb07ce0648eed297eae2747a36737231287cac8c4976212e23c02fca6",
+    );
+}
@@ -0,0 +1,57 @@
+//! Bit flips in a real trust score proof, end to end
+//!
+//! A flipped transcript byte must make the proof fail to verify (`Ok(false)`,
+//! not an error and never `Ok(true)`), while a flipped envelope header byte
+//! must be reported as a parse error before any verification work.
+//!
+//! Like the golden vectors this only builds without the `node` feature;
+//! `npm test` runs it through `test-integration`:
+//!
+//! ```sh
+//! cargo test --no-default-features --features prover --test tamper
+//! ```
+
+#![cfg(all(not(feature = "node"), feature = "prover"))]
+
+use zk_circuits::api::init_system;
+use zk_circuits::envelope::{HEADER_LEN, PROOF_FORMAT_VERSION};
+use zk_circuits::keys::TRUST_SCORE_K;
+use zk_circuits::ZkError;
+
+#[test]
+fn test_tampered_transcript_fails_verification() {
+    let system = init_system(TRUST_SCORE_K).unwrap();
+    let proof = system.prove_trust_score(85, 70).unwrap();
+    assert!(system.verify_trust_score(&proof, 70, true).unwrap());
+
+    // The first, middle and last bytes of the transcript
+    let middle = HEADER_LEN + (proof.len() - HEADER_LEN) / 2;
+    for index in [HEADER_LEN, middle, proof.len() - 1] {
+        for mask in [0x01, 0x80] {
+            let mut tampered = proof.clone();
+            tampered[index] ^= mask;
+            assert_eq!(system.verify_trust_score(&tampered, 70, true), Ok(false), "byte {} ^ {:#04x}", index, mask);
+            assert_eq!(system.verify_trust_score(&tampered, 70, false), Ok(false), "byte {} ^ {:#04x}", index, mask);
+        }
+    }
+}
+
+#[test]
+fn test_tampered_header_is_a_parse_error() {
+    let system = init_system(TRUST_SCORE_K).unwrap();
+    let proof = system.prove_trust_score(85, 70).unwrap();
+
+    let flipped = |index: usize| {
+        let mut tampered = proof.clone();
+        tampered[index] ^= 0x01;
+        system.verify_trust_score(&tampered, 70, true)
+    };
+
+    // Magic, then the proof length
+    for index in (0..4).chain(6..HEADER_LEN) {
+        assert!(matches!(flipped(index), Err(ZkError::Serialization(_))), "header byte {}", index);
+    }
+    assert_eq!(flipped(4), Err(ZkError::UnsupportedProofVersion(PROOF_FORMAT_VERSION ^ 0x01)));
+    // Tag 1 (trust score) becomes 0, which no circuit uses
+    assert_eq!(flipped(5), Err(ZkError::UnknownCircuitTag(0)));
+}
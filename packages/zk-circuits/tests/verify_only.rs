@@ -0,0 +1,51 @@
+//! Verification of a pre-generated proof in a verifier-only build
+//!
+//! Lenders' servers only check proofs, so they build without the `prover`
+//! feature; this runs the verify path the way such a build would, and
+//! `npm test` runs it through `test-verify`:
+//!
+//! ```sh
+//! cargo test --no-default-features --features verify --test verify_only
+//! ```
+//!
+//! The fixture is a trust score proof of a score of 85 against a threshold of
+//! 70, for the keys `init_system(TRUST_SCORE_K)` derives, made with a
+//! `ChaCha20Rng` seeded with `FIXTURE_SEED`. Builds with the prover check it
+//! still regenerates byte for byte, so a change to the circuit or transcript
+//! fails there rather than only here.
+
+#![cfg(not(feature = "node"))]
+
+use zk_circuits::api::init_system;
+use zk_circuits::envelope::proof_from_hex;
+use zk_circuits::keys::TRUST_SCORE_K;
+
+/// Hex proof envelope of 85 against 70
+const FIXTURE: &str = include_str!("fixtures/trust_score_85_70.hex");
+
+/// Seed of the proving randomness the fixture was made with
+#[cfg(feature = "prover")]
+const FIXTURE_SEED: u64 = 1845;
+
+#[test]
+fn test_verify_pregenerated_proof() {
+    let system = init_system(TRUST_SCORE_K).unwrap();
+    let proof = proof_from_hex(FIXTURE.trim()).unwrap();
+
+    assert!(system.verify_trust_score(&proof, 70, true).unwrap());
+    assert!(!system.verify_trust_score(&proof, 70, false).unwrap());
+    assert!(!system.verify_trust_score(&proof, 80, true).unwrap());
+    assert!(system.verify_trust_score_outcome(&proof, 70).unwrap().passed());
+}
+
+#[cfg(feature = "prover")]
+#[test]
+fn test_fixture_regenerates() {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+    use zk_circuits::envelope::proof_to_hex;
+
+    let system = init_system(TRUST_SCORE_K).unwrap();
+    let proof = system.prove_trust_score_with_rng(85, 70, ChaCha20Rng::seed_from_u64(FIXTURE_SEED)).unwrap();
+    assert_eq!(proof_to_hex(&proof), FIXTURE.trim());
+}
@@ -0,0 +1,36 @@
+//! Headless browser tests for the WebAssembly bindings
+//!
+//! Run with `wasm-pack test --headless --chrome -- --no-default-features --features wasm`.
+
+#![cfg(all(feature = "wasm", target_arch = "wasm32"))]
+
+use wasm_bindgen_test::*;
+use zk_circuits::envelope::{decode_proof, CircuitTag};
+use zk_circuits::wasm::{wasm_generate_trust_proof, wasm_verify_trust_proof};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[wasm_bindgen_test]
+fn test_trust_proof_round_trip() {
+    let proof = wasm_generate_trust_proof(85, 70).unwrap();
+
+    assert!(wasm_verify_trust_proof(&proof, 70, true));
+    assert!(!wasm_verify_trust_proof(&proof, 70, false));
+    assert!(!wasm_verify_trust_proof(&proof, 80, true));
+}
+
+#[wasm_bindgen_test]
+fn test_trust_proof_is_enveloped() {
+    let envelope = wasm_generate_trust_proof(85, 70).unwrap();
+    let (circuit, proof) = decode_proof(&envelope).unwrap();
+    assert_eq!(circuit, CircuitTag::TrustScore);
+
+    // A bare transcript from an older build no longer verifies
+    assert!(!wasm_verify_trust_proof(proof, 70, true));
+}
+
+#[wasm_bindgen_test]
+fn test_malformed_proof_rejected() {
+    assert!(!wasm_verify_trust_proof(&[], 70, true));
+    assert!(!wasm_verify_trust_proof(&[0u8; 16], 70, true));
+}